@@ -1,13 +1,28 @@
 use anyhow::{anyhow, Context, Result};
-use async_std::sync::Arc;
+use futures_util::future::join_all;
 use once_cell::sync::Lazy;
-use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, time::SystemTime};
+use std::{
+    fs::File,
+    io::Read,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
 static PAGESIZE: Lazy<usize> = Lazy::new(sysconf::pagesize);
 
-static UID_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"Uid:\s*(\d+)").unwrap());
+const PROC: &str = "/proc";
+
+/// More threads than this don't speed up reading procfs any further
+const MAX_READER_THREADS: usize = 8;
+
+/// Spinning up a thread isn't worth it for just a handful of processes
+const MIN_BATCH_SIZE: usize = 32;
+
+/// Parts of commandlines that hint at a game started by Steam, either natively
+/// or through Proton or Wine
+const STEAM_HINTS: &[&str] = &["steam", "Steam", "proton", "wine"];
 
 #[derive(Debug, Clone, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Containerization {
@@ -16,6 +31,114 @@ pub enum Containerization {
     Flatpak,
 }
 
+/// The policy the kernel's scheduler treats a process with, see sched(7)
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SchedulingPolicy {
+    #[default]
+    Other,
+    Fifo,
+    RoundRobin,
+    Batch,
+    Idle,
+    Deadline,
+}
+
+impl SchedulingPolicy {
+    /// Converts the policy as given in /proc/<pid>/stat, e.g. 1 for `SCHED_FIFO`
+    pub fn from_raw(raw: u32) -> Option<Self> {
+        match raw {
+            0 => Some(Self::Other),
+            1 => Some(Self::Fifo),
+            2 => Some(Self::RoundRobin),
+            3 => Some(Self::Batch),
+            5 => Some(Self::Idle),
+            6 => Some(Self::Deadline),
+            _ => None,
+        }
+    }
+
+    /// The name of the policy as used by chrt(1), e.g. "fifo"
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Other => "other",
+            Self::Fifo => "fifo",
+            Self::RoundRobin => "rr",
+            Self::Batch => "batch",
+            Self::Idle => "idle",
+            Self::Deadline => "deadline",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            Self::Other,
+            Self::Fifo,
+            Self::RoundRobin,
+            Self::Batch,
+            Self::Idle,
+            Self::Deadline,
+        ]
+        .into_iter()
+        .find(|policy| policy.name() == name)
+    }
+
+    /// Whether the policy is one of the realtime ones that come with a
+    /// priority of 1 to 99
+    pub fn is_realtime(self) -> bool {
+        matches!(self, Self::Fifo | Self::RoundRobin)
+    }
+}
+
+/// The fields of /proc/<pid>/stat that are of interest, numbered as in proc(5)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Stat {
+    /// Field 10
+    minor_faults: u64,
+    /// Field 12
+    major_faults: u64,
+    /// Field 14, in clock ticks
+    utime: u64,
+    /// Field 15, in clock ticks
+    stime: u64,
+    /// Field 40
+    realtime_priority: u32,
+    /// Field 41
+    policy: u32,
+}
+
+impl Stat {
+    fn parse(stat: &str) -> Result<Self> {
+        // comm is enclosed in parentheses and may contain spaces and even
+        // parentheses itself, so only start splitting after the last one
+        let mut fields = stat
+            .rsplit_once(')')
+            .context("malformed /stat")?
+            .1
+            .split_ascii_whitespace();
+
+        // the first field after comm is the state, field 3
+        let mut next_field = 3;
+        let mut field = |number: usize, name: &str| -> Result<u64> {
+            let value = fields
+                .nth(number - next_field)
+                .with_context(|| format!("no {name} in /stat"))?;
+            next_field = number + 1;
+            value
+                .parse()
+                .with_context(|| format!("invalid {name} in /stat"))
+        };
+
+        Ok(Self {
+            minor_faults: field(10, "minflt")?,
+            major_faults: field(12, "majflt")?,
+            utime: field(14, "utime")?,
+            stime: field(15, "stime")?,
+            realtime_priority: field(40, "rt_priority")?.try_into()?,
+            policy: field(41, "policy")?.try_into()?,
+        })
+    }
+}
+
 /// Data that could be transferred using `resources-processes`, separated from
 /// `Process` mainly due to `Icon` not being able to derive `Serialize` and
 /// `Deserialize`.
@@ -31,6 +154,24 @@ pub struct ProcessData {
     pub memory_usage: usize,
     pub cgroup: Option<String>,
     pub containerization: Containerization,
+    /// The UID of the Kubernetes pod this process runs in, if any
+    pub kubernetes_pod: Option<String>,
+    /// The app ID of the Steam game this process belongs to, if any
+    pub steam_app_id: Option<u32>,
+    /// The number of open file descriptors, `None` if not permitted to know or
+    /// if they haven't been asked for
+    pub open_files: Option<usize>,
+    /// The soft limit of open file descriptors, `None` if unlimited
+    pub open_files_limit: Option<usize>,
+    pub scheduling_policy: SchedulingPolicy,
+    /// 1 to 99 for realtime policies, 0 otherwise
+    pub realtime_priority: u32,
+    /// Page faults since the process started that could be resolved without
+    /// loading anything from disk
+    pub minor_faults: u64,
+    /// Page faults since the process started that had to load a page from disk,
+    /// e.g. from swap
+    pub major_faults: u64,
 }
 
 impl ProcessData {
@@ -67,56 +208,168 @@ impl ProcessData {
         }
     }
 
-    async fn get_uid(proc_path: &PathBuf) -> Result<u32> {
-        let status = async_std::fs::read_to_string(proc_path.join("status")).await?;
-        if let Some(captures) = UID_REGEX.captures(&status) {
-            let first_num_str = captures.get(1).context("no uid found")?;
-            first_num_str
-                .as_str()
-                .parse::<u32>()
-                .context("couldn't parse uid in /status")
-        } else {
-            Ok(0)
+    /// Reads the data of all processes in procfs.
+    ///
+    /// Reading hundreds of tiny files one after another is dominated by
+    /// syscall latency rather than by parsing, so the PIDs are split into one
+    /// batch per available CPU thread and each batch is read on its own
+    /// blocking thread, reusing a single buffer for all of its processes.
+    /// Processes that vanish while being read are skipped.
+    ///
+    /// Counting the open files of a process means listing all of its file
+    /// descriptors, so that only happens if `with_open_files` is set.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if /proc can't be listed
+    pub async fn all(with_open_files: bool) -> Result<Vec<Self>> {
+        let proc_paths: Vec<PathBuf> = std::fs::read_dir(PROC)
+            .context("unable to read /proc")?
+            .flatten()
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .map_or(false, |name| name.bytes().all(|b| b.is_ascii_digit()))
+            })
+            .map(|entry| entry.path())
+            .collect();
+
+        let threads = std::thread::available_parallelism()
+            .map_or(1, NonZeroUsize::get)
+            .min(MAX_READER_THREADS);
+        let batch_size = proc_paths.len().div_ceil(threads).max(MIN_BATCH_SIZE);
+
+        let batches = proc_paths.chunks(batch_size).map(|batch| {
+            let batch = batch.to_vec();
+            async_std::task::spawn_blocking(move || Self::read_batch(batch, with_open_files))
+        });
+
+        Ok(join_all(batches).await.into_iter().flatten().collect())
+    }
+
+    fn read_batch(proc_paths: Vec<PathBuf>, with_open_files: bool) -> Vec<Self> {
+        let mut buffer = String::with_capacity(1024);
+        proc_paths
+            .into_iter()
+            .filter_map(|proc_path| {
+                Self::try_from_path(proc_path, &mut buffer, with_open_files).ok()
+            })
+            .collect()
+    }
+
+    /// Extracts the pod UID from the cgroup of a process that has been started
+    /// by kubelet. Depending on kubelet's cgroup driver, the cgroup looks like
+    /// `/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod<uid>.slice/…`
+    /// (with dashes in the UID replaced by underscores) or like
+    /// `/kubepods/burstable/pod<uid>/…`.
+    fn parse_kubernetes_pod<S: AsRef<str>>(cgroup: S) -> Option<String> {
+        let cgroups_v2_line = cgroup.as_ref().lines().find(|s| s.starts_with("0::"))?;
+        if !cgroups_v2_line.contains("kubepods") {
+            return None;
         }
+
+        cgroups_v2_line.split('/').find_map(|segment| {
+            let segment = segment.trim_end_matches(".slice");
+            let uid = match segment.rsplit_once("-pod") {
+                Some((_, uid)) => uid,
+                None => segment.strip_prefix("pod")?,
+            };
+            (uid.len() == 36).then(|| uid.replace('_', "-"))
+        })
     }
 
-    pub async fn try_from_path(proc_path: PathBuf) -> Result<Self> {
-        // Stat
-        let shared_proc_path = Arc::new(proc_path.clone());
-        let stat = async_std::task::spawn(async move {
-            async_std::fs::read_to_string(shared_proc_path.join("stat")).await
-        });
+    /// Whether the process might have been started by Steam, in which case
+    /// its environment is worth a look. Reading the environment of every
+    /// process on every refresh would be a waste.
+    fn may_be_steam_game(cgroup: Option<&str>, commandline: &str) -> bool {
+        cgroup.map_or(false, |cgroup| cgroup.to_lowercase().contains("steam"))
+            || STEAM_HINTS.iter().any(|hint| commandline.contains(hint))
+    }
 
-        // Statm
-        let shared_proc_path = Arc::new(proc_path.clone());
-        let statm = async_std::task::spawn(async move {
-            async_std::fs::read_to_string(shared_proc_path.join("statm")).await
-        });
+    /// Steam tells games their app ID using environment variables, which are
+    /// inherited by everything the game starts, e.g. Proton's Wine processes
+    fn read_steam_app_id(proc_path: &Path) -> Option<u32> {
+        let environ = std::fs::read(proc_path.join("environ")).ok()?;
+        environ.split(|b| *b == 0).find_map(|variable| {
+            let value = variable
+                .strip_prefix(b"SteamAppId=")
+                .or_else(|| variable.strip_prefix(b"STEAM_COMPAT_APP_ID="))?;
+            std::str::from_utf8(value)
+                .ok()?
+                .parse::<u32>()
+                .ok()
+                .filter(|app_id| *app_id != 0)
+        })
+    }
 
-        // Comm
-        let shared_proc_path = Arc::new(proc_path.clone());
-        let comm = async_std::task::spawn(async move {
-            async_std::fs::read_to_string(shared_proc_path.join("comm")).await
-        });
+    /// Counts the entries of /proc/<pid>/fd, which is only allowed for
+    /// processes of the same user
+    fn count_open_files(proc_path: &Path) -> Option<usize> {
+        std::fs::read_dir(proc_path.join("fd"))
+            .ok()
+            .map(|entries| entries.count())
+    }
 
-        // Cmdline
-        let shared_proc_path = Arc::new(proc_path.clone());
-        let commandline = async_std::task::spawn(async move {
-            async_std::fs::read_to_string(shared_proc_path.join("cmdline")).await
-        });
+    /// Returns the soft limit of open files from the contents of
+    /// /proc/<pid>/limits
+    fn parse_open_files_limit(limits: &str) -> Option<usize> {
+        limits
+            .lines()
+            .find_map(|line| line.strip_prefix("Max open files"))?
+            .split_ascii_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    }
 
-        // Cgroup
-        let shared_proc_path = Arc::new(proc_path.clone());
-        let cgroup = async_std::task::spawn(async move {
-            async_std::fs::read_to_string(shared_proc_path.join("cgroup")).await
-        });
+    /// Reads the file at `path` into `buffer`, replacing its previous contents.
+    /// Reusing the buffer for several files avoids an allocation per file.
+    fn read_into(path: &Path, buffer: &mut String) -> Result<()> {
+        buffer.clear();
+        File::open(path)?.read_to_string(buffer)?;
+        Ok(())
+    }
+
+    fn parse_uid(status: &str) -> Result<u32> {
+        let Some(uids) = status.lines().find_map(|line| line.strip_prefix("Uid:")) else {
+            return Ok(0);
+        };
+        uids.split_ascii_whitespace()
+            .next()
+            .context("no uid found")?
+            .parse::<u32>()
+            .context("couldn't parse uid in /status")
+    }
 
-        let stat = stat.await?;
-        let statm = statm.await?;
-        let comm = comm.await?;
-        let commandline = commandline.await?;
-        let cgroup = cgroup.await?;
+    /// Returns the resident, non-shared memory in bytes from the contents of
+    /// /proc/<pid>/statm
+    fn parse_memory_usage(statm: &str) -> Result<usize> {
+        let mut fields = statm.split_ascii_whitespace().skip(1);
+        let resident = fields
+            .next()
+            .context("no resident in /statm")?
+            .parse::<usize>()?;
+        let shared = fields
+            .next()
+            .context("no shared in /statm")?
+            .parse::<usize>()?;
+        Ok(resident.saturating_sub(shared) * *PAGESIZE)
+    }
 
+    /// Reads the data of the process at `proc_path` (e.g. "/proc/1"). `buffer`
+    /// is used as scratch space and can be reused across calls. The open files
+    /// are only counted if `with_open_files` is set.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the process doesn't exist (anymore) or its files
+    /// couldn't be parsed
+    pub fn try_from_path(
+        proc_path: PathBuf,
+        buffer: &mut String,
+        with_open_files: bool,
+    ) -> Result<Self> {
         let pid = proc_path
             .file_name()
             .ok_or_else(|| anyhow!(""))?
@@ -124,46 +377,318 @@ impl ProcessData {
             .ok_or_else(|| anyhow!(""))?
             .parse()?;
 
-        let uid = Self::get_uid(&proc_path).await?;
+        // the files that are only parsed share the buffer, only the ones whose
+        // contents are kept get their own allocation
+        Self::read_into(&proc_path.join("stat"), buffer)?;
+        let stat = Stat::parse(buffer)?;
 
-        let stat = stat
-            .split(' ')
-            .map(std::string::ToString::to_string)
-            .collect::<Vec<_>>();
+        let cpu_time_timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)?
+            .as_millis() as u64;
 
-        let statm = statm
-            .split(' ')
-            .map(std::string::ToString::to_string)
-            .collect::<Vec<_>>();
+        Self::read_into(&proc_path.join("statm"), buffer)?;
+        let memory_usage = Self::parse_memory_usage(buffer)?;
 
-        let comm = comm.replace('\n', "");
+        Self::read_into(&proc_path.join("status"), buffer)?;
+        let uid = Self::parse_uid(buffer)?;
 
-        let cpu_time = stat[13].parse::<u64>()? + stat[14].parse::<u64>()?;
+        Self::read_into(&proc_path.join("cgroup"), buffer)?;
+        let cgroup = Self::sanitize_cgroup(&*buffer);
+        let kubernetes_pod = Self::parse_kubernetes_pod(&*buffer);
 
-        let cpu_time_timestamp = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)?
-            .as_millis() as u64;
+        let mut comm = std::fs::read_to_string(proc_path.join("comm"))?;
+        comm.retain(|c| c != '\n');
 
-        let memory_usage = (statm[1].parse::<usize>()? - statm[2].parse::<usize>()?) * *PAGESIZE;
+        let commandline = std::fs::read_to_string(proc_path.join("cmdline"))?;
 
-        let cgroup = Self::sanitize_cgroup(cgroup);
+        let steam_app_id = if Self::may_be_steam_game(cgroup.as_deref(), &commandline) {
+            Self::read_steam_app_id(&proc_path)
+        } else {
+            None
+        };
 
         let containerization = match &proc_path.join("root").join(".flatpak-info").exists() {
             true => Containerization::Flatpak,
             false => Containerization::None,
         };
 
+        // the limit is of no use without knowing how many files are open
+        let open_files = if with_open_files {
+            Self::count_open_files(&proc_path)
+        } else {
+            None
+        };
+        let open_files_limit = if open_files.is_some() {
+            Self::read_into(&proc_path.join("limits"), buffer)
+                .ok()
+                .and_then(|_| Self::parse_open_files_limit(buffer))
+        } else {
+            None
+        };
+
         Ok(Self {
             pid,
             uid,
             comm,
             commandline,
-            cpu_time,
+            cpu_time: stat.utime + stat.stime,
             cpu_time_timestamp,
             memory_usage,
             cgroup,
             proc_path,
             containerization,
+            kubernetes_pod,
+            steam_app_id,
+            open_files,
+            open_files_limit,
+            scheduling_policy: SchedulingPolicy::from_raw(stat.policy).unwrap_or_default(),
+            realtime_priority: stat.realtime_priority,
+            minor_faults: stat.minor_faults,
+            major_faults: stat.major_faults,
+        })
+    }
+}
+
+/// How a process has opened a file
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FileAccess {
+    #[default]
+    Read,
+    Write,
+    ReadWrite,
+}
+
+/// A regular file that is currently opened by a process
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenFile {
+    pub fd: u32,
+    pub path: String,
+    pub access: FileAccess,
+    /// The file offset of the descriptor, which advances with every read and
+    /// write
+    pub position: u64,
+}
+
+/// A snapshot of the files a process has opened and of how much it has read
+/// and written so far. Comparing two snapshots tells what the process is doing
+/// to the disk.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileActivity {
+    pub files: Vec<OpenFile>,
+    /// Bytes actually fetched from storage, `None` if not permitted to know
+    pub read_bytes: Option<u64>,
+    /// Bytes actually sent to storage, `None` if not permitted to know
+    pub written_bytes: Option<u64>,
+}
+
+impl FileActivity {
+    /// Takes a snapshot of the file activity of the process with `pid`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the open files of the process can't be listed,
+    /// e.g. because it belongs to another user
+    pub fn of_pid(pid: i32) -> Result<Self> {
+        let proc_path = Path::new(PROC).join(pid.to_string());
+
+        let mut files = Vec::new();
+        let mut buffer = String::new();
+        let entries = std::fs::read_dir(proc_path.join("fd"))
+            .with_context(|| format!("unable to list open files of process {pid}"))?;
+        for entry in entries.flatten() {
+            let Ok(fd) = entry.file_name().to_string_lossy().parse::<u32>() else {
+                continue;
+            };
+
+            // sockets, pipes and the like show up as e.g. "socket:[1234]"
+            let Ok(target) = std::fs::read_link(entry.path()) else {
+                continue;
+            };
+            let path = target.to_string_lossy();
+            if !path.starts_with('/') || path.starts_with("/dev/") {
+                continue;
+            }
+
+            if ProcessData::read_into(&proc_path.join("fdinfo").join(fd.to_string()), &mut buffer)
+                .is_err()
+            {
+                continue;
+            }
+            let Some((position, access)) = Self::parse_fdinfo(&buffer) else {
+                continue;
+            };
+
+            files.push(OpenFile {
+                fd,
+                path: path.to_string(),
+                access,
+                position,
+            });
+        }
+
+        // /proc/<pid>/io needs the same permissions as ptrace, so it may be
+        // unavailable even though the open files could be read
+        let (read_bytes, written_bytes) =
+            ProcessData::read_into(&proc_path.join("io"), &mut buffer)
+                .map(|_| Self::parse_io(&buffer))
+                .unwrap_or_default();
+
+        Ok(Self {
+            files,
+            read_bytes,
+            written_bytes,
         })
     }
+
+    /// Returns the position and access mode from the contents of
+    /// /proc/<pid>/fdinfo/<fd>
+    fn parse_fdinfo(fdinfo: &str) -> Option<(u64, FileAccess)> {
+        let field = |name: &str| {
+            fdinfo
+                .lines()
+                .find_map(|line| line.strip_prefix(name))
+                .map(str::trim)
+        };
+
+        let position = field("pos:")?.parse().ok()?;
+        // flags are printed in octal, the lowest two bits are O_ACCMODE
+        let access = match u32::from_str_radix(field("flags:")?, 8).ok()? & 0b11 {
+            0 => FileAccess::Read,
+            1 => FileAccess::Write,
+            _ => FileAccess::ReadWrite,
+        };
+
+        Some((position, access))
+    }
+
+    /// Returns read_bytes and write_bytes from the contents of /proc/<pid>/io
+    fn parse_io(io: &str) -> (Option<u64>, Option<u64>) {
+        let field = |name: &str| {
+            io.lines()
+                .find_map(|line| line.strip_prefix(name))
+                .and_then(|value| value.trim().parse().ok())
+        };
+
+        (field("read_bytes:"), field("write_bytes:"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // taken from a process that renamed itself to "Web ) (Co"
+    const STAT_WITH_PARENTHESES: &str = "21633 (Web ) (Co) R 21629 21633 21629 0 -1 4194304 2785 \
+        5966 2 0 3 1 3 0 20 0 1 0 572461 17154048 3428 18446744073709551615 94402297552896 \
+        94402297553237 140735712752768 0 0 0 0 16781312 2 0 0 0 17 0 0 0 0 0 0 94402297564592 \
+        94402297565208 94403139588096 140735712760737 140735712760780 140735712760780 \
+        140735712763855 0\n";
+
+    // taken from a process running with SCHED_FIFO at priority 10
+    const STAT_FIFO: &str = "21689 (python3) R 21685 21689 21685 0 -1 4194560 2745 5985 0 0 5 0 \
+        3 1 -11 0 1 0 572922 16969728 3357 18446744073709551615 94877681831936 94877681832277 \
+        140728253845376 0 0 0 0 16781312 2 0 0 0 17 0 10 1 0 0 0 94877681843632 94877681844248 \
+        94877792088064 140728253850529 140728253850572 140728253850572 140728253853647 0\n";
+
+    #[test]
+    fn test_parse_stat() {
+        let stat = Stat::parse(STAT_WITH_PARENTHESES).unwrap();
+        assert_eq!(
+            stat,
+            Stat {
+                minor_faults: 2785,
+                major_faults: 2,
+                utime: 3,
+                stime: 1,
+                realtime_priority: 0,
+                policy: 0,
+            }
+        );
+
+        let stat = Stat::parse(STAT_FIFO).unwrap();
+        assert_eq!(stat.utime + stat.stime, 5);
+        assert_eq!(stat.realtime_priority, 10);
+        assert_eq!(
+            SchedulingPolicy::from_raw(stat.policy),
+            Some(SchedulingPolicy::Fifo)
+        );
+    }
+
+    #[test]
+    fn test_parse_stat_malformed() {
+        assert!(Stat::parse("").is_err());
+        assert!(Stat::parse("1 (truncated) S 0 0 0 0 -1 4194560 160393").is_err());
+        assert!(Stat::parse("1 (not a number) S 0 0 0 0 -1 4194560 x").is_err());
+    }
+
+    #[test]
+    fn test_parse_open_files_limit() {
+        let limits =
+            "Limit                     Soft Limit           Hard Limit           Units     \n\
+            Max cpu time              unlimited            unlimited            seconds   \n\
+            Max open files            1024                 524288               files     \n\
+            Max locked memory         8388608              8388608              bytes     \n";
+        assert_eq!(ProcessData::parse_open_files_limit(limits), Some(1024));
+
+        let unlimited =
+            "Max open files            unlimited            unlimited            files     \n";
+        assert_eq!(ProcessData::parse_open_files_limit(unlimited), None);
+    }
+
+    #[test]
+    fn test_parse_kubernetes_pod() {
+        // systemd cgroup driver
+        assert_eq!(
+            ProcessData::parse_kubernetes_pod(
+                "0::/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod1b9c0f8e_3a6d_4d2b_9c1e_5f7a8b9c0d1e.slice/cri-containerd-0123.scope\n"
+            ),
+            Some("1b9c0f8e-3a6d-4d2b-9c1e-5f7a8b9c0d1e".to_string())
+        );
+
+        // cgroupfs cgroup driver
+        assert_eq!(
+            ProcessData::parse_kubernetes_pod(
+                "0::/kubepods/besteffort/pod1b9c0f8e-3a6d-4d2b-9c1e-5f7a8b9c0d1e/0123456789abcdef\n"
+            ),
+            Some("1b9c0f8e-3a6d-4d2b-9c1e-5f7a8b9c0d1e".to_string())
+        );
+
+        assert_eq!(
+            ProcessData::parse_kubernetes_pod(
+                "0::/user.slice/user-1000.slice/user@1000.service/app.slice/app-gnome-firefox-4321.scope\n"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_fdinfo() {
+        let fdinfo = "pos:\t4096\nflags:\t0100002\nmnt_id:\t29\nino:\t1234567\n";
+        assert_eq!(
+            FileActivity::parse_fdinfo(fdinfo),
+            Some((4096, FileAccess::ReadWrite))
+        );
+
+        let fdinfo = "pos:\t0\nflags:\t0100000\nmnt_id:\t29\nino:\t1234567\n";
+        assert_eq!(
+            FileActivity::parse_fdinfo(fdinfo),
+            Some((0, FileAccess::Read))
+        );
+
+        let fdinfo = "pos:\t12\nflags:\t02101\nmnt_id:\t29\nino:\t1234567\n";
+        assert_eq!(
+            FileActivity::parse_fdinfo(fdinfo),
+            Some((12, FileAccess::Write))
+        );
+
+        assert_eq!(FileActivity::parse_fdinfo("pos:\t12\n"), None);
+    }
+
+    #[test]
+    fn test_parse_io() {
+        let io = "rchar: 3980\nwchar: 0\nsyscr: 9\nsyscw: 0\nread_bytes: 8192\n\
+            write_bytes: 4096\ncancelled_write_bytes: 0\n";
+        assert_eq!(FileActivity::parse_io(io), (Some(8192), Some(4096)));
+        assert_eq!(FileActivity::parse_io(""), (None, None));
+    }
 }
@@ -0,0 +1,464 @@
+use anyhow::{Context, Result};
+use async_std::stream::StreamExt;
+use regex::Regex;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+static RE_DRIVE: OnceLock<Regex> = OnceLock::new();
+
+const SYS_BLOCK: &str = "/sys/block";
+const SYS_CLASS_BLOCK: &str = "/sys/class/block";
+const UDEV_DATA: &str = "/run/udev/data";
+
+const SYS_STATS: &str = r" *(?P<read_ios>[0-9]*) *(?P<read_merges>[0-9]*) *(?P<read_sectors>[0-9]*) *(?P<read_ticks>[0-9]*) *(?P<write_ios>[0-9]*) *(?P<write_merges>[0-9]*) *(?P<write_sectors>[0-9]*) *(?P<write_ticks>[0-9]*) *(?P<in_flight>[0-9]*) *(?P<io_ticks>[0-9]*) *(?P<time_in_queue>[0-9]*) *(?P<discard_ios>[0-9]*) *(?P<discard_merges>[0-9]*) *(?P<discard_sectors>[0-9]*) *(?P<discard_ticks>[0-9]*) *(?P<flush_ios>[0-9]*) *(?P<flush_ticks>[0-9]*)";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DriveType {
+    CdDvdBluray,
+    Emmc,
+    Flash,
+    Floppy,
+    Hdd,
+    Nvme,
+    #[default]
+    Unknown,
+    Ssd,
+}
+
+impl DriveType {
+    /// Guesses the type of the block device `block_device` (e.g. "sda") from
+    /// its name and, if that isn't conclusive, from whether it is rotational
+    /// and removable
+    pub fn guess(block_device: &str, rotational: Option<bool>, removable: bool) -> Self {
+        if block_device.starts_with("nvme") {
+            DriveType::Nvme
+        } else if block_device.starts_with("mmc") {
+            DriveType::Emmc
+        } else if block_device.starts_with("fd") {
+            DriveType::Floppy
+        } else if block_device.starts_with("sr") {
+            DriveType::CdDvdBluray
+        } else {
+            match rotational {
+                Some(true) => DriveType::Hdd,
+                Some(false) if removable => DriveType::Flash,
+                Some(false) => DriveType::Ssd,
+                None => DriveType::Unknown,
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq)]
+pub struct Drive {
+    pub model: Option<String>,
+    pub drive_type: DriveType,
+    pub block_device: String,
+    pub sys_fs_path: PathBuf,
+}
+
+impl PartialEq for Drive {
+    fn eq(&self, other: &Self) -> bool {
+        self.block_device == other.block_device
+    }
+}
+
+/// The values of a `Drive` that change over time, read at once. Counters like
+/// `disk_stats` only turn into rates by comparing two snapshots.
+///
+/// Missing values fall back to defaults rather than failing the whole
+/// snapshot, e.g. a sector size of 512 bytes.
+#[derive(Debug, Clone, Default)]
+pub struct DriveData {
+    pub disk_stats: HashMap<String, usize>,
+    pub capacity: u64,
+    pub sector_size: u64,
+    pub writable: bool,
+    pub removable: bool,
+    pub io_scheduler: Option<IoScheduler>,
+}
+
+/// The block I/O scheduler of a drive along with the ones it could use instead
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IoScheduler {
+    /// The scheduler currently in use, e.g. "mq-deadline"
+    pub active: String,
+    /// All schedulers the kernel offers for the drive, including `active`
+    pub available: Vec<String>,
+}
+
+impl DriveData {
+    pub async fn new(drive: &Drive) -> Self {
+        Self {
+            disk_stats: drive.sys_stats().await.unwrap_or_default(),
+            capacity: drive.capacity().await.unwrap_or(0),
+            sector_size: drive.sector_size().await.unwrap_or(512),
+            writable: drive.writable().await.unwrap_or(false),
+            removable: drive.removable().await.unwrap_or(false),
+            io_scheduler: drive.io_scheduler().await.ok(),
+        }
+    }
+}
+
+impl Drive {
+    /// Creates a `Drive` using a SysFS Path
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the are errors during
+    /// reading or parsing
+    pub async fn from_sysfs<P: AsRef<Path>>(sysfs_path: P) -> Result<Drive> {
+        let path = sysfs_path.as_ref().to_path_buf();
+        let block_device = path
+            .file_name()
+            .expect("sysfs path ends with \"..\"?")
+            .to_string_lossy()
+            .to_string();
+
+        let mut drive = Self::default();
+        drive.sys_fs_path = path;
+        drive.block_device = block_device;
+        drive.model = drive
+            .model()
+            .await
+            .ok()
+            .map(|model| model.trim().to_string());
+        drive.drive_type = drive.drive_type().await.unwrap_or_default();
+        Ok(drive)
+    }
+
+    /// Returns the SysFS Paths of possible drives
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the are errors during
+    /// reading or parsing
+    pub async fn get_sysfs_paths(skip_virtual_devices: bool) -> Result<Vec<PathBuf>> {
+        let list = Self::read_block_devices(SYS_BLOCK, skip_virtual_devices)
+            .await
+            .unwrap_or_default();
+        if !list.is_empty() {
+            return Ok(list);
+        }
+
+        // some minimal environments (e.g. containers) don't expose /sys/block, so fall back to
+        // /sys/class/block which additionally lists partitions that we need to filter out
+        log::debug!("no drives found in {SYS_BLOCK}, falling back to {SYS_CLASS_BLOCK}");
+        Ok(
+            Self::read_block_devices(SYS_CLASS_BLOCK, skip_virtual_devices)
+                .await?
+                .into_iter()
+                .filter(|path| !path.join("partition").exists())
+                .collect(),
+        )
+    }
+
+    async fn read_block_devices(dir: &str, skip_virtual_devices: bool) -> Result<Vec<PathBuf>> {
+        let mut list = Vec::new();
+        let mut entries = async_std::fs::read_dir(dir).await?;
+        while let Some(entry) = entries.next().await {
+            let entry = entry?;
+            let block_device = entry.file_name().to_string_lossy().to_string();
+            if block_device.is_empty()
+                || (skip_virtual_devices && is_virtual_block_device(&block_device))
+            {
+                continue;
+            }
+            list.push(entry.path().into());
+        }
+        Ok(list)
+    }
+
+    /// Returns the value of the property `key` that udev has stored
+    /// for this drive in its database
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the udev database is not available
+    /// or if the property doesn't exist
+    pub async fn udev_property(&self, key: &str) -> Result<String> {
+        let dev = async_std::fs::read_to_string(self.sys_fs_path.join("dev"))
+            .await
+            .with_context(|| "unable to read dev sysfs file")?;
+        let udev_data = async_std::fs::read_to_string(format!("{UDEV_DATA}/b{}", dev.trim()))
+            .await
+            .with_context(|| format!("unable to read udev data of {}", self.block_device))?;
+        udev_data
+            .lines()
+            .filter_map(|line| line.strip_prefix("E:"))
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            .map(str::to_string)
+            .with_context(|| format!("no udev property {key} for {}", self.block_device))
+    }
+
+    /// Returns the current SysFS stats for the drive
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the are errors during
+    /// reading or parsing
+    pub async fn sys_stats(&self) -> Result<HashMap<String, usize>> {
+        let stat = async_std::fs::read_to_string(self.sys_fs_path.join("stat"))
+            .await
+            .with_context(|| format!("unable to read /sys/block/{}/stat", self.block_device))?;
+
+        parse_sys_stats(&stat)
+            .with_context(|| format!("unable to parse /sys/block/{}/stat", self.block_device))
+    }
+
+    async fn drive_type(&self) -> Result<DriveType> {
+        let rotational =
+            match async_std::fs::read_to_string(self.sys_fs_path.join("queue/rotational")).await {
+                // turn rot into a boolean
+                Ok(rot) => Some(rot.replace('\n', "").parse::<u8>().map(|rot| rot != 0)?),
+                Err(_) => None,
+            };
+        let removable = if rotational == Some(false) {
+            self.removable().await?
+        } else {
+            false
+        };
+        Ok(DriveType::guess(&self.block_device, rotational, removable))
+    }
+
+    /// Returns, whether the drive is removable
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the are errors during
+    /// reading or parsing
+    pub async fn removable(&self) -> Result<bool> {
+        async_std::fs::read_to_string(self.sys_fs_path.join("removable"))
+            .await?
+            .replace('\n', "")
+            .parse::<u8>()
+            .map(|rem| rem != 0)
+            .with_context(|| "unable to parse removable sysfs file")
+    }
+
+    /// Returns, whether the drive is writable
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the are errors during
+    /// reading or parsing
+    pub async fn writable(&self) -> Result<bool> {
+        async_std::fs::read_to_string(self.sys_fs_path.join("ro"))
+            .await?
+            .replace('\n', "")
+            .parse::<u8>()
+            .map(|ro| ro == 0)
+            .with_context(|| "unable to parse ro sysfs file")
+    }
+
+    /// Returns the capacity **in sectors** of the drive
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the are errors during
+    /// reading or parsing
+    pub async fn capacity(&self) -> Result<u64> {
+        async_std::fs::read_to_string(self.sys_fs_path.join("size"))
+            .await?
+            .replace('\n', "")
+            .parse()
+            .with_context(|| "unable to parse size sysfs file")
+    }
+
+    /// Returns the model information of the drive
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the are errors during
+    /// reading or parsing
+    pub async fn model(&self) -> Result<String> {
+        if let Ok(model) =
+            async_std::fs::read_to_string(self.sys_fs_path.join("device/model")).await
+        {
+            return Ok(model);
+        }
+
+        // not every kind of block device exposes its model in sysfs, udev might know it though
+        self.udev_property("ID_MODEL")
+            .await
+            .map(|model| model.replace('_', " "))
+            .with_context(|| "unable to get model from sysfs or udev")
+    }
+
+    /// Returns the World-Wide Identification of the drive
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the are errors during
+    /// reading or parsing
+    pub async fn wwid(&self) -> Result<String> {
+        async_std::fs::read_to_string(self.sys_fs_path.join("device/wwid"))
+            .await
+            .with_context(|| "unable to parse wwid sysfs file")
+    }
+
+    /// Returns the sector size of the drive
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the are errors during
+    /// reading or parsing
+    pub async fn sector_size(&self) -> Result<u64> {
+        async_std::fs::read_to_string(self.sys_fs_path.join("queue/hw_sector_size"))
+            .await?
+            .replace('\n', "")
+            .parse()
+            .with_context(|| "unable to parse hw_sector_size")
+    }
+
+    /// Returns the block I/O scheduler of the drive and the available
+    /// alternatives
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the are errors during
+    /// reading or parsing
+    pub async fn io_scheduler(&self) -> Result<IoScheduler> {
+        let schedulers =
+            async_std::fs::read_to_string(self.sys_fs_path.join("queue/scheduler")).await?;
+
+        parse_io_scheduler(&schedulers).with_context(|| "unable to parse scheduler sysfs file")
+    }
+}
+
+/// Parses the contents of a drive's queue/scheduler file in sysfs, which lists
+/// the available schedulers with the active one in brackets, e.g.
+/// "mq-deadline kyber [bfq] none"
+pub fn parse_io_scheduler(schedulers: &str) -> Option<IoScheduler> {
+    let mut active = None;
+    let available = schedulers
+        .split_whitespace()
+        .map(|scheduler| {
+            if let Some(scheduler) = scheduler
+                .strip_prefix('[')
+                .and_then(|scheduler| scheduler.strip_suffix(']'))
+            {
+                active = Some(scheduler.to_string());
+                scheduler.to_string()
+            } else {
+                scheduler.to_string()
+            }
+        })
+        .collect();
+
+    Some(IoScheduler {
+        active: active?,
+        available,
+    })
+}
+
+/// Returns whether the block device `block_device` (e.g. "loop0") is backed by
+/// something other than a drive, like a file, memory or other block devices
+pub fn is_virtual_block_device(block_device: &str) -> bool {
+    ["loop", "ram", "zram", "md", "dm", "zd"]
+        .iter()
+        .any(|prefix| block_device.starts_with(prefix))
+}
+
+/// Parses the contents of a drive's stat file in sysfs, which has the same
+/// format as a line of /proc/diskstats without the device numbers and name
+///
+/// # Errors
+///
+/// Will return `Err` if `stat` is not in the expected format
+pub fn parse_sys_stats(stat: &str) -> Result<HashMap<String, usize>> {
+    let re_drive = RE_DRIVE.get_or_init(|| Regex::new(SYS_STATS).unwrap());
+
+    let captures = re_drive
+        .captures(stat)
+        .context("unexpected format of drive stats")?;
+
+    Ok(re_drive
+        .capture_names()
+        .flatten()
+        .filter_map(|named_capture| {
+            Some((
+                named_capture.to_string(),
+                captures.name(named_capture)?.as_str().parse().ok()?,
+            ))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drive_type_guess() {
+        assert_eq!(
+            DriveType::guess("nvme0n1", Some(false), false),
+            DriveType::Nvme
+        );
+        assert_eq!(DriveType::guess("mmcblk0", None, true), DriveType::Emmc);
+        assert_eq!(
+            DriveType::guess("sr0", Some(true), true),
+            DriveType::CdDvdBluray
+        );
+        assert_eq!(DriveType::guess("sda", Some(true), false), DriveType::Hdd);
+        assert_eq!(DriveType::guess("sdb", Some(false), false), DriveType::Ssd);
+        assert_eq!(DriveType::guess("sdc", Some(false), true), DriveType::Flash);
+        assert_eq!(DriveType::guess("sdd", None, false), DriveType::Unknown);
+    }
+
+    #[test]
+    fn test_is_virtual_block_device() {
+        assert!(is_virtual_block_device("loop0"));
+        assert!(is_virtual_block_device("zram0"));
+        assert!(is_virtual_block_device("dm-1"));
+        assert!(!is_virtual_block_device("nvme0n1"));
+        assert!(!is_virtual_block_device("sda"));
+    }
+
+    #[test]
+    fn test_parse_sys_stats() {
+        let stats = parse_sys_stats(
+            "  181360    53170 14367410    39398   245516   214356 17651584   232010        0   205260   283862    14330        0 116946536     1178    24473    11275\n",
+        )
+        .unwrap();
+
+        assert_eq!(stats.get("read_ios"), Some(&181_360));
+        assert_eq!(stats.get("read_sectors"), Some(&14_367_410));
+        assert_eq!(stats.get("write_sectors"), Some(&17_651_584));
+        assert_eq!(stats.get("io_ticks"), Some(&205_260));
+        assert_eq!(stats.get("flush_ticks"), Some(&11_275));
+    }
+
+    #[test]
+    fn test_parse_sys_stats_old_kernel() {
+        // kernels before 4.18 don't report discards and flushes
+        let stats = parse_sys_stats(
+            "    4915     1380   314926     4452     1540     1877    27480     6112        0     5256    10564\n",
+        )
+        .unwrap();
+
+        assert_eq!(stats.get("read_ios"), Some(&4_915));
+        assert_eq!(stats.get("time_in_queue"), Some(&10_564));
+        assert_eq!(stats.get("discard_ios"), None);
+    }
+
+    #[test]
+    fn test_parse_io_scheduler() {
+        assert_eq!(
+            parse_io_scheduler("mq-deadline kyber [bfq] none\n"),
+            Some(IoScheduler {
+                active: "bfq".into(),
+                available: vec![
+                    "mq-deadline".into(),
+                    "kyber".into(),
+                    "bfq".into(),
+                    "none".into()
+                ],
+            })
+        );
+        assert_eq!(parse_io_scheduler("none\n"), None);
+    }
+}
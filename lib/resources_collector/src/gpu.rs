@@ -0,0 +1,645 @@
+use anyhow::{anyhow, bail, Context, Result};
+use nvml_wrapper::{
+    enum_wrappers::device::{Clock, TemperatureSensor},
+    error::NvmlError,
+    Nvml,
+};
+use once_cell::sync::Lazy;
+
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    path::{Path, PathBuf},
+};
+
+use glob::glob;
+use pci_ids::Device;
+
+use crate::sysfs::{parse_pci_id, parse_uevent};
+
+const VID_AMD: u16 = 4098;
+const VID_INTEL: u16 = 32902;
+const VID_NVIDIA: u16 = 4318;
+
+static NVML: Lazy<Result<Nvml, NvmlError>> = Lazy::new(Nvml::init);
+
+#[derive(Debug, Clone, Default)]
+pub struct GPU {
+    pub device: Option<&'static Device>,
+    /// Empty if unknown
+    pub pci_slot: String,
+    /// Empty if unknown
+    pub driver: String,
+    sysfs_path: PathBuf,
+    hwmon_paths: Vec<PathBuf>,
+}
+
+/// The values of a `GPU` that change over time, read at once. Every value is
+/// read on its own and fails on its own, since drivers expose different
+/// subsets of them.
+///
+/// NVML calls block, so callers that run an event loop should create this on
+/// a worker thread.
+#[derive(Debug)]
+pub struct GpuData {
+    pub usage: Result<isize>,
+    pub total_vram: Result<isize>,
+    pub used_vram: Result<isize>,
+    pub temp: Result<f64>,
+    pub power_usage: Result<f64>,
+    pub gpu_speed: Result<f64>,
+    pub vram_speed: Result<f64>,
+    pub power_cap: Result<f64>,
+    pub power_cap_max: Result<f64>,
+}
+
+/// Parses the contents of a sysfs file that holds a single integer, like
+/// `gpu_busy_percent` or `freq1_input`
+fn parse_sysfs_int(contents: &str) -> Result<isize> {
+    contents
+        .trim()
+        .parse::<isize>()
+        .with_context(|| format!("{:?} is not an integer", contents.trim()))
+}
+
+impl GpuData {
+    pub async fn new(gpu: &GPU) -> Self {
+        Self {
+            usage: gpu.get_gpu_usage().await,
+            total_vram: gpu.get_total_vram().await,
+            used_vram: gpu.get_used_vram().await,
+            temp: gpu.get_gpu_temp().await,
+            power_usage: gpu.get_power_usage().await,
+            gpu_speed: gpu.get_gpu_speed().await,
+            vram_speed: gpu.get_vram_speed().await,
+            power_cap: gpu.get_power_cap().await,
+            power_cap_max: gpu.get_power_cap_max().await,
+        }
+    }
+}
+
+impl GPU {
+    /// Returns a `Vec` of all GPUs currently found in the system.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if there are problems detecting
+    /// the GPUs in the system
+    pub async fn get_gpus() -> Result<Vec<GPU>> {
+        let mut gpu_vec: Vec<GPU> = Vec::new();
+        for entry in glob("/sys/class/drm/card?")?.flatten() {
+            let sysfs_device_path = entry.join("device");
+            let uevent_contents = parse_uevent(
+                &async_std::fs::read_to_string(sysfs_device_path.join("uevent")).await?,
+            );
+
+            let mut hwmon_vec: Vec<PathBuf> = Vec::new();
+            for hwmon in glob(&format!(
+                "{}/hwmon/hwmon?",
+                sysfs_device_path
+                    .to_str()
+                    .with_context(|| anyhow!("error transforming PathBuf to str"))?
+            ))?
+            .flatten()
+            {
+                hwmon_vec.push(hwmon);
+            }
+
+            gpu_vec.push(GPU::from_uevent(entry, &uevent_contents, hwmon_vec)?);
+        }
+        Ok(gpu_vec)
+    }
+
+    /// Creates a `GPU` from the parsed uevent file of its device
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the uevent has an invalid PCI ID
+    fn from_uevent(
+        sysfs_path: PathBuf,
+        uevent: &HashMap<String, String>,
+        hwmon_paths: Vec<PathBuf>,
+    ) -> Result<GPU> {
+        let (vid, pid) = match uevent.get("PCI_ID") {
+            Some(pci_id) => parse_pci_id(pci_id)?,
+            None => (0, 0),
+        };
+
+        Ok(GPU {
+            device: Device::from_vid_pid(vid, pid),
+            pci_slot: uevent.get("PCI_SLOT_NAME").cloned().unwrap_or_default(),
+            driver: uevent.get("DRIVER").cloned().unwrap_or_default(),
+            sysfs_path,
+            hwmon_paths,
+        })
+    }
+
+    fn get_pid_name(&self) -> Result<String> {
+        Ok(self.device.context("no device")?.name().to_owned())
+    }
+
+    /// Returns the Vendor name using the GPU's Vendor ID
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the vendor is unknown
+    /// in the PCI IDs database
+    pub fn get_vendor(&self) -> Result<String> {
+        Ok(self.device.context("no device")?.vendor().name().to_owned())
+    }
+
+    async fn read_sysfs_int<P: AsRef<Path>>(&self, file: P) -> Result<isize> {
+        let path = self.sysfs_path.join(file);
+        parse_sysfs_int(&async_std::fs::read_to_string(&path).await?).context(format!(
+            "error parsing file {}",
+            &path
+                .to_str()
+                .with_context(|| anyhow!("error transforming PathBuf to str"))?
+        ))
+    }
+
+    async fn read_device_int<P: AsRef<Path>>(&self, file: P) -> Result<isize> {
+        let path = self.sysfs_path.join("device").join(file);
+        parse_sysfs_int(&async_std::fs::read_to_string(&path).await?).context(format!(
+            "error parsing file {}",
+            &path
+                .to_str()
+                .with_context(|| anyhow!("error transforming PathBuf to str"))?
+        ))
+    }
+
+    async fn read_hwmon_int<P: AsRef<Path>>(&self, hwmon: usize, file: P) -> Result<isize> {
+        let path = self.hwmon_paths[hwmon].join(file);
+        parse_sysfs_int(&async_std::fs::read_to_string(&path).await?).context(format!(
+            "error parsing file {}",
+            &path
+                .to_str()
+                .with_context(|| anyhow!("error transforming PathBuf to str"))?
+        ))
+    }
+
+    fn get_amd_name(&self) -> Result<String> {
+        self.get_pid_name()
+    }
+
+    fn get_intel_name(&self) -> Result<String> {
+        self.get_pid_name()
+    }
+
+    fn get_nvidia_name(&self) -> Result<String> {
+        if let Ok(nv) = NVML.as_ref() {
+            let dev = nv
+                .device_by_pci_bus_id(self.pci_slot.clone())
+                .context("failed to get GPU by PCI bus")?;
+            return dev.name().context("failed to get utilization rates");
+        }
+        bail!("no NVML connection, nouveau not implemented yet")
+    }
+
+    /// Returns the product name of the GPU. If the nvidia driver is used,
+    /// the name will be obtained using NVML, otherwise it will be obtained
+    /// from the PCI ID
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if there's no name either exposed through a driver,
+    /// exposed through a sysfs file or findable in the PCI IDs database.
+    pub fn get_name(&self) -> Result<String> {
+        if let Some(dev) = self.device {
+            return match dev.vendor().id() {
+                VID_AMD => self.get_amd_name(),
+                VID_INTEL => self.get_intel_name(),
+                VID_NVIDIA => self.get_nvidia_name(),
+                _ => self.get_pid_name(),
+            };
+        }
+        bail!("no device")
+    }
+
+    async fn get_amd_gpu_usage(&self) -> Result<isize> {
+        self.read_device_int("gpu_busy_percent").await
+    }
+
+    fn get_intel_gpu_usage(&self) -> Result<isize> {
+        bail!("unimplemented")
+    }
+
+    fn get_nvidia_gpu_usage(&self) -> Result<isize> {
+        if let Ok(nv) = NVML.as_ref() {
+            let dev = nv
+                .device_by_pci_bus_id(self.pci_slot.clone())
+                .context("failed to get GPU by PCI bus")?;
+            return Ok(dev
+                .utilization_rates()
+                .context("failed to get utilization rates")?
+                .gpu
+                .try_into()?);
+        }
+        bail!("no NVML connection, nouveau not implemented yet")
+    }
+
+    /// Returns the GPU usage in percent
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the GPU usage
+    /// is for some reason unreadable or is simply
+    /// not exposed
+    pub async fn get_gpu_usage(&self) -> Result<isize> {
+        if let Some(dev) = self.device {
+            return match dev.vendor().id() {
+                VID_AMD => self.get_amd_gpu_usage().await,
+                VID_INTEL => self.get_intel_gpu_usage(),
+                VID_NVIDIA => self.get_nvidia_gpu_usage(),
+                _ => bail!("unimplemented"),
+            };
+        }
+        bail!("no device")
+    }
+
+    async fn get_amd_used_vram(&self) -> Result<isize> {
+        self.read_device_int("mem_info_vram_used").await
+    }
+
+    fn get_intel_used_vram(&self) -> Result<isize> {
+        bail!("unimplemented")
+    }
+
+    fn get_nvidia_used_vram(&self) -> Result<isize> {
+        if let Ok(nv) = NVML.as_ref() {
+            let dev = nv
+                .device_by_pci_bus_id(self.pci_slot.clone())
+                .context("failed to get GPU by PCI bus")?;
+            return Ok(dev
+                .memory_info()
+                .context("failed to get memory info")?
+                .used
+                .try_into()?);
+        }
+        bail!("no NVML connection, nouveau not implemented yet")
+    }
+
+    /// Returns the used VRAM in bytes
+    ///
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the used amount of VRAM
+    /// is for some reason unreadable or is simply
+    /// not exposed
+    pub async fn get_used_vram(&self) -> Result<isize> {
+        if let Some(dev) = self.device {
+            return match dev.vendor().id() {
+                VID_AMD => self.get_amd_used_vram().await,
+                VID_INTEL => self.get_intel_used_vram(),
+                VID_NVIDIA => self.get_nvidia_used_vram(),
+                _ => bail!("unimplemented"),
+            };
+        }
+        bail!("no device")
+    }
+
+    async fn get_amd_total_vram(&self) -> Result<isize> {
+        self.read_device_int("mem_info_vram_total").await
+    }
+
+    fn get_intel_total_vram(&self) -> Result<isize> {
+        bail!("unimplemented")
+    }
+
+    fn get_nvidia_total_vram(&self) -> Result<isize> {
+        if let Ok(nv) = NVML.as_ref() {
+            let dev = nv
+                .device_by_pci_bus_id(self.pci_slot.clone())
+                .context("failed to get GPU by PCI bus")?;
+            return Ok(dev
+                .memory_info()
+                .context("failed to get memory info")?
+                .total
+                .try_into()?);
+        }
+        bail!("no NVML connection, nouveau not implemented yet")
+    }
+
+    /// Returns the total VRAM in bytes
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the VRAM size
+    /// is for some reason unreadable or is simply
+    /// not exposed
+    pub async fn get_total_vram(&self) -> Result<isize> {
+        if let Some(dev) = self.device {
+            return match dev.vendor().id() {
+                VID_AMD => self.get_amd_total_vram().await,
+                VID_INTEL => self.get_intel_total_vram(),
+                VID_NVIDIA => self.get_nvidia_total_vram(),
+                _ => bail!("unimplemented"),
+            };
+        }
+        bail!("no device")
+    }
+
+    async fn get_amd_gpu_temp(&self) -> Result<f64> {
+        Ok(self.read_hwmon_int(0, "temp1_input").await? as f64 / 1000.0)
+    }
+
+    fn get_intel_gpu_temp(&self) -> Result<f64> {
+        bail!("unimplemented")
+    }
+
+    fn get_nvidia_gpu_temp(&self) -> Result<f64> {
+        if let Ok(nv) = NVML.as_ref() {
+            let dev = nv
+                .device_by_pci_bus_id(self.pci_slot.clone())
+                .context("failed to get GPU by PCI bus")?;
+            return Ok(dev
+                .temperature(TemperatureSensor::Gpu)
+                .context("failed to get temperature info")?
+                .try_into()?);
+        }
+        bail!("no NVML connection, nouveau not implemented yet")
+    }
+
+    /// Returns the GPU temperature in °C
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the temperature
+    /// is for some reason unreadable or is simply
+    /// not exposed
+    pub async fn get_gpu_temp(&self) -> Result<f64> {
+        if let Some(dev) = self.device {
+            return match dev.vendor().id() {
+                VID_AMD => self.get_amd_gpu_temp().await,
+                VID_INTEL => self.get_intel_gpu_temp(),
+                VID_NVIDIA => self.get_nvidia_gpu_temp(),
+                _ => bail!("unimplemented"),
+            };
+        }
+        bail!("no device")
+    }
+
+    async fn get_amd_power_usage(&self) -> Result<f64> {
+        Ok(self.read_hwmon_int(0, "power1_average").await? as f64 / 1_000_000.0)
+    }
+
+    fn get_intel_power_usage(&self) -> Result<f64> {
+        bail!("unimplemented")
+    }
+
+    fn get_nvidia_power_usage(&self) -> Result<f64> {
+        if let Ok(nv) = NVML.as_ref() {
+            let dev = nv
+                .device_by_pci_bus_id(self.pci_slot.clone())
+                .context("failed to get GPU by PCI bus")?;
+            return Ok(f64::from(dev.power_usage().context("failed to get power usage")?) / 1000.0);
+        }
+        bail!("no NVML connection, nouveau not implemented yet")
+    }
+
+    /// Returns the GPU power usage in Watts
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the power usage
+    /// is for some reason unreadable or is simply
+    /// not exposed
+    pub async fn get_power_usage(&self) -> Result<f64> {
+        if let Some(dev) = self.device {
+            return match dev.vendor().id() {
+                VID_AMD => self.get_amd_power_usage().await,
+                VID_INTEL => self.get_intel_power_usage(),
+                VID_NVIDIA => self.get_nvidia_power_usage(),
+                _ => bail!("unimplemented"),
+            };
+        }
+        bail!("no device")
+    }
+
+    async fn get_amd_gpu_speed(&self) -> Result<f64> {
+        Ok(self.read_hwmon_int(0, "freq1_input").await? as f64)
+    }
+
+    async fn get_intel_gpu_speed(&self) -> Result<f64> {
+        Ok(self.read_sysfs_int("gt_cur_freq_mhz").await? as f64 * 1_000_000.0)
+    }
+
+    fn get_nvidia_gpu_speed(&self) -> Result<f64> {
+        if let Ok(nv) = NVML.as_ref() {
+            let dev = nv
+                .device_by_pci_bus_id(self.pci_slot.clone())
+                .context("failed to get GPU by PCI bus")?;
+            return Ok(f64::from(
+                dev.clock_info(Clock::Graphics)
+                    .context("failed to get clock info")?,
+            ) * 1_000_000.0);
+        }
+        bail!("no NVML connection, nouveau not implemented yet")
+    }
+
+    /// Returns the GPU clockspeed (typically the 3-D
+    /// graphics part) in Hz
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the clockspeed
+    /// is for some reason unreadable or is simply
+    /// not exposed
+    pub async fn get_gpu_speed(&self) -> Result<f64> {
+        if let Some(dev) = self.device {
+            return match dev.vendor().id() {
+                VID_AMD => self.get_amd_gpu_speed().await,
+                VID_INTEL => self.get_intel_gpu_speed().await,
+                VID_NVIDIA => self.get_nvidia_gpu_speed(),
+                _ => bail!("unimplemented"),
+            };
+        }
+        bail!("no device")
+    }
+
+    async fn get_amd_vram_speed(&self) -> Result<f64> {
+        Ok(self.read_hwmon_int(0, "freq2_input").await? as f64)
+    }
+
+    fn get_intel_vram_speed(&self) -> Result<f64> {
+        bail!("unimplemented")
+    }
+
+    fn get_nvidia_vram_speed(&self) -> Result<f64> {
+        if let Ok(nv) = NVML.as_ref() {
+            let dev = nv
+                .device_by_pci_bus_id(self.pci_slot.clone())
+                .context("failed to get GPU by PCI bus")?;
+            return Ok(f64::from(
+                dev.clock_info(Clock::Memory)
+                    .context("failed to get clock info")?,
+            ) * 1_000_000.0);
+        }
+        bail!("no NVML connection, nouveau not implemented yet")
+    }
+
+    /// Returns the VRAM speed in Hz
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the VRAM speed
+    /// is for some reason unreadable or is simply
+    /// not exposed
+    pub async fn get_vram_speed(&self) -> Result<f64> {
+        if let Some(dev) = self.device {
+            return match dev.vendor().id() {
+                VID_AMD => self.get_amd_vram_speed().await,
+                VID_INTEL => self.get_intel_vram_speed(),
+                VID_NVIDIA => self.get_nvidia_vram_speed(),
+                _ => bail!("unimplemented"),
+            };
+        }
+        bail!("no device")
+    }
+
+    async fn get_amd_power_cap(&self) -> Result<f64> {
+        Ok(self.read_hwmon_int(0, "power1_cap").await? as f64 / 1_000_000.0)
+    }
+
+    fn get_intel_power_cap(&self) -> Result<f64> {
+        bail!("unimplemented")
+    }
+
+    fn get_nvidia_power_cap(&self) -> Result<f64> {
+        if let Ok(nv) = NVML.as_ref() {
+            let dev = nv
+                .device_by_pci_bus_id(self.pci_slot.clone())
+                .context("failed to get GPU by PCI bus")?;
+            return Ok(f64::from(
+                dev.power_management_limit_default()
+                    .context("failed to get power cap info")?,
+            ) / 1000.0);
+        }
+        bail!("no NVML connection, nouveau not implemented yet")
+    }
+
+    /// Returns the current power cap in Watts
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the current power cap
+    /// is for some reason unreadable or is simply
+    /// not exposed
+    pub async fn get_power_cap(&self) -> Result<f64> {
+        if let Some(dev) = self.device {
+            return match dev.vendor().id() {
+                VID_AMD => self.get_amd_power_cap().await,
+                VID_INTEL => self.get_intel_power_cap(),
+                VID_NVIDIA => self.get_nvidia_power_cap(),
+                _ => bail!("unimplemented"),
+            };
+        }
+        bail!("no device")
+    }
+
+    async fn get_amd_power_cap_max(&self) -> Result<f64> {
+        Ok(self.read_hwmon_int(0, "power1_cap_max").await? as f64 / 1_000_000.0)
+    }
+
+    fn get_intel_power_cap_max(&self) -> Result<f64> {
+        bail!("unimplemented")
+    }
+
+    fn get_nvidia_power_cap_max(&self) -> Result<f64> {
+        if let Ok(nv) = NVML.as_ref() {
+            let dev = nv
+                .device_by_pci_bus_id(self.pci_slot.clone())
+                .context("failed to get GPU by PCI bus")?;
+            return Ok(f64::from(
+                dev.power_management_limit_constraints()
+                    .context("failed to get max power cap info")?
+                    .max_limit,
+            ) / 1000.0);
+        }
+        bail!("no NVML connection, nouveau not implemented yet")
+    }
+
+    /// Returns the max power cap in Watts
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the max power cap
+    /// is for some reason unreadable or is simply
+    /// not exposed
+    pub async fn get_power_cap_max(&self) -> Result<f64> {
+        if let Some(dev) = self.device {
+            return match dev.vendor().id() {
+                VID_AMD => self.get_amd_power_cap_max().await,
+                VID_INTEL => self.get_intel_power_cap_max(),
+                VID_NVIDIA => self.get_nvidia_power_cap_max(),
+                _ => bail!("unimplemented"),
+            };
+        }
+        bail!("no device")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sysfs_int() {
+        assert_eq!(parse_sysfs_int("42\n").unwrap(), 42);
+        assert_eq!(parse_sysfs_int("2500000000\n").unwrap(), 2_500_000_000);
+        assert_eq!(parse_sysfs_int("-7").unwrap(), -7);
+        assert!(parse_sysfs_int("").is_err());
+        assert!(parse_sysfs_int("N/A\n").is_err());
+        assert!(parse_sysfs_int("12.5\n").is_err());
+    }
+
+    #[test]
+    fn test_from_uevent() {
+        let uevent = parse_uevent(
+            "DRIVER=amdgpu
+PCI_CLASS=30000
+PCI_ID=1002:73BF
+PCI_SUBSYS_ID=1DA2:E438
+PCI_SLOT_NAME=0000:03:00.0
+",
+        );
+        let hwmon_paths = vec![PathBuf::from("/sys/class/drm/card0/device/hwmon/hwmon3")];
+
+        let gpu = GPU::from_uevent(
+            PathBuf::from("/sys/class/drm/card0"),
+            &uevent,
+            hwmon_paths.clone(),
+        )
+        .unwrap();
+
+        assert_eq!(gpu.driver, "amdgpu");
+        assert_eq!(gpu.pci_slot, "0000:03:00.0");
+        assert_eq!(gpu.sysfs_path, PathBuf::from("/sys/class/drm/card0"));
+        assert_eq!(gpu.hwmon_paths, hwmon_paths);
+    }
+
+    #[test]
+    fn test_from_uevent_without_pci() {
+        // e.g. the virtual GPU of a virtual machine
+        let uevent = parse_uevent("DRIVER=virtio-gpu\n");
+
+        let gpu =
+            GPU::from_uevent(PathBuf::from("/sys/class/drm/card0"), &uevent, Vec::new()).unwrap();
+
+        assert!(gpu.device.is_none());
+        assert_eq!(gpu.driver, "virtio-gpu");
+        assert!(gpu.pci_slot.is_empty());
+        assert!(gpu.get_name().is_err());
+        assert!(gpu.get_vendor().is_err());
+    }
+
+    #[test]
+    fn test_from_uevent_invalid_pci_id() {
+        let uevent = parse_uevent("DRIVER=amdgpu\nPCI_ID=1002\n");
+
+        assert!(
+            GPU::from_uevent(PathBuf::from("/sys/class/drm/card0"), &uevent, Vec::new()).is_err()
+        );
+    }
+}
@@ -0,0 +1,11 @@
+//! Collects the data that Resources shows from procfs, sysfs and D-Bus.
+//!
+//! Nothing in here depends on GTK, so it can be used by the GUI, a command
+//! line interface or other programs alike. Everything that is only needed for
+//! showing the data (icons, translated names) stays in the GUI.
+
+pub mod drive;
+pub mod gpu;
+pub mod network;
+pub mod process;
+mod sysfs;
@@ -0,0 +1,228 @@
+//! Processes are read by `process-data`, this keeps track of them across
+//! refreshes to tell how quickly their counters change.
+
+use std::collections::VecDeque;
+
+pub use process_data::*;
+
+/// How often the memory usage of a process is remembered to tell its trend
+const MEMORY_SAMPLE_INTERVAL_MS: u64 = 15_000;
+
+/// Together with `MEMORY_SAMPLE_INTERVAL_MS`, the memory usage of the last five
+/// minutes is considered
+const MEMORY_SAMPLES: usize = 21;
+
+/// Growing by less than this isn't worth mentioning, no matter how steadily
+const MIN_MEMORY_GROWTH_BYTES: usize = 4 * 1024 * 1024;
+const MIN_MEMORY_GROWTH_FRACTION: f64 = 0.1;
+
+/// How the memory usage of a process has developed recently
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum MemoryTrend {
+    /// The process hasn't been watched for long enough yet
+    #[default]
+    Unknown,
+    Stable,
+    /// The memory usage has only been going up, which hints at a leak
+    Growing {
+        bytes_per_second: f64,
+        duration_secs: u64,
+    },
+}
+
+/// The latest data of a process along with what's needed from earlier
+/// readings to calculate its rates
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct SampledProcess {
+    pub data: ProcessData,
+    pub cpu_time_before: u64,
+    pub cpu_time_before_timestamp: u64,
+    pub minor_faults_before: u64,
+    pub major_faults_before: u64,
+    /// Pairs of timestamp (in milliseconds since the UNIX epoch) and memory
+    /// usage, oldest first
+    pub memory_history: VecDeque<(u64, usize)>,
+}
+
+impl SampledProcess {
+    pub fn new(data: ProcessData) -> Self {
+        let mut process = Self {
+            data,
+            cpu_time_before: 0,
+            cpu_time_before_timestamp: 0,
+            minor_faults_before: 0,
+            major_faults_before: 0,
+            memory_history: VecDeque::with_capacity(MEMORY_SAMPLES),
+        };
+        process.record_memory_usage();
+        process
+    }
+
+    /// Replaces the data of the process with `data`, a newer reading of the
+    /// same process. The rates are calculated between these two readings.
+    pub fn update(&mut self, data: ProcessData) {
+        self.cpu_time_before = self.data.cpu_time;
+        self.cpu_time_before_timestamp = self.data.cpu_time_timestamp;
+        self.minor_faults_before = self.data.minor_faults;
+        self.major_faults_before = self.data.major_faults;
+        self.data = data;
+        self.record_memory_usage();
+    }
+
+    /// Remembers the current memory usage for `memory_trend()` if the last
+    /// sample is old enough
+    fn record_memory_usage(&mut self) {
+        let timestamp = self.data.cpu_time_timestamp;
+        let due = self.memory_history.back().map_or(true, |(last, _)| {
+            timestamp.saturating_sub(*last) >= MEMORY_SAMPLE_INTERVAL_MS
+        });
+        if !due {
+            return;
+        }
+
+        if self.memory_history.len() >= MEMORY_SAMPLES {
+            self.memory_history.pop_front();
+        }
+        self.memory_history
+            .push_back((timestamp, self.data.memory_usage));
+    }
+
+    /// Looks for sustained growth of the memory usage. Any decrease within the
+    /// considered timespan counts as stable, as does growth that's too small
+    /// to matter.
+    #[must_use]
+    pub fn memory_trend(&self) -> MemoryTrend {
+        if self.memory_history.len() < MEMORY_SAMPLES {
+            return MemoryTrend::Unknown;
+        }
+
+        let (Some((first_timestamp, first)), Some((last_timestamp, last))) =
+            (self.memory_history.front(), self.memory_history.back())
+        else {
+            return MemoryTrend::Unknown;
+        };
+
+        let monotonic = self
+            .memory_history
+            .iter()
+            .zip(self.memory_history.iter().skip(1))
+            .all(|((_, before), (_, after))| after >= before);
+
+        let growth = last.saturating_sub(*first);
+        let significant = growth >= MIN_MEMORY_GROWTH_BYTES
+            && growth as f64 >= *first as f64 * MIN_MEMORY_GROWTH_FRACTION;
+
+        let duration_ms = last_timestamp.saturating_sub(*first_timestamp);
+
+        if monotonic && significant && duration_ms > 0 {
+            MemoryTrend::Growing {
+                bytes_per_second: growth as f64 / (duration_ms as f64 / 1000.0),
+                duration_secs: duration_ms / 1000,
+            }
+        } else {
+            MemoryTrend::Stable
+        }
+    }
+
+    #[must_use]
+    pub fn cpu_time_ratio(&self) -> f32 {
+        if self.cpu_time_before == 0 {
+            0.0
+        } else {
+            (self.data.cpu_time.saturating_sub(self.cpu_time_before) as f32
+                / (self
+                    .data
+                    .cpu_time_timestamp
+                    .saturating_sub(self.cpu_time_before_timestamp)) as f32)
+                .clamp(0.0, 1.0)
+        }
+    }
+
+    /// Returns the minor and major page faults per second since the last
+    /// refresh
+    #[must_use]
+    pub fn page_fault_rates(&self) -> (f32, f32) {
+        let elapsed_ms = self
+            .data
+            .cpu_time_timestamp
+            .saturating_sub(self.cpu_time_before_timestamp);
+        if self.cpu_time_before_timestamp == 0 || elapsed_ms == 0 {
+            return (0.0, 0.0);
+        }
+
+        let rate = |faults: u64, faults_before: u64| {
+            faults.saturating_sub(faults_before) as f32 * 1000.0 / elapsed_ms as f32
+        };
+        (
+            rate(self.data.minor_faults, self.minor_faults_before),
+            rate(self.data.major_faults, self.major_faults_before),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(timestamp: u64, cpu_time: u64, memory_usage: usize) -> ProcessData {
+        let mut data = ProcessData::default();
+        data.pid = 1000;
+        data.cpu_time = cpu_time;
+        data.cpu_time_timestamp = timestamp;
+        data.memory_usage = memory_usage;
+        data
+    }
+
+    fn faults(timestamp: u64, minor_faults: u64, major_faults: u64) -> ProcessData {
+        let mut data = reading(timestamp, 0, 0);
+        data.minor_faults = minor_faults;
+        data.major_faults = major_faults;
+        data
+    }
+
+    #[test]
+    fn test_cpu_time_ratio() {
+        let mut process = SampledProcess::new(reading(1_000, 500, 0));
+        assert_eq!(process.cpu_time_ratio(), 0.0);
+
+        process.update(reading(3_000, 1_500, 0));
+        assert_eq!(process.cpu_time_ratio(), 0.5);
+    }
+
+    #[test]
+    fn test_page_fault_rates() {
+        let mut process = SampledProcess::new(faults(1_000, 100, 2));
+        assert_eq!(process.page_fault_rates(), (0.0, 0.0));
+
+        process.update(faults(3_000, 300, 6));
+        assert_eq!(process.page_fault_rates(), (100.0, 2.0));
+    }
+
+    #[test]
+    fn test_memory_trend() {
+        let mut growing = SampledProcess::new(reading(0, 0, 100_000_000));
+        let mut stable = SampledProcess::new(reading(0, 0, 100_000_000));
+        assert_eq!(growing.memory_trend(), MemoryTrend::Unknown);
+
+        for sample in 1..MEMORY_SAMPLES {
+            let timestamp = sample as u64 * MEMORY_SAMPLE_INTERVAL_MS;
+            growing.update(reading(timestamp, 0, 100_000_000 + sample * 1_000_000));
+            // dropping once in between breaks the trend
+            let memory_usage = if sample == 10 {
+                90_000_000
+            } else {
+                100_000_000 + sample * 1_000_000
+            };
+            stable.update(reading(timestamp, 0, memory_usage));
+        }
+
+        assert_eq!(
+            growing.memory_trend(),
+            MemoryTrend::Growing {
+                bytes_per_second: 20_000_000.0 / 300.0,
+                duration_secs: 300,
+            }
+        );
+        assert_eq!(stable.memory_trend(), MemoryTrend::Stable);
+    }
+}
@@ -0,0 +1,67 @@
+//! Parsers for file formats that several kinds of devices share in sysfs
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+
+/// Parses the contents of a uevent file into its `KEY=value` pairs. Lines that
+/// aren't such a pair are skipped.
+pub fn parse_uevent(uevent: &str) -> HashMap<String, String> {
+    uevent
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Parses a PCI ID like "1002:73BF", as found in uevent files, into its
+/// vendor and device ID
+///
+/// # Errors
+///
+/// Will return `Err` if `pci_id` isn't made up of two hexadecimal numbers
+pub fn parse_pci_id(pci_id: &str) -> Result<(u16, u16)> {
+    let (vid, pid) = pci_id.split_once(':').context("invalid PCI ID")?;
+    Ok((u16::from_str_radix(vid, 16)?, u16::from_str_radix(pid, 16)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GPU_UEVENT: &str = "DRIVER=amdgpu
+PCI_CLASS=30000
+PCI_ID=1002:73BF
+PCI_SUBSYS_ID=1DA2:E438
+PCI_SLOT_NAME=0000:03:00.0
+MODALIAS=pci:v00001002d000073BFsv00001DA2sd0000E438bc03sc00i00
+";
+
+    #[test]
+    fn test_parse_uevent() {
+        let uevent = parse_uevent(GPU_UEVENT);
+
+        assert_eq!(uevent.len(), 6);
+        assert_eq!(uevent.get("DRIVER").map(String::as_str), Some("amdgpu"));
+        assert_eq!(
+            uevent.get("PCI_SLOT_NAME").map(String::as_str),
+            Some("0000:03:00.0")
+        );
+    }
+
+    #[test]
+    fn test_parse_uevent_skips_other_lines() {
+        let uevent = parse_uevent("DRIVER=igc\n\nbroken\n");
+
+        assert_eq!(uevent.len(), 1);
+        assert_eq!(uevent.get("DRIVER").map(String::as_str), Some("igc"));
+    }
+
+    #[test]
+    fn test_parse_pci_id() {
+        assert_eq!(parse_pci_id("1002:73BF").unwrap(), (0x1002, 0x73BF));
+        assert_eq!(parse_pci_id("8086:15f3").unwrap(), (0x8086, 0x15F3));
+        assert!(parse_pci_id("1002").is_err());
+        assert!(parse_pci_id("1002:xyz").is_err());
+    }
+}
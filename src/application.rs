@@ -7,10 +7,19 @@ use gtk::{gdk, gio, glib};
 use crate::config::{self, APP_ID, PKGDATADIR, PROFILE, VERSION};
 use crate::i18n::i18n;
 use crate::ui::dialogs::settings_dialog::ResSettingsDialog;
+use crate::ui::remote_window::ResRemoteWindow;
 use crate::ui::window::MainWindow;
+use crate::utils::autostart::BACKGROUND_OPTION;
+use crate::utils::demo::{self, DEMO_OPTION};
+use crate::utils::remote::RemoteHost;
+use crate::utils::settings::SETTINGS;
+use crate::utils::{host_path, IS_FLATPAK};
+
+/// The command line option that starts Resources in kiosk mode
+pub const KIOSK_OPTION: &str = "kiosk";
 
 mod imp {
-    use std::sync::OnceLock;
+    use std::{cell::Cell, sync::OnceLock};
 
     use super::*;
     use glib::WeakRef;
@@ -18,6 +27,8 @@ mod imp {
     #[derive(Debug, Default)]
     pub struct Application {
         pub window: OnceLock<WeakRef<MainWindow>>,
+        pub start_in_background: Cell<bool>,
+        pub start_in_kiosk_mode: Cell<bool>,
     }
 
     #[glib::object_subclass]
@@ -42,11 +53,48 @@ mod imp {
             }
 
             let window = MainWindow::new(&app);
+            window.set_hide_on_close(SETTINGS.run_in_background());
             self.window
                 .set(window.downgrade())
                 .expect("Window already set.");
 
-            app.main_window().present();
+            // the hidden window keeps the application alive and collects data
+            // until the user opens Resources for real
+            if self.start_in_background.replace(false) {
+                info!("Starting in the background");
+                return;
+            }
+
+            window.present();
+            if self.start_in_kiosk_mode.replace(false) {
+                window.set_kiosk_mode(true);
+            }
+        }
+
+        fn handle_local_options(&self, options: &glib::VariantDict) -> glib::ExitCode {
+            if options.contains(DEMO_OPTION) {
+                demo::enable();
+            }
+
+            if options.contains(KIOSK_OPTION) {
+                self.start_in_kiosk_mode.set(true);
+            }
+
+            if options.contains(BACKGROUND_OPTION) {
+                let app = self.obj();
+                if let Err(error) = app.register(None::<&gio::Cancellable>) {
+                    log::warn!("Unable to register application: {error}");
+                }
+
+                // don't pop up the window of an instance that's already running
+                if app.is_remote() {
+                    return glib::ExitCode::SUCCESS;
+                }
+
+                self.start_in_background.set(true);
+            }
+
+            self.parent_handle_local_options(options)
         }
 
         fn startup(&self) {
@@ -58,8 +106,20 @@ mod imp {
             gtk::Window::set_default_icon_name(APP_ID);
 
             app.setup_css();
+            if *IS_FLATPAK {
+                app.setup_icon_theme();
+            }
             app.setup_gactions();
             app.setup_accels();
+
+            SETTINGS.migrate_alert_rules();
+
+            // closing the window only hides it while running in the background
+            SETTINGS.connect_run_in_background(clone!(@weak app => move |run_in_background| {
+                if let Some(window) = app.imp().window.get().and_then(WeakRef::upgrade) {
+                    window.set_hide_on_close(run_in_background);
+                }
+            }));
         }
     }
 
@@ -76,11 +136,40 @@ glib::wrapper! {
 
 impl Application {
     pub fn new() -> Self {
-        glib::Object::builder::<Self>()
+        let app: Self = glib::Object::builder::<Self>()
             .property("application-id", Some(APP_ID))
             .property("flags", gio::ApplicationFlags::empty())
             .property("resource-base-path", Some("/net/nokyan/Resources/"))
-            .build()
+            .build();
+
+        app.add_main_option(
+            BACKGROUND_OPTION,
+            glib::Char::from(b'b'),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::None,
+            &i18n("Start without showing a window"),
+            None,
+        );
+
+        app.add_main_option(
+            DEMO_OPTION,
+            glib::Char::from(b'\0'),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::None,
+            &i18n("Show synthetic data instead of the data of this system"),
+            None,
+        );
+
+        app.add_main_option(
+            KIOSK_OPTION,
+            glib::Char::from(b'\0'),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::None,
+            &i18n("Start in fullscreen for a dedicated monitoring display"),
+            None,
+        );
+
+        app
     }
 
     fn main_window(&self) -> MainWindow {
@@ -104,6 +193,59 @@ impl Application {
         }));
         self.add_action(&action_settings);
 
+        // Mini Mode
+        let action_mini_mode = gio::SimpleAction::new("mini-mode", None);
+        action_mini_mode.connect_activate(clone!(@weak self as app => move |_, _| {
+            let window = app.main_window();
+            window.set_mini_mode(!window.is_mini_mode());
+        }));
+        self.add_action(&action_mini_mode);
+
+        // Kiosk Mode
+        let action_kiosk_mode = gio::SimpleAction::new("kiosk-mode", None);
+        action_kiosk_mode.connect_activate(clone!(@weak self as app => move |_, _| {
+            let window = app.main_window();
+            window.set_kiosk_mode(!window.is_kiosk_mode());
+        }));
+        self.add_action(&action_kiosk_mode);
+
+        // Compare
+        let action_compare = gio::SimpleAction::new("compare", None);
+        action_compare.connect_activate(clone!(@weak self as app => move |_, _| {
+            let window = app.main_window();
+            window.set_comparing(!window.is_comparing());
+        }));
+        self.add_action(&action_compare);
+
+        // History
+        let action_browse_history = gio::SimpleAction::new("browse-history", None);
+        action_browse_history.connect_activate(clone!(@weak self as app => move |_, _| {
+            let window = app.main_window();
+            window.set_browsing_history(!window.is_browsing_history());
+        }));
+        self.add_action(&action_browse_history);
+
+        // Remote Host
+        let action_connect_remote = gio::SimpleAction::new("connect-remote", None);
+        action_connect_remote.connect_activate(clone!(@weak self as app => move |_, _| {
+            app.show_connect_remote_dialog();
+        }));
+        self.add_action(&action_connect_remote);
+
+        // Show a process, e.g. for crash handlers or shell extensions:
+        // gapplication action net.nokyan.Resources show-process 1234
+        let action_show_process =
+            gio::SimpleAction::new("show-process", Some(glib::VariantTy::INT32));
+        action_show_process.connect_activate(clone!(@weak self as app => move |_, parameter| {
+            let Some(pid) = parameter.and_then(glib::Variant::get::<i32>) else {
+                return;
+            };
+            // also creates the window if Resources has been started just for this
+            app.activate();
+            app.main_window().reveal_process(pid);
+        }));
+        self.add_action(&action_show_process);
+
         // About
         let action_about = gio::SimpleAction::new("about", None);
         action_about.connect_activate(clone!(@weak self as app => move |_, _| {
@@ -115,6 +257,9 @@ impl Application {
     // Sets up keyboard shortcuts
     fn setup_accels(&self) {
         self.set_accels_for_action("app.quit", &["<Control>q"]);
+        self.set_accels_for_action("app.mini-mode", &["<Control>m"]);
+        self.set_accels_for_action("app.kiosk-mode", &["F11"]);
+        self.set_accels_for_action("app.browse-history", &["<Control>h"]);
     }
 
     fn setup_css(&self) {
@@ -129,6 +274,16 @@ impl Application {
         }
     }
 
+    // Inside the Flatpak sandbox, the icons of applications installed on the host
+    // aren't part of the icon theme's search path by default
+    fn setup_icon_theme(&self) {
+        if let Some(display) = gdk::Display::default() {
+            let icon_theme = gtk::IconTheme::for_display(&display);
+            icon_theme.add_search_path(host_path("/usr/share/icons"));
+            icon_theme.add_search_path(host_path("/usr/share/pixmaps"));
+        }
+    }
+
     fn show_settings_dialog(&self) {
         let settings = ResSettingsDialog::new();
 
@@ -140,6 +295,45 @@ impl Application {
         settings.present();
     }
 
+    fn show_connect_remote_dialog(&self) {
+        let entry = gtk::Entry::builder()
+            .placeholder_text("user@example.com")
+            .activates_default(true)
+            .build();
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&self.main_window())
+            .modal(true)
+            .heading(i18n("Connect to Remote Host"))
+            .body(i18n(
+                "Enter the SSH destination of the machine you want to monitor. Only key-based authentication is supported.",
+            ))
+            .extra_child(&entry)
+            .build();
+
+        dialog.add_response("cancel", &i18n("Cancel"));
+        dialog.add_response("connect", &i18n("Connect"));
+        dialog.set_response_appearance("connect", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("connect"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            Some("connect"),
+            clone!(@weak self as app, @weak entry => move |_, _| {
+                match RemoteHost::new(entry.text()) {
+                    Ok(host) => {
+                        let window = ResRemoteWindow::new(&app);
+                        window.init(host);
+                        window.present();
+                    }
+                    Err(err) => log::warn!("Unable to connect to remote host: {}", err),
+                }
+            }),
+        );
+
+        dialog.present();
+    }
+
     fn show_about_dialog(&self) {
         let about = adw::AboutWindow::builder()
             .application_name(i18n("Resources"))
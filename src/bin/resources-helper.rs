@@ -0,0 +1,413 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context, Result};
+use gio::glib::{self, ToVariant};
+use once_cell::sync::Lazy;
+use process_data::SchedulingPolicy;
+
+// the helper runs as root, so it only shares the D-Bus interface with the GUI
+// instead of linking all of it
+#[path = "../utils/helper_interface.rs"]
+mod helper_interface;
+
+use helper_interface::{
+    Capability, HELPER_BUS_NAME, HELPER_INTERFACE, HELPER_INTROSPECTION_XML, HELPER_OBJECT_PATH,
+};
+
+/// The helper is D-Bus activated, so there's no need to keep it around when
+/// nobody uses it
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+const SMARTCTL_PATHS: &[&str] = &["/usr/sbin/smartctl", "/usr/bin/smartctl", "/sbin/smartctl"];
+
+const CHRT_PATHS: &[&str] = &["/usr/bin/chrt", "/bin/chrt"];
+
+static LAST_ACTIVITY: Lazy<Mutex<Instant>> = Lazy::new(|| Mutex::new(Instant::now()));
+
+fn main() {
+    pretty_env_logger::init();
+
+    let main_loop = glib::MainLoop::new(None, false);
+
+    let name_lost_loop = main_loop.clone();
+    let _owner_id = gio::bus_own_name(
+        gio::BusType::System,
+        HELPER_BUS_NAME,
+        gio::BusNameOwnerFlags::NONE,
+        |connection, _| {
+            let interface_info = gio::DBusNodeInfo::for_xml(HELPER_INTROSPECTION_XML)
+                .expect("invalid introspection data")
+                .lookup_interface(HELPER_INTERFACE)
+                .expect("introspection data is missing the helper interface");
+
+            if let Err(error) = connection.register_object(
+                HELPER_OBJECT_PATH,
+                &interface_info,
+                method_call,
+                // GDBus answers Get and Set for properties that the interface
+                // doesn't declare (all of them) with an error before these are
+                // called, but a client must never be able to crash the helper
+                |_, _, _, _, property| {
+                    log::warn!("Refusing to get unknown property {property}");
+                    ().to_variant()
+                },
+                |_, _, _, _, _, _| false,
+            ) {
+                log::error!("Unable to register helper object: {error}");
+            }
+        },
+        |_, _| {},
+        move |_, _| name_lost_loop.quit(),
+    );
+
+    let idle_loop = main_loop.clone();
+    glib::timeout_add_seconds(10, move || {
+        if LAST_ACTIVITY.lock().unwrap().elapsed() >= IDLE_TIMEOUT {
+            idle_loop.quit();
+            glib::ControlFlow::Break
+        } else {
+            glib::ControlFlow::Continue
+        }
+    });
+
+    main_loop.run();
+}
+
+fn method_call(
+    connection: gio::DBusConnection,
+    sender: &str,
+    _object_path: &str,
+    _interface_name: &str,
+    method_name: &str,
+    parameters: glib::Variant,
+    invocation: gio::DBusMethodInvocation,
+) {
+    *LAST_ACTIVITY.lock().unwrap() = Instant::now();
+
+    let Some(capability) = Capability::from_method_name(method_name) else {
+        invocation.return_dbus_error(
+            "org.freedesktop.DBus.Error.UnknownMethod",
+            &format!("unknown method {method_name}"),
+        );
+        return;
+    };
+
+    let sender = sender.to_string();
+    glib::MainContext::default().spawn_local(async move {
+        match is_authorized(&connection, &sender, capability).await {
+            Ok(true) => match handle_call(capability, &parameters) {
+                Ok(reply) => invocation.return_value(reply.as_ref()),
+                Err(error) => {
+                    log::warn!("{} failed: {error}", capability.method_name());
+                    invocation
+                        .return_dbus_error("org.freedesktop.DBus.Error.Failed", &error.to_string());
+                }
+            },
+            Ok(false) => {
+                log::info!(
+                    "{sender} is not authorized for {}",
+                    capability.polkit_action()
+                );
+                invocation.return_dbus_error(
+                    "org.freedesktop.DBus.Error.AccessDenied",
+                    &format!("not authorized for {}", capability.polkit_action()),
+                );
+            }
+            Err(error) => {
+                log::warn!("Unable to check authorization of {sender}: {error}");
+                invocation
+                    .return_dbus_error("org.freedesktop.DBus.Error.AuthFailed", &error.to_string());
+            }
+        }
+        *LAST_ACTIVITY.lock().unwrap() = Instant::now();
+    });
+}
+
+/// Asks polkit whether `sender` may use `capability`, allowing polkit to
+/// prompt the user for authentication
+async fn is_authorized(
+    connection: &gio::DBusConnection,
+    sender: &str,
+    capability: Capability,
+) -> Result<bool> {
+    let subject_details = HashMap::from([("name".to_string(), sender.to_variant())]);
+    let subject = ("system-bus-name", subject_details);
+    let details: HashMap<String, String> = HashMap::new();
+    // 1 = AllowUserInteraction
+    let parameters = (subject, capability.polkit_action(), details, 1u32, "").to_variant();
+
+    let reply = connection
+        .call_future(
+            Some("org.freedesktop.PolicyKit1"),
+            "/org/freedesktop/PolicyKit1/Authority",
+            "org.freedesktop.PolicyKit1.Authority",
+            "CheckAuthorization",
+            Some(&parameters),
+            glib::VariantTy::new("((bba{ss}))").ok(),
+            gio::DBusCallFlags::NONE,
+            i32::MAX,
+        )
+        .await
+        .context("unable to check authorization with polkit")?;
+
+    reply
+        .child_value(0)
+        .child_value(0)
+        .get::<bool>()
+        .context("unexpected reply from polkit")
+}
+
+fn smartctl_path() -> Option<&'static str> {
+    SMARTCTL_PATHS
+        .iter()
+        .find(|path| Path::new(path).exists())
+        .copied()
+}
+
+fn chrt_path() -> Option<&'static str> {
+    CHRT_PATHS
+        .iter()
+        .find(|path| Path::new(path).exists())
+        .copied()
+}
+
+fn handle_call(
+    capability: Capability,
+    parameters: &glib::Variant,
+) -> Result<Option<glib::Variant>> {
+    match capability {
+        Capability::ProcessIo => {
+            let (pid,) = parameters.get::<(i32,)>().context("invalid parameters")?;
+            process_io(pid).map(|io| Some(io.to_variant()))
+        }
+        Capability::SmartData => {
+            let (device,) = parameters
+                .get::<(String,)>()
+                .context("invalid parameters")?;
+            smart_data(&device).map(|json| Some((json,).to_variant()))
+        }
+        Capability::DropCaches => {
+            let (level,) = parameters.get::<(u32,)>().context("invalid parameters")?;
+            drop_caches(level).map(|_| None)
+        }
+        Capability::SetGovernor => {
+            let (cpu, governor) = parameters
+                .get::<(u32, String)>()
+                .context("invalid parameters")?;
+            set_governor(cpu, &governor).map(|_| None)
+        }
+        Capability::SetEnergyPerformancePreference => {
+            let (cpu, preference) = parameters
+                .get::<(u32, String)>()
+                .context("invalid parameters")?;
+            set_energy_performance_preference(cpu, &preference).map(|_| None)
+        }
+        Capability::SetSysctl => {
+            let (path, value) = parameters
+                .get::<(String, String)>()
+                .context("invalid parameters")?;
+            set_sysctl(&path, &value).map(|_| None)
+        }
+        Capability::SetScheduling => {
+            let (pid, policy, priority) = parameters
+                .get::<(i32, String, u32)>()
+                .context("invalid parameters")?;
+            set_scheduling(pid, &policy, priority).map(|_| None)
+        }
+        Capability::SetIoScheduler => {
+            let (device, scheduler) = parameters
+                .get::<(String, String)>()
+                .context("invalid parameters")?;
+            set_io_scheduler(&device, &scheduler).map(|_| None)
+        }
+    }
+}
+
+fn process_io(pid: i32) -> Result<(u64, u64)> {
+    if pid <= 0 {
+        bail!("invalid PID {pid}");
+    }
+
+    let io = std::fs::read_to_string(format!("/proc/{pid}/io"))
+        .with_context(|| format!("unable to read I/O stats of process {pid}"))?;
+
+    let field = |name: &str| -> Result<u64> {
+        io.lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|value| value.trim().parse().ok())
+            .with_context(|| format!("unable to find {name} for process {pid}"))
+    };
+
+    Ok((field("read_bytes:")?, field("write_bytes:")?))
+}
+
+fn smart_data(device: &str) -> Result<String> {
+    // only accept plain block device names so that the caller can't make us
+    // open arbitrary files
+    if device.is_empty()
+        || !device.chars().all(|c| c.is_ascii_alphanumeric())
+        || !Path::new("/sys/block").join(device).exists()
+    {
+        bail!("invalid block device {device}");
+    }
+
+    let smartctl = smartctl_path().context("smartctl is not installed")?;
+    let output = std::process::Command::new(smartctl)
+        .args(["--json", "--all"])
+        .arg(Path::new("/dev").join(device))
+        .output()
+        .context("unable to run smartctl")?;
+
+    // smartctl uses its exit status as a bit mask that is also set for
+    // warnings about the drive itself, so only fail if there's no output
+    let json = String::from_utf8(output.stdout).context("smartctl output is not UTF-8")?;
+    if json.trim().is_empty() {
+        bail!("smartctl failed with {}", output.status);
+    }
+
+    Ok(json)
+}
+
+fn drop_caches(level: u32) -> Result<()> {
+    if !(1..=3).contains(&level) {
+        bail!("invalid drop_caches level {level}");
+    }
+
+    std::fs::write("/proc/sys/vm/drop_caches", level.to_string())
+        .context("unable to write to /proc/sys/vm/drop_caches")
+}
+
+fn set_governor(cpu: u32, governor: &str) -> Result<()> {
+    let cpufreq = Path::new("/sys/devices/system/cpu")
+        .join(format!("cpu{cpu}"))
+        .join("cpufreq");
+
+    let available = std::fs::read_to_string(cpufreq.join("scaling_available_governors"))
+        .with_context(|| format!("unable to read available governors of CPU {cpu}"))?;
+    if !available.split_whitespace().any(|g| g == governor) {
+        bail!("governor {governor} is not available for CPU {cpu}");
+    }
+
+    std::fs::write(cpufreq.join("scaling_governor"), governor)
+        .with_context(|| format!("unable to set governor of CPU {cpu}"))
+}
+
+fn set_energy_performance_preference(cpu: u32, preference: &str) -> Result<()> {
+    let cpufreq = Path::new("/sys/devices/system/cpu")
+        .join(format!("cpu{cpu}"))
+        .join("cpufreq");
+
+    let available =
+        std::fs::read_to_string(cpufreq.join("energy_performance_available_preferences"))
+            .with_context(|| {
+                format!("unable to read available energy-performance preferences of CPU {cpu}")
+            })?;
+    if !available.split_whitespace().any(|p| p == preference) {
+        bail!("energy-performance preference {preference} is not available for CPU {cpu}");
+    }
+
+    std::fs::write(cpufreq.join("energy_performance_preference"), preference)
+        .with_context(|| format!("unable to set energy-performance preference of CPU {cpu}"))
+}
+
+fn set_sysctl(path: &str, value: &str) -> Result<()> {
+    // only accept plain relative paths so that the caller can't make us write
+    // anywhere outside of /proc/sys
+    if path.is_empty()
+        || path.starts_with('/')
+        || path
+            .split('/')
+            .any(|segment| segment.is_empty() || segment == "." || segment == "..")
+    {
+        bail!("invalid kernel parameter {path}");
+    }
+
+    let full_path = Path::new("/proc/sys")
+        .join(path)
+        .canonicalize()
+        .with_context(|| format!("unable to find kernel parameter {path}"))?;
+    if !full_path.starts_with("/proc/sys") || !full_path.is_file() {
+        bail!("invalid kernel parameter {path}");
+    }
+
+    std::fs::write(&full_path, value)
+        .with_context(|| format!("unable to set kernel parameter {path}"))
+}
+
+fn set_scheduling(pid: i32, policy: &str, priority: u32) -> Result<()> {
+    if pid <= 0 {
+        bail!("invalid PID {pid}");
+    }
+
+    // deadline scheduling needs a runtime, deadline and period, which this
+    // doesn't offer
+    let policy = match SchedulingPolicy::from_name(policy) {
+        Some(policy) if policy != SchedulingPolicy::Deadline => policy,
+        _ => bail!("unsupported scheduling policy {policy}"),
+    };
+    let valid_priority = if policy.is_realtime() {
+        (1..=99).contains(&priority)
+    } else {
+        priority == 0
+    };
+    if !valid_priority {
+        bail!(
+            "invalid priority {priority} for scheduling policy {}",
+            policy.name()
+        );
+    }
+
+    let chrt = chrt_path().context("chrt is not installed")?;
+    let output = std::process::Command::new(chrt)
+        .arg(format!("--{}", policy.name()))
+        // without it only the main thread would be rescheduled
+        .arg("--all-tasks")
+        .arg("--pid")
+        .arg(priority.to_string())
+        .arg(pid.to_string())
+        .output()
+        .context("unable to run chrt")?;
+
+    if !output.status.success() {
+        bail!(
+            "chrt failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
+
+fn set_io_scheduler(device: &str, scheduler: &str) -> Result<()> {
+    // only accept plain block device names so that the caller can't make us
+    // write to arbitrary files
+    if device.is_empty()
+        || !device.chars().all(|c| c.is_ascii_alphanumeric())
+        || !Path::new("/sys/block").join(device).exists()
+    {
+        bail!("invalid block device {device}");
+    }
+
+    let scheduler_path = Path::new("/sys/block")
+        .join(device)
+        .join("queue")
+        .join("scheduler");
+
+    let available = std::fs::read_to_string(&scheduler_path)
+        .with_context(|| format!("unable to read I/O schedulers of {device}"))?;
+    if !available
+        .split_whitespace()
+        .map(|s| s.trim_start_matches('[').trim_end_matches(']'))
+        .any(|s| s == scheduler)
+    {
+        bail!("I/O scheduler {scheduler} is not available for {device}");
+    }
+
+    std::fs::write(&scheduler_path, scheduler)
+        .with_context(|| format!("unable to set I/O scheduler of {device}"))
+}
@@ -1,33 +1,32 @@
 use anyhow::{Context, Result};
-use async_std::sync::Arc;
-use async_std::sync::Mutex;
-use futures_util::future::join_all;
-use glob::glob;
-use process_data::ProcessData;
+use process_data::{FileActivity, ProcessData};
 
-#[async_std::main]
-async fn main() -> Result<()> {
-    let return_vec = Arc::new(Mutex::new(Vec::new()));
+/// Makes this print the file activity of the given PID instead of the data of
+/// all processes
+const FILE_ACTIVITY_ARG: &str = "--file-activity";
 
-    let mut handles = vec![];
-    for entry in glob("/proc/[0-9]*/").context("unable to glob")?.flatten() {
-        let return_vec = Arc::clone(&return_vec);
+/// Makes this count the open files of every process as well
+const OPEN_FILES_ARG: &str = "--with-open-files";
 
-        let handle = async_std::task::spawn(async move {
-            if let Ok(process_data) = ProcessData::try_from_path(entry).await {
-                return_vec.lock().await.push(process_data);
-            }
-        });
+#[async_std::main]
+async fn main() -> Result<()> {
+    let stdout = std::io::stdout();
+    let mut handle = stdout.lock();
 
-        handles.push(handle);
+    let args: Vec<String> = std::env::args().collect();
+    if let [_, arg, pid] = args.as_slice() {
+        if arg == FILE_ACTIVITY_ARG {
+            let pid = pid.parse().context("invalid PID")?;
+            let file_activity = FileActivity::of_pid(pid)?;
+            rmp_serde::encode::write(&mut handle, &file_activity)?;
+            return Ok(());
+        }
     }
-    join_all(handles).await;
 
-    let stdout = std::io::stdout();
-    let mut handle = stdout.lock();
+    let with_open_files = args.iter().any(|arg| arg == OPEN_FILES_ARG);
+    let return_vec = ProcessData::all(with_open_files).await?;
 
-    let return_vec = return_vec.lock().await;
-    rmp_serde::encode::write(&mut handle, &*return_vec).unwrap();
+    rmp_serde::encode::write(&mut handle, &return_vec).unwrap();
 
     Ok(())
 }
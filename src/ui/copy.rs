@@ -0,0 +1,142 @@
+use adw::prelude::*;
+use gtk::{
+    gdk, gio,
+    glib::{self, clone},
+};
+
+use crate::i18n::i18n;
+use crate::ui::graph_window::ResGraphWindow;
+use crate::ui::pages::dashboard;
+use crate::ui::widgets::graph_box::ResGraphBox;
+
+/// Lets the user copy the statistics shown on `page` as text, either with a
+/// right click on a row or by pressing Ctrl+C while a row is focused.
+///
+/// This works on whatever rows are below `page` at the time of the click, so
+/// it also covers rows that are added after this has been called.
+pub fn setup_copy_actions(page: &impl IsA<gtk::Widget>) {
+    let page = page.as_ref();
+
+    let gesture = gtk::GestureClick::builder()
+        .button(gdk::BUTTON_SECONDARY)
+        .build();
+    gesture.connect_pressed(clone!(@weak page => move |gesture, _, x, y| {
+        let Some(row) = page
+            .pick(x, y, gtk::PickFlags::DEFAULT)
+            .and_then(|picked| copyable_row(&picked))
+        else {
+            return;
+        };
+        gesture.set_state(gtk::EventSequenceState::Claimed);
+
+        let (x, y) = page.translate_coordinates(&row, x, y).unwrap_or((x, y));
+        show_copy_menu(&row, x as i32, y as i32);
+    }));
+    page.add_controller(gesture);
+
+    let shortcut = gtk::Shortcut::new(
+        gtk::ShortcutTrigger::parse_string("<Control>c"),
+        Some(gtk::CallbackAction::new(|page, _| {
+            let row = page
+                .root()
+                .and_then(|root| root.focus())
+                .and_then(|focus| copyable_row(&focus));
+
+            if let Some(text) = row.as_ref().and_then(row_text) {
+                page.clipboard().set_text(&text);
+                glib::Propagation::Stop
+            } else {
+                glib::Propagation::Proceed
+            }
+        })),
+    );
+    let controller = gtk::ShortcutController::new();
+    controller.add_shortcut(shortcut);
+    page.add_controller(controller);
+}
+
+/// Finds the row `widget` belongs to if that row shows something worth copying
+fn copyable_row(widget: &gtk::Widget) -> Option<gtk::Widget> {
+    widget
+        .ancestor(ResGraphBox::static_type())
+        .or_else(|| widget.ancestor(adw::ActionRow::static_type()))
+        .filter(|row| row_text(row).is_some())
+}
+
+/// Returns the text that is copied for `row`, e.g. "Temperature: 52 °C"
+fn row_text(row: &gtk::Widget) -> Option<String> {
+    if let Some(graph_box) = row.downcast_ref::<ResGraphBox>() {
+        return graph_box.copy_text();
+    }
+
+    let row = row.downcast_ref::<adw::ActionRow>()?;
+    let title = row.title();
+    let subtitle = row.subtitle().unwrap_or_default();
+
+    match (title.trim().is_empty(), subtitle.trim().is_empty()) {
+        (true, true) => None,
+        (false, true) => Some(title.trim().to_string()),
+        (true, false) => Some(subtitle.trim().to_string()),
+        (false, false) => Some(format!("{}: {}", title.trim(), subtitle.trim())),
+    }
+}
+
+fn show_copy_menu(row: &gtk::Widget, x: i32, y: i32) {
+    let copy_action = gio::SimpleAction::new("copy", None);
+    copy_action.connect_activate(clone!(@weak row => move |_, _| {
+        if let Some(text) = row_text(&row) {
+            row.clipboard().set_text(&text);
+        }
+    }));
+    let action_group = gio::SimpleActionGroup::new();
+    action_group.add_action(&copy_action);
+    row.insert_action_group("stat", Some(&action_group));
+
+    let menu = gio::Menu::new();
+    menu.append(Some(&i18n("Copy")), Some("stat.copy"));
+
+    if let Some(graph_box) = row.downcast_ref::<ResGraphBox>() {
+        let detach_action = gio::SimpleAction::new("detach", None);
+        detach_action.connect_activate(clone!(@weak graph_box => move |_, _| {
+            let Some(app) = graph_box
+                .root()
+                .and_downcast::<gtk::Window>()
+                .and_then(|window| window.application())
+            else {
+                return;
+            };
+            ResGraphWindow::new(&app, &graph_box).present();
+        }));
+        action_group.add_action(&detach_action);
+        menu.append(Some(&i18n("Open in New Window")), Some("stat.detach"));
+    }
+
+    let dashboard_key = row
+        .downcast_ref::<ResGraphBox>()
+        .and_then(ResGraphBox::dashboard_key);
+    if let Some(dashboard_key) = dashboard_key {
+        let pinned = dashboard::is_pinned(&dashboard_key);
+
+        let pin_action = gio::SimpleAction::new("pin", None);
+        pin_action.connect_activate(move |_, _| dashboard::set_pinned(&dashboard_key, !pinned));
+        action_group.add_action(&pin_action);
+
+        let label = if pinned {
+            i18n("Unpin from Dashboard")
+        } else {
+            i18n("Pin to Dashboard")
+        };
+        menu.append(Some(&label), Some("stat.pin"));
+    }
+
+    let popover = gtk::PopoverMenu::from_model(Some(&menu));
+    popover.set_parent(row);
+    popover.set_has_arrow(false);
+    popover.set_halign(gtk::Align::Start);
+    popover.set_pointing_to(Some(&gdk::Rectangle::new(x, y, 1, 1)));
+    popover.connect_closed(|popover| {
+        // unparent once the menu item's action has had its chance to run
+        glib::idle_add_local_once(clone!(@weak popover => move || popover.unparent()));
+    });
+    popover.popup();
+}
@@ -1,14 +1,18 @@
 use adw::{prelude::*, subclass::prelude::*};
-use gtk::glib;
+use gtk::glib::{self, clone};
 use process_data::Containerization;
 
 use crate::config::PROFILE;
 use crate::i18n::i18n;
+use crate::ui::copy::setup_copy_actions;
+use crate::ui::dialogs::limits_dialog::show_limits_dialog;
 use crate::ui::window::MainWindow;
 use crate::utils::app::AppItem;
+use crate::utils::systemd::SystemdUnit;
 use crate::utils::units::convert_storage;
 
 mod imp {
+    use std::cell::OnceCell;
 
     use super::*;
 
@@ -33,6 +37,10 @@ mod imp {
         pub processes_amount: TemplateChild<adw::ActionRow>,
         #[template_child]
         pub containerized: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub resource_limits: TemplateChild<adw::ActionRow>,
+
+        pub unit: OnceCell<SystemdUnit>,
     }
 
     #[glib::object_subclass]
@@ -81,6 +89,19 @@ impl ResAppDialog {
     pub fn init(&self, app: &AppItem) {
         self.set_transient_for(Some(&MainWindow::default()));
         self.setup_widgets(app);
+        self.setup_signals();
+        setup_copy_actions(self);
+    }
+
+    pub fn setup_signals(&self) {
+        let imp = self.imp();
+
+        imp.resource_limits
+            .connect_activated(clone!(@weak self as this => move |_| {
+                if let Some(unit) = this.imp().unit.get().cloned() {
+                    show_limits_dialog(&this, unit);
+                }
+            }));
     }
 
     pub fn setup_widgets(&self, app: &AppItem) {
@@ -113,6 +134,13 @@ impl ResAppDialog {
             Containerization::Flatpak => i18n("Yes (Flatpak)"),
         };
         imp.containerized.set_subtitle(&containerized);
+
+        // apps that have been started by the desktop run in a scope of their own
+        if let Some(unit) = app.pids.iter().find_map(|pid| SystemdUnit::of_pid(*pid)) {
+            imp.resource_limits.set_subtitle(&unit.name);
+            imp.resource_limits.set_visible(true);
+            imp.unit.set(unit).unwrap_or_default();
+        }
     }
 
     pub fn set_cpu_usage(&self, usage: f32) {
@@ -0,0 +1,289 @@
+use std::time::{Duration, Instant};
+
+use adw::{prelude::*, subclass::prelude::*};
+use anyhow::Result;
+use gtk::glib::{self, clone, timeout_future, MainContext};
+use hashbrown::HashMap;
+use process_data::{FileAccess, FileActivity, OpenFile};
+
+use crate::config::PROFILE;
+use crate::i18n::{i18n, i18n_f};
+use crate::ui::copy::setup_copy_actions;
+use crate::utils::helper;
+use crate::utils::process::Process;
+use crate::utils::settings::SETTINGS;
+use crate::utils::units::convert_speed;
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    use gtk::CompositeTemplate;
+
+    #[derive(Debug, CompositeTemplate, Default)]
+    #[template(resource = "/net/nokyan/Resources/ui/dialogs/file_activity_dialog.ui")]
+    pub struct ResFileActivityDialog {
+        #[template_child]
+        pub window_title: TemplateChild<adw::WindowTitle>,
+        #[template_child]
+        pub authentication_banner: TemplateChild<adw::Banner>,
+        #[template_child]
+        pub reading: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub writing: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub files_list: TemplateChild<gtk::ListBox>,
+
+        pub pid: Cell<i32>,
+        pub last_activity: RefCell<Option<(Instant, FileActivity)>>,
+        /// Whether the user has agreed to read the process's I/O through
+        /// resources-helper
+        pub privileged: Cell<bool>,
+        pub watching: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResFileActivityDialog {
+        const NAME: &'static str = "ResFileActivityDialog";
+        type Type = super::ResFileActivityDialog;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        // You must call `Widget`'s `init_template()` within `instance_init()`.
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ResFileActivityDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // Devel Profile
+            if PROFILE == "Devel" {
+                obj.add_css_class("devel");
+            }
+        }
+    }
+
+    impl WidgetImpl for ResFileActivityDialog {}
+    impl WindowImpl for ResFileActivityDialog {}
+    impl AdwWindowImpl for ResFileActivityDialog {}
+}
+
+glib::wrapper! {
+    pub struct ResFileActivityDialog(ObjectSubclass<imp::ResFileActivityDialog>)
+        @extends gtk::Widget, gtk::Window, adw::Window;
+}
+
+impl Default for ResFileActivityDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResFileActivityDialog {
+    pub fn new() -> Self {
+        glib::Object::new::<Self>()
+    }
+
+    pub fn init(&self, pid: i32, display_name: &str) {
+        let imp = self.imp();
+
+        imp.pid.set(pid);
+        imp.window_title.set_subtitle(display_name);
+        imp.files_list.set_placeholder(Some(
+            &gtk::Label::builder()
+                .label(i18n("No open files"))
+                .margin_top(12)
+                .margin_bottom(12)
+                .css_classes(vec!["dim-label"])
+                .build(),
+        ));
+
+        setup_copy_actions(self);
+
+        let display_name = display_name.to_owned();
+        imp.authentication_banner.connect_button_clicked(
+            clone!(@weak self as this => move |banner| {
+                let imp = this.imp();
+                banner.set_revealed(false);
+                imp.privileged.set(true);
+                if !imp.watching.get() {
+                    imp.window_title.set_subtitle(&display_name);
+                    this.watch();
+                }
+            }),
+        );
+
+        self.watch();
+    }
+
+    fn watch(&self) {
+        self.imp().watching.set(true);
+        // the process is only watched while the dialog is open
+        MainContext::default().spawn_local(Self::refresh_loop(self.downgrade()));
+    }
+
+    /// Reads the file activity of the process with `pid`. If `privileged` is
+    /// set, the bytes read and written that the process's owner hides from us
+    /// are asked from resources-helper, which makes the open files optional.
+    async fn file_activity(pid: i32, privileged: bool) -> Result<FileActivity> {
+        let file_activity = Process::file_activity(pid).await;
+        if !privileged {
+            return file_activity;
+        }
+
+        let mut file_activity = match file_activity {
+            Ok(file_activity) if file_activity.read_bytes.is_some() => return Ok(file_activity),
+            Ok(file_activity) => file_activity,
+            Err(error) => {
+                log::debug!("Unable to read open files of process {pid}: {error}");
+                FileActivity::default()
+            }
+        };
+
+        let (read_bytes, written_bytes) = helper::process_io(pid).await?;
+        file_activity.read_bytes = Some(read_bytes);
+        file_activity.written_bytes = Some(written_bytes);
+
+        Ok(file_activity)
+    }
+
+    // only holds a weak reference between refreshes so that closing the dialog
+    // also ends the loop
+    async fn refresh_loop(this: glib::WeakRef<Self>) {
+        loop {
+            let Some(dialog) = this.upgrade() else {
+                return;
+            };
+            let pid = dialog.imp().pid.get();
+            let privileged = dialog.imp().privileged.get();
+            drop(dialog);
+
+            let file_activity = Self::file_activity(pid, privileged).await;
+
+            let Some(dialog) = this.upgrade() else {
+                return;
+            };
+            let imp = dialog.imp();
+            match file_activity {
+                Ok(file_activity) => {
+                    if file_activity.read_bytes.is_none() {
+                        imp.authentication_banner.set_revealed(true);
+                    }
+                    dialog.refresh(file_activity);
+                }
+                Err(error) => {
+                    log::warn!("Unable to read file activity of process {pid}: {error}");
+                    imp.window_title
+                        .set_subtitle(&i18n_f("Unable to watch process {}", &[&pid.to_string()]));
+                    // a process of another user might still be watched with
+                    // the helper's help
+                    imp.authentication_banner.set_revealed(!privileged);
+                    imp.watching.set(false);
+                    return;
+                }
+            }
+            drop(dialog);
+
+            timeout_future(Duration::from_secs_f32(
+                SETTINGS.refresh_speed().process_refresh_interval(),
+            ))
+            .await;
+        }
+    }
+
+    /// Compares `file_activity` to the previous snapshot and shows the rates
+    /// that result from that
+    fn refresh(&self, file_activity: FileActivity) {
+        let imp = self.imp();
+
+        let now = Instant::now();
+        let previous = imp.last_activity.take();
+
+        // the first snapshot only serves as a baseline for the next one
+        let seconds = previous.as_ref().map_or(1.0, |(timestamp, _)| {
+            now.duration_since(*timestamp)
+                .as_secs_f64()
+                .max(f64::EPSILON)
+        });
+        let last_activity = previous.as_ref().map(|(_, last_activity)| last_activity);
+
+        let rate = |current: Option<u64>, last: Option<u64>| match (current, last) {
+            (Some(current), Some(last)) => Some(current.saturating_sub(last) as f64 / seconds),
+            _ => None,
+        };
+
+        let reading = rate(
+            file_activity.read_bytes,
+            last_activity.and_then(|last| last.read_bytes),
+        );
+        let writing = rate(
+            file_activity.written_bytes,
+            last_activity.and_then(|last| last.written_bytes),
+        );
+        imp.reading
+            .set_subtitle(&reading.map_or_else(|| i18n("N/A"), convert_speed));
+        imp.writing
+            .set_subtitle(&writing.map_or_else(|| i18n("N/A"), convert_speed));
+
+        let last_positions: HashMap<(u32, &str), u64> = last_activity
+            .map(|last| {
+                last.files
+                    .iter()
+                    .map(|file| ((file.fd, file.path.as_str()), file.position))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut files: Vec<(&OpenFile, f64)> = file_activity
+            .files
+            .iter()
+            .map(|file| {
+                // seeking backwards isn't activity
+                let rate = last_positions
+                    .get(&(file.fd, file.path.as_str()))
+                    .map_or(0.0, |last_position| {
+                        file.position.saturating_sub(*last_position) as f64 / seconds
+                    });
+                (file, rate)
+            })
+            .collect();
+        files.sort_by(|(a, rate_a), (b, rate_b)| rate_b.total_cmp(rate_a).then(a.fd.cmp(&b.fd)));
+
+        while let Some(row) = imp.files_list.row_at_index(0) {
+            imp.files_list.remove(&row);
+        }
+
+        for (file, rate) in files {
+            let subtitle = if rate > 0.0 {
+                let verb = match file.access {
+                    FileAccess::Read => i18n("Reading"),
+                    FileAccess::Write => i18n("Writing"),
+                    FileAccess::ReadWrite => i18n("Reading or writing"),
+                };
+                format!("{verb} · {}", convert_speed(rate))
+            } else {
+                i18n("Idle")
+            };
+
+            let row = adw::ActionRow::builder()
+                .title(glib::markup_escape_text(&file.path))
+                .subtitle(subtitle)
+                .title_lines(2)
+                .build();
+            if rate <= 0.0 {
+                row.add_css_class("dim-label");
+            }
+            imp.files_list.append(&row);
+        }
+
+        imp.last_activity.replace(Some((now, file_activity)));
+    }
+}
@@ -0,0 +1,259 @@
+use std::time::Duration;
+
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::glib::{self, clone, timeout_future, MainContext};
+
+use crate::config::PROFILE;
+use crate::i18n::{i18n, i18n_f};
+use crate::utils::journal::{self, JournalEntry, JournalSource, Priority};
+use crate::utils::settings::SETTINGS;
+
+/// How many entries are loaded at once
+const JOURNAL_LINES: usize = 500;
+
+mod imp {
+    use std::cell::{Cell, OnceCell, RefCell};
+
+    use super::*;
+
+    use gtk::CompositeTemplate;
+
+    #[derive(Debug, CompositeTemplate, Default)]
+    #[template(resource = "/net/nokyan/Resources/ui/dialogs/journal_dialog.ui")]
+    pub struct ResJournalDialog {
+        #[template_child]
+        pub window_title: TemplateChild<adw::WindowTitle>,
+        #[template_child]
+        pub priority_dropdown: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub follow_button: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub text_view: TemplateChild<gtk::TextView>,
+
+        pub source: OnceCell<JournalSource>,
+        pub cursor: RefCell<Option<String>>,
+        // increased on every reload so that slow journalctl calls that have been
+        // started before can't mix their entries into the new ones
+        pub generation: Cell<u64>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResJournalDialog {
+        const NAME: &'static str = "ResJournalDialog";
+        type Type = super::ResJournalDialog;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        // You must call `Widget`'s `init_template()` within `instance_init()`.
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ResJournalDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // Devel Profile
+            if PROFILE == "Devel" {
+                obj.add_css_class("devel");
+            }
+        }
+    }
+
+    impl WidgetImpl for ResJournalDialog {}
+    impl WindowImpl for ResJournalDialog {}
+    impl AdwWindowImpl for ResJournalDialog {}
+}
+
+glib::wrapper! {
+    pub struct ResJournalDialog(ObjectSubclass<imp::ResJournalDialog>)
+        @extends gtk::Widget, gtk::Window, adw::Window;
+}
+
+impl Default for ResJournalDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResJournalDialog {
+    pub fn new() -> Self {
+        glib::Object::new::<Self>()
+    }
+
+    pub fn init(&self, source: JournalSource) {
+        let imp = self.imp();
+
+        let subtitle = match &source {
+            JournalSource::Unit(unit) => unit.name.clone(),
+            JournalSource::Pid(pid) => i18n_f("Process {}", &[&pid.to_string()]),
+        };
+        imp.window_title.set_subtitle(&subtitle);
+        imp.source.set(source).unwrap_or_default();
+
+        self.setup_widgets();
+        self.setup_signals();
+
+        self.reload();
+        MainContext::default().spawn_local(Self::follow_loop(self.downgrade()));
+    }
+
+    fn setup_widgets(&self) {
+        let imp = self.imp();
+
+        imp.priority_dropdown
+            .set_selected(Priority::default() as u32);
+
+        let buffer = imp.text_view.buffer();
+        buffer.create_tag(Some("timestamp"), &[("foreground", &"#77767b")]);
+        buffer.create_tag(
+            Some("error"),
+            &[("foreground", &"#e01b24"), ("weight", &700)],
+        );
+        buffer.create_tag(Some("warning"), &[("foreground", &"#e5a50a")]);
+        buffer.create_mark(Some("end"), &buffer.end_iter(), false);
+    }
+
+    fn setup_signals(&self) {
+        let imp = self.imp();
+
+        imp.priority_dropdown
+            .connect_selected_notify(clone!(@weak self as this => move |_| this.reload()));
+
+        imp.follow_button.connect_active_notify(
+            clone!(@weak self as this => move |follow_button| {
+                if follow_button.is_active() {
+                    this.scroll_to_end();
+                }
+            }),
+        );
+    }
+
+    fn priority(&self) -> Priority {
+        Priority::from_repr(self.imp().priority_dropdown.selected() as u8).unwrap_or_default()
+    }
+
+    /// Throws away all shown entries and loads the latest ones again, e.g. after
+    /// the priority has been changed
+    fn reload(&self) {
+        let imp = self.imp();
+        let Some(source) = imp.source.get().cloned() else {
+            return;
+        };
+
+        let generation = imp.generation.get() + 1;
+        imp.generation.set(generation);
+        imp.cursor.replace(None);
+        imp.text_view.buffer().set_text("");
+
+        let priority = self.priority();
+        MainContext::default().spawn_local(clone!(@weak self as this => async move {
+            let entries = journal::entries(&source, priority, JOURNAL_LINES, None).await;
+            if this.imp().generation.get() != generation {
+                return;
+            }
+
+            match entries {
+                Ok(entries) if entries.is_empty() => this.append_text(&format!("{}\n", i18n("No journal entries")), Some("timestamp")),
+                Ok(entries) => this.append_entries(&entries),
+                Err(error) => {
+                    log::warn!("Unable to read the journal: {error}");
+                    this.append_text(&format!("{}\n", i18n("Unable to read the journal")), Some("error"));
+                }
+            }
+        }));
+    }
+
+    // only holds a weak reference between refreshes so that closing the dialog
+    // also ends the loop
+    async fn follow_loop(this: glib::WeakRef<Self>) {
+        loop {
+            timeout_future(Duration::from_secs_f32(
+                SETTINGS.refresh_speed().process_refresh_interval(),
+            ))
+            .await;
+
+            let Some(dialog) = this.upgrade() else {
+                return;
+            };
+            let imp = dialog.imp();
+
+            let Some(source) = imp.source.get().cloned() else {
+                return;
+            };
+            let cursor = imp.cursor.borrow().clone();
+            // only follow once reload() has loaded the latest entries
+            let (true, Some(cursor)) = (imp.follow_button.is_active(), cursor) else {
+                continue;
+            };
+            let generation = imp.generation.get();
+            let priority = dialog.priority();
+            drop(dialog);
+
+            let entries = journal::entries(&source, priority, JOURNAL_LINES, Some(&cursor)).await;
+
+            let Some(dialog) = this.upgrade() else {
+                return;
+            };
+            if dialog.imp().generation.get() == generation {
+                if let Ok(entries) = entries {
+                    dialog.append_entries(&entries);
+                }
+            }
+        }
+    }
+
+    fn append_entries(&self, entries: &[JournalEntry]) {
+        let imp = self.imp();
+
+        for entry in entries {
+            let timestamp = glib::DateTime::from_unix_local(entry.timestamp / 1_000_000)
+                .and_then(|date_time| date_time.format("%Y-%m-%d %H:%M:%S"))
+                .map(|timestamp| timestamp.to_string())
+                .unwrap_or_default();
+            self.append_text(&format!("{timestamp} "), Some("timestamp"));
+
+            let tag = match entry.priority {
+                Priority::Emergency | Priority::Alert | Priority::Critical | Priority::Error => {
+                    Some("error")
+                }
+                Priority::Warning => Some("warning"),
+                _ => None,
+            };
+            let line = match &entry.identifier {
+                Some(identifier) => format!("{identifier}: {}\n", entry.message),
+                None => format!("{}\n", entry.message),
+            };
+            self.append_text(&line, tag);
+        }
+
+        if let Some(last_entry) = entries.last() {
+            imp.cursor.replace(Some(last_entry.cursor.clone()));
+        }
+
+        if imp.follow_button.is_active() {
+            self.scroll_to_end();
+        }
+    }
+
+    fn append_text(&self, text: &str, tag: Option<&str>) {
+        let buffer = self.imp().text_view.buffer();
+        let mut end = buffer.end_iter();
+        match tag {
+            Some(tag) => buffer.insert_with_tags_by_name(&mut end, text, &[tag]),
+            None => buffer.insert(&mut end, text),
+        }
+    }
+
+    fn scroll_to_end(&self) {
+        let imp = self.imp();
+        if let Some(mark) = imp.text_view.buffer().mark("end") {
+            imp.text_view.scroll_mark_onscreen(&mark);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use adw::prelude::*;
+use gtk::glib::{clone, MainContext};
+
+use crate::i18n::{i18n, i18n_f};
+use crate::utils::systemd::{ResourceLimits, SystemdUnit};
+
+const MEBIBYTE: u64 = 1024 * 1024;
+
+/// Lets the user change the CPU and memory limits of `unit`. The limits are
+/// applied through systemd and last until the next reboot.
+pub fn show_limits_dialog(parent: &impl IsA<gtk::Window>, unit: SystemdUnit) {
+    let parent: gtk::Window = parent.as_ref().clone();
+    let logical_cpus = std::thread::available_parallelism().map_or(1, usize::from);
+
+    let cpu_weight_row = adw::SpinRow::with_range(0.0, 10000.0, 10.0);
+    cpu_weight_row.set_title(&i18n("CPU Weight"));
+    cpu_weight_row.set_subtitle(&i18n(
+        "Share of processor time compared to others, 0 uses the default",
+    ));
+
+    let cpu_quota_row = adw::SpinRow::with_range(0.0, (logical_cpus * 100) as f64, 5.0);
+    cpu_quota_row.set_title(&i18n("CPU Quota (%)"));
+    cpu_quota_row.set_subtitle(&i18n(
+        "100 % equals one full processor thread, 0 means unlimited",
+    ));
+
+    let memory_max_row = adw::SpinRow::with_range(0.0, (1u64 << 24) as f64, 64.0);
+    memory_max_row.set_title(&i18n("Memory Limit (MiB)"));
+    memory_max_row.set_subtitle(&i18n("0 means unlimited"));
+
+    let list = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(vec!["boxed-list"])
+        .sensitive(false)
+        .build();
+    list.append(&cpu_weight_row);
+    list.append(&cpu_quota_row);
+    list.append(&memory_max_row);
+
+    let dialog = adw::MessageDialog::builder()
+        .transient_for(&parent)
+        .modal(true)
+        .heading(i18n("Resource Limits"))
+        .body(i18n_f(
+            "Limits for {} are applied immediately and last until the next reboot.",
+            &[&unit.name],
+        ))
+        .extra_child(&list)
+        .build();
+
+    dialog.add_response("cancel", &i18n("Cancel"));
+    dialog.add_response("apply", &i18n("Apply"));
+    dialog.set_response_appearance("apply", adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("apply"));
+    dialog.set_close_response("cancel");
+    dialog.set_response_enabled("apply", false);
+
+    // start with the limits that are in place right now
+    MainContext::default().spawn_local(clone!(@strong unit, @weak dialog, @weak list, @weak cpu_weight_row, @weak cpu_quota_row, @weak memory_max_row => async move {
+        match unit.resource_limits().await {
+            Ok(limits) => {
+                cpu_weight_row.set_value(limits.cpu_weight.unwrap_or(0) as f64);
+                cpu_quota_row.set_value(limits.cpu_quota.unwrap_or(0) as f64);
+                memory_max_row.set_value((limits.memory_max.unwrap_or(0) / MEBIBYTE) as f64);
+            }
+            Err(error) => log::warn!("Unable to get resource limits of {}: {error}", unit.name),
+        }
+        list.set_sensitive(true);
+        dialog.set_response_enabled("apply", true);
+    }));
+
+    dialog.connect_response(
+        Some("apply"),
+        clone!(@weak parent, @weak cpu_weight_row, @weak cpu_quota_row, @weak memory_max_row => move |_, _| {
+            let value = |row: &adw::SpinRow| Some(row.value() as u64).filter(|value| *value > 0);
+            let limits = ResourceLimits {
+                cpu_weight: value(&cpu_weight_row),
+                cpu_quota: value(&cpu_quota_row),
+                memory_max: value(&memory_max_row).map(|mebibytes| mebibytes * MEBIBYTE),
+            };
+
+            MainContext::default().spawn_local(clone!(@strong unit, @weak parent => async move {
+                if let Err(error) = unit.set_resource_limits(limits).await {
+                    log::error!("{error:?}");
+                    let error_dialog = adw::MessageDialog::builder()
+                        .transient_for(&parent)
+                        .modal(true)
+                        .heading(i18n("Unable to Set Resource Limits"))
+                        .body(error.to_string())
+                        .build();
+                    error_dialog.add_response("close", &i18n("Close"));
+                    error_dialog.present();
+                }
+            }));
+        }),
+    );
+
+    dialog.present();
+}
@@ -1,3 +1,6 @@
 pub mod app_dialog;
+pub mod file_activity_dialog;
+pub mod journal_dialog;
+pub mod limits_dialog;
 pub mod process_dialog;
 pub mod settings_dialog;
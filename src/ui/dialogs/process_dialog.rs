@@ -1,14 +1,35 @@
 use adw::{prelude::*, subclass::prelude::*};
-use gtk::glib;
-use process_data::Containerization;
+use gtk::glib::{self, clone, MainContext};
+use process_data::{Containerization, SchedulingPolicy};
 
 use crate::config::PROFILE;
-use crate::i18n::i18n;
+use crate::i18n::{i18n, i18n_f, ni18n_f};
+use crate::ui::copy::setup_copy_actions;
+use crate::ui::dialogs::file_activity_dialog::ResFileActivityDialog;
+use crate::ui::dialogs::journal_dialog::ResJournalDialog;
+use crate::ui::dialogs::limits_dialog::show_limits_dialog;
 use crate::ui::window::MainWindow;
-use crate::utils::process::ProcessItem;
+use crate::utils::demo;
+use crate::utils::helper;
+use crate::utils::journal::JournalSource;
+use crate::utils::process::{
+    describe_scheduling, scheduling_policy_name, MemoryTrend, ProcessItem,
+};
+use crate::utils::systemd::SystemdUnit;
 use crate::utils::units::convert_storage;
 
+/// The policies that can be chosen when changing the scheduling of a process.
+/// Deadline scheduling needs more parameters than just a priority.
+const EDITABLE_SCHEDULING_POLICIES: [SchedulingPolicy; 5] = [
+    SchedulingPolicy::Other,
+    SchedulingPolicy::Batch,
+    SchedulingPolicy::Idle,
+    SchedulingPolicy::Fifo,
+    SchedulingPolicy::RoundRobin,
+];
+
 mod imp {
+    use std::cell::{Cell, OnceCell};
 
     use super::*;
 
@@ -24,6 +45,8 @@ mod imp {
         #[template_child]
         pub memory_usage: TemplateChild<adw::ActionRow>,
         #[template_child]
+        pub memory_trend: TemplateChild<adw::ActionRow>,
+        #[template_child]
         pub pid: TemplateChild<adw::ActionRow>,
         #[template_child]
         pub commandline: TemplateChild<adw::ActionRow>,
@@ -33,6 +56,24 @@ mod imp {
         pub cgroup: TemplateChild<adw::ActionRow>,
         #[template_child]
         pub containerized: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub resource_limits: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub journal: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub file_activity: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub scheduling: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub scheduling_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub toast_overlay: TemplateChild<adw::ToastOverlay>,
+
+        pub process_id: Cell<i32>,
+        pub scheduling_policy: Cell<SchedulingPolicy>,
+        pub realtime_priority: Cell<u32>,
+        pub journal_source: OnceCell<JournalSource>,
+        pub unit: OnceCell<SystemdUnit>,
     }
 
     #[glib::object_subclass]
@@ -81,6 +122,44 @@ impl ResProcessDialog {
     pub fn init<S: AsRef<str>>(&self, process: &ProcessItem, user: S) {
         self.set_transient_for(Some(&MainWindow::default()));
         self.setup_widgets(process, user.as_ref());
+        self.setup_signals();
+        setup_copy_actions(self);
+    }
+
+    pub fn setup_signals(&self) {
+        let imp = self.imp();
+
+        imp.journal
+            .connect_activated(clone!(@weak self as this => move |_| {
+                let Some(source) = this.imp().journal_source.get().cloned() else {
+                    return;
+                };
+                let journal_dialog = ResJournalDialog::new();
+                journal_dialog.set_transient_for(Some(&this));
+                journal_dialog.init(source);
+                journal_dialog.present();
+            }));
+
+        imp.file_activity
+            .connect_activated(clone!(@weak self as this => move |_| {
+                let imp = this.imp();
+                let file_activity_dialog = ResFileActivityDialog::new();
+                file_activity_dialog.set_transient_for(Some(&this));
+                file_activity_dialog.init(imp.process_id.get(), &imp.name.label());
+                file_activity_dialog.present();
+            }));
+
+        imp.scheduling_button
+            .connect_clicked(clone!(@weak self as this => move |_| {
+                this.show_scheduling_dialog();
+            }));
+
+        imp.resource_limits
+            .connect_activated(clone!(@weak self as this => move |_| {
+                if let Some(unit) = this.imp().unit.get().cloned() {
+                    show_limits_dialog(&this, unit);
+                }
+            }));
     }
 
     pub fn setup_widgets(&self, process: &ProcessItem, user: &str) {
@@ -92,7 +171,10 @@ impl ResProcessDialog {
 
         self.set_memory_usage(process.memory_usage);
 
+        self.set_memory_trend(process.memory_trend);
+
         imp.pid.set_subtitle(&process.pid.to_string());
+        imp.process_id.set(process.pid);
 
         imp.commandline.set_subtitle(&process.commandline);
         imp.commandline.set_tooltip_text(Some(&process.commandline));
@@ -109,6 +191,30 @@ impl ResProcessDialog {
             Containerization::Flatpak => i18n("Yes (Flatpak)"),
         };
         imp.containerized.set_subtitle(&containerized);
+
+        self.set_scheduling(process.scheduling_policy, process.realtime_priority);
+
+        // the demo PIDs belong to whatever actually runs under them, so don't look
+        // them up or let anyone act on them
+        if demo::is_enabled() {
+            imp.journal.set_visible(false);
+            imp.file_activity.set_visible(false);
+            imp.scheduling_button.set_visible(false);
+            return;
+        }
+
+        let journal_source = JournalSource::for_pid(process.pid);
+        imp.journal.set_subtitle(&match &journal_source {
+            JournalSource::Unit(unit) => unit.name.clone(),
+            JournalSource::Pid(_) => i18n("This process only"),
+        });
+        imp.journal_source.set(journal_source).unwrap_or_default();
+
+        if let Some(unit) = SystemdUnit::of_pid(process.pid) {
+            imp.resource_limits.set_subtitle(&unit.name);
+            imp.resource_limits.set_visible(true);
+            imp.unit.set(unit).unwrap_or_default();
+        }
     }
 
     pub fn set_cpu_usage(&self, usage: f32) {
@@ -122,4 +228,123 @@ impl ResProcessDialog {
         imp.memory_usage
             .set_subtitle(&convert_storage(usage as f64, false));
     }
+
+    pub fn set_memory_trend(&self, trend: MemoryTrend) {
+        let imp = self.imp();
+
+        let subtitle = match trend {
+            MemoryTrend::Unknown => i18n("Not enough data yet"),
+            MemoryTrend::Stable => i18n("No steady growth"),
+            MemoryTrend::Growing {
+                bytes_per_second,
+                duration_secs,
+            } => {
+                let minutes = (duration_secs / 60).max(1) as u32;
+                ni18n_f(
+                    "Growing by {} per minute for {} minute, possibly leaking",
+                    "Growing by {} per minute for {} minutes, possibly leaking",
+                    minutes,
+                    &[
+                        &convert_storage(bytes_per_second * 60.0, false),
+                        &minutes.to_string(),
+                    ],
+                )
+            }
+        };
+        imp.memory_trend.set_subtitle(&subtitle);
+
+        if matches!(trend, MemoryTrend::Growing { .. }) {
+            imp.memory_trend.add_css_class("warning");
+        } else {
+            imp.memory_trend.remove_css_class("warning");
+        }
+    }
+
+    pub fn set_scheduling(&self, policy: SchedulingPolicy, realtime_priority: u32) {
+        let imp = self.imp();
+        imp.scheduling_policy.set(policy);
+        imp.realtime_priority.set(realtime_priority);
+        imp.scheduling
+            .set_subtitle(&describe_scheduling(policy, realtime_priority));
+    }
+
+    fn show_scheduling_dialog(&self) {
+        let imp = self.imp();
+
+        let policy_names: Vec<String> = EDITABLE_SCHEDULING_POLICIES
+            .into_iter()
+            .map(scheduling_policy_name)
+            .collect();
+        let policy_row = adw::ComboRow::builder()
+            .title(i18n("Policy"))
+            .model(&gtk::StringList::new(
+                &policy_names.iter().map(String::as_str).collect::<Vec<_>>(),
+            ))
+            .selected(
+                EDITABLE_SCHEDULING_POLICIES
+                    .iter()
+                    .position(|policy| *policy == imp.scheduling_policy.get())
+                    .unwrap_or_default() as u32,
+            )
+            .build();
+
+        let priority_row = adw::SpinRow::with_range(1.0, 99.0, 1.0);
+        priority_row.set_title(&i18n("Realtime Priority"));
+        priority_row.set_value(imp.realtime_priority.get().max(1) as f64);
+
+        let selected_policy =
+            |row: &adw::ComboRow| EDITABLE_SCHEDULING_POLICIES[row.selected() as usize];
+
+        priority_row.set_sensitive(selected_policy(&policy_row).is_realtime());
+        policy_row.connect_selected_notify(clone!(@weak priority_row => move |row| {
+            priority_row.set_sensitive(selected_policy(row).is_realtime());
+        }));
+
+        let group = adw::PreferencesGroup::new();
+        group.add(&policy_row);
+        group.add(&priority_row);
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(self)
+            .modal(true)
+            .heading(i18n("Change Scheduling"))
+            .body(i18n(
+                "Realtime policies let a process take precedence over almost everything else. A misbehaving realtime process can make the system unresponsive.",
+            ))
+            .extra_child(&group)
+            .build();
+
+        dialog.add_response("cancel", &i18n("Cancel"));
+        dialog.add_response("apply", &i18n("Change Scheduling"));
+        dialog.set_response_appearance("apply", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("apply"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            Some("apply"),
+            clone!(@weak self as this, @weak policy_row, @weak priority_row => move |_, _| {
+                let pid = this.imp().process_id.get();
+                let policy = selected_policy(&policy_row);
+                let priority = if policy.is_realtime() {
+                    priority_row.value() as u32
+                } else {
+                    0
+                };
+                MainContext::default().spawn_local(clone!(@weak this => async move {
+                    match helper::set_scheduling(pid, policy, priority).await {
+                        Ok(()) => this.set_scheduling(policy, priority),
+                        Err(error) => {
+                            log::warn!("Unable to change scheduling of process {pid}: {error}");
+                            this.imp().toast_overlay.add_toast(adw::Toast::new(&i18n_f(
+                                "Unable to change scheduling of {}",
+                                &[&this.imp().name.label()],
+                            )));
+                        }
+                    }
+                }));
+            }),
+        );
+
+        dialog.present();
+    }
 }
@@ -1,16 +1,30 @@
+use std::time::Duration;
+
 use adw::{prelude::*, subclass::prelude::*};
-use gtk::glib;
+use gtk::{
+    gio,
+    glib::{self, clone, MainContext},
+};
 
 use crate::{
     config::PROFILE,
-    utils::settings::{Base, RefreshSpeed, TemperatureUnit, SETTINGS},
+    i18n::{i18n, i18n_f},
+    ui::widgets::graph_box::{pinnable_graph_box, pinnable_keys},
+    utils::{
+        alerts::{metric_name, AlertAction, AlertRule, Comparator},
+        autostart::set_autostart,
+        mqtt,
+        profile::{export_profile, import_profile},
+        settings::{Base, RefreshSpeed, TemperatureUnit, SETTINGS},
+    },
 };
 
 mod imp {
+    use std::cell::RefCell;
 
     use super::*;
 
-    use gtk::CompositeTemplate;
+    use gtk::{glib::SignalHandlerId, CompositeTemplate};
 
     #[derive(Debug, CompositeTemplate, Default)]
     #[template(resource = "/net/nokyan/Resources/ui/dialogs/settings_dialog.ui")]
@@ -21,6 +35,52 @@ mod imp {
         pub temperature_combo_row: TemplateChild<adw::ComboRow>,
         #[template_child]
         pub refresh_speed_combo_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub smooth_graphs_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub open_files_column_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub scheduling_column_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub page_faults_column_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub add_alert_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub thermal_throttling_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub gpu_resets_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub run_in_background_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub sysctl_editing_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub export_profile_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub import_profile_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub alerts_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub mqtt_enabled_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub mqtt_broker_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub mqtt_password_row: TemplateChild<adw::PasswordEntryRow>,
+        #[template_child]
+        pub mqtt_topic_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub mqtt_cpu_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub mqtt_memory_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub mqtt_gpu_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub mqtt_drive_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub mqtt_network_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub mqtt_temperature_row: TemplateChild<adw::SwitchRow>,
+
+        pub alert_rules_handler: RefCell<Option<SignalHandlerId>>,
     }
 
     #[glib::object_subclass]
@@ -49,6 +109,12 @@ mod imp {
                 obj.add_css_class("devel");
             }
         }
+
+        fn dispose(&self) {
+            if let Some(handler) = self.alert_rules_handler.take() {
+                SETTINGS.disconnect(handler);
+            }
+        }
     }
 
     impl WidgetImpl for ResSettingsDialog {}
@@ -83,6 +149,49 @@ impl ResSettingsDialog {
             .set_selected((SETTINGS.temperature_unit() as u8) as u32);
         imp.refresh_speed_combo_row
             .set_selected((SETTINGS.refresh_speed() as u8) as u32);
+        imp.smooth_graphs_row.set_active(SETTINGS.smooth_graphs());
+        imp.open_files_column_row
+            .set_active(SETTINGS.processes_show_open_files());
+        imp.scheduling_column_row
+            .set_active(SETTINGS.processes_show_scheduling());
+        imp.page_faults_column_row
+            .set_active(SETTINGS.processes_show_page_faults());
+        imp.thermal_throttling_row
+            .set_active(SETTINGS.notify_thermal_throttling());
+        imp.gpu_resets_row.set_active(SETTINGS.notify_gpu_resets());
+        imp.run_in_background_row
+            .set_active(SETTINGS.run_in_background());
+        imp.sysctl_editing_row.set_active(SETTINGS.sysctl_editing());
+
+        imp.alerts_list.set_placeholder(Some(
+            &gtk::Label::builder()
+                .label(i18n("No Alert Rules"))
+                .margin_top(12)
+                .margin_bottom(12)
+                .css_classes(vec!["dim-label"])
+                .build(),
+        ));
+        self.refresh_alert_rows(&SETTINGS.alert_rules());
+
+        imp.mqtt_enabled_row.set_active(SETTINGS.mqtt_enabled());
+        imp.mqtt_broker_row.set_text(&SETTINGS.mqtt_broker());
+        MainContext::default().spawn_local(clone!(@weak self as this => async move {
+            let Ok((broker, _)) = mqtt::split_password(&SETTINGS.mqtt_broker()) else {
+                return;
+            };
+            match mqtt::broker_password(&broker).await {
+                Ok(password) => this
+                    .imp()
+                    .mqtt_password_row
+                    .set_text(&password.unwrap_or_default()),
+                Err(error) => log::debug!("Unable to get the MQTT broker's password: {error}"),
+            }
+        }));
+        imp.mqtt_topic_row.set_text(&SETTINGS.mqtt_topic());
+        let mqtt_metrics = SETTINGS.mqtt_metrics();
+        for (category, row) in self.mqtt_metric_rows() {
+            row.set_active(mqtt_metrics.iter().any(|metric| metric == category));
+        }
     }
 
     pub fn setup_signals(&self) {
@@ -109,5 +218,386 @@ impl ResSettingsDialog {
                     let _ = SETTINGS.set_refresh_speed(refresh_speed);
                 }
             });
+
+        imp.smooth_graphs_row.connect_active_notify(|switch_row| {
+            let _ = SETTINGS.set_smooth_graphs(switch_row.is_active());
+        });
+
+        imp.open_files_column_row
+            .connect_active_notify(|switch_row| {
+                let _ = SETTINGS.set_processes_show_open_files(switch_row.is_active());
+            });
+
+        imp.scheduling_column_row
+            .connect_active_notify(|switch_row| {
+                let _ = SETTINGS.set_processes_show_scheduling(switch_row.is_active());
+            });
+
+        imp.page_faults_column_row
+            .connect_active_notify(|switch_row| {
+                let _ = SETTINGS.set_processes_show_page_faults(switch_row.is_active());
+            });
+
+        imp.thermal_throttling_row
+            .connect_active_notify(|switch_row| {
+                let _ = SETTINGS.set_notify_thermal_throttling(switch_row.is_active());
+            });
+
+        imp.gpu_resets_row.connect_active_notify(|switch_row| {
+            let _ = SETTINGS.set_notify_gpu_resets(switch_row.is_active());
+        });
+
+        imp.run_in_background_row
+            .connect_active_notify(|switch_row| {
+                let enabled = switch_row.is_active();
+                let _ = SETTINGS.set_run_in_background(enabled);
+                MainContext::default().spawn_local(clone!(@weak switch_row => async move {
+                    if let Err(error) = set_autostart(enabled).await {
+                        log::warn!("Unable to change autostart: {error}");
+                        // don't pretend to run from login if we aren't allowed to
+                        if enabled {
+                            switch_row.set_active(false);
+                        }
+                    }
+                }));
+            });
+
+        imp.sysctl_editing_row.connect_active_notify(|switch_row| {
+            let _ = SETTINGS.set_sysctl_editing(switch_row.is_active());
+        });
+
+        imp.export_profile_row
+            .connect_activated(clone!(@weak self as this => move |_| {
+                MainContext::default().spawn_local(clone!(@weak this => async move {
+                    this.export_profile().await;
+                }));
+            }));
+
+        imp.import_profile_row
+            .connect_activated(clone!(@weak self as this => move |_| {
+                MainContext::default().spawn_local(clone!(@weak this => async move {
+                    this.import_profile().await;
+                }));
+            }));
+
+        imp.add_alert_button
+            .connect_clicked(clone!(@weak self as this => move |_| {
+                this.show_add_alert_dialog();
+            }));
+
+        imp.mqtt_enabled_row.connect_active_notify(|switch_row| {
+            let _ = SETTINGS.set_mqtt_enabled(switch_row.is_active());
+        });
+
+        imp.mqtt_broker_row
+            .connect_apply(clone!(@weak self as this => move |entry_row| {
+                let broker = entry_row.text().trim().to_string();
+                let Ok((broker, password)) = mqtt::split_password(&broker) else {
+                    let _ = SETTINGS.set_mqtt_broker(&broker);
+                    return;
+                };
+
+                // the password doesn't belong into the settings, it's moved into
+                // the password row and the keyring instead
+                entry_row.set_text(&broker);
+                let _ = SETTINGS.set_mqtt_broker(&broker);
+                let password_row = &this.imp().mqtt_password_row;
+                if let Some(password) = password {
+                    password_row.set_text(&password);
+                }
+                Self::store_mqtt_password(broker, password_row.text().to_string());
+            }));
+
+        imp.mqtt_password_row.connect_apply(|entry_row| {
+            if let Ok((broker, _)) = mqtt::split_password(&SETTINGS.mqtt_broker()) {
+                Self::store_mqtt_password(broker, entry_row.text().to_string());
+            }
+        });
+
+        imp.mqtt_topic_row.connect_apply(|entry_row| {
+            let _ = SETTINGS.set_mqtt_topic(entry_row.text().trim().trim_end_matches('/'));
+        });
+
+        for (_, row) in self.mqtt_metric_rows() {
+            row.connect_active_notify(clone!(@weak self as this => move |_| {
+                let metrics: Vec<String> = this
+                    .mqtt_metric_rows()
+                    .into_iter()
+                    .filter(|(_, row)| row.is_active())
+                    .map(|(category, _)| category.to_string())
+                    .collect();
+                let _ = SETTINGS.set_mqtt_metrics(&metrics);
+            }));
+        }
+
+        let handler = SETTINGS.connect_alert_rules(clone!(@weak self as this => move |rules| {
+            this.refresh_alert_rows(&rules);
+        }));
+        *imp.alert_rules_handler.borrow_mut() = Some(handler);
+    }
+
+    /// Returns the switch rows for the categories of metrics that can be
+    /// published via MQTT, along with the categories they stand for
+    fn store_mqtt_password(broker: String, password: String) {
+        MainContext::default().spawn_local(async move {
+            if let Err(error) = mqtt::set_broker_password(&broker, &password).await {
+                log::warn!("Unable to store the MQTT broker's password: {error}");
+            }
+        });
+    }
+
+    fn mqtt_metric_rows(&self) -> [(&'static str, adw::SwitchRow); 6] {
+        let imp = self.imp();
+        [
+            ("cpu", imp.mqtt_cpu_row.get()),
+            ("memory", imp.mqtt_memory_row.get()),
+            ("gpu", imp.mqtt_gpu_row.get()),
+            ("drive", imp.mqtt_drive_row.get()),
+            ("network", imp.mqtt_network_row.get()),
+            ("temperature", imp.mqtt_temperature_row.get()),
+        ]
+    }
+
+    fn profile_file_dialog(&self, title: &str) -> gtk::FileDialog {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some(&i18n("Resources Settings")));
+        filter.add_suffix("json");
+
+        let filters = gio::ListStore::new::<gtk::FileFilter>();
+        filters.append(&filter);
+
+        gtk::FileDialog::builder()
+            .title(title)
+            .modal(true)
+            .filters(&filters)
+            .initial_name("resources-settings.json")
+            .build()
+    }
+
+    async fn export_profile(&self) {
+        let dialog = self.profile_file_dialog(&i18n("Export Settings"));
+
+        // an error here means that the dialog has been dismissed
+        let Ok(file) = dialog.save_future(Some(self)).await else {
+            return;
+        };
+
+        let toast_message = match file
+            .replace_contents_future(
+                export_profile().into_bytes(),
+                None,
+                false,
+                gio::FileCreateFlags::REPLACE_DESTINATION,
+            )
+            .await
+        {
+            Ok(_) => i18n("Settings exported"),
+            Err((_, error)) => {
+                log::warn!("Unable to export settings: {error}");
+                i18n("Unable to export settings")
+            }
+        };
+
+        self.add_toast(adw::Toast::new(&toast_message));
+    }
+
+    async fn import_profile(&self) {
+        let dialog = self.profile_file_dialog(&i18n("Import Settings"));
+
+        let Ok(file) = dialog.open_future(Some(self)).await else {
+            return;
+        };
+
+        let result = file
+            .load_contents_future()
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|(contents, _)| import_profile(String::from_utf8_lossy(&contents)));
+
+        let toast_message = match result {
+            Ok(imported) => {
+                // the widgets don't follow the settings by themselves
+                self.setup_widgets();
+                if imported.disarmed_alerts > 0 {
+                    i18n("Settings imported, alerts will only show notifications")
+                } else {
+                    i18n("Settings imported")
+                }
+            }
+            Err(error) => {
+                log::warn!("Unable to import settings: {error}");
+                i18n("Unable to import settings")
+            }
+        };
+
+        self.add_toast(adw::Toast::new(&toast_message));
+    }
+
+    fn refresh_alert_rows(&self, rules: &[AlertRule]) {
+        let imp = self.imp();
+
+        while let Some(row) = imp.alerts_list.row_at_index(0) {
+            imp.alerts_list.remove(&row);
+        }
+
+        for (i, rule) in rules.iter().enumerate() {
+            let subtitle = match rule.action {
+                AlertAction::Notify => i18n("Show a notification"),
+                AlertAction::RunCommand => {
+                    i18n_f("Run {}", &[&glib::markup_escape_text(&rule.target)])
+                }
+                AlertAction::PostWebhook => {
+                    i18n_f("Post to {}", &[&glib::markup_escape_text(&rule.target)])
+                }
+            };
+
+            let row = adw::ActionRow::builder()
+                .title(rule.description())
+                .subtitle(subtitle)
+                .build();
+
+            let remove_button = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text(i18n("Remove Rule"))
+                .valign(gtk::Align::Center)
+                .css_classes(vec!["flat"])
+                .build();
+            remove_button.connect_clicked(move |_| {
+                let mut rules = SETTINGS.alert_rules();
+                if i < rules.len() {
+                    rules.remove(i);
+                    let _ = SETTINGS.set_alert_rules(&rules);
+                }
+            });
+            row.add_suffix(&remove_button);
+
+            imp.alerts_list.append(&row);
+        }
+    }
+
+    fn show_add_alert_dialog(&self) {
+        // thresholds are fractions, so only offer graphs that show fractions as well,
+        // e.g. no network throughput
+        let mut metrics: Vec<String> = pinnable_keys()
+            .into_iter()
+            .filter(|key| {
+                pinnable_graph_box(key).map_or(false, |graph_box| graph_box.shows_fractions())
+            })
+            .collect();
+        metrics.sort();
+
+        let metric_names: Vec<String> = metrics.iter().map(|key| metric_name(key)).collect();
+        let metric_names: Vec<&str> = metric_names.iter().map(String::as_str).collect();
+
+        let metric_row = adw::ComboRow::builder()
+            .title(i18n("Metric"))
+            .model(&gtk::StringList::new(&metric_names))
+            .build();
+
+        let comparator_row = adw::ComboRow::builder()
+            .title(i18n("Condition"))
+            .model(&gtk::StringList::new(&[
+                i18n("Above").as_str(),
+                i18n("Below").as_str(),
+            ]))
+            .build();
+
+        let threshold_row = adw::SpinRow::with_range(0.0, 100.0, 1.0);
+        threshold_row.set_title(&i18n("Threshold (%)"));
+        threshold_row.set_value(90.0);
+
+        let duration_row = adw::SpinRow::with_range(0.0, 3600.0, 1.0);
+        duration_row.set_title(&i18n("Duration (s)"));
+        duration_row.set_value(30.0);
+
+        // in the order of `AlertAction`'s variants
+        let action_row = adw::ComboRow::builder()
+            .title(i18n("Action"))
+            .model(&gtk::StringList::new(&[
+                i18n("Show a Notification").as_str(),
+                i18n("Run a Command").as_str(),
+                i18n("Post to a Webhook").as_str(),
+            ]))
+            .build();
+
+        let target_row = adw::EntryRow::builder().visible(false).build();
+
+        let list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(vec!["boxed-list"])
+            .build();
+        list.append(&metric_row);
+        list.append(&comparator_row);
+        list.append(&threshold_row);
+        list.append(&duration_row);
+        list.append(&action_row);
+        list.append(&target_row);
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(self)
+            .modal(true)
+            .heading(i18n("Add Alert Rule"))
+            .body(i18n(
+                "Only metrics shown as percentages can be watched, not throughput, temperatures or power",
+            ))
+            .extra_child(&list)
+            .build();
+
+        dialog.add_response("cancel", &i18n("Cancel"));
+        dialog.add_response("add", &i18n("Add"));
+        dialog.set_response_appearance("add", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("add"));
+        dialog.set_close_response("cancel");
+        dialog.set_response_enabled("add", !metrics.is_empty());
+
+        let has_metrics = !metrics.is_empty();
+        let update_action = clone!(@weak dialog, @weak action_row, @weak target_row => move || {
+            let action = AlertAction::from_repr(action_row.selected() as u8).unwrap_or_default();
+            match action {
+                AlertAction::Notify => {}
+                AlertAction::RunCommand => target_row.set_title(&i18n("Command")),
+                AlertAction::PostWebhook => target_row.set_title(&i18n("Webhook URL")),
+            }
+            target_row.set_visible(action.needs_target());
+            dialog.set_response_enabled(
+                "add",
+                has_metrics && (!action.needs_target() || !target_row.text().trim().is_empty()),
+            );
+        });
+        action_row
+            .connect_selected_notify(clone!(@strong update_action => move |_| update_action()));
+        target_row.connect_changed(move |_| update_action());
+
+        dialog.connect_response(
+            Some("add"),
+            clone!(@weak metric_row, @weak comparator_row, @weak threshold_row, @weak duration_row, @weak action_row, @weak target_row => move |_, _| {
+                let Some(metric) = metrics.get(metric_row.selected() as usize) else {
+                    return;
+                };
+
+                let action = AlertAction::from_repr(action_row.selected() as u8).unwrap_or_default();
+                let target = if action.needs_target() {
+                    target_row.text().trim().to_string()
+                } else {
+                    String::new()
+                };
+
+                let rule = AlertRule {
+                    metric: metric.clone(),
+                    comparator: Comparator::from_repr(comparator_row.selected() as u8)
+                        .unwrap_or_default(),
+                    threshold: threshold_row.value() / 100.0,
+                    duration: Duration::from_secs_f64(duration_row.value()),
+                    action,
+                    target,
+                };
+
+                let mut rules = SETTINGS.alert_rules();
+                rules.push(rule);
+                let _ = SETTINGS.set_alert_rules(&rules);
+            }),
+        );
+
+        dialog.present();
     }
 }
@@ -0,0 +1,124 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::glib::{self, clone};
+
+use crate::config::PROFILE;
+use crate::ui::pages::dashboard::page_name;
+use crate::ui::widgets::graph::ResGraph;
+use crate::ui::widgets::graph_box::ResGraphBox;
+
+mod imp {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    use gtk::{glib::SignalHandlerId, CompositeTemplate};
+
+    #[derive(Debug, CompositeTemplate, Default)]
+    #[template(resource = "/net/nokyan/Resources/ui/graph_window.ui")]
+    pub struct ResGraphWindow {
+        #[template_child]
+        pub window_title: TemplateChild<adw::WindowTitle>,
+        #[template_child]
+        pub graph: TemplateChild<ResGraph>,
+        #[template_child]
+        pub info_label: TemplateChild<gtk::Label>,
+
+        /// Receives the graph box' title, which is then shown in the header bar
+        pub title_label: gtk::Label,
+
+        pub source: RefCell<Option<(glib::WeakRef<ResGraphBox>, SignalHandlerId)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResGraphWindow {
+        const NAME: &'static str = "ResGraphWindow";
+        type Type = super::ResGraphWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        // You must call `Widget`'s `init_template()` within `instance_init()`.
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ResGraphWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // Devel Profile
+            if PROFILE == "Devel" {
+                obj.add_css_class("devel");
+            }
+        }
+
+        fn dispose(&self) {
+            // the graph box outlives this window, so don't leave our handler connected to it
+            if let Some((graph_box, handler)) = self.source.take() {
+                if let Some(graph_box) = graph_box.upgrade() {
+                    graph_box.disconnect(handler);
+                }
+            }
+        }
+    }
+
+    impl WidgetImpl for ResGraphWindow {}
+
+    impl WindowImpl for ResGraphWindow {}
+
+    impl AdwWindowImpl for ResGraphWindow {}
+}
+
+glib::wrapper! {
+    pub struct ResGraphWindow(ObjectSubclass<imp::ResGraphWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window,
+        @implements gtk::Root;
+}
+
+impl ResGraphWindow {
+    /// Creates a small window that mirrors `graph_box`, so that a single graph
+    /// can be kept in view (e.g. on another monitor) independently of the page
+    /// the main window is showing. The window closes itself once the graph box
+    /// is gone, e.g. because the device has been unplugged.
+    pub fn new(app: &impl IsA<gtk::Application>, graph_box: &ResGraphBox) -> Self {
+        let this: Self = glib::Object::builder::<Self>()
+            .property("application", app)
+            .build();
+        this.mirror(graph_box);
+        this
+    }
+
+    fn mirror(&self, graph_box: &ResGraphBox) {
+        let imp = self.imp();
+
+        imp.title_label
+            .bind_property("label", &*imp.window_title, "title")
+            .sync_create()
+            .build();
+        imp.title_label
+            .bind_property("label", self, "title")
+            .sync_create()
+            .build();
+        graph_box.bind_labels(&imp.title_label, &imp.info_label);
+
+        imp.window_title
+            .set_subtitle(&page_name(graph_box.upcast_ref()).unwrap_or_default());
+
+        let (r, g, b) = graph_box.graph_color();
+        imp.graph.set_graph_color(r, g, b);
+        imp.graph.set_locked_max_y(graph_box.locked_max_y());
+
+        let graph = imp.graph.get();
+        let handler = graph_box.connect_data_point_pushed(
+            clone!(@weak graph => move |data_point| graph.push_data_point(data_point)),
+        );
+
+        graph_box.connect_destroy(clone!(@weak self as this => move |_| this.close()));
+
+        *imp.source.borrow_mut() = Some((graph_box.downgrade(), handler));
+    }
+}
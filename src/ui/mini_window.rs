@@ -0,0 +1,139 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::glib::{self, clone};
+
+use crate::config::PROFILE;
+use crate::ui::widgets::graph::ResGraph;
+
+mod imp {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    use gtk::{glib::SignalHandlerId, CompositeTemplate};
+
+    #[derive(Debug, CompositeTemplate, Default)]
+    #[template(resource = "/net/nokyan/Resources/ui/mini_window.ui")]
+    pub struct ResMiniWindow {
+        #[template_child]
+        pub graphs_box: TemplateChild<gtk::Box>,
+
+        pub usage_handlers: RefCell<Vec<(gtk::Widget, SignalHandlerId)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResMiniWindow {
+        const NAME: &'static str = "ResMiniWindow";
+        type Type = super::ResMiniWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        // You must call `Widget`'s `init_template()` within `instance_init()`.
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ResMiniWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // Devel Profile
+            if PROFILE == "Devel" {
+                obj.add_css_class("devel");
+            }
+        }
+
+        fn dispose(&self) {
+            // the pages outlive this window, so don't leave our handlers connected to them
+            for (page, handler) in self.usage_handlers.take() {
+                page.disconnect(handler);
+            }
+        }
+    }
+
+    impl WidgetImpl for ResMiniWindow {}
+
+    impl WindowImpl for ResMiniWindow {}
+
+    impl AdwWindowImpl for ResMiniWindow {}
+}
+
+glib::wrapper! {
+    pub struct ResMiniWindow(ObjectSubclass<imp::ResMiniWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window,
+        @implements gtk::Root;
+}
+
+impl ResMiniWindow {
+    /// Creates a small, frameless window that only shows tiny graphs of the most
+    /// important pages. GTK 4 leaves stacking to the compositor, so keeping it
+    /// above other windows is up to the user's window manager (e.g. "Always on
+    /// Top" in the window menu of GNOME Shell).
+    pub fn new(app: &impl IsA<gtk::Application>) -> Self {
+        glib::Object::builder::<Self>()
+            .property("application", app)
+            .build()
+    }
+
+    /// Adds a tiny graph that follows the `usage` property of `page`. If
+    /// `show_percentage` is set, the current usage is also shown as a
+    /// percentage next to the page's name.
+    pub fn add_page(
+        &self,
+        page: &impl IsA<gtk::Widget>,
+        color: (u8, u8, u8),
+        show_percentage: bool,
+    ) {
+        let imp = self.imp();
+        let page = page.as_ref();
+
+        let name_label = gtk::Label::builder()
+            .label(page.property::<String>("tab_name"))
+            .halign(gtk::Align::Start)
+            .hexpand(true)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .build();
+        name_label.add_css_class("caption");
+
+        let usage_label = gtk::Label::builder()
+            .halign(gtk::Align::End)
+            .visible(show_percentage)
+            .build();
+        usage_label.add_css_class("caption");
+        usage_label.add_css_class("numeric");
+        usage_label.add_css_class("dim-label");
+
+        let label_box = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+        label_box.append(&name_label);
+        label_box.append(&usage_label);
+
+        let graph = ResGraph::new();
+        graph.set_data_points_max_amount(30);
+        graph.set_graph_color(color.0, color.1, color.2);
+        graph.set_locked_max_y(Some(1.0));
+        graph.set_height_request(32);
+        graph.set_hexpand(true);
+
+        let row = gtk::Box::new(gtk::Orientation::Vertical, 2);
+        row.append(&label_box);
+        row.append(&graph);
+        imp.graphs_box.append(&row);
+
+        let handler = page.connect_notify_local(
+            Some("usage"),
+            clone!(@weak graph, @weak usage_label => move |page, _| {
+                let usage: f64 = page.property("usage");
+                graph.push_data_point(usage);
+                usage_label.set_label(&format!("{} %", (usage * 100.0).round()));
+            }),
+        );
+
+        imp.usage_handlers
+            .borrow_mut()
+            .push((page.clone(), handler));
+    }
+}
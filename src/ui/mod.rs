@@ -1,4 +1,8 @@
+pub mod copy;
 pub mod dialogs;
+pub mod graph_window;
+pub mod mini_window;
 pub mod pages;
+pub mod remote_window;
 pub mod widgets;
 pub mod window;
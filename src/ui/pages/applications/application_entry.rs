@@ -33,6 +33,9 @@ mod imp {
         cpu_usage: Cell<f32>,
         #[property(get, set)]
         memory_usage: Cell<u64>,
+        /// Estimated power drawn from the battery in W
+        #[property(get, set)]
+        power_usage: Cell<f64>,
 
         pub app_item: RefCell<Option<AppItem>>,
     }
@@ -47,6 +50,7 @@ mod imp {
 
                 cpu_usage: Cell::new(0.0),
                 memory_usage: Cell::new(0),
+                power_usage: Cell::new(0.0),
 
                 app_item: RefCell::new(None),
             }
@@ -143,9 +147,16 @@ impl ApplicationEntry {
         this
     }
 
+    /// Updates the usage properties of this entry in place. Only properties
+    /// whose values have actually changed are set, so that cells bound to
+    /// unchanged properties aren't notified needlessly.
     pub fn update(&self, app_item: AppItem) {
-        self.set_cpu_usage(app_item.cpu_time_ratio);
-        self.set_memory_usage(app_item.memory_usage as u64);
+        if self.cpu_usage() != app_item.cpu_time_ratio {
+            self.set_cpu_usage(app_item.cpu_time_ratio);
+        }
+        if self.memory_usage() != app_item.memory_usage as u64 {
+            self.set_memory_usage(app_item.memory_usage as u64);
+        }
         self.imp().app_item.replace(Some(app_item));
     }
 
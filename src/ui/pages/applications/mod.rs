@@ -6,7 +6,7 @@ use std::collections::HashSet;
 use adw::ResponseAppearance;
 use adw::{prelude::*, subclass::prelude::*};
 use gtk::glib::{self, clone, closure, Object, Sender};
-use gtk::{gio, CustomSorter, FilterChange, Ordering, SortType, Widget};
+use gtk::{gio, CustomSorter, FilterChange, Ordering, SortType, SorterChange, Widget};
 use gtk_macros::send;
 
 use log::error;
@@ -53,6 +53,8 @@ mod imp {
         #[template_child]
         pub search_button: TemplateChild<gtk::ToggleButton>,
         #[template_child]
+        pub processes_button: TemplateChild<gtk::Button>,
+        #[template_child]
         pub information_button: TemplateChild<gtk::Button>,
         #[template_child]
         pub end_application_button: TemplateChild<adw::SplitButton>,
@@ -62,6 +64,8 @@ mod imp {
         pub filter_model: RefCell<gtk::FilterListModel>,
         pub sort_model: RefCell<gtk::SortListModel>,
         pub column_view: RefCell<gtk::ColumnView>,
+        pub power_column: RefCell<Option<gtk::ColumnViewColumn>>,
+        pub discharge_rate: Cell<Option<f64>>,
         pub open_dialog: RefCell<Option<(Option<String>, ResAppDialog)>>,
 
         pub sender: OnceLock<Sender<Action>>,
@@ -92,12 +96,15 @@ mod imp {
                 search_revealer: Default::default(),
                 search_entry: Default::default(),
                 search_button: Default::default(),
+                processes_button: Default::default(),
                 information_button: Default::default(),
                 store: gio::ListStore::new::<ApplicationEntry>().into(),
                 selection_model: Default::default(),
                 filter_model: Default::default(),
                 sort_model: Default::default(),
                 column_view: Default::default(),
+                power_column: Default::default(),
+                discharge_rate: Default::default(),
                 open_dialog: Default::default(),
                 sender: Default::default(),
                 applications_scrolled_window: Default::default(),
@@ -297,9 +304,38 @@ impl ResApplications {
         });
         cpu_col.set_sorter(Some(&cpu_col_sorter));
 
+        let power_col_factory = gtk::SignalListItemFactory::new();
+        let power_col =
+            gtk::ColumnViewColumn::new(Some(&i18n("Energy")), Some(power_col_factory.clone()));
+        power_col.set_resizable(true);
+        // only makes sense while running on battery
+        power_col.set_visible(false);
+        power_col_factory.connect_setup(move |_factory, item| {
+            let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+            let row = gtk::Inscription::new(None);
+            row.set_tooltip_text(Some(&i18n(
+                "Estimated share of the battery drain, based on processor usage",
+            )));
+            item.set_child(Some(&row));
+            item.property_expression("item")
+                .chain_property::<ApplicationEntry>("power_usage")
+                .chain_closure::<String>(closure!(|_: Option<Object>, power_usage: f64| {
+                    format!("{power_usage:.1} W")
+                }))
+                .bind(&row, "text", Widget::NONE);
+        });
+        let power_col_sorter = CustomSorter::new(move |a, b| {
+            let item_a = a.downcast_ref::<ApplicationEntry>().unwrap().power_usage();
+            let item_b = b.downcast_ref::<ApplicationEntry>().unwrap().power_usage();
+            item_a.total_cmp(&item_b).into()
+        });
+        power_col.set_sorter(Some(&power_col_sorter));
+
         column_view.append_column(&name_col);
         column_view.append_column(&memory_col);
         column_view.append_column(&cpu_col);
+        column_view.append_column(&power_col);
+        *imp.power_column.borrow_mut() = Some(power_col);
         column_view.sort_by_column(Some(&name_col), SortType::Ascending);
         column_view.set_enable_rubberband(true);
         imp.applications_scrolled_window
@@ -320,6 +356,7 @@ impl ResApplications {
                     .id()
                     .is_none()
                 });
+                imp.processes_button.set_sensitive(model.selected() != u32::MAX);
                 imp.information_button.set_sensitive(model.selected() != u32::MAX);
                 imp.end_application_button.set_sensitive(model.selected() != u32::MAX && !is_system_processes);
             }),
@@ -345,6 +382,27 @@ impl ResApplications {
                 }
             }));
 
+        imp.processes_button
+            .connect_clicked(clone!(@strong self as this => move |_| {
+                let imp = this.imp();
+                let selection_option = imp.selection_model.borrow()
+                .selected_item()
+                .map(|object| {
+                    object
+                    .downcast::<ApplicationEntry>()
+                    .unwrap()
+                });
+                if let Some(selection) = selection_option {
+                    send!(
+                        imp.sender.get().unwrap(),
+                        Action::ShowAppProcesses(
+                            selection.id().map(|gs| gs.to_string()),
+                            selection.name().to_string()
+                        )
+                    );
+                }
+            }));
+
         imp.information_button
             .connect_clicked(clone!(@strong self as this => move |_| {
                 let imp = this.imp();
@@ -384,6 +442,28 @@ impl ResApplications {
                 .contains(&search_string)
     }
 
+    /// Selects the app with the given `id` and scrolls to it, provided that it
+    /// is currently listed
+    pub fn select_app(&self, id: &str) {
+        let imp = self.imp();
+
+        let selection_model = imp.selection_model.borrow();
+        let position = selection_model
+            .iter::<ApplicationEntry>()
+            .flatten()
+            .position(|entry| entry.id().as_deref() == Some(id));
+
+        if let Some(position) = position {
+            selection_model.set_selected(position as u32);
+            imp.column_view.borrow().scroll_to(
+                position as u32,
+                None::<&gtk::ColumnViewColumn>,
+                gtk::ListScrollFlags::FOCUS,
+                None,
+            );
+        }
+    }
+
     fn get_selected_app_item(&self) -> Option<AppItem> {
         self.imp()
             .selection_model
@@ -392,6 +472,17 @@ impl ResApplications {
             .and_then(|object| object.downcast::<ApplicationEntry>().unwrap().app_item())
     }
 
+    /// Sets the rate at which the battery is currently being drained in W, or
+    /// `None` if not running on battery. The energy column is only shown while
+    /// running on battery.
+    pub fn set_discharge_rate(&self, discharge_rate: Option<f64>) {
+        let imp = self.imp();
+        imp.discharge_rate.set(discharge_rate);
+        if let Some(power_column) = imp.power_column.borrow().as_ref() {
+            power_column.set_visible(discharge_rate.is_some());
+        }
+    }
+
     pub fn refresh_apps_list(&self, apps: &AppsContext) {
         let imp = self.imp();
 
@@ -401,6 +492,13 @@ impl ResApplications {
         let mut new_items = apps.app_items();
         let mut ids_to_remove = HashSet::new();
 
+        // each app is attributed the share of the battery drain that corresponds
+        // to its share of the processor's capacity, the rest is what the machine
+        // needs while idling. there's no per-process GPU usage to take into
+        // account (yet).
+        let discharge_rate = imp.discharge_rate.get().unwrap_or_default();
+        let power_usage = |cpu_usage: f32| discharge_rate * f64::from(cpu_usage);
+
         // change process entries of apps that have run before
         store
             .iter::<ApplicationEntry>()
@@ -430,9 +528,9 @@ impl ResApplications {
                             dialog.set_processes_amount(new_item.processes_amount);
                         }
                     }
-                    object.set_cpu_usage(new_item.cpu_time_ratio);
-                    object.set_memory_usage(new_item.memory_usage as u64);
+                    object.update(new_item);
                 }
+                object.set_power_usage(power_usage(object.cpu_usage()));
             });
 
         // remove apps that recently have stopped running
@@ -448,11 +546,22 @@ impl ResApplications {
         });
 
         // add the newly started apps to the store
-        new_items
+        let new_entries: Vec<ApplicationEntry> = new_items
             .drain()
-            .for_each(|(_, new_item)| store.append(&ApplicationEntry::new(new_item)));
-
-        store.items_changed(0, store.n_items(), store.n_items());
+            .map(|(_, new_item)| {
+                let entry = ApplicationEntry::new(new_item);
+                entry.set_power_usage(power_usage(entry.cpu_usage()));
+                entry
+            })
+            .collect();
+        store.extend_from_slice(&new_entries);
+
+        // the entries have been updated in place, so only their order might be
+        // outdated. unlike replacing all items, this keeps the selection and
+        // scroll position intact.
+        if let Some(sorter) = imp.sort_model.borrow().sorter() {
+            sorter.changed(SorterChange::Different);
+        }
     }
 
     pub fn execute_process_action_dialog(&self, app: AppItem, action: ProcessAction) {
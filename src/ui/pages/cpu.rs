@@ -1,16 +1,40 @@
+use std::cell::Cell;
+use std::time::Instant;
+
 use adw::{prelude::*, subclass::prelude::*};
 use anyhow::Context;
-use gtk::glib::{self, clone, MainContext};
+use gtk::glib::{self, clone, MainContext, Sender};
 use gtk::FlowBoxChild;
+use gtk_macros::send;
 
 use crate::config::PROFILE;
 use crate::i18n::{i18n, i18n_f};
+use crate::ui::copy::setup_copy_actions;
 use crate::ui::widgets::graph_box::ResGraphBox;
+use crate::ui::window::Action;
+use crate::utils::app::AppsContext;
+use crate::utils::cpu::{CPUInfo, CpuData, FrequencyScaling, IdleState};
+use crate::utils::gamemode::GameModeStatus;
+use crate::utils::process::ProcessItem;
 use crate::utils::units::{convert_frequency, convert_temperature};
-use crate::utils::{cpu, NaNDefault};
+use crate::utils::{cpu, helper, NaNDefault};
+
+/// How many of the busiest processes are shown
+const TOP_PROCESSES_AMOUNT: usize = 5;
+
+#[derive(Debug)]
+pub struct TopProcessRow {
+    row: adw::ActionRow,
+    icon: gtk::Image,
+    /// The process currently shown by the row
+    pid: Cell<i32>,
+}
 
 mod imp {
-    use std::cell::{Cell, RefCell};
+    use std::{
+        cell::{Cell, RefCell},
+        sync::OnceLock,
+    };
 
     use crate::ui::widgets::graph_box::ResGraphBox;
 
@@ -37,6 +61,14 @@ mod imp {
         #[template_child]
         pub total_cpu: TemplateChild<ResGraphBox>,
         #[template_child]
+        pub steal_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub steal_time: TemplateChild<ResGraphBox>,
+        #[template_child]
+        pub frequency_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub frequency: TemplateChild<ResGraphBox>,
+        #[template_child]
         pub thread_box: TemplateChild<gtk::FlowBox>,
         #[template_child]
         pub max_speed: TemplateChild<adw::ActionRow>,
@@ -52,8 +84,40 @@ mod imp {
         pub architecture: TemplateChild<adw::ActionRow>,
         #[template_child]
         pub temperature: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub throttling: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub gamemode_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub gamemode: TemplateChild<adw::ExpanderRow>,
+        #[template_child]
+        pub idle_states_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub top_processes_group: TemplateChild<adw::PreferencesGroup>,
+        pub top_process_rows: RefCell<Vec<TopProcessRow>>,
+        pub sender: OnceLock<Sender<Action>>,
+        pub idle_state_rows: RefCell<Vec<(adw::ExpanderRow, Vec<adw::ActionRow>)>>,
+        pub old_idle_states: RefCell<Vec<Vec<IdleState>>>,
+        pub old_idle_instant: Cell<Option<Instant>>,
+        pub old_throttle_counts: RefCell<Vec<Option<u64>>>,
+        pub last_throttled: RefCell<Option<glib::GString>>,
+        #[template_child]
+        pub scaling_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub scaling_driver: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub governor: TemplateChild<adw::ComboRow>,
+        pub governors: RefCell<Vec<String>>,
+        #[template_child]
+        pub energy_performance_preference: TemplateChild<adw::ComboRow>,
+        pub energy_performance_preferences: RefCell<Vec<String>>,
+        pub updating_scaling: Cell<bool>,
+        pub gamemode_rows: RefCell<Vec<adw::ActionRow>>,
+        pub gamemode_status: RefCell<Option<GameModeStatus>>,
         pub thread_graphs: RefCell<Vec<ResGraphBox>>,
         pub old_total_usage: Cell<(u64, u64)>,
+        /// `None` until the first refresh, which only serves as a baseline
+        pub old_total_steal: Cell<Option<u64>>,
         pub old_thread_usages: RefCell<Vec<(u64, u64)>>,
         pub logical_cpus_amount: Cell<usize>,
 
@@ -87,6 +151,10 @@ mod imp {
                 total_page: Default::default(),
                 logical_page: Default::default(),
                 total_cpu: Default::default(),
+                steal_group: Default::default(),
+                steal_time: Default::default(),
+                frequency_group: Default::default(),
+                frequency: Default::default(),
                 thread_box: Default::default(),
                 max_speed: Default::default(),
                 logical_cpus: Default::default(),
@@ -95,12 +163,34 @@ mod imp {
                 virtualization: Default::default(),
                 architecture: Default::default(),
                 temperature: Default::default(),
+                throttling: Default::default(),
+                gamemode_group: Default::default(),
+                gamemode: Default::default(),
+                idle_states_group: Default::default(),
+                top_processes_group: Default::default(),
+                top_process_rows: Default::default(),
+                sender: Default::default(),
+                idle_state_rows: Default::default(),
+                old_idle_states: Default::default(),
+                old_idle_instant: Default::default(),
+                old_throttle_counts: Default::default(),
+                last_throttled: Default::default(),
+                scaling_group: Default::default(),
+                scaling_driver: Default::default(),
+                governor: Default::default(),
+                governors: Default::default(),
+                energy_performance_preference: Default::default(),
+                energy_performance_preferences: Default::default(),
+                updating_scaling: Default::default(),
+                gamemode_rows: Default::default(),
+                gamemode_status: Default::default(),
                 thread_graphs: Default::default(),
                 uses_progress_bar: Cell::new(true),
                 icon: RefCell::new(ThemedIcon::new("processor-symbolic").into()),
                 usage: Default::default(),
                 tab_name: Cell::new(glib::GString::from(i18n("Processor"))),
                 old_total_usage: Cell::default(),
+                old_total_steal: Cell::default(),
                 old_thread_usages: RefCell::default(),
                 logical_cpus_amount: Cell::default(),
             }
@@ -161,9 +251,12 @@ impl ResCPU {
         glib::Object::new::<Self>()
     }
 
-    pub fn init(&self) {
+    pub fn init(&self, sender: Sender<Action>) {
+        self.imp().sender.set(sender).unwrap();
         self.setup_widgets();
         self.setup_signals();
+        self.setup_top_processes();
+        setup_copy_actions(self);
     }
 
     pub fn setup_widgets(&self) {
@@ -176,7 +269,6 @@ impl ResCPU {
             let imp = this.imp();
 
             let logical_cpus = cpu_info.logical_cpus.unwrap_or(0);
-            imp.logical_cpus_amount.set(logical_cpus);
 
             imp.old_total_usage.set(cpu::get_cpu_usage(None).await.unwrap_or((0, 0)));
             *imp.old_thread_usages.borrow_mut() = Vec::with_capacity(logical_cpus);
@@ -185,62 +277,173 @@ impl ResCPU {
                 imp.old_thread_usages.borrow_mut().push(cpu::get_cpu_usage(Some(i)).await.unwrap_or((0, 0)));
             }
 
-            imp.total_cpu.set_title_label(&i18n("CPU"));
-            imp.total_cpu.set_subtitle(&i18n("N/A"));
-            imp.total_cpu.set_data_points_max_amount(60);
-            imp.total_cpu.set_graph_color(28, 113, 216);
+            this.setup_cpu_info(cpu_info);
+            this.setup_dashboard_keys();
+            this.refresh_frequency_scaling();
+        });
+        main_context.spawn_local(widget_setup);
+    }
 
-            // if our CPU happens to only have one thread, showing a single thread box with the exact
-            // same fraction as the progress bar for total CPU usage would be silly, so only do
-            // thread boxes if we have more than one thread
+    // the processes of remote machines aren't known, so the top processes are
+    // only set up for this one
+    fn setup_top_processes(&self) {
+        let imp = self.imp();
 
-            imp.logical_switch.set_sensitive(logical_cpus > 0);
-            for i in 0..logical_cpus {
-                let thread_box = ResGraphBox::new();
-                thread_box.set_subtitle(&i18n_f("CPU {}", &[&(i + 1).to_string()]));
-                thread_box.set_title_label(&i18n("N/A"));
-                thread_box.set_graph_height_request(72);
-                thread_box.set_data_points_max_amount(60);
-                thread_box.set_graph_color(28, 113, 216);
-                let flow_box_chld = FlowBoxChild::builder()
-                    .child(&thread_box)
-                    .css_classes(vec!["tile", "card"])
-                    .build();
-                imp.thread_box.append(&flow_box_chld);
-                imp.thread_graphs.borrow_mut().push(thread_box);
-            }
+        let mut rows = imp.top_process_rows.borrow_mut();
+        for _ in 0..TOP_PROCESSES_AMOUNT {
+            let icon = gtk::Image::new();
+            let row = adw::ActionRow::builder()
+                .activatable(true)
+                .visible(false)
+                .build();
+            row.add_prefix(&icon);
+            row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+            imp.top_processes_group.add(&row);
+
+            let index = rows.len();
+            row.connect_activated(clone!(@weak self as this => move |_| {
+                let imp = this.imp();
+                let pid = imp.top_process_rows.borrow()[index].pid.get();
+                send!(imp.sender.get().unwrap(), Action::ShowProcess(pid));
+            }));
+
+            rows.push(TopProcessRow {
+                row,
+                icon,
+                pid: Cell::default(),
+            });
+        }
+    }
 
-            imp.max_speed.set_subtitle(
-                &cpu_info
-                    .max_speed
-                    .map_or_else(|| i18n("N/A"), |x| convert_frequency(x as f64)),
-            );
-
-            imp.logical_cpus.set_subtitle(
-                &cpu_info
-                    .logical_cpus
-                    .map_or_else(|| i18n("N/A"), |x| x.to_string()),
-            );
-
-            imp.physical_cpus.set_subtitle(
-                &cpu_info
-                    .physical_cpus
-                    .map_or_else(|| i18n("N/A"), |x| x.to_string()),
-            );
-
-            imp.sockets.set_subtitle(
-                &cpu_info
-                    .sockets
-                    .map_or_else(|| i18n("N/A"), |x| x.to_string()),
-            );
-
-            imp.virtualization
-                .set_subtitle(&cpu_info.virtualization.unwrap_or_else(|| i18n("N/A")));
-
-            imp.architecture
-                .set_subtitle(&cpu_info.architecture.unwrap_or_else(|| i18n("N/A")));
+    /// Shows the processes that currently use the most processor time
+    pub fn refresh_top_processes(&self, apps: &AppsContext) {
+        let imp = self.imp();
+
+        let mut processes: Vec<ProcessItem> = apps
+            .process_items()
+            .into_values()
+            .filter(|process| process.cpu_time_ratio > 0.0)
+            .collect();
+        processes.sort_by(|a, b| {
+            b.cpu_time_ratio
+                .total_cmp(&a.cpu_time_ratio)
+                .then(a.pid.cmp(&b.pid))
         });
-        main_context.spawn_local(widget_setup);
+        processes.truncate(TOP_PROCESSES_AMOUNT);
+
+        imp.top_processes_group.set_visible(!processes.is_empty());
+
+        for (i, top_process_row) in imp.top_process_rows.borrow().iter().enumerate() {
+            let Some(process) = processes.get(i) else {
+                top_process_row.row.set_visible(false);
+                continue;
+            };
+
+            let row = &top_process_row.row;
+            row.set_title(&glib::markup_escape_text(&process.display_name));
+            row.set_subtitle(&format!("{:.1} %", process.cpu_time_ratio * 100.0));
+            row.set_tooltip_text(Some(&process.commandline));
+            row.set_visible(true);
+            top_process_row.icon.set_from_gicon(&process.icon);
+            top_process_row.pid.set(process.pid);
+        }
+    }
+
+    /// Sets up the page for a machine that is not this one, e.g. one that is
+    /// monitored over SSH. `cpu_info` describes the remote CPU.
+    pub fn init_remote(&self, cpu_info: CPUInfo) {
+        let logical_cpus = cpu_info.logical_cpus.unwrap_or(0);
+        *self.imp().old_thread_usages.borrow_mut() = vec![(0, 0); logical_cpus];
+
+        self.setup_cpu_info(cpu_info);
+        self.setup_signals();
+        setup_copy_actions(self);
+    }
+
+    // only the local processor's graphs can be pinned to the dashboard, so this isn't
+    // part of setup_cpu_info()
+    fn setup_dashboard_keys(&self) {
+        let imp = self.imp();
+        imp.total_cpu.set_dashboard_key("cpu");
+        imp.steal_time.set_dashboard_key("cpu-steal");
+        imp.frequency.set_dashboard_key("cpu-frequency");
+        for (i, thread_box) in imp.thread_graphs.borrow().iter().enumerate() {
+            thread_box.set_dashboard_key(&format!("cpu-{i}"));
+        }
+    }
+
+    /// Sets up the graphs and properties for the CPU described by `cpu_info`
+    fn setup_cpu_info(&self, cpu_info: CPUInfo) {
+        let imp = self.imp();
+
+        let logical_cpus = cpu_info.logical_cpus.unwrap_or(0);
+        imp.logical_cpus_amount.set(logical_cpus);
+
+        imp.total_cpu.set_title_label(&i18n("CPU"));
+        imp.total_cpu.set_subtitle(&i18n("N/A"));
+        imp.total_cpu.set_data_points_max_amount(60);
+        imp.total_cpu.set_graph_color(28, 113, 216);
+
+        imp.steal_time.set_title_label(&i18n("Steal Time"));
+        imp.steal_time.set_subtitle(&i18n("N/A"));
+        imp.steal_time.set_data_points_max_amount(60);
+        imp.steal_time.set_graph_color(192, 28, 40);
+
+        imp.frequency.set_title_label(&i18n("Frequency"));
+        imp.frequency.set_subtitle(&i18n("N/A"));
+        imp.frequency.set_data_points_max_amount(60);
+        imp.frequency.set_graph_color(38, 162, 105);
+        imp.frequency.set_locked_max_y(None);
+
+        // if our CPU happens to only have one thread, showing a single thread box with the exact
+        // same fraction as the progress bar for total CPU usage would be silly, so only do
+        // thread boxes if we have more than one thread
+
+        imp.logical_switch.set_sensitive(logical_cpus > 0);
+        for i in 0..logical_cpus {
+            let thread_box = ResGraphBox::new();
+            thread_box.set_subtitle(&i18n_f("CPU {}", &[&(i + 1).to_string()]));
+            thread_box.set_title_label(&i18n("N/A"));
+            thread_box.set_graph_height_request(72);
+            thread_box.set_data_points_max_amount(60);
+            thread_box.set_graph_color(28, 113, 216);
+            let flow_box_chld = FlowBoxChild::builder()
+                .child(&thread_box)
+                .css_classes(vec!["tile", "card"])
+                .build();
+            imp.thread_box.append(&flow_box_chld);
+            imp.thread_graphs.borrow_mut().push(thread_box);
+        }
+
+        imp.max_speed.set_subtitle(
+            &cpu_info
+                .max_speed
+                .map_or_else(|| i18n("N/A"), |x| convert_frequency(x as f64)),
+        );
+
+        imp.logical_cpus.set_subtitle(
+            &cpu_info
+                .logical_cpus
+                .map_or_else(|| i18n("N/A"), |x| x.to_string()),
+        );
+
+        imp.physical_cpus.set_subtitle(
+            &cpu_info
+                .physical_cpus
+                .map_or_else(|| i18n("N/A"), |x| x.to_string()),
+        );
+
+        imp.sockets.set_subtitle(
+            &cpu_info
+                .sockets
+                .map_or_else(|| i18n("N/A"), |x| x.to_string()),
+        );
+
+        imp.virtualization
+            .set_subtitle(&cpu_info.virtualization.unwrap_or_else(|| i18n("N/A")));
+
+        imp.architecture
+            .set_subtitle(&cpu_info.architecture.unwrap_or_else(|| i18n("N/A")));
     }
 
     pub fn setup_signals(&self) {
@@ -254,14 +457,172 @@ impl ResCPU {
                     imp.stack.set_visible_child(&imp.total_page.get());
                 }
             }));
+
+        imp.governor
+            .connect_selected_notify(clone!(@weak self as this => move |row| {
+                let imp = this.imp();
+                if imp.updating_scaling.get() {
+                    return;
+                }
+                let Some(governor) = imp.governors.borrow().get(row.selected() as usize).cloned()
+                else {
+                    return;
+                };
+                MainContext::default().spawn_local(clone!(@weak this => async move {
+                    this.set_governor(&governor).await;
+                }));
+            }));
+
+        imp.energy_performance_preference.connect_selected_notify(
+            clone!(@weak self as this => move |row| {
+                let imp = this.imp();
+                if imp.updating_scaling.get() {
+                    return;
+                }
+                let Some(preference) = imp
+                    .energy_performance_preferences
+                    .borrow()
+                    .get(row.selected() as usize)
+                    .cloned()
+                else {
+                    return;
+                };
+                MainContext::default().spawn_local(clone!(@weak this => async move {
+                    this.set_energy_performance_preference(&preference).await;
+                }));
+            }),
+        );
     }
 
-    pub async fn refresh_page(&self) {
+    /// Shows the frequency scaling driver, governor and energy-performance
+    /// preference (EPP) that are currently in use. The group stays hidden if
+    /// the CPU has no cpufreq driver.
+    fn refresh_frequency_scaling(&self) {
         let imp = self.imp();
 
-        let new_total_usage = cpu::get_cpu_usage(None).await.unwrap_or((0, 0));
+        let scaling = match FrequencyScaling::current() {
+            Ok(scaling) => scaling,
+            Err(error) => {
+                log::debug!("Not showing frequency scaling information: {error}");
+                imp.scaling_group.set_visible(false);
+                return;
+            }
+        };
+        imp.scaling_group.set_visible(true);
+
+        imp.scaling_driver
+            .set_subtitle(&scaling.driver_mode.as_ref().map_or_else(
+                || scaling.driver.clone(),
+                |mode| format!("{} ({mode})", scaling.driver),
+            ));
+
+        imp.updating_scaling.set(true);
+
+        // some drivers don't tell which governors there are, so at least show the current one
+        let governors = if scaling.available_governors.is_empty() {
+            scaling.governor.iter().cloned().collect()
+        } else {
+            scaling.available_governors
+        };
+        let selected = scaling
+            .governor
+            .and_then(|current| governors.iter().position(|governor| *governor == current));
+        let labels: Vec<&str> = governors.iter().map(String::as_str).collect();
+        imp.governor.set_model(Some(&gtk::StringList::new(&labels)));
+        imp.governor
+            .set_selected(selected.map_or(gtk::INVALID_LIST_POSITION, |i| i as u32));
+        imp.governor.set_visible(selected.is_some());
+        imp.governor.set_sensitive(governors.len() > 1);
+        *imp.governors.borrow_mut() = governors;
+
+        let preferences = scaling.available_energy_performance_preferences;
+        let selected = scaling.energy_performance_preference.and_then(|current| {
+            preferences
+                .iter()
+                .position(|preference| *preference == current)
+        });
+
+        let labels: Vec<String> = preferences
+            .iter()
+            .map(|preference| energy_performance_preference_label(preference))
+            .collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        imp.energy_performance_preference
+            .set_model(Some(&gtk::StringList::new(&labels)));
+        imp.energy_performance_preference
+            .set_selected(selected.map_or(gtk::INVALID_LIST_POSITION, |i| i as u32));
+        imp.energy_performance_preference
+            .set_visible(selected.is_some());
+        *imp.energy_performance_preferences.borrow_mut() = preferences;
+        imp.updating_scaling.set(false);
+    }
+
+    /// Asks resources-helper to set `governor` as the cpufreq governor of every
+    /// logical CPU. Like with the EPP, the page shows what the kernel ended up
+    /// with afterwards.
+    async fn set_governor(&self, governor: &str) {
+        let logical_cpus = self.imp().logical_cpus_amount.get().max(1);
+
+        for cpu in 0..logical_cpus {
+            if let Err(error) = helper::set_governor(cpu as u32, governor).await {
+                log::warn!("Unable to set governor of CPU {cpu}: {error}");
+                break;
+            }
+        }
+
+        self.refresh_frequency_scaling();
+    }
+
+    /// Asks resources-helper to set `preference` as the EPP of every logical
+    /// CPU. Afterwards the page shows whatever the kernel actually ended up
+    /// with, so a failed or cancelled authentication reverts the selection.
+    async fn set_energy_performance_preference(&self, preference: &str) {
+        let logical_cpus = self.imp().logical_cpus_amount.get().max(1);
+
+        for cpu in 0..logical_cpus {
+            if let Err(error) =
+                helper::set_energy_performance_preference(cpu as u32, preference).await
+            {
+                log::warn!("Unable to set energy-performance preference of CPU {cpu}: {error}");
+                break;
+            }
+        }
+
+        self.refresh_frequency_scaling();
+    }
+
+    pub fn refresh_page(&self, cpu_data: CpuData) {
+        let imp = self.imp();
+
+        let CpuData {
+            new_total_usage,
+            new_total_steal,
+            new_thread_usages,
+            frequencies,
+            idle_states,
+            throttle_counts,
+            temperature,
+        } = cpu_data;
+
         let idle_total_delta = new_total_usage.0 - imp.old_total_usage.get().0;
         let sum_total_delta = new_total_usage.1 - imp.old_total_usage.get().1;
+
+        // steal time only ever goes up inside virtual machines, so don't bother
+        // anyone else with it
+        if new_total_steal > 0 {
+            imp.steal_group.set_visible(true);
+            if let Some(old_total_steal) = imp.old_total_steal.get() {
+                let steal_fraction = (new_total_steal.saturating_sub(old_total_steal) as f64
+                    / sum_total_delta as f64)
+                    .nan_default(0.0)
+                    .clamp(0.0, 1.0);
+                imp.steal_time.push_data_point(steal_fraction);
+                imp.steal_time
+                    .set_subtitle(&format!("{} %", (steal_fraction * 100.0).round()));
+            }
+        }
+        imp.old_total_steal.set(Some(new_total_steal));
+
         let work_total_time = sum_total_delta - idle_total_delta;
         let total_fraction = ((work_total_time as f64) / (sum_total_delta as f64)).nan_default(0.0);
         imp.total_cpu.push_data_point(total_fraction);
@@ -270,14 +631,14 @@ impl ResCPU {
         imp.old_total_usage.set(new_total_usage);
 
         if imp.logical_cpus_amount.get() > 1 {
-            for (i, old_thread_usage) in imp
+            for (i, (old_thread_usage, new_thread_usage)) in imp
                 .old_thread_usages
                 .borrow_mut()
                 .iter_mut()
+                .zip(new_thread_usages)
                 .enumerate()
                 .take(imp.logical_cpus_amount.get())
             {
-                let new_thread_usage = cpu::get_cpu_usage(Some(i)).await.unwrap_or((0, 0));
                 let idle_thread_delta = new_thread_usage.0 - old_thread_usage.0;
                 let sum_thread_delta = new_thread_usage.1 - old_thread_usage.1;
                 let work_thread_time = sum_thread_delta - idle_thread_delta;
@@ -286,14 +647,24 @@ impl ResCPU {
                     ((work_thread_time as f64) / (sum_thread_delta as f64)).nan_default(0.0);
                 curr_threadbox.push_data_point(thread_fraction);
                 curr_threadbox.set_title_label(&format!("{} %", (thread_fraction * 100.0).round()));
-                if let Ok(freq) = cpu::get_cpu_freq(i) {
-                    curr_threadbox.set_subtitle(&convert_frequency(freq as f64));
+                if let Some(Some(freq)) = frequencies.get(i) {
+                    curr_threadbox.set_subtitle(&convert_frequency(*freq as f64));
                 }
                 *old_thread_usage = new_thread_usage;
             }
         }
 
-        let temperature = cpu::get_temperature().await;
+        // frequencies are only known for this machine's CPUs, not for remote ones
+        let known_frequencies: Vec<u64> = frequencies.iter().flatten().copied().collect();
+        if !known_frequencies.is_empty() {
+            let average_frequency =
+                known_frequencies.iter().sum::<u64>() as f64 / known_frequencies.len() as f64;
+            imp.frequency_group.set_visible(true);
+            imp.frequency.push_data_point(average_frequency);
+            imp.frequency
+                .set_subtitle(&convert_frequency(average_frequency));
+        }
+
         if let Ok(temp) = temperature {
             imp.temperature
                 .set_subtitle(&convert_temperature(temp as f64));
@@ -301,6 +672,184 @@ impl ResCPU {
             imp.temperature.set_subtitle(&i18n("N/A"));
         }
 
+        self.refresh_idle_states(idle_states);
+        self.refresh_throttling(throttle_counts);
+
         self.set_property("usage", total_fraction);
     }
+
+    /// Shows how much of the time since the last refresh each logical CPU has
+    /// spent in each of its idle states
+    fn refresh_idle_states(&self, idle_states: Vec<Vec<IdleState>>) {
+        let imp = self.imp();
+
+        let now = Instant::now();
+        let elapsed = imp
+            .old_idle_instant
+            .replace(Some(now))
+            .map(|old_instant| now.duration_since(old_instant).as_micros() as f64);
+        let old_idle_states = imp.old_idle_states.replace(idle_states);
+        let idle_states = imp.old_idle_states.borrow();
+
+        // all CPUs usually share the same idle states, so the first one is used
+        // for the names
+        let state_names: Vec<&str> = idle_states
+            .first()
+            .map(|states| states.iter().map(|state| state.name.as_str()).collect())
+            .unwrap_or_default();
+
+        imp.idle_states_group.set_visible(!state_names.is_empty());
+        if state_names.is_empty() {
+            return;
+        }
+
+        if imp.idle_state_rows.borrow().len() != state_names.len() {
+            self.build_idle_state_rows(&state_names, idle_states.len());
+        }
+
+        let Some(elapsed) = elapsed.filter(|elapsed| *elapsed > 0.0) else {
+            return;
+        };
+
+        for (state, (expander_row, cpu_rows)) in imp.idle_state_rows.borrow().iter().enumerate() {
+            let mut fraction_sum = 0.0;
+            for (cpu, cpu_row) in cpu_rows.iter().enumerate() {
+                let old_time = old_idle_states
+                    .get(cpu)
+                    .and_then(|states| states.get(state))
+                    .map(|state| state.time);
+                let new_time = idle_states
+                    .get(cpu)
+                    .and_then(|states| states.get(state))
+                    .map(|state| state.time);
+
+                let fraction = match (old_time, new_time) {
+                    (Some(old_time), Some(new_time)) => {
+                        (new_time.saturating_sub(old_time) as f64 / elapsed).clamp(0.0, 1.0)
+                    }
+                    _ => 0.0,
+                };
+                fraction_sum += fraction;
+                cpu_row.set_subtitle(&format!("{} %", (fraction * 100.0).round()));
+            }
+
+            let average = (fraction_sum / cpu_rows.len() as f64).nan_default(0.0);
+            expander_row.set_subtitle(&format!("{} %", (average * 100.0).round()));
+        }
+    }
+
+    /// Looks for thermal throttling since the last refresh and marks the
+    /// frequency graph if there was any
+    fn refresh_throttling(&self, throttle_counts: Vec<Option<u64>>) {
+        let imp = self.imp();
+
+        let old_throttle_counts = imp.old_throttle_counts.replace(throttle_counts);
+        let throttle_counts = imp.old_throttle_counts.borrow();
+
+        imp.throttling
+            .set_visible(throttle_counts.iter().any(Option::is_some));
+
+        if cpu::is_throttling(&old_throttle_counts, &throttle_counts) {
+            imp.frequency.mark_latest_data_point();
+            *imp.last_throttled.borrow_mut() = glib::DateTime::now_local()
+                .and_then(|now| now.format("%X"))
+                .ok();
+            imp.throttling.set_subtitle(&i18n("Throttling"));
+        } else if let Some(last_throttled) = imp.last_throttled.borrow().as_ref() {
+            imp.throttling
+                .set_subtitle(&i18n_f("Last throttled at {}", &[last_throttled.as_str()]));
+        } else {
+            imp.throttling.set_subtitle(&i18n("Not throttled"));
+        }
+    }
+
+    fn build_idle_state_rows(&self, state_names: &[&str], logical_cpus: usize) {
+        let imp = self.imp();
+
+        for (expander_row, _) in imp.idle_state_rows.take() {
+            imp.idle_states_group.remove(&expander_row);
+        }
+
+        for state_name in state_names {
+            let expander_row = adw::ExpanderRow::builder().title(*state_name).build();
+
+            let cpu_rows: Vec<adw::ActionRow> = (0..logical_cpus)
+                .map(|cpu| {
+                    let row = adw::ActionRow::builder()
+                        .title(i18n_f("CPU {}", &[&(cpu + 1).to_string()]))
+                        .subtitle_selectable(true)
+                        .build();
+                    row.add_css_class("property");
+                    expander_row.add_row(&row);
+                    row
+                })
+                .collect();
+
+            imp.idle_states_group.add(&expander_row);
+            imp.idle_state_rows
+                .borrow_mut()
+                .push((expander_row, cpu_rows));
+        }
+    }
+
+    /// Shows whether Feral's GameMode is currently active and which games have
+    /// requested it. `None` hides the GameMode section altogether.
+    pub fn refresh_gamemode(&self, status: Option<GameModeStatus>) {
+        let imp = self.imp();
+
+        // the list of games rarely changes, so don't rebuild the rows every time
+        if *imp.gamemode_status.borrow() == status {
+            return;
+        }
+
+        imp.gamemode_group.set_visible(status.is_some());
+
+        for row in imp.gamemode_rows.take() {
+            imp.gamemode.remove(&row);
+        }
+
+        if let Some(status) = &status {
+            if status.is_active() {
+                imp.gamemode.set_subtitle(&i18n("Active"));
+            } else {
+                imp.gamemode.set_subtitle(&i18n("Inactive"));
+            }
+
+            for game in &status.games {
+                let row = adw::ActionRow::builder()
+                    .title(
+                        game.executable
+                            .clone()
+                            .unwrap_or_else(|| i18n("Unknown Game")),
+                    )
+                    .subtitle(i18n_f("PID {}", &[&game.pid.to_string()]))
+                    .subtitle_selectable(true)
+                    .build();
+                row.add_css_class("property");
+                imp.gamemode.add_row(&row);
+                imp.gamemode_rows.borrow_mut().push(row);
+            }
+
+            imp.gamemode.set_enable_expansion(!status.games.is_empty());
+        }
+
+        *imp.gamemode_status.borrow_mut() = status;
+    }
+
+    pub fn logical_cpus_amount(&self) -> usize {
+        self.imp().logical_cpus_amount.get()
+    }
+}
+
+/// Returns a human-readable name for one of the energy-performance preferences
+/// that cpufreq offers
+fn energy_performance_preference_label(preference: &str) -> String {
+    match preference {
+        "default" => i18n("Default"),
+        "performance" => i18n("Performance"),
+        "balance_performance" => i18n("Balance Performance"),
+        "balance_power" => i18n("Balance Power"),
+        "power" => i18n("Power Saving"),
+        _ => preference.to_string(),
+    }
 }
@@ -0,0 +1,325 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::glib::{self, clone};
+use gtk::FlowBoxChild;
+
+use crate::config::PROFILE;
+use crate::i18n::i18n;
+use crate::ui::widgets::graph::ResGraph;
+use crate::ui::widgets::graph_box::{pinnable_graph_box, ResGraphBox};
+use crate::utils::settings::SETTINGS;
+
+/// A metric that has been pinned to the dashboard. It mirrors the graph box with
+/// the same dashboard key for as long as that graph box exists.
+#[derive(Debug)]
+pub struct Tile {
+    key: String,
+    child: FlowBoxChild,
+    page_label: gtk::Label,
+    title_label: gtk::Label,
+    info_label: gtk::Label,
+    graph: ResGraph,
+    source: Option<(glib::WeakRef<ResGraphBox>, glib::SignalHandlerId)>,
+}
+
+impl Tile {
+    fn new(key: &str) -> Self {
+        let page_label = gtk::Label::builder()
+            .halign(gtk::Align::Start)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .css_classes(vec!["caption", "dim-label"])
+            .build();
+
+        let title_label = gtk::Label::builder()
+            .label(i18n("N/A"))
+            .halign(gtk::Align::Start)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .build();
+
+        let unpin_button = gtk::Button::builder()
+            .icon_name("window-close-symbolic")
+            .tooltip_text(i18n("Unpin"))
+            .valign(gtk::Align::Center)
+            .css_classes(vec!["flat", "circular"])
+            .build();
+        unpin_button.connect_clicked(clone!(@strong key => move |_| set_pinned(&key, false)));
+
+        let labels_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        labels_box.set_hexpand(true);
+        labels_box.append(&page_label);
+        labels_box.append(&title_label);
+
+        let header_box = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+        header_box.append(&labels_box);
+        header_box.append(&unpin_button);
+
+        let graph = ResGraph::new();
+        graph.set_data_points_max_amount(60);
+        graph.set_height_request(72);
+        graph.set_hexpand(true);
+
+        let info_label = gtk::Label::builder()
+            .halign(gtk::Align::Start)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .css_classes(vec!["dim-label"])
+            .build();
+
+        let tile_box = gtk::Box::new(gtk::Orientation::Vertical, 6);
+        tile_box.set_margin_top(12);
+        tile_box.set_margin_bottom(12);
+        tile_box.set_margin_start(12);
+        tile_box.set_margin_end(12);
+        tile_box.append(&header_box);
+        tile_box.append(&graph);
+        tile_box.append(&info_label);
+
+        let child = FlowBoxChild::builder()
+            .child(&tile_box)
+            .css_classes(vec!["tile", "card"])
+            .build();
+
+        Self {
+            key: key.to_string(),
+            child,
+            page_label,
+            title_label,
+            info_label,
+            graph,
+            source: None,
+        }
+    }
+
+    /// Starts mirroring the graph box for this tile if it exists by now
+    fn attach(&mut self) {
+        if let Some((graph_box, _)) = &self.source {
+            if graph_box.upgrade().is_some() {
+                return;
+            }
+            // the page of the graph box is gone (e.g. an unplugged drive), so wait
+            // for it to come back
+            self.source = None;
+            self.title_label.set_label(&i18n("N/A"));
+            self.info_label.set_label("");
+        }
+
+        let Some(graph_box) = pinnable_graph_box(&self.key) else {
+            return;
+        };
+
+        graph_box.bind_labels(&self.title_label, &self.info_label);
+        self.page_label
+            .set_label(&page_name(graph_box.upcast_ref()).unwrap_or_default());
+
+        let (r, g, b) = graph_box.graph_color();
+        self.graph.set_graph_color(r, g, b);
+        self.graph.set_locked_max_y(graph_box.locked_max_y());
+
+        let graph = self.graph.clone();
+        let handler = graph_box.connect_data_point_pushed(
+            clone!(@weak graph => move |data_point| graph.push_data_point(data_point)),
+        );
+
+        self.source = Some((graph_box.downgrade(), handler));
+    }
+
+    fn detach(&mut self) {
+        if let Some((graph_box, handler)) = self.source.take() {
+            if let Some(graph_box) = graph_box.upgrade() {
+                graph_box.disconnect(handler);
+            }
+        }
+    }
+}
+
+/// Returns the name of the page `widget` is shown on
+pub fn page_name(widget: &gtk::Widget) -> Option<String> {
+    let mut ancestor = widget.parent();
+    while let Some(widget) = ancestor {
+        if widget.find_property("tab_name").is_some() {
+            return Some(widget.property::<String>("tab_name"));
+        }
+        ancestor = widget.parent();
+    }
+    None
+}
+
+pub fn is_pinned(dashboard_key: &str) -> bool {
+    SETTINGS
+        .dashboard_tiles()
+        .iter()
+        .any(|tile| tile == dashboard_key)
+}
+
+/// Pins the metric with `dashboard_key` to the dashboard or removes it from it
+pub fn set_pinned(dashboard_key: &str, pinned: bool) {
+    let mut tiles = SETTINGS.dashboard_tiles();
+    if pinned {
+        if !tiles.iter().any(|tile| tile == dashboard_key) {
+            tiles.push(dashboard_key.to_string());
+        }
+    } else {
+        tiles.retain(|tile| tile != dashboard_key);
+    }
+
+    if let Err(error) = SETTINGS.set_dashboard_tiles(&tiles) {
+        log::warn!("Unable to save dashboard tiles: {error}");
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    use gtk::{
+        gio::{Icon, ThemedIcon},
+        glib::{ParamSpec, Properties, Value},
+        CompositeTemplate,
+    };
+
+    #[derive(CompositeTemplate, Properties)]
+    #[template(resource = "/net/nokyan/Resources/ui/pages/dashboard.ui")]
+    #[properties(wrapper_type = super::ResDashboard)]
+    pub struct ResDashboard {
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub tiles_box: TemplateChild<gtk::FlowBox>,
+
+        pub tiles: RefCell<Vec<Tile>>,
+
+        #[property(get)]
+        uses_progress_bar: Cell<bool>,
+
+        #[property(get)]
+        icon: RefCell<Icon>,
+
+        #[property(get = Self::tab_name, type = glib::GString)]
+        tab_name: Cell<glib::GString>,
+    }
+
+    impl ResDashboard {
+        pub fn tab_name(&self) -> glib::GString {
+            let tab_name = self.tab_name.take();
+            let result = tab_name.clone();
+            self.tab_name.set(tab_name);
+            result
+        }
+    }
+
+    impl Default for ResDashboard {
+        fn default() -> Self {
+            Self {
+                stack: Default::default(),
+                tiles_box: Default::default(),
+                tiles: Default::default(),
+                uses_progress_bar: Cell::new(false),
+                icon: RefCell::new(ThemedIcon::new("view-grid-symbolic").into()),
+                tab_name: Cell::from(glib::GString::from(i18n("Dashboard"))),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResDashboard {
+        const NAME: &'static str = "ResDashboard";
+        type Type = super::ResDashboard;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        // You must call `Widget`'s `init_template()` within `instance_init()`.
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ResDashboard {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // Devel Profile
+            if PROFILE == "Devel" {
+                obj.add_css_class("devel");
+            }
+        }
+
+        fn dispose(&self) {
+            for mut tile in self.tiles.take() {
+                tile.detach();
+            }
+        }
+
+        fn properties() -> &'static [ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn set_property(&self, id: usize, value: &Value, pspec: &ParamSpec) {
+            self.derived_set_property(id, value, pspec);
+        }
+
+        fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
+            self.derived_property(id, pspec)
+        }
+    }
+
+    impl WidgetImpl for ResDashboard {}
+    impl BinImpl for ResDashboard {}
+}
+
+glib::wrapper! {
+    pub struct ResDashboard(ObjectSubclass<imp::ResDashboard>)
+        @extends gtk::Widget, adw::Bin;
+}
+
+impl Default for ResDashboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResDashboard {
+    pub fn new() -> Self {
+        glib::Object::new::<Self>()
+    }
+
+    pub fn init(&self) {
+        self.rebuild_tiles();
+
+        SETTINGS.connect_dashboard_tiles(clone!(@weak self as this => move |_| {
+            this.rebuild_tiles();
+        }));
+    }
+
+    fn rebuild_tiles(&self) {
+        let imp = self.imp();
+
+        for mut tile in imp.tiles.take() {
+            tile.detach();
+            imp.tiles_box.remove(&tile.child);
+        }
+
+        let keys = SETTINGS.dashboard_tiles();
+        imp.stack
+            .set_visible_child_name(if keys.is_empty() { "empty" } else { "tiles" });
+
+        for key in keys {
+            let tile = Tile::new(&key);
+            imp.tiles_box.append(&tile.child);
+            imp.tiles.borrow_mut().push(tile);
+        }
+
+        self.attach_tiles();
+    }
+
+    /// Connects tiles to their graph boxes. Pages for GPUs, drives and network
+    /// interfaces are only added after the dashboard has been set up and may
+    /// come and go, so this is called periodically.
+    pub fn attach_tiles(&self) {
+        for tile in self.imp().tiles.borrow_mut().iter_mut() {
+            tile.attach();
+        }
+    }
+}
@@ -1,12 +1,18 @@
 use std::time::{Duration, SystemTime};
 
 use adw::{prelude::*, subclass::prelude::*};
-use gtk::glib;
+use gtk::glib::{self, clone, MainContext};
 
 use crate::config::PROFILE;
-use crate::i18n::i18n;
-use crate::utils::drive::Drive;
-use crate::utils::units::{convert_speed, convert_storage};
+use crate::i18n::{i18n, i18n_f};
+use crate::ui::copy::setup_copy_actions;
+use crate::utils::drive::{Drive, DriveData, DriveExt, IoScheduler};
+use crate::utils::helper;
+use crate::utils::smart::{SmartHistory, SmartSample};
+use crate::utils::units::{convert_speed, convert_storage, convert_temperature};
+
+/// How many hourly SMART samples are shown in the health graphs
+const SMART_GRAPH_SAMPLES: usize = 24 * 30;
 
 mod imp {
     use std::{
@@ -44,6 +50,21 @@ mod imp {
         pub writable: TemplateChild<adw::ActionRow>,
         #[template_child]
         pub removable: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub io_scheduler: TemplateChild<adw::ComboRow>,
+        pub io_schedulers: RefCell<Vec<String>>,
+        pub updating_io_scheduler: Cell<bool>,
+        #[template_child]
+        pub health: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub smartctl_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub temperature_history: TemplateChild<ResGraphBox>,
+        #[template_child]
+        pub reallocated_sectors_history: TemplateChild<ResGraphBox>,
+        #[template_child]
+        pub wear_history: TemplateChild<ResGraphBox>,
+        pub smart_history: RefCell<Option<SmartHistory>>,
         pub old_stats: RefCell<HashMap<String, usize>>,
         pub drive: RefCell<Drive>,
         pub last_timestamp: Cell<SystemTime>,
@@ -96,6 +117,15 @@ mod imp {
                 capacity: Default::default(),
                 writable: Default::default(),
                 removable: Default::default(),
+                io_scheduler: Default::default(),
+                io_schedulers: Default::default(),
+                updating_io_scheduler: Default::default(),
+                health: Default::default(),
+                temperature_history: Default::default(),
+                reallocated_sectors_history: Default::default(),
+                wear_history: Default::default(),
+                smartctl_button: Default::default(),
+                smart_history: Default::default(),
                 uses_progress_bar: Cell::new(true),
                 icon: RefCell::new(Drive::default_icon()),
                 usage: Default::default(),
@@ -167,7 +197,53 @@ impl ResDrive {
 
     pub fn init(&self, drive: Drive) {
         self.imp().set_icon(&drive.icon());
+        self.imp()
+            .total_usage
+            .set_dashboard_key(&format!("drive-{}", drive.block_device));
         self.setup_widgets(drive);
+        self.setup_signals();
+        setup_copy_actions(self);
+    }
+
+    /// Sets up the page for a drive of a machine that is not this one, e.g. one
+    /// that is monitored over SSH. Its graph can't be pinned to the dashboard
+    /// and its I/O scheduler can't be changed from here.
+    pub fn init_remote(&self, drive: Drive) {
+        self.imp().set_icon(&drive.icon());
+        self.setup_widgets(drive);
+        setup_copy_actions(self);
+    }
+
+    pub fn setup_signals(&self) {
+        let imp = self.imp();
+
+        imp.io_scheduler
+            .connect_selected_notify(clone!(@weak self as this => move |row| {
+                let imp = this.imp();
+                if imp.updating_io_scheduler.get() {
+                    return;
+                }
+                let Some(scheduler) = imp
+                    .io_schedulers
+                    .borrow()
+                    .get(row.selected() as usize)
+                    .cloned()
+                else {
+                    return;
+                };
+                MainContext::default().spawn_local(clone!(@weak this => async move {
+                    this.set_io_scheduler(&scheduler).await;
+                }));
+            }));
+
+        imp.smartctl_button
+            .connect_clicked(clone!(@weak self as this => move |button| {
+                button.set_sensitive(false);
+                MainContext::default().spawn_local(clone!(@weak this, @weak button => async move {
+                    this.read_smart_with_smartctl().await;
+                    button.set_sensitive(true);
+                }));
+            }));
     }
 
     pub fn setup_widgets(&self, drive: Drive) {
@@ -189,6 +265,26 @@ impl ResDrive {
         );
         imp.device.set_subtitle(&drive.block_device);
 
+        imp.temperature_history
+            .set_title_label(&i18n("Temperature"));
+        imp.temperature_history
+            .set_data_points_max_amount(SMART_GRAPH_SAMPLES);
+        imp.temperature_history.set_graph_color(229, 165, 10);
+        imp.temperature_history.set_locked_max_y(None);
+
+        imp.reallocated_sectors_history
+            .set_title_label(&i18n("Reallocated Sectors"));
+        imp.reallocated_sectors_history
+            .set_data_points_max_amount(SMART_GRAPH_SAMPLES);
+        imp.reallocated_sectors_history
+            .set_graph_color(229, 165, 10);
+        imp.reallocated_sectors_history.set_locked_max_y(None);
+
+        imp.wear_history.set_title_label(&i18n("Wear"));
+        imp.wear_history
+            .set_data_points_max_amount(SMART_GRAPH_SAMPLES);
+        imp.wear_history.set_graph_color(229, 165, 10);
+
         imp.last_timestamp.set(
             SystemTime::now()
                 .checked_sub(Duration::from_secs(1))
@@ -198,23 +294,35 @@ impl ResDrive {
         *imp.drive.borrow_mut() = drive;
     }
 
-    pub async fn refresh_page(&self) {
+    pub fn drive(&self) -> Drive {
+        self.imp().drive.borrow().clone()
+    }
+
+    pub fn refresh_page(&self, drive_data: DriveData) {
         let imp = self.imp();
 
-        let hw_sector_size = imp.drive.borrow().sector_size().await.unwrap_or(512) as usize;
-        let disk_stats = imp.drive.borrow().sys_stats().await.unwrap_or_default();
+        let DriveData {
+            disk_stats,
+            capacity,
+            sector_size,
+            writable,
+            removable,
+            io_scheduler,
+        } = drive_data;
+
+        let hw_sector_size = sector_size as usize;
 
         let time_passed = SystemTime::now()
             .duration_since(imp.last_timestamp.get())
             .map_or(1.0f64, |timestamp| timestamp.as_secs_f64());
 
-        if imp.drive.borrow().writable().await.unwrap_or(false) {
+        if writable {
             imp.writable.set_subtitle(&i18n("Yes"));
         } else {
             imp.writable.set_subtitle(&i18n("No"));
         }
 
-        if imp.drive.borrow().removable().await.unwrap_or(false) {
+        if removable {
             imp.removable.set_subtitle(&i18n("Yes"));
         } else {
             imp.removable.set_subtitle(&i18n("No"));
@@ -258,11 +366,162 @@ impl ResDrive {
                 .set_subtitle(&convert_speed(write_bytes_per_second));
         }
 
-        let capacity = imp.drive.borrow().capacity().await.unwrap_or(0) * (hw_sector_size as u64);
+        self.refresh_io_scheduler(io_scheduler);
+
+        let capacity = capacity * sector_size;
         imp.capacity
             .set_subtitle(&convert_storage(capacity as f64, false));
 
         *imp.old_stats.borrow_mut() = disk_stats;
         imp.last_timestamp.set(SystemTime::now());
     }
+
+    /// Shows the I/O scheduler the drive currently uses. The row stays hidden
+    /// for drives that don't go through the block layer's scheduling, e.g.
+    /// some virtual devices.
+    fn refresh_io_scheduler(&self, io_scheduler: Option<IoScheduler>) {
+        let imp = self.imp();
+
+        let Some(IoScheduler { active, available }) = io_scheduler else {
+            imp.io_scheduler.set_visible(false);
+            return;
+        };
+
+        imp.updating_io_scheduler.set(true);
+        // only replace the model if it has actually changed, which would
+        // otherwise close the popover every refresh
+        if *imp.io_schedulers.borrow() != available {
+            let labels: Vec<&str> = available.iter().map(String::as_str).collect();
+            imp.io_scheduler
+                .set_model(Some(&gtk::StringList::new(&labels)));
+            *imp.io_schedulers.borrow_mut() = available;
+        }
+        let selected = imp
+            .io_schedulers
+            .borrow()
+            .iter()
+            .position(|scheduler| *scheduler == active);
+        if let Some(selected) = selected {
+            if imp.io_scheduler.selected() != selected as u32 {
+                imp.io_scheduler.set_selected(selected as u32);
+            }
+        }
+        imp.io_scheduler.set_visible(selected.is_some());
+        imp.updating_io_scheduler.set(false);
+    }
+
+    /// Asks resources-helper to switch the drive to `scheduler`. The next
+    /// refresh shows whatever the kernel actually ended up with, so a failed
+    /// or cancelled authentication reverts the selection.
+    async fn set_io_scheduler(&self, scheduler: &str) {
+        let block_device = self.imp().drive.borrow().block_device.clone();
+
+        if let Err(error) = helper::set_io_scheduler(&block_device, scheduler).await {
+            log::warn!("Unable to set I/O scheduler of {block_device}: {error}");
+        }
+    }
+
+    /// Reads the drive's current SMART attributes from UDisks, records them in
+    /// the drive's SMART history if the last sample is old enough and updates
+    /// the health graphs accordingly. If UDisks has nothing, the user is offered
+    /// to read them with smartctl instead.
+    pub async fn refresh_smart(&self) {
+        let imp = self.imp();
+
+        let block_device = imp.drive.borrow().block_device.clone();
+        match SmartSample::current(&block_device).await {
+            Ok((drive_id, sample)) => self.show_smart_sample(&drive_id, sample),
+            Err(error) => {
+                log::debug!("Unable to get SMART data of {block_device} from UDisks: {error}");
+                // don't offer smartctl for drives that it has failed for already
+                if imp.smart_history.borrow().is_none() {
+                    imp.smartctl_button.set_visible(true);
+                    imp.health.set_visible(true);
+                }
+            }
+        }
+    }
+
+    /// Reads the drive's SMART attributes with smartctl through resources-helper.
+    /// This asks for authentication, so it's only done when the user asks for it.
+    async fn read_smart_with_smartctl(&self) {
+        let imp = self.imp();
+
+        let block_device = imp.drive.borrow().block_device.clone();
+        match SmartSample::current_from_smartctl(&block_device).await {
+            Ok((drive_id, sample)) => self.show_smart_sample(&drive_id, sample),
+            Err(error) => {
+                log::warn!("Unable to get SMART data of {block_device} from smartctl: {error}");
+                imp.smartctl_button.set_visible(false);
+                imp.health.set_visible(
+                    imp.temperature_history.is_visible()
+                        || imp.reallocated_sectors_history.is_visible()
+                        || imp.wear_history.is_visible(),
+                );
+            }
+        }
+    }
+
+    fn show_smart_sample(&self, drive_id: &str, sample: SmartSample) {
+        let imp = self.imp();
+        let block_device = imp.drive.borrow().block_device.clone();
+
+        let mut smart_history = imp.smart_history.borrow_mut();
+        let first_refresh = smart_history.is_none();
+        let history = smart_history.get_or_insert_with(|| SmartHistory::load(drive_id));
+
+        let recorded = match history.record(sample) {
+            Ok(recorded) => recorded,
+            Err(error) => {
+                log::warn!("Unable to record SMART data of {block_device}: {error}");
+                false
+            }
+        };
+
+        // the graphs are filled with the whole history once and then only
+        // get the newly recorded samples
+        let new_samples = if first_refresh {
+            history.samples.clone()
+        } else if recorded {
+            vec![sample]
+        } else {
+            Vec::new()
+        };
+        drop(smart_history);
+
+        for new_sample in &new_samples {
+            if let Some(temperature) = new_sample.temperature {
+                imp.temperature_history.push_data_point(temperature);
+                imp.temperature_history.set_visible(true);
+            }
+            if let Some(reallocated_sectors) = new_sample.reallocated_sectors {
+                imp.reallocated_sectors_history
+                    .push_data_point(reallocated_sectors as f64);
+                imp.reallocated_sectors_history.set_visible(true);
+            }
+            if let Some(wear) = new_sample.wear {
+                imp.wear_history.push_data_point(wear);
+                imp.wear_history.set_visible(true);
+            }
+        }
+
+        if let Some(temperature) = sample.temperature {
+            imp.temperature_history
+                .set_subtitle(&convert_temperature(temperature));
+        }
+        if let Some(reallocated_sectors) = sample.reallocated_sectors {
+            imp.reallocated_sectors_history
+                .set_subtitle(&reallocated_sectors.to_string());
+        }
+        if let Some(wear) = sample.wear {
+            imp.wear_history
+                .set_subtitle(&i18n_f("{} % used", &[&(wear * 100.0).round().to_string()]));
+        }
+
+        imp.health.set_visible(
+            sample.temperature.is_some()
+                || sample.reallocated_sectors.is_some()
+                || sample.wear.is_some(),
+        );
+    }
 }
@@ -2,8 +2,10 @@ use adw::{prelude::*, subclass::prelude::*};
 use gtk::glib::{self};
 
 use crate::config::PROFILE;
-use crate::i18n::i18n;
-use crate::utils::gpu::GPU;
+use crate::i18n::{i18n, i18n_f};
+use crate::ui::copy::setup_copy_actions;
+use crate::utils::gpu::{GpuData, GPU};
+use crate::utils::gpu_resets::{GpuReset, GpuResetKind};
 use crate::utils::units::{convert_frequency, convert_power, convert_storage, convert_temperature};
 use crate::utils::NaNDefault;
 
@@ -49,6 +51,8 @@ mod imp {
         pub current_power_cap: TemplateChild<adw::ActionRow>,
         #[template_child]
         pub max_power_cap: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub resets_list: TemplateChild<gtk::ListBox>,
 
         pub gpu: OnceLock<GPU>,
         pub number: OnceLock<usize>,
@@ -93,6 +97,7 @@ mod imp {
                 driver_used: Default::default(),
                 current_power_cap: Default::default(),
                 max_power_cap: Default::default(),
+                resets_list: Default::default(),
                 gpu: Default::default(),
                 number: Default::default(),
                 uses_progress_bar: Cell::new(true),
@@ -162,6 +167,7 @@ impl ResGPU {
         imp.gpu.set(gpu).unwrap_or_default();
         imp.number.set(number).unwrap_or_default();
         self.setup_widgets();
+        setup_copy_actions(self);
     }
 
     pub fn setup_widgets(&self) {
@@ -173,16 +179,53 @@ impl ResGPU {
         imp.vram_usage.set_title_label(&i18n("Video Memory Usage"));
         imp.vram_usage.set_data_points_max_amount(60);
         imp.vram_usage.set_graph_color(192, 28, 40);
+        let number = imp.number.get().copied().unwrap_or_default();
+        imp.gpu_usage
+            .set_dashboard_key(&format!("gpu-{number}-usage"));
+        imp.vram_usage
+            .set_dashboard_key(&format!("gpu-{number}-vram"));
         imp.manufacturer
             .set_subtitle(&gpu.get_vendor().unwrap_or_else(|_| i18n("N/A")));
-        imp.pci_slot.set_subtitle(&gpu.pci_slot);
-        imp.driver_used.set_subtitle(&gpu.driver);
+        for (row, value) in [
+            (&imp.pci_slot, &gpu.pci_slot),
+            (&imp.driver_used, &gpu.driver),
+        ] {
+            if value.is_empty() {
+                row.set_subtitle(&i18n("N/A"));
+            } else {
+                row.set_subtitle(value);
+            }
+        }
+        imp.resets_list.set_placeholder(Some(
+            &gtk::Label::builder()
+                .label(i18n("No resets recorded"))
+                .margin_top(12)
+                .margin_bottom(12)
+                .css_classes(vec!["dim-label"])
+                .build(),
+        ));
     }
 
-    pub async fn refresh_page(&self) {
+    pub fn gpu(&self) -> GPU {
+        self.imp().gpu.get().cloned().unwrap_or_default()
+    }
+
+    pub fn refresh_page(&self, gpu_data: GpuData) {
         let imp = self.imp();
-        let gpu = imp.gpu.get().unwrap();
-        if let Ok(gpu_usage) = gpu.get_gpu_usage().await {
+
+        let GpuData {
+            usage,
+            total_vram,
+            used_vram,
+            temp,
+            power_usage,
+            gpu_speed,
+            vram_speed,
+            power_cap,
+            power_cap_max,
+        } = gpu_data;
+
+        if let Ok(gpu_usage) = usage {
             let gpu_usage_fraction = gpu_usage as f64 / 100.0;
             imp.gpu_usage.set_subtitle(&format!("{gpu_usage} %"));
             imp.gpu_usage.push_data_point(gpu_usage_fraction);
@@ -194,9 +237,7 @@ impl ResGPU {
             imp.gpu_usage.set_graph_visible(false);
         }
 
-        if let (Ok(total_vram), Ok(used_vram)) =
-            (gpu.get_total_vram().await, gpu.get_used_vram().await)
-        {
+        if let (Ok(total_vram), Ok(used_vram)) = (total_vram, used_vram) {
             let used_vram_fraction = (used_vram as f64 / total_vram as f64).nan_default(0.0);
             imp.vram_usage.set_subtitle(&format!(
                 "{} / {} · {} %",
@@ -212,42 +253,62 @@ impl ResGPU {
             imp.vram_usage.set_graph_visible(false);
         }
 
-        imp.temperature.set_subtitle(
-            &gpu.get_gpu_temp()
-                .await
-                .map_or_else(|_| i18n("N/A"), convert_temperature),
-        );
+        imp.temperature
+            .set_subtitle(&temp.map_or_else(|_| i18n("N/A"), convert_temperature));
 
-        imp.power_usage.set_subtitle(
-            &gpu.get_power_usage()
-                .await
-                .map_or_else(|_| i18n("N/A"), convert_power),
-        );
+        imp.power_usage
+            .set_subtitle(&power_usage.map_or_else(|_| i18n("N/A"), convert_power));
 
-        if let Ok(gpu_clockspeed) = gpu.get_gpu_speed().await {
+        if let Ok(gpu_clockspeed) = gpu_speed {
             imp.gpu_clockspeed
                 .set_subtitle(&convert_frequency(gpu_clockspeed));
         } else {
             imp.gpu_clockspeed.set_subtitle(&i18n("N/A"));
         }
 
-        if let Ok(vram_clockspeed) = gpu.get_vram_speed().await {
+        if let Ok(vram_clockspeed) = vram_speed {
             imp.vram_clockspeed
                 .set_subtitle(&convert_frequency(vram_clockspeed));
         } else {
             imp.vram_clockspeed.set_subtitle(&i18n("N/A"));
         }
 
-        imp.current_power_cap.set_subtitle(
-            &gpu.get_power_cap()
-                .await
-                .map_or_else(|_| i18n("N/A"), convert_power),
-        );
-
-        imp.max_power_cap.set_subtitle(
-            &gpu.get_power_cap_max()
-                .await
-                .map_or_else(|_| i18n("N/A"), convert_power),
-        );
+        imp.current_power_cap
+            .set_subtitle(&power_cap.map_or_else(|_| i18n("N/A"), convert_power));
+
+        imp.max_power_cap
+            .set_subtitle(&power_cap_max.map_or_else(|_| i18n("N/A"), convert_power));
+    }
+
+    /// Adds those of `resets` that affect this page's GPU to the top of the
+    /// history and returns them
+    pub fn add_resets<'a>(&self, resets: &'a [GpuReset]) -> Vec<&'a GpuReset> {
+        let imp = self.imp();
+        let pci_slot = &imp.gpu.get().unwrap().pci_slot;
+
+        let resets: Vec<&GpuReset> = resets
+            .iter()
+            .filter(|reset| reset.pci_slot.eq_ignore_ascii_case(pci_slot))
+            .collect();
+
+        for reset in &resets {
+            let title = match reset.kind {
+                GpuResetKind::Hang => i18n("GPU Hang"),
+                GpuResetKind::Reset => i18n("GPU Reset"),
+            };
+            let time = glib::DateTime::from_unix_local(reset.timestamp / 1_000_000)
+                .and_then(|date_time| date_time.format("%Y-%m-%d %H:%M:%S"))
+                .map(|time| time.to_string())
+                .unwrap_or_default();
+
+            let row = adw::ActionRow::builder()
+                .title(i18n_f("{} at {}", &[&title, &time]))
+                .subtitle(glib::markup_escape_text(&reset.message))
+                .subtitle_selectable(true)
+                .build();
+            imp.resets_list.prepend(&row);
+        }
+
+        resets
     }
 }
@@ -0,0 +1,320 @@
+use std::time::Instant;
+
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::glib;
+use hashbrown::HashMap;
+
+use crate::config::PROFILE;
+use crate::i18n::{i18n, i18n_f};
+use crate::ui::copy::setup_copy_actions;
+use crate::utils::interrupts::{Interrupt, InterruptData, InterruptKind};
+
+/// If a single CPU handles an interrupt more often than this per second, the
+/// interrupt is most likely misbehaving (e.g. an interrupt storm caused by a
+/// broken driver or firmware)
+const RUNAWAY_RATE: f64 = 5000.0;
+
+#[derive(Debug)]
+pub struct InterruptRow {
+    row: adw::ActionRow,
+    rate_label: gtk::Label,
+    old_counts: Vec<u64>,
+}
+
+impl InterruptRow {
+    fn new() -> Self {
+        let rate_label = gtk::Label::builder()
+            .label(i18n("N/A"))
+            .valign(gtk::Align::Center)
+            .css_classes(vec!["numeric"])
+            .build();
+
+        let row = adw::ActionRow::builder().subtitle_selectable(true).build();
+        row.add_css_class("property");
+        row.add_suffix(&rate_label);
+
+        Self {
+            row,
+            rate_label,
+            old_counts: Vec::new(),
+        }
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    use super::*;
+
+    use gtk::{
+        gio::{Icon, ThemedIcon},
+        glib::{ParamSpec, Properties, Value},
+        CompositeTemplate,
+    };
+
+    #[derive(CompositeTemplate, Properties)]
+    #[template(resource = "/net/nokyan/Resources/ui/pages/interrupts.ui")]
+    #[properties(wrapper_type = super::ResInterrupts)]
+    pub struct ResInterrupts {
+        #[template_child]
+        pub hardware_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub software_list: TemplateChild<gtk::ListBox>,
+
+        pub rows: RefCell<HashMap<String, InterruptRow>>,
+        pub rates: Rc<RefCell<HashMap<gtk::ListBoxRow, f64>>>,
+        pub last_refresh: Cell<Option<Instant>>,
+
+        #[property(get)]
+        uses_progress_bar: Cell<bool>,
+
+        #[property(get)]
+        icon: RefCell<Icon>,
+
+        #[property(get = Self::tab_name, type = glib::GString)]
+        tab_name: Cell<glib::GString>,
+    }
+
+    impl ResInterrupts {
+        pub fn tab_name(&self) -> glib::GString {
+            let tab_name = self.tab_name.take();
+            let result = tab_name.clone();
+            self.tab_name.set(tab_name);
+            result
+        }
+    }
+
+    impl Default for ResInterrupts {
+        fn default() -> Self {
+            Self {
+                hardware_list: Default::default(),
+                software_list: Default::default(),
+                rows: Default::default(),
+                rates: Default::default(),
+                last_refresh: Default::default(),
+                uses_progress_bar: Cell::new(false),
+                icon: RefCell::new(ThemedIcon::new("emblem-system-symbolic").into()),
+                tab_name: Cell::from(glib::GString::from(i18n("Interrupts"))),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResInterrupts {
+        const NAME: &'static str = "ResInterrupts";
+        type Type = super::ResInterrupts;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        // You must call `Widget`'s `init_template()` within `instance_init()`.
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ResInterrupts {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // Devel Profile
+            if PROFILE == "Devel" {
+                obj.add_css_class("devel");
+            }
+        }
+
+        fn properties() -> &'static [ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn set_property(&self, id: usize, value: &Value, pspec: &ParamSpec) {
+            self.derived_set_property(id, value, pspec);
+        }
+
+        fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
+            self.derived_property(id, pspec)
+        }
+    }
+
+    impl WidgetImpl for ResInterrupts {}
+    impl BinImpl for ResInterrupts {}
+}
+
+glib::wrapper! {
+    pub struct ResInterrupts(ObjectSubclass<imp::ResInterrupts>)
+        @extends gtk::Widget, adw::Bin;
+}
+
+impl Default for ResInterrupts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResInterrupts {
+    pub fn new() -> Self {
+        glib::Object::new::<Self>()
+    }
+
+    pub fn init(&self) {
+        let imp = self.imp();
+
+        // busiest interrupts first
+        for list in [&imp.hardware_list, &imp.software_list] {
+            let rates = imp.rates.clone();
+            list.set_sort_func(move |a, b| {
+                let rates = rates.borrow();
+                let rate_a = rates.get(a).copied().unwrap_or_default();
+                let rate_b = rates.get(b).copied().unwrap_or_default();
+                rate_b.total_cmp(&rate_a).into()
+            });
+        }
+
+        setup_copy_actions(self);
+    }
+
+    pub fn refresh_page(&self, interrupt_data: InterruptData) {
+        let imp = self.imp();
+
+        let now = Instant::now();
+        let elapsed = imp
+            .last_refresh
+            .replace(Some(now))
+            .map(|last_refresh| now.duration_since(last_refresh).as_secs_f64())
+            .filter(|elapsed| *elapsed > 0.0);
+
+        let mut rows = imp.rows.borrow_mut();
+
+        // interrupts can disappear, e.g. when a device is removed
+        rows.retain(|key, interrupt_row| {
+            let present = interrupt_data
+                .interrupts
+                .iter()
+                .any(|interrupt| interrupt.key() == *key);
+            if !present {
+                imp.rates
+                    .borrow_mut()
+                    .remove(interrupt_row.row.upcast_ref::<gtk::ListBoxRow>());
+                if let Some(list) = interrupt_row.row.parent().and_downcast::<gtk::ListBox>() {
+                    list.remove(&interrupt_row.row);
+                }
+            }
+            present
+        });
+
+        for interrupt in &interrupt_data.interrupts {
+            if interrupt.total() == 0 {
+                continue;
+            }
+
+            // appending a row runs the sort function, so `rates` must not be
+            // borrowed at that point
+            let interrupt_row = rows.entry(interrupt.key()).or_insert_with(|| {
+                let interrupt_row = InterruptRow::new();
+                match interrupt.kind {
+                    InterruptKind::Hardware => imp.hardware_list.append(&interrupt_row.row),
+                    InterruptKind::Software => imp.software_list.append(&interrupt_row.row),
+                }
+                interrupt_row
+            });
+
+            let rate = refresh_row(interrupt_row, interrupt, elapsed);
+            imp.rates
+                .borrow_mut()
+                .insert(interrupt_row.row.clone().upcast(), rate);
+        }
+
+        drop(rows);
+
+        imp.hardware_list.invalidate_sort();
+        imp.software_list.invalidate_sort();
+    }
+}
+
+/// Updates `interrupt_row` with the latest counters of `interrupt` and returns
+/// how often per second the interrupt has fired since the last refresh
+fn refresh_row(
+    interrupt_row: &mut InterruptRow,
+    interrupt: &Interrupt,
+    elapsed: Option<f64>,
+) -> f64 {
+    let deltas: Vec<u64> = interrupt
+        .counts
+        .iter()
+        .enumerate()
+        .map(|(cpu, count)| {
+            count.saturating_sub(interrupt_row.old_counts.get(cpu).copied().unwrap_or(*count))
+        })
+        .collect();
+    interrupt_row.old_counts.clone_from(&interrupt.counts);
+
+    // if nothing happened since the last refresh, the CPU that handled the
+    // interrupt most often since boot is the best guess
+    let busiest_cpu = if deltas.iter().any(|delta| *delta > 0) {
+        max_index(&deltas)
+    } else {
+        max_index(&interrupt.counts)
+    };
+
+    let title = interrupt
+        .description
+        .clone()
+        .unwrap_or_else(|| interrupt.id.clone());
+    interrupt_row
+        .row
+        .set_title(&glib::markup_escape_text(&title));
+
+    let mut subtitle_parts = Vec::with_capacity(2);
+    if interrupt.kind == InterruptKind::Hardware && interrupt.description.is_some() {
+        if interrupt.id.chars().all(|c| c.is_ascii_digit()) {
+            subtitle_parts.push(i18n_f("IRQ {}", &[&interrupt.id]));
+        } else {
+            subtitle_parts.push(interrupt.id.clone());
+        }
+    }
+    // only worth mentioning if there's more than one CPU to choose from
+    if let Some(busiest_cpu) = busiest_cpu.filter(|_| interrupt.counts.len() > 1) {
+        subtitle_parts.push(i18n_f(
+            "Mostly handled by CPU {}",
+            &[&(busiest_cpu + 1).to_string()],
+        ));
+    }
+    interrupt_row.row.set_subtitle(&subtitle_parts.join(" · "));
+
+    let Some(elapsed) = elapsed else {
+        return 0.0;
+    };
+
+    let rate = deltas.iter().sum::<u64>() as f64 / elapsed;
+    let busiest_cpu_rate = busiest_cpu
+        .and_then(|cpu| deltas.get(cpu))
+        .map_or(0.0, |delta| *delta as f64 / elapsed);
+
+    interrupt_row
+        .rate_label
+        .set_label(&i18n_f("{}/s", &[&rate.round().to_string()]));
+
+    if busiest_cpu_rate > RUNAWAY_RATE {
+        interrupt_row.rate_label.add_css_class("error");
+        interrupt_row
+            .rate_label
+            .set_tooltip_text(Some(&i18n("Unusually high interrupt rate")));
+    } else {
+        interrupt_row.rate_label.remove_css_class("error");
+        interrupt_row.rate_label.set_tooltip_text(None);
+    }
+
+    rate
+}
+
+fn max_index(values: &[u64]) -> Option<usize> {
+    values
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, value)| **value)
+        .map(|(i, _)| i)
+}
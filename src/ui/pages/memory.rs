@@ -1,12 +1,13 @@
 use adw::{prelude::*, subclass::prelude::*};
-use anyhow::Context;
-use gtk::glib::{self, clone};
+use gtk::glib::{self, clone, MainContext};
 
 use crate::config::PROFILE;
-use crate::i18n::{i18n, i18n_f};
-use crate::utils::memory::{
-    self, get_available_memory, get_free_swap, get_total_memory, get_total_swap, MemoryDevice,
-};
+use crate::i18n::{i18n, i18n_f, ni18n_f};
+use crate::ui::copy::setup_copy_actions;
+use crate::utils::demo;
+use crate::utils::helper;
+use crate::utils::memory::{self, KsmStats, MemoryData, MemoryDevice};
+use crate::utils::oom::{OomKill, OomKiller, OomdMonitor, OomdStatus};
 use crate::utils::units::convert_storage;
 use crate::utils::NaNDefault;
 
@@ -32,6 +33,8 @@ mod imp {
         #[template_child]
         pub swap: TemplateChild<ResGraphBox>,
         #[template_child]
+        pub drop_caches_button: TemplateChild<gtk::Button>,
+        #[template_child]
         pub authentication_banner: TemplateChild<adw::Banner>,
         #[template_child]
         pub properties: TemplateChild<adw::PreferencesGroup>,
@@ -45,6 +48,28 @@ mod imp {
         pub memory_type: TemplateChild<adw::ActionRow>,
         #[template_child]
         pub type_detail: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub ksm: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub ksm_saved: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub ksm_pages_shared: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub ksm_pages_sharing: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub ksm_pages_unshared: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub oomd: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub oomd_cgroups_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub oomd_kills: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub oomd_kills_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub kernel_kills: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub kernel_kills_list: TemplateChild<gtk::ListBox>,
 
         #[property(get)]
         uses_progress_bar: Cell<bool>,
@@ -72,6 +97,7 @@ mod imp {
             Self {
                 memory: Default::default(),
                 swap: Default::default(),
+                drop_caches_button: Default::default(),
                 authentication_banner: Default::default(),
                 properties: Default::default(),
                 slots_used: Default::default(),
@@ -79,6 +105,17 @@ mod imp {
                 form_factor: Default::default(),
                 memory_type: Default::default(),
                 type_detail: Default::default(),
+                ksm: Default::default(),
+                ksm_saved: Default::default(),
+                ksm_pages_shared: Default::default(),
+                ksm_pages_sharing: Default::default(),
+                ksm_pages_unshared: Default::default(),
+                oomd: Default::default(),
+                oomd_cgroups_list: Default::default(),
+                oomd_kills: Default::default(),
+                oomd_kills_list: Default::default(),
+                kernel_kills: Default::default(),
+                kernel_kills_list: Default::default(),
                 uses_progress_bar: Cell::new(true),
                 icon: RefCell::new(ThemedIcon::new("memory-symbolic").into()),
                 usage: Default::default(),
@@ -144,9 +181,20 @@ impl ResMemory {
     pub fn init(&self) {
         self.setup_widgets();
         self.setup_signals();
+        setup_copy_actions(self);
     }
 
-    pub fn setup_widgets(&self) {
+    /// Sets up the page for a machine that is not this one, e.g. one that is
+    /// monitored over SSH. Memory devices can't be queried remotely and the
+    /// caches can't be dropped there, so these are hidden.
+    pub fn init_remote(&self) {
+        self.setup_graphs();
+        self.imp().properties.set_visible(false);
+        self.imp().drop_caches_button.set_visible(false);
+        setup_copy_actions(self);
+    }
+
+    fn setup_graphs(&self) {
         let imp = self.imp();
         imp.memory.set_title_label(&i18n("Memory"));
         imp.memory.set_graph_color(129, 61, 156);
@@ -154,8 +202,31 @@ impl ResMemory {
         imp.swap.set_title_label(&i18n("Swap"));
         imp.swap.set_graph_color(46, 194, 126);
         imp.swap.set_data_points_max_amount(60);
+    }
 
-        if let Ok(memory_devices) = memory::get_memory_devices() {
+    pub fn setup_widgets(&self) {
+        let imp = self.imp();
+        self.setup_graphs();
+        imp.memory.set_dashboard_key("memory");
+        imp.swap.set_dashboard_key("swap");
+
+        for list in [&imp.oomd_kills_list, &imp.kernel_kills_list] {
+            list.set_placeholder(Some(
+                &gtk::Label::builder()
+                    .label(i18n("No processes have been killed"))
+                    .margin_top(12)
+                    .margin_bottom(12)
+                    .css_classes(vec!["dim-label"])
+                    .build(),
+            ));
+        }
+
+        // there are no real caches behind the demo's memory graph
+        imp.drop_caches_button.set_visible(!demo::is_enabled());
+
+        if demo::is_enabled() {
+            self.setup_properties(&demo::memory_devices());
+        } else if let Ok(memory_devices) = memory::get_memory_devices() {
             self.setup_properties(&memory_devices);
         } else {
             imp.properties.set_visible(false);
@@ -208,25 +279,32 @@ impl ResMemory {
                 }
                 imp.authentication_banner.set_revealed(false)
             }));
+
+        imp.drop_caches_button.connect_clicked(|button| {
+            button.set_sensitive(false);
+            MainContext::default().spawn_local(clone!(@weak button => async move {
+                // 3 drops the page cache as well as dentries and inodes, the effect
+                // shows up in the memory graph
+                if let Err(error) = helper::drop_caches(3).await {
+                    log::warn!("Unable to drop caches: {error}");
+                }
+                button.set_sensitive(true);
+            }));
+        });
     }
 
-    pub async fn refresh_page(&self) {
+    pub fn refresh_page(&self, memory_data: MemoryData) {
         let imp = self.imp();
 
-        let total_mem = get_total_memory()
-            .with_context(|| "unable to get total memory")
-            .unwrap_or_default();
-        let available_mem = get_available_memory()
-            .with_context(|| "unable to get available memory")
-            .unwrap_or_default();
-        let used_mem = total_mem - available_mem;
+        let MemoryData {
+            total_mem,
+            available_mem,
+            total_swap,
+            free_swap,
+            ksm,
+        } = memory_data;
 
-        let total_swap = get_total_swap()
-            .with_context(|| "unable to get total swap")
-            .unwrap_or_default();
-        let free_swap = get_free_swap()
-            .with_context(|| "unable to get free swap")
-            .unwrap_or_default();
+        let used_mem = total_mem - available_mem;
         let used_swap = total_swap - free_swap;
 
         let memory_fraction = used_mem as f64 / total_mem as f64;
@@ -254,6 +332,142 @@ impl ResMemory {
             ));
         }
 
+        self.refresh_ksm(ksm);
+
         self.set_property("usage", memory_fraction);
     }
+
+    /// Shows the statistics of kernel samepage merging. The group is only
+    /// visible while KSM is in use, which usually is the case on hosts of
+    /// virtual machines.
+    fn refresh_ksm(&self, ksm: Option<KsmStats>) {
+        let imp = self.imp();
+
+        let Some(ksm) = ksm.filter(KsmStats::is_active) else {
+            imp.ksm.set_visible(false);
+            return;
+        };
+        imp.ksm.set_visible(true);
+
+        let pages = |pages: usize| {
+            ni18n_f(
+                "{} page",
+                "{} pages",
+                pages.try_into().unwrap_or(u32::MAX),
+                &[&pages.to_string()],
+            )
+        };
+
+        imp.ksm_saved
+            .set_subtitle(&convert_storage(ksm.saved_bytes() as f64, false));
+        imp.ksm_pages_shared.set_subtitle(&pages(ksm.pages_shared));
+        imp.ksm_pages_sharing
+            .set_subtitle(&pages(ksm.pages_sharing));
+        imp.ksm_pages_unshared
+            .set_subtitle(&pages(ksm.pages_unshared));
+    }
+
+    /// Shows the cgroups that systemd-oomd is monitoring, or hides its group if
+    /// `status` is `None` because systemd-oomd isn't running
+    pub fn set_oomd_status(&self, status: Option<&OomdStatus>) {
+        let imp = self.imp();
+
+        while let Some(row) = imp.oomd_cgroups_list.row_at_index(0) {
+            imp.oomd_cgroups_list.remove(&row);
+        }
+
+        let Some(status) = status else {
+            imp.oomd.set_visible(false);
+            // kills from before systemd-oomd has been stopped are still of interest
+            imp.oomd_kills
+                .set_visible(imp.oomd_kills_list.row_at_index(0).is_some());
+            return;
+        };
+        imp.oomd.set_visible(true);
+        imp.oomd_kills.set_visible(true);
+
+        let percentage = |fraction: f64| format!("{} %", (fraction * 100.0).round());
+
+        for cgroup in &status.cgroups {
+            let (subtitle, exceeded) = match cgroup.monitor {
+                OomdMonitor::Swap => (
+                    status.swap_used_limit.map_or_else(
+                        || i18n("Killed when swap is running out"),
+                        |limit| {
+                            i18n_f(
+                                "Killed when more than {} of swap is used",
+                                &[&percentage(limit)],
+                            )
+                        },
+                    ),
+                    false,
+                ),
+                OomdMonitor::MemoryPressure { limit, pressure } => {
+                    let limit = limit.or(status.default_memory_pressure_limit);
+                    let subtitle = i18n_f(
+                        "Memory pressure: {} · Limit: {}",
+                        &[
+                            &pressure.map_or_else(|| i18n("N/A"), percentage),
+                            &limit.map_or_else(|| i18n("N/A"), percentage),
+                        ],
+                    );
+                    let exceeded = matches!((pressure, limit), (Some(pressure), Some(limit)) if pressure >= limit);
+                    (subtitle, exceeded)
+                }
+            };
+
+            let row = adw::ActionRow::builder()
+                .title(glib::markup_escape_text(&cgroup.path))
+                .subtitle(subtitle)
+                .title_selectable(true)
+                .build();
+            if exceeded {
+                row.add_css_class("warning");
+            }
+            imp.oomd_cgroups_list.append(&row);
+        }
+
+        if status.dry_run {
+            imp.oomd.set_description(Some(&i18n(
+                "systemd-oomd is in dry run mode and only logs which control groups it would kill",
+            )));
+        }
+    }
+
+    /// Adds rows for `kills` to the list of the respective killer, newest
+    /// first. Expects `kills` to be sorted from oldest to newest.
+    pub fn add_oom_kills(&self, killer: OomKiller, kills: &[OomKill]) {
+        let imp = self.imp();
+
+        let (group, list) = match killer {
+            OomKiller::Kernel => (&imp.kernel_kills, &imp.kernel_kills_list),
+            OomKiller::Oomd => (&imp.oomd_kills, &imp.oomd_kills_list),
+        };
+
+        // the kernel's OOM killer is always there, so knowing that it hasn't
+        // killed anything is worth showing as well
+        if killer == OomKiller::Kernel || !kills.is_empty() {
+            group.set_visible(true);
+        }
+
+        for kill in kills {
+            let time = glib::DateTime::from_unix_local(kill.timestamp / 1_000_000)
+                .and_then(|date_time| date_time.format("%Y-%m-%d %H:%M:%S"))
+                .map(|time| time.to_string())
+                .unwrap_or_default();
+
+            let subtitle = match &kill.reason {
+                Some(reason) => format!("{time} · {reason}"),
+                None => time,
+            };
+
+            let row = adw::ActionRow::builder()
+                .title(glib::markup_escape_text(&kill.name))
+                .subtitle(glib::markup_escape_text(&subtitle))
+                .subtitle_selectable(true)
+                .tooltip_text(&kill.message)
+                .build();
+            list.prepend(&row);
+        }
+    }
 }
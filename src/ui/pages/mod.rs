@@ -1,7 +1,16 @@
 pub mod applications;
 pub mod cpu;
+pub mod dashboard;
 pub mod drive;
 pub mod gpu;
+pub mod interrupts;
 pub mod memory;
+pub mod modules;
 pub mod network;
+pub mod pods;
 pub mod processes;
+pub mod sensors;
+pub mod services;
+pub mod sessions;
+pub mod sysctl;
+pub mod users;
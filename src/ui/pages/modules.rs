@@ -0,0 +1,268 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::glib::{self, clone};
+use hashbrown::HashMap;
+
+use crate::config::PROFILE;
+use crate::i18n::{i18n, i18n_f, ni18n_f};
+use crate::ui::copy::setup_copy_actions;
+use crate::utils::modules::KernelModule;
+use crate::utils::units::convert_storage;
+
+#[derive(Debug)]
+pub struct ModuleRow {
+    row: adw::ActionRow,
+    size_label: gtk::Label,
+    use_count_label: gtk::Label,
+}
+
+impl ModuleRow {
+    fn new(name: &str) -> Self {
+        let row = adw::ActionRow::builder()
+            .title(glib::markup_escape_text(name))
+            .subtitle_selectable(true)
+            .build();
+        row.add_css_class("property");
+
+        let size_label = gtk::Label::builder()
+            .valign(gtk::Align::Center)
+            .css_classes(vec!["numeric"])
+            .build();
+
+        let use_count_label = gtk::Label::builder()
+            .valign(gtk::Align::Center)
+            .css_classes(vec!["numeric", "dim-label"])
+            .build();
+
+        row.add_suffix(&use_count_label);
+        row.add_suffix(&size_label);
+
+        Self {
+            row,
+            size_label,
+            use_count_label,
+        }
+    }
+
+    fn refresh(&self, module: &KernelModule) {
+        let subtitle = if module.used_by.is_empty() {
+            String::new()
+        } else {
+            i18n_f("Used by {}", &[&module.used_by.join(", ")])
+        };
+        self.row.set_subtitle(&glib::markup_escape_text(&subtitle));
+
+        self.size_label
+            .set_label(&convert_storage(module.size as f64, false));
+        self.use_count_label.set_label(&ni18n_f(
+            "{} user",
+            "{} users",
+            module.use_count as u32,
+            &[&module.use_count.to_string()],
+        ));
+
+        if module.state != "Live" && !module.state.is_empty() {
+            self.row.set_tooltip_text(Some(&module.state));
+        } else {
+            self.row.set_tooltip_text(None);
+        }
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    use gtk::{
+        gio::{Icon, ThemedIcon},
+        glib::{ParamSpec, Properties, Value},
+        CompositeTemplate,
+    };
+
+    #[derive(CompositeTemplate, Properties)]
+    #[template(resource = "/net/nokyan/Resources/ui/pages/modules.ui")]
+    #[properties(wrapper_type = super::ResModules)]
+    pub struct ResModules {
+        #[template_child]
+        pub search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub modules_list: TemplateChild<gtk::ListBox>,
+
+        pub modules: RefCell<Vec<KernelModule>>,
+        pub rows: RefCell<HashMap<String, ModuleRow>>,
+
+        #[property(get)]
+        uses_progress_bar: Cell<bool>,
+
+        #[property(get)]
+        icon: RefCell<Icon>,
+
+        #[property(get = Self::tab_name, type = glib::GString)]
+        tab_name: Cell<glib::GString>,
+    }
+
+    impl ResModules {
+        pub fn tab_name(&self) -> glib::GString {
+            let tab_name = self.tab_name.take();
+            let result = tab_name.clone();
+            self.tab_name.set(tab_name);
+            result
+        }
+    }
+
+    impl Default for ResModules {
+        fn default() -> Self {
+            Self {
+                search_entry: Default::default(),
+                modules_list: Default::default(),
+                modules: Default::default(),
+                rows: Default::default(),
+                uses_progress_bar: Cell::new(false),
+                icon: RefCell::new(ThemedIcon::new("application-x-addon-symbolic").into()),
+                tab_name: Cell::from(glib::GString::from(i18n("Kernel Modules"))),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResModules {
+        const NAME: &'static str = "ResModules";
+        type Type = super::ResModules;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        // You must call `Widget`'s `init_template()` within `instance_init()`.
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ResModules {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // Devel Profile
+            if PROFILE == "Devel" {
+                obj.add_css_class("devel");
+            }
+        }
+
+        fn properties() -> &'static [ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn set_property(&self, id: usize, value: &Value, pspec: &ParamSpec) {
+            self.derived_set_property(id, value, pspec);
+        }
+
+        fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
+            self.derived_property(id, pspec)
+        }
+    }
+
+    impl WidgetImpl for ResModules {}
+    impl BinImpl for ResModules {}
+}
+
+glib::wrapper! {
+    pub struct ResModules(ObjectSubclass<imp::ResModules>)
+        @extends gtk::Widget, adw::Bin;
+}
+
+impl Default for ResModules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResModules {
+    pub fn new() -> Self {
+        glib::Object::new::<Self>()
+    }
+
+    pub fn init(&self) {
+        let imp = self.imp();
+
+        imp.modules_list.set_placeholder(Some(
+            &gtk::Label::builder()
+                .label(i18n("No Modules Found"))
+                .margin_top(12)
+                .margin_bottom(12)
+                .css_classes(vec!["dim-label"])
+                .build(),
+        ));
+
+        // matches both the name of a module and the modules that use it
+        let search_entry = imp.search_entry.get();
+        imp.modules_list.set_filter_func(
+            clone!(@weak search_entry => @default-return true, move |row| {
+                let query = search_entry.text().to_lowercase();
+                if query.is_empty() {
+                    return true;
+                }
+                row.downcast_ref::<adw::ActionRow>().map_or(true, |row| {
+                    row.title().to_lowercase().contains(&query)
+                        || row
+                            .subtitle()
+                            .map_or(false, |subtitle| subtitle.to_lowercase().contains(&query))
+                })
+            }),
+        );
+
+        imp.modules_list.set_sort_func(|a, b| {
+            let title = |row: &gtk::ListBoxRow| {
+                row.downcast_ref::<adw::ActionRow>()
+                    .map(|row| row.title())
+                    .unwrap_or_default()
+            };
+            title(a).cmp(&title(b)).into()
+        });
+
+        imp.search_entry
+            .connect_search_changed(clone!(@weak self as this => move |_| {
+                this.imp().modules_list.invalidate_filter();
+            }));
+
+        setup_copy_actions(self);
+    }
+
+    pub fn refresh_page(&self, modules: Vec<KernelModule>) {
+        let imp = self.imp();
+
+        // modules are rarely loaded or unloaded, so only touch the list if
+        // something has changed
+        if *imp.modules.borrow() == modules {
+            return;
+        }
+
+        let mut rows = imp.rows.borrow_mut();
+
+        rows.retain(|name, module_row| {
+            let present = modules.iter().any(|module| module.name == *name);
+            if !present {
+                imp.modules_list.remove(&module_row.row);
+            }
+            present
+        });
+
+        for module in &modules {
+            let module_row = rows.entry(module.name.clone()).or_insert_with(|| {
+                let module_row = ModuleRow::new(&module.name);
+                imp.modules_list.append(&module_row.row);
+                module_row
+            });
+            module_row.refresh(module);
+        }
+
+        drop(rows);
+
+        // the subtitles may have changed, which the search looks at as well
+        imp.modules_list.invalidate_filter();
+
+        *imp.modules.borrow_mut() = modules;
+    }
+}
@@ -5,7 +5,8 @@ use gtk::glib;
 
 use crate::config::PROFILE;
 use crate::i18n::i18n;
-use crate::utils::network::NetworkInterface;
+use crate::ui::copy::setup_copy_actions;
+use crate::utils::network::{NetworkData, NetworkInterface, NetworkInterfaceExt};
 use crate::utils::units::{convert_speed, convert_storage};
 use crate::utils::NaNDefault;
 
@@ -170,9 +171,30 @@ impl ResNetwork {
         network_interface: NetworkInterface,
         received_bytes: usize,
         sent_bytes: usize,
+    ) {
+        let imp = self.imp();
+        imp.set_icon(&network_interface.icon());
+        let interface_name = network_interface.interface_name.to_string_lossy();
+        imp.receiving
+            .set_dashboard_key(&format!("network-{interface_name}-receiving"));
+        imp.sending
+            .set_dashboard_key(&format!("network-{interface_name}-sending"));
+        self.setup_widgets(network_interface, received_bytes, sent_bytes);
+        setup_copy_actions(self);
+    }
+
+    /// Sets up the page for a network interface of a machine that is not this
+    /// one, e.g. one that is monitored over SSH. Its graphs can't be pinned to
+    /// the dashboard.
+    pub fn init_remote(
+        &self,
+        network_interface: NetworkInterface,
+        received_bytes: usize,
+        sent_bytes: usize,
     ) {
         self.imp().set_icon(&network_interface.icon());
         self.setup_widgets(network_interface, received_bytes, sent_bytes);
+        setup_copy_actions(self);
     }
 
     pub fn setup_widgets(
@@ -232,25 +254,22 @@ impl ResNetwork {
         *imp.network_interface.borrow_mut() = network_interface;
     }
 
-    pub async fn refresh_page(&self) {
+    pub fn network_interface(&self) -> NetworkInterface {
+        self.imp().network_interface.borrow().clone()
+    }
+
+    pub fn refresh_page(&self, network_data: NetworkData) {
         let imp = self.imp();
+
+        let NetworkData {
+            received_bytes,
+            sent_bytes,
+        } = network_data;
+
         let time_passed = SystemTime::now()
             .duration_since(imp.last_timestamp.get())
             .map_or(1.0f64, |timestamp| timestamp.as_secs_f64());
 
-        let received_bytes = imp
-            .network_interface
-            .borrow()
-            .received_bytes()
-            .await
-            .unwrap_or(0);
-        let sent_bytes = imp
-            .network_interface
-            .borrow()
-            .sent_bytes()
-            .await
-            .unwrap_or(0);
-
         let received_delta =
             (received_bytes.saturating_sub(imp.old_received_bytes.get())) as f64 / time_passed;
         let sent_delta = (sent_bytes.saturating_sub(imp.old_sent_bytes.get())) as f64 / time_passed;
@@ -0,0 +1,335 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::glib;
+use hashbrown::HashMap;
+
+use crate::config::PROFILE;
+use crate::i18n::{i18n, ni18n_f};
+use crate::ui::copy::setup_copy_actions;
+use crate::utils::app::{AppsContext, PodItem};
+use crate::utils::kubernetes::{pod_names, PodName};
+use crate::utils::units::convert_storage;
+
+#[derive(Debug)]
+pub struct PodRow {
+    row: adw::ActionRow,
+    cpu_label: gtk::Label,
+    memory_label: gtk::Label,
+    namespace: String,
+}
+
+impl PodRow {
+    fn new(pod_name: &PodName) -> Self {
+        let cpu_label = gtk::Label::builder()
+            .valign(gtk::Align::Center)
+            .width_chars(7)
+            .xalign(1.0)
+            .css_classes(vec!["numeric"])
+            .tooltip_text(i18n("Processor"))
+            .build();
+
+        let memory_label = gtk::Label::builder()
+            .valign(gtk::Align::Center)
+            .width_chars(9)
+            .xalign(1.0)
+            .css_classes(vec!["numeric", "dim-label"])
+            .tooltip_text(i18n("Memory"))
+            .build();
+
+        let row = adw::ActionRow::builder()
+            .title(glib::markup_escape_text(&pod_name.name))
+            .subtitle_selectable(true)
+            .build();
+        row.add_css_class("property");
+        row.add_suffix(&memory_label);
+        row.add_suffix(&cpu_label);
+
+        Self {
+            row,
+            cpu_label,
+            memory_label,
+            namespace: pod_name.namespace.clone(),
+        }
+    }
+
+    fn refresh(&self, pod_item: &PodItem) {
+        self.row.set_subtitle(&ni18n_f(
+            "{} process",
+            "{} processes",
+            pod_item.processes_amount as u32,
+            &[&pod_item.processes_amount.to_string()],
+        ));
+        self.cpu_label
+            .set_label(&format!("{:.1} %", pod_item.cpu_time_ratio * 100.0));
+        self.memory_label
+            .set_label(&convert_storage(pod_item.memory_usage as f64, false));
+    }
+}
+
+/// The pods of a single namespace, shown as their own group
+#[derive(Debug)]
+pub struct NamespaceGroup {
+    group: adw::PreferencesGroup,
+    list: gtk::ListBox,
+    pod_items: Rc<RefCell<HashMap<gtk::ListBoxRow, PodItem>>>,
+}
+
+impl NamespaceGroup {
+    fn new(namespace: &str) -> Self {
+        let list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(vec!["boxed-list"])
+            .build();
+
+        let group = adw::PreferencesGroup::builder()
+            .title(glib::markup_escape_text(namespace))
+            .build();
+        group.add(&list);
+
+        // heaviest pods first
+        let pod_items: Rc<RefCell<HashMap<gtk::ListBoxRow, PodItem>>> = Rc::default();
+        let sort_items = pod_items.clone();
+        list.set_sort_func(move |a, b| {
+            let pod_items = sort_items.borrow();
+            let (cpu_a, memory_a) = pod_items
+                .get(a)
+                .map_or((0.0, 0), |item| (item.cpu_time_ratio, item.memory_usage));
+            let (cpu_b, memory_b) = pod_items
+                .get(b)
+                .map_or((0.0, 0), |item| (item.cpu_time_ratio, item.memory_usage));
+            cpu_b.total_cmp(&cpu_a).then(memory_b.cmp(&memory_a)).into()
+        });
+
+        Self {
+            group,
+            list,
+            pod_items,
+        }
+    }
+}
+
+mod imp {
+    use std::cell::Cell;
+
+    use super::*;
+
+    use gtk::{
+        gio::{Icon, ThemedIcon},
+        glib::{ParamSpec, Properties, Value},
+        CompositeTemplate,
+    };
+
+    #[derive(CompositeTemplate, Properties)]
+    #[template(resource = "/net/nokyan/Resources/ui/pages/pods.ui")]
+    #[properties(wrapper_type = super::ResPods)]
+    pub struct ResPods {
+        #[template_child]
+        pub namespaces_box: TemplateChild<gtk::Box>,
+
+        pub groups: RefCell<BTreeMap<String, NamespaceGroup>>,
+        pub rows: RefCell<HashMap<String, PodRow>>,
+        pub pod_names: RefCell<HashMap<String, PodName>>,
+
+        #[property(get)]
+        uses_progress_bar: Cell<bool>,
+
+        #[property(get)]
+        icon: RefCell<Icon>,
+
+        #[property(get = Self::tab_name, type = glib::GString)]
+        tab_name: Cell<glib::GString>,
+    }
+
+    impl ResPods {
+        pub fn tab_name(&self) -> glib::GString {
+            let tab_name = self.tab_name.take();
+            let result = tab_name.clone();
+            self.tab_name.set(tab_name);
+            result
+        }
+    }
+
+    impl Default for ResPods {
+        fn default() -> Self {
+            Self {
+                namespaces_box: Default::default(),
+                groups: Default::default(),
+                rows: Default::default(),
+                pod_names: Default::default(),
+                uses_progress_bar: Cell::new(false),
+                icon: RefCell::new(ThemedIcon::new("package-x-generic-symbolic").into()),
+                tab_name: Cell::from(glib::GString::from(i18n("Pods"))),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResPods {
+        const NAME: &'static str = "ResPods";
+        type Type = super::ResPods;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        // You must call `Widget`'s `init_template()` within `instance_init()`.
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ResPods {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // Devel Profile
+            if PROFILE == "Devel" {
+                obj.add_css_class("devel");
+            }
+        }
+
+        fn properties() -> &'static [ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn set_property(&self, id: usize, value: &Value, pspec: &ParamSpec) {
+            self.derived_set_property(id, value, pspec);
+        }
+
+        fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
+            self.derived_property(id, pspec)
+        }
+    }
+
+    impl WidgetImpl for ResPods {}
+    impl BinImpl for ResPods {}
+}
+
+glib::wrapper! {
+    pub struct ResPods(ObjectSubclass<imp::ResPods>)
+        @extends gtk::Widget, adw::Bin;
+}
+
+impl Default for ResPods {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResPods {
+    pub fn new() -> Self {
+        glib::Object::new::<Self>()
+    }
+
+    pub fn init(&self) {
+        setup_copy_actions(self);
+    }
+
+    /// Groups the containerized processes in `apps_context` by their pod and
+    /// the pods by their namespace. Returns whether there are any pods at all.
+    pub fn refresh_pods_list(&self, apps_context: &AppsContext) -> bool {
+        let imp = self.imp();
+
+        let new_pod_items = apps_context.pod_items();
+
+        // kubelet creates the log directory before the pod's containers start,
+        // so a pod we don't know yet means that it's time to look again
+        if new_pod_items
+            .keys()
+            .any(|uid| !imp.pod_names.borrow().contains_key(uid))
+        {
+            *imp.pod_names.borrow_mut() = pod_names();
+        }
+
+        let mut rows = imp.rows.borrow_mut();
+        let mut groups = imp.groups.borrow_mut();
+
+        // pods whose last process has exited
+        rows.retain(|uid, pod_row| {
+            let present = new_pod_items.contains_key(uid);
+            if !present {
+                if let Some(group) = groups.get(&pod_row.namespace) {
+                    group
+                        .pod_items
+                        .borrow_mut()
+                        .remove(pod_row.row.upcast_ref::<gtk::ListBoxRow>());
+                    group.list.remove(&pod_row.row);
+                }
+            }
+            present
+        });
+
+        for (uid, pod_item) in new_pod_items {
+            let pod_row = rows.entry(uid).or_insert_with_key(|uid| {
+                let pod_name =
+                    imp.pod_names
+                        .borrow()
+                        .get(uid)
+                        .cloned()
+                        .unwrap_or_else(|| PodName {
+                            namespace: i18n("Unknown Namespace"),
+                            name: uid.clone(),
+                        });
+                let pod_row = PodRow::new(&pod_name);
+
+                let group = self.namespace_group(&mut groups, &pod_name.namespace);
+                // appending a row runs the sort function, so `pod_items` must not
+                // be borrowed at that point
+                group.list.append(&pod_row.row);
+
+                pod_row
+            });
+
+            pod_row.refresh(&pod_item);
+            if let Some(group) = groups.get(&pod_row.namespace) {
+                group
+                    .pod_items
+                    .borrow_mut()
+                    .insert(pod_row.row.clone().upcast(), pod_item);
+            }
+        }
+
+        // namespaces whose last pod is gone
+        groups.retain(|_, group| {
+            let empty = group.pod_items.borrow().is_empty();
+            if empty {
+                imp.namespaces_box.remove(&group.group);
+            }
+            !empty
+        });
+
+        for group in groups.values() {
+            group.list.invalidate_sort();
+        }
+
+        !rows.is_empty()
+    }
+
+    /// Returns the group for `namespace`, creating it at its alphabetical
+    /// position if it doesn't exist yet
+    fn namespace_group<'a>(
+        &self,
+        groups: &'a mut BTreeMap<String, NamespaceGroup>,
+        namespace: &str,
+    ) -> &'a NamespaceGroup {
+        let imp = self.imp();
+
+        if !groups.contains_key(namespace) {
+            let group = NamespaceGroup::new(namespace);
+            let previous = groups
+                .range::<str, _>(..namespace)
+                .next_back()
+                .map(|(_, previous)| previous.group.clone());
+            imp.namespaces_box
+                .insert_child_after(&group.group, previous.as_ref());
+            groups.insert(namespace.to_string(), group);
+        }
+
+        &groups[namespace]
+    }
+}
@@ -6,22 +6,44 @@ use std::collections::HashSet;
 use adw::ResponseAppearance;
 use adw::{prelude::*, subclass::prelude::*};
 use gtk::glib::{self, clone, closure, Object, Sender};
-use gtk::{gio, CustomSorter, FilterChange, Ordering, SortType, Widget};
+use gtk::{gio, CustomSorter, FilterChange, Ordering, SortType, SorterChange, Widget};
 use gtk_macros::send;
 
 use log::error;
 
 use crate::config::PROFILE;
-use crate::i18n::i18n;
+use crate::i18n::{i18n, i18n_f};
 use crate::ui::dialogs::process_dialog::ResProcessDialog;
 use crate::ui::window::{self, Action, MainWindow};
 use crate::utils::app::AppsContext;
+use crate::utils::demo;
 use crate::utils::process::{ProcessAction, ProcessItem};
+use crate::utils::settings::SETTINGS;
 use crate::utils::units::convert_storage;
 
-use self::process_entry::ProcessEntry;
+use self::process_entry::{open_files_usage, ProcessEntry};
 use self::process_name_cell::ResProcessNameCell;
 
+/// Processes that use more than this fraction of their open file limit are
+/// about to run out of file descriptors
+const OPEN_FILES_WARNING_USAGE: f64 = 0.8;
+
+/// Processes with more major page faults per second than this are probably
+/// slowed down by having to wait for pages to be read from disk or swap
+const MAJOR_FAULTS_WARNING_RATE: f32 = 100.0;
+
+/// Restricts the list to the processes of a single app, see
+/// `ResProcesses::set_app_filter()`, or to a single process, see
+/// `ResProcesses::reveal_process()`
+#[derive(Debug, Clone, Default)]
+pub struct AppFilter {
+    /// `None` stands for the system processes
+    id: Option<String>,
+    /// Set if only this process is shown instead of all processes of the app
+    pid: Option<i32>,
+    pids: hashbrown::HashSet<i32>,
+}
+
 mod imp {
     use std::{
         cell::{Cell, RefCell},
@@ -46,6 +68,8 @@ mod imp {
         #[template_child]
         pub toast_overlay: TemplateChild<adw::ToastOverlay>,
         #[template_child]
+        pub app_filter_banner: TemplateChild<adw::Banner>,
+        #[template_child]
         pub search_revealer: TemplateChild<gtk::Revealer>,
         #[template_child]
         pub search_entry: TemplateChild<gtk::SearchEntry>,
@@ -54,6 +78,8 @@ mod imp {
         #[template_child]
         pub search_button: TemplateChild<gtk::ToggleButton>,
         #[template_child]
+        pub application_button: TemplateChild<gtk::Button>,
+        #[template_child]
         pub information_button: TemplateChild<gtk::Button>,
         #[template_child]
         pub end_process_button: TemplateChild<adw::SplitButton>,
@@ -64,6 +90,9 @@ mod imp {
         pub sort_model: RefCell<gtk::SortListModel>,
         pub column_view: RefCell<gtk::ColumnView>,
         pub open_dialog: RefCell<Option<(i32, ResProcessDialog)>>,
+        pub app_filter: RefCell<Option<AppFilter>>,
+        /// A process that is to be revealed once it shows up in the list
+        pub pending_reveal: Cell<Option<i32>>,
 
         pub username_cache: RefCell<HashMap<u32, String>>,
 
@@ -92,10 +121,12 @@ mod imp {
         fn default() -> Self {
             Self {
                 toast_overlay: Default::default(),
+                app_filter_banner: Default::default(),
                 search_revealer: Default::default(),
                 search_entry: Default::default(),
                 processes_scrolled_window: Default::default(),
                 search_button: Default::default(),
+                application_button: Default::default(),
                 information_button: Default::default(),
                 end_process_button: Default::default(),
                 store: gio::ListStore::new::<ProcessEntry>().into(),
@@ -104,6 +135,8 @@ mod imp {
                 sort_model: Default::default(),
                 column_view: Default::default(),
                 open_dialog: Default::default(),
+                app_filter: Default::default(),
+                pending_reveal: Default::default(),
                 username_cache: Default::default(),
                 sender: Default::default(),
                 uses_progress_bar: Cell::new(false),
@@ -306,6 +339,19 @@ impl ResProcesses {
                     convert_storage(memory_usage as f64, false)
                 }))
                 .bind(&row, "text", Widget::NONE);
+            // highlight likely leaks
+            item.property_expression("item")
+                .chain_property::<ProcessEntry>("memory_growing")
+                .chain_closure::<Vec<String>>(closure!(
+                    |_: Option<Object>, memory_growing: bool| {
+                        if memory_growing {
+                            vec!["warning".to_string()]
+                        } else {
+                            Vec::new()
+                        }
+                    }
+                ))
+                .bind(&row, "css-classes", Widget::NONE);
         });
         let memory_col_sorter = CustomSorter::new(move |a, b| {
             let item_a = a.downcast_ref::<ProcessEntry>().unwrap().memory_usage();
@@ -342,11 +388,182 @@ impl ResProcesses {
         });
         cpu_col.set_sorter(Some(&cpu_col_sorter));
 
+        let open_files_col_factory = gtk::SignalListItemFactory::new();
+        let open_files_col = gtk::ColumnViewColumn::new(
+            Some(&i18n("Open Files")),
+            Some(open_files_col_factory.clone()),
+        );
+        open_files_col.set_resizable(true);
+        open_files_col_factory.connect_setup(move |_factory, item| {
+            let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+            let row = gtk::Inscription::new(None);
+            row.set_min_chars(11);
+            item.set_child(Some(&row));
+            let open_files = item
+                .property_expression("item")
+                .chain_property::<ProcessEntry>("open_files");
+            let open_files_limit = item
+                .property_expression("item")
+                .chain_property::<ProcessEntry>("open_files_limit");
+            gtk::ClosureExpression::new::<String>(
+                [&open_files, &open_files_limit],
+                closure!(
+                    |_: Option<Object>, open_files: i64, open_files_limit: u64| {
+                        match (open_files, open_files_limit) {
+                            (open_files, _) if open_files < 0 => i18n("N/A"),
+                            (open_files, 0) => open_files.to_string(),
+                            (open_files, open_files_limit) => {
+                                format!("{open_files} / {open_files_limit}")
+                            }
+                        }
+                    }
+                ),
+            )
+            .bind(&row, "text", Widget::NONE);
+            // flag processes that are about to run out of file descriptors
+            gtk::ClosureExpression::new::<Vec<String>>(
+                [&open_files, &open_files_limit],
+                closure!(
+                    |_: Option<Object>, open_files: i64, open_files_limit: u64| {
+                        if open_files_usage(open_files, open_files_limit)
+                            .map_or(false, |usage| usage >= OPEN_FILES_WARNING_USAGE)
+                        {
+                            vec!["warning".to_string()]
+                        } else {
+                            Vec::new()
+                        }
+                    }
+                ),
+            )
+            .bind(&row, "css-classes", Widget::NONE);
+        });
+        let open_files_col_sorter = CustomSorter::new(move |a, b| {
+            let item_a = a.downcast_ref::<ProcessEntry>().unwrap();
+            let item_b = b.downcast_ref::<ProcessEntry>().unwrap();
+            let usage_a = item_a.open_files_usage().unwrap_or(-1.0);
+            let usage_b = item_b.open_files_usage().unwrap_or(-1.0);
+            usage_a
+                .total_cmp(&usage_b)
+                .then(item_a.open_files().cmp(&item_b.open_files()))
+                .into()
+        });
+        open_files_col.set_sorter(Some(&open_files_col_sorter));
+        open_files_col.set_visible(SETTINGS.processes_show_open_files());
+        SETTINGS.connect_processes_show_open_files(clone!(@weak open_files_col => move |visible| {
+            open_files_col.set_visible(visible);
+        }));
+
+        let scheduling_col_factory = gtk::SignalListItemFactory::new();
+        let scheduling_col = gtk::ColumnViewColumn::new(
+            Some(&i18n("Scheduling")),
+            Some(scheduling_col_factory.clone()),
+        );
+        scheduling_col.set_resizable(true);
+        scheduling_col_factory.connect_setup(move |_factory, item| {
+            let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+            let row = gtk::Inscription::new(None);
+            row.set_min_chars(12);
+            item.set_child(Some(&row));
+            item.property_expression("item")
+                .chain_property::<ProcessEntry>("scheduling")
+                .bind(&row, "text", Widget::NONE);
+        });
+        // by realtime priority first, which is 0 for everything that isn't realtime
+        let scheduling_col_sorter = CustomSorter::new(move |a, b| {
+            let item_a = a.downcast_ref::<ProcessEntry>().unwrap();
+            let item_b = b.downcast_ref::<ProcessEntry>().unwrap();
+            item_a
+                .realtime_priority()
+                .cmp(&item_b.realtime_priority())
+                .then(item_a.scheduling().cmp(&item_b.scheduling()))
+                .into()
+        });
+        scheduling_col.set_sorter(Some(&scheduling_col_sorter));
+        scheduling_col.set_visible(SETTINGS.processes_show_scheduling());
+        SETTINGS.connect_processes_show_scheduling(clone!(@weak scheduling_col => move |visible| {
+            scheduling_col.set_visible(visible);
+        }));
+
+        let minor_faults_col_factory = gtk::SignalListItemFactory::new();
+        let minor_faults_col = gtk::ColumnViewColumn::new(
+            Some(&i18n("Minor Faults")),
+            Some(minor_faults_col_factory.clone()),
+        );
+        minor_faults_col.set_resizable(true);
+        minor_faults_col_factory.connect_setup(move |_factory, item| {
+            let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+            let row = gtk::Inscription::new(None);
+            row.set_min_chars(8);
+            item.set_child(Some(&row));
+            item.property_expression("item")
+                .chain_property::<ProcessEntry>("minor_faults")
+                .chain_closure::<String>(closure!(|_: Option<Object>, minor_faults: f32| {
+                    format!("{minor_faults:.0}/s")
+                }))
+                .bind(&row, "text", Widget::NONE);
+        });
+        let minor_faults_col_sorter = CustomSorter::new(move |a, b| {
+            let item_a = a.downcast_ref::<ProcessEntry>().unwrap().minor_faults();
+            let item_b = b.downcast_ref::<ProcessEntry>().unwrap().minor_faults();
+            item_a.total_cmp(&item_b).into()
+        });
+        minor_faults_col.set_sorter(Some(&minor_faults_col_sorter));
+
+        let major_faults_col_factory = gtk::SignalListItemFactory::new();
+        let major_faults_col = gtk::ColumnViewColumn::new(
+            Some(&i18n("Major Faults")),
+            Some(major_faults_col_factory.clone()),
+        );
+        major_faults_col.set_resizable(true);
+        major_faults_col_factory.connect_setup(move |_factory, item| {
+            let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+            let row = gtk::Inscription::new(None);
+            row.set_min_chars(8);
+            item.set_child(Some(&row));
+            let major_faults = item
+                .property_expression("item")
+                .chain_property::<ProcessEntry>("major_faults");
+            major_faults
+                .chain_closure::<String>(closure!(|_: Option<Object>, major_faults: f32| {
+                    format!("{major_faults:.0}/s")
+                }))
+                .bind(&row, "text", Widget::NONE);
+            // flag processes that are held up by memory pressure
+            major_faults
+                .chain_closure::<Vec<String>>(closure!(|_: Option<Object>, major_faults: f32| {
+                    if major_faults >= MAJOR_FAULTS_WARNING_RATE {
+                        vec!["warning".to_string()]
+                    } else {
+                        Vec::new()
+                    }
+                }))
+                .bind(&row, "css-classes", Widget::NONE);
+        });
+        let major_faults_col_sorter = CustomSorter::new(move |a, b| {
+            let item_a = a.downcast_ref::<ProcessEntry>().unwrap().major_faults();
+            let item_b = b.downcast_ref::<ProcessEntry>().unwrap().major_faults();
+            item_a.total_cmp(&item_b).into()
+        });
+        major_faults_col.set_sorter(Some(&major_faults_col_sorter));
+
+        for page_faults_col in [minor_faults_col.clone(), major_faults_col.clone()] {
+            page_faults_col.set_visible(SETTINGS.processes_show_page_faults());
+            SETTINGS.connect_processes_show_page_faults(
+                clone!(@weak page_faults_col => move |visible| {
+                    page_faults_col.set_visible(visible);
+                }),
+            );
+        }
+
         column_view.append_column(&name_col);
         column_view.append_column(&pid_col);
         column_view.append_column(&user_col);
         column_view.append_column(&memory_col);
         column_view.append_column(&cpu_col);
+        column_view.append_column(&open_files_col);
+        column_view.append_column(&scheduling_col);
+        column_view.append_column(&minor_faults_col);
+        column_view.append_column(&major_faults_col);
         column_view.sort_by_column(Some(&memory_col), SortType::Descending);
         column_view.set_enable_rubberband(true);
         imp.processes_scrolled_window.set_child(Some(&column_view));
@@ -359,11 +576,33 @@ impl ResProcesses {
         imp.selection_model.borrow().connect_selection_changed(
             clone!(@strong self as this => move |model, _, _| {
                 let imp = this.imp();
+                let belongs_to_app = this
+                    .get_selected_process_item()
+                    .map_or(false, |process| process.app_id.is_some());
+                imp.application_button.set_sensitive(belongs_to_app);
                 imp.information_button.set_sensitive(model.selected() != u32::MAX);
                 imp.end_process_button.set_sensitive(model.selected() != u32::MAX);
             }),
         );
 
+        imp.app_filter_banner
+            .connect_button_clicked(clone!(@strong self as this => move |_| {
+                this.clear_app_filter();
+            }));
+
+        imp.application_button
+            .connect_clicked(clone!(@strong self as this => move |_| {
+                let app_id = this
+                    .get_selected_process_item()
+                    .and_then(|process| process.app_id);
+                if let Some(app_id) = app_id {
+                    send!(
+                        this.imp().sender.get().unwrap(),
+                        Action::ShowApplication(app_id)
+                    );
+                }
+            }));
+
         imp.search_button
             .connect_toggled(clone!(@strong self as this => move |button| {
                 let imp = this.imp();
@@ -414,9 +653,113 @@ impl ResProcesses {
         let imp = self.imp();
         let item = obj.downcast_ref::<ProcessEntry>().unwrap();
         let search_string = imp.search_entry.text().to_string().to_lowercase();
-        !imp.search_revealer.reveals_child()
-            || item.name().to_lowercase().contains(&search_string)
-            || item.commandline().to_lowercase().contains(&search_string)
+        let matches_app = imp
+            .app_filter
+            .borrow()
+            .as_ref()
+            .map_or(true, |app_filter| app_filter.pids.contains(&item.pid()));
+        matches_app
+            && (!imp.search_revealer.reveals_child()
+                || item.name().to_lowercase().contains(&search_string)
+                || item.commandline().to_lowercase().contains(&search_string))
+    }
+
+    /// Only shows the processes of the app with the given `id` until the user
+    /// dismisses the filter. If `id` is `None`, only system processes are
+    /// shown.
+    pub fn set_app_filter(&self, id: Option<String>, display_name: String, apps: &AppsContext) {
+        let imp = self.imp();
+
+        let pids = apps.pids_of_app(id.as_deref());
+        *imp.app_filter.borrow_mut() = Some(AppFilter {
+            id,
+            pid: None,
+            pids,
+        });
+
+        imp.app_filter_banner
+            .set_title(&i18n_f("Only showing processes of {}", &[&display_name]));
+        imp.app_filter_banner.set_revealed(true);
+
+        if let Some(filter) = imp.filter_model.borrow().filter() {
+            filter.changed(FilterChange::Different);
+        }
+    }
+
+    pub fn clear_app_filter(&self) {
+        let imp = self.imp();
+
+        *imp.app_filter.borrow_mut() = None;
+        imp.app_filter_banner.set_revealed(false);
+
+        if let Some(filter) = imp.filter_model.borrow().filter() {
+            filter.changed(FilterChange::LessStrict);
+        }
+    }
+
+    /// Only shows the process with the given `pid` and selects it. If the
+    /// process isn't known yet, e.g. because Resources has just been started,
+    /// this happens after the next refresh.
+    pub fn reveal_process(&self, pid: i32, apps: &AppsContext) {
+        let imp = self.imp();
+
+        let Some(process) = apps.get_process(pid).filter(|process| process.alive) else {
+            imp.pending_reveal.set(Some(pid));
+            return;
+        };
+
+        *imp.app_filter.borrow_mut() = Some(AppFilter {
+            id: None,
+            pid: Some(pid),
+            pids: [pid].into_iter().collect(),
+        });
+
+        imp.app_filter_banner.set_title(&i18n_f(
+            "Only showing {} (PID {})",
+            &[&process.display_name, &pid.to_string()],
+        ));
+        imp.app_filter_banner.set_revealed(true);
+        imp.search_button.set_active(false);
+
+        if let Some(filter) = imp.filter_model.borrow().filter() {
+            filter.changed(FilterChange::Different);
+        }
+
+        let selection_model = imp.selection_model.borrow();
+        let position = selection_model
+            .iter::<ProcessEntry>()
+            .flatten()
+            .position(|entry| entry.pid() == pid);
+        if let Some(position) = position {
+            selection_model.set_selected(position as u32);
+        }
+    }
+
+    /// Selects the process with the given `pid` and scrolls to it. Filters
+    /// that might hide the process are cleared first.
+    pub fn select_process(&self, pid: i32) {
+        let imp = self.imp();
+
+        if imp.app_filter.borrow().is_some() {
+            self.clear_app_filter();
+        }
+        imp.search_button.set_active(false);
+
+        let selection_model = imp.selection_model.borrow();
+        let position = selection_model
+            .iter::<ProcessEntry>()
+            .flatten()
+            .position(|entry| entry.pid() == pid);
+
+        if let Some(position) = position {
+            selection_model.set_selected(position as u32);
+            imp.column_view.borrow().scroll_to(
+                position as u32,
+                None::<&gtk::ColumnViewColumn>,
+                gtk::ListScrollFlags::FOCUS,
+                None,
+            );
+        }
     }
 
     fn get_selected_process_item(&self) -> Option<ProcessItem> {
@@ -436,6 +779,13 @@ impl ResProcesses {
         let mut new_items = apps.process_items();
         let mut pids_to_remove = HashSet::new();
 
+        // the app might have started new processes since the filter was set
+        if let Some(app_filter) = imp.app_filter.borrow_mut().as_mut() {
+            if app_filter.pid.is_none() {
+                app_filter.pids = apps.pids_of_app(app_filter.id.as_deref());
+            }
+        }
+
         // change process entries of processes that have existed before
         store.iter::<ProcessEntry>().flatten().for_each(|object| {
             let item_pid = object.pid();
@@ -454,10 +804,12 @@ impl ResProcesses {
                     if *dialog_pid == item_pid {
                         dialog.set_cpu_usage(new_item.cpu_time_ratio);
                         dialog.set_memory_usage(new_item.memory_usage);
+                        dialog.set_memory_trend(new_item.memory_trend);
+                        dialog
+                            .set_scheduling(new_item.scheduling_policy, new_item.realtime_priority);
                     }
                 }
-                object.set_cpu_usage(new_item.cpu_time_ratio);
-                object.set_memory_usage(new_item.memory_usage as u64);
+                object.update(new_item);
             }
         });
 
@@ -467,12 +819,35 @@ impl ResProcesses {
         });
 
         // add the newly started process to the store
-        for (_, new_item) in new_items.drain() {
-            let user_name = self.get_user_name_by_uid(new_item.uid);
-            store.append(&ProcessEntry::new(new_item, &user_name));
+        let new_entries: Vec<ProcessEntry> = new_items
+            .drain()
+            .map(|(_, new_item)| {
+                let user_name = self.get_user_name_by_uid(new_item.uid);
+                ProcessEntry::new(new_item, &user_name)
+            })
+            .collect();
+        store.extend_from_slice(&new_entries);
+
+        // the entries have been updated in place, so only their order (and for
+        // the app filter, their visibility) might be outdated. unlike replacing
+        // all items, this keeps the selection and scroll position intact.
+        if imp.app_filter.borrow().is_some() {
+            if let Some(filter) = imp.filter_model.borrow().filter() {
+                filter.changed(FilterChange::Different);
+            }
+        }
+        if let Some(sorter) = imp.sort_model.borrow().sorter() {
+            sorter.changed(SorterChange::Different);
         }
+        drop(store);
 
-        store.items_changed(0, store.n_items(), store.n_items());
+        if let Some(pid) = imp.pending_reveal.take() {
+            if apps.get_process(pid).map_or(false, |process| process.alive) {
+                self.reveal_process(pid, apps);
+            } else {
+                log::warn!("Unable to show process {pid} because it doesn't exist");
+            }
+        }
     }
 
     pub fn execute_process_action_dialog(&self, process: ProcessItem, action: ProcessAction) {
@@ -538,6 +913,9 @@ impl ResProcesses {
             .borrow_mut()
             .entry(uid)
             .or_insert_with(|| {
+                if demo::is_enabled() {
+                    return demo::user_name(uid);
+                }
                 uzers::get_user_by_uid(uid).map_or_else(
                     || i18n("root"),
                     |user| user.name().to_string_lossy().to_string(),
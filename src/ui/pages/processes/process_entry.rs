@@ -1,9 +1,10 @@
 use gtk::{
+    gio::prelude::IconExt,
     glib::{self},
     subclass::prelude::ObjectSubclassIsExt,
 };
 
-use crate::utils::process::ProcessItem;
+use crate::utils::process::{describe_scheduling, MemoryTrend, ProcessItem};
 
 mod imp {
     use std::cell::{Cell, RefCell};
@@ -35,6 +36,24 @@ mod imp {
         cpu_usage: Cell<f32>,
         #[property(get, set)]
         memory_usage: Cell<u64>,
+        #[property(get, set)]
+        memory_growing: Cell<bool>,
+        /// -1 if not permitted to know
+        #[property(get, set)]
+        open_files: Cell<i64>,
+        /// 0 if unlimited or unknown
+        #[property(get, set)]
+        open_files_limit: Cell<u64>,
+        #[property(get = Self::scheduling, set = Self::set_scheduling, type = glib::GString)]
+        scheduling: Cell<glib::GString>,
+        #[property(get, set)]
+        realtime_priority: Cell<u32>,
+        /// Per second
+        #[property(get, set)]
+        minor_faults: Cell<f32>,
+        /// Per second
+        #[property(get, set)]
+        major_faults: Cell<f32>,
 
         pub process_item: RefCell<Option<ProcessItem>>,
     }
@@ -50,6 +69,13 @@ mod imp {
 
                 cpu_usage: Cell::new(0.0),
                 memory_usage: Cell::new(0),
+                memory_growing: Cell::new(false),
+                open_files: Cell::new(-1),
+                open_files_limit: Cell::new(0),
+                scheduling: Cell::new(glib::GString::default()),
+                realtime_priority: Cell::new(0),
+                minor_faults: Cell::new(0.0),
+                major_faults: Cell::new(0.0),
 
                 process_item: RefCell::new(None),
             }
@@ -90,6 +116,17 @@ mod imp {
             self.user.set(glib::GString::from(user));
         }
 
+        pub fn scheduling(&self) -> glib::GString {
+            let scheduling = self.scheduling.take();
+            let result = scheduling.clone();
+            self.scheduling.set(scheduling);
+            result
+        }
+
+        pub fn set_scheduling(&self, scheduling: &str) {
+            self.scheduling.set(glib::GString::from(scheduling));
+        }
+
         pub fn icon(&self) -> Icon {
             let icon = self
                 .icon
@@ -144,16 +181,85 @@ impl ProcessEntry {
             .build();
         this.set_cpu_usage(process_item.cpu_time_ratio);
         this.set_memory_usage(process_item.memory_usage as u64);
+        this.set_memory_growing(Self::memory_growing_of(&process_item));
+        this.set_open_files(Self::open_files_of(&process_item));
+        this.set_open_files_limit(process_item.open_files_limit.unwrap_or(0) as u64);
+        this.set_scheduling(&Self::scheduling_of(&process_item));
+        this.set_realtime_priority(process_item.realtime_priority);
+        this.set_minor_faults(process_item.minor_faults_per_second);
+        this.set_major_faults(process_item.major_faults_per_second);
         this.imp().process_item.replace(Some(process_item));
         this
     }
 
+    /// Updates the properties of this entry in place. Only properties whose
+    /// values have actually changed are set, so that cells bound to unchanged
+    /// properties aren't notified needlessly.
     pub fn update(&self, process_item: ProcessItem) {
-        self.set_cpu_usage(process_item.cpu_time_ratio);
-        self.set_memory_usage(process_item.memory_usage as u64);
+        // processes can change their name and icon when exec'ing
+        if self.name() != process_item.display_name {
+            self.set_name(&process_item.display_name);
+        }
+        if self.commandline() != process_item.commandline {
+            self.set_commandline(&process_item.commandline);
+        }
+        if !self.icon().equal(Some(&process_item.icon)) {
+            self.set_icon(&process_item.icon);
+        }
+        if self.cpu_usage() != process_item.cpu_time_ratio {
+            self.set_cpu_usage(process_item.cpu_time_ratio);
+        }
+        if self.memory_usage() != process_item.memory_usage as u64 {
+            self.set_memory_usage(process_item.memory_usage as u64);
+        }
+        if self.memory_growing() != Self::memory_growing_of(&process_item) {
+            self.set_memory_growing(Self::memory_growing_of(&process_item));
+        }
+        if self.open_files() != Self::open_files_of(&process_item) {
+            self.set_open_files(Self::open_files_of(&process_item));
+        }
+        if self.open_files_limit() != process_item.open_files_limit.unwrap_or(0) as u64 {
+            self.set_open_files_limit(process_item.open_files_limit.unwrap_or(0) as u64);
+        }
+        let scheduling = Self::scheduling_of(&process_item);
+        if self.scheduling() != scheduling {
+            self.set_scheduling(&scheduling);
+        }
+        if self.realtime_priority() != process_item.realtime_priority {
+            self.set_realtime_priority(process_item.realtime_priority);
+        }
+        if self.minor_faults() != process_item.minor_faults_per_second {
+            self.set_minor_faults(process_item.minor_faults_per_second);
+        }
+        if self.major_faults() != process_item.major_faults_per_second {
+            self.set_major_faults(process_item.major_faults_per_second);
+        }
         self.imp().process_item.replace(Some(process_item));
     }
 
+    /// The fraction of the open file limit that is in use, `None` if the
+    /// number of open files or the limit is unknown
+    pub fn open_files_usage(&self) -> Option<f64> {
+        open_files_usage(self.open_files(), self.open_files_limit())
+    }
+
+    fn scheduling_of(process_item: &ProcessItem) -> String {
+        describe_scheduling(
+            process_item.scheduling_policy,
+            process_item.realtime_priority,
+        )
+    }
+
+    fn memory_growing_of(process_item: &ProcessItem) -> bool {
+        matches!(process_item.memory_trend, MemoryTrend::Growing { .. })
+    }
+
+    fn open_files_of(process_item: &ProcessItem) -> i64 {
+        process_item
+            .open_files
+            .map_or(-1, |open_files| open_files as i64)
+    }
+
     pub fn process_item(&self) -> Option<ProcessItem> {
         let imp = self.imp();
         let item = imp.process_item.take();
@@ -161,3 +267,9 @@ impl ProcessEntry {
         item
     }
 }
+
+/// See `ProcessEntry::open_files_usage`, takes the raw property values so that
+/// it can be used in expressions
+pub fn open_files_usage(open_files: i64, open_files_limit: u64) -> Option<f64> {
+    (open_files >= 0 && open_files_limit > 0).then(|| open_files as f64 / open_files_limit as f64)
+}
@@ -0,0 +1,398 @@
+use std::cell::{Cell, RefCell};
+
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::glib::{self, clone};
+
+use crate::config::PROFILE;
+use crate::i18n::{i18n, i18n_f};
+use crate::ui::copy::setup_copy_actions;
+use crate::utils::sensors::{Sensor, SensorKind};
+use crate::utils::settings::SETTINGS;
+
+#[derive(Debug)]
+pub struct SensorRow {
+    sensor: Sensor,
+    row: adw::ActionRow,
+    hide_button: gtk::ToggleButton,
+    value: Cell<Option<f64>>,
+}
+
+/// The sensors of a single chip, shown as their own group
+#[derive(Debug)]
+pub struct ChipGroup {
+    group: adw::PreferencesGroup,
+    rows: Vec<SensorRow>,
+}
+
+mod imp {
+    use super::*;
+
+    use gtk::{
+        gio::{Icon, ThemedIcon},
+        glib::{ParamSpec, Properties, Value},
+        CompositeTemplate,
+    };
+
+    #[derive(CompositeTemplate, Properties)]
+    #[template(resource = "/net/nokyan/Resources/ui/pages/sensors.ui")]
+    #[properties(wrapper_type = super::ResSensors)]
+    pub struct ResSensors {
+        #[template_child]
+        pub chips_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub show_hidden_row: TemplateChild<adw::SwitchRow>,
+
+        pub groups: RefCell<Vec<ChipGroup>>,
+
+        #[property(get)]
+        uses_progress_bar: Cell<bool>,
+
+        #[property(get)]
+        icon: RefCell<Icon>,
+
+        #[property(get = Self::tab_name, type = glib::GString)]
+        tab_name: Cell<glib::GString>,
+    }
+
+    impl ResSensors {
+        pub fn tab_name(&self) -> glib::GString {
+            let tab_name = self.tab_name.take();
+            let result = tab_name.clone();
+            self.tab_name.set(tab_name);
+            result
+        }
+    }
+
+    impl Default for ResSensors {
+        fn default() -> Self {
+            Self {
+                chips_box: Default::default(),
+                show_hidden_row: Default::default(),
+                groups: Default::default(),
+                uses_progress_bar: Cell::new(false),
+                icon: RefCell::new(ThemedIcon::new("temperature-symbolic").into()),
+                tab_name: Cell::from(glib::GString::from(i18n("Sensors"))),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResSensors {
+        const NAME: &'static str = "ResSensors";
+        type Type = super::ResSensors;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        // You must call `Widget`'s `init_template()` within `instance_init()`.
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ResSensors {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // Devel Profile
+            if PROFILE == "Devel" {
+                obj.add_css_class("devel");
+            }
+        }
+
+        fn properties() -> &'static [ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn set_property(&self, id: usize, value: &Value, pspec: &ParamSpec) {
+            self.derived_set_property(id, value, pspec);
+        }
+
+        fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
+            self.derived_property(id, pspec)
+        }
+    }
+
+    impl WidgetImpl for ResSensors {}
+    impl BinImpl for ResSensors {}
+}
+
+glib::wrapper! {
+    pub struct ResSensors(ObjectSubclass<imp::ResSensors>)
+        @extends gtk::Widget, adw::Bin;
+}
+
+impl Default for ResSensors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResSensors {
+    pub fn new() -> Self {
+        glib::Object::new::<Self>()
+    }
+
+    pub fn init(&self) {
+        setup_copy_actions(self);
+
+        let imp = self.imp();
+
+        imp.show_hidden_row
+            .connect_active_notify(clone!(@weak self as this => move |_| {
+                this.apply_hidden_sensors();
+            }));
+
+        SETTINGS.connect_sensor_aliases(clone!(@weak self as this => move |_| {
+            this.apply_sensor_aliases();
+        }));
+
+        SETTINGS.connect_hidden_sensors(clone!(@weak self as this => move |_| {
+            this.apply_hidden_sensors();
+        }));
+    }
+
+    /// Creates a row for each of `sensors`, grouped by their chip. Expects
+    /// `sensors` to be sorted by chip, as returned by `Sensor::all`.
+    pub fn set_sensors(&self, sensors: Vec<Sensor>) {
+        let imp = self.imp();
+
+        let mut groups = imp.groups.borrow_mut();
+        for group in groups.drain(..) {
+            imp.chips_box.remove(&group.group);
+        }
+
+        for sensor in sensors {
+            let same_chip = groups.last().map_or(false, |group: &ChipGroup| {
+                group.rows[0].sensor.chip == sensor.chip
+                    && group.rows[0].sensor.device == sensor.device
+            });
+
+            if !same_chip {
+                let group = adw::PreferencesGroup::builder()
+                    .title(glib::markup_escape_text(&sensor.chip))
+                    .description(glib::markup_escape_text(
+                        sensor.device.as_deref().unwrap_or_default(),
+                    ))
+                    .build();
+                imp.chips_box.append(&group);
+                groups.push(ChipGroup {
+                    group,
+                    rows: Vec::new(),
+                });
+            }
+
+            let group = groups.last_mut().unwrap();
+            let sensor_row = self.create_row(sensor);
+            group.group.add(&sensor_row.row);
+            group.rows.push(sensor_row);
+        }
+
+        drop(groups);
+
+        self.apply_sensor_aliases();
+        self.apply_hidden_sensors();
+    }
+
+    /// Shows `values`, which are expected to be in the same order as the
+    /// sensors that have been passed to `set_sensors`
+    pub fn refresh_values(&self, values: &[Option<f64>]) {
+        let groups = self.imp().groups.borrow();
+
+        for (sensor_row, value) in groups
+            .iter()
+            .flat_map(|group| group.rows.iter())
+            .zip(values)
+        {
+            sensor_row.value.set(*value);
+            let value = value.map_or_else(
+                || i18n("N/A"),
+                |value| sensor_row.sensor.format_value(value),
+            );
+            sensor_row.row.set_subtitle(&value);
+        }
+    }
+
+    /// Returns the latest values of all temperature sensors that aren't
+    /// hidden, in °C, along with the names they're shown with
+    pub fn temperatures(&self) -> Vec<(String, f64)> {
+        let aliases = SETTINGS.sensor_aliases();
+        let hidden_sensors = SETTINGS.hidden_sensors();
+
+        self.imp()
+            .groups
+            .borrow()
+            .iter()
+            .flat_map(|group| group.rows.iter())
+            .filter(|sensor_row| {
+                sensor_row.sensor.kind == SensorKind::Temperature
+                    && !hidden_sensors.contains(&sensor_row.sensor.id)
+            })
+            .filter_map(|sensor_row| {
+                let sensor = &sensor_row.sensor;
+                let name = aliases
+                    .get(&sensor.id)
+                    .cloned()
+                    .unwrap_or_else(|| format!("{} {}", sensor.chip, sensor.default_name()));
+                sensor_row.value.get().map(|value| (name, value))
+            })
+            .collect()
+    }
+
+    fn create_row(&self, sensor: Sensor) -> SensorRow {
+        let rename_button = gtk::Button::builder()
+            .icon_name("document-edit-symbolic")
+            .tooltip_text(i18n("Rename Sensor"))
+            .valign(gtk::Align::Center)
+            .css_classes(vec!["flat"])
+            .build();
+
+        let hide_button = gtk::ToggleButton::builder()
+            .icon_name("view-conceal-symbolic")
+            .tooltip_text(i18n("Hide Sensor"))
+            .valign(gtk::Align::Center)
+            .css_classes(vec!["flat"])
+            .build();
+
+        let row = adw::ActionRow::builder().subtitle_selectable(true).build();
+        row.add_css_class("property");
+        row.add_suffix(&rename_button);
+        row.add_suffix(&hide_button);
+
+        rename_button.connect_clicked(clone!(@weak self as this, @strong sensor => move |_| {
+            this.show_rename_dialog(&sensor);
+        }));
+
+        hide_button.connect_toggled(clone!(@strong sensor => move |button| {
+            let mut hidden_sensors = SETTINGS.hidden_sensors();
+            let was_hidden = hidden_sensors.contains(&sensor.id);
+            if button.is_active() == was_hidden {
+                return;
+            }
+
+            if was_hidden {
+                hidden_sensors.retain(|id| *id != sensor.id);
+            } else {
+                hidden_sensors.push(sensor.id.clone());
+            }
+
+            if let Err(error) = SETTINGS.set_hidden_sensors(&hidden_sensors) {
+                log::warn!("Unable to save hidden sensors: {error}");
+            }
+        }));
+
+        SensorRow {
+            sensor,
+            row,
+            hide_button,
+            value: Cell::default(),
+        }
+    }
+
+    fn show_rename_dialog(&self, sensor: &Sensor) {
+        let Some(window) = self.root().and_downcast::<gtk::Window>() else {
+            return;
+        };
+
+        let entry = gtk::Entry::builder()
+            .text(
+                SETTINGS
+                    .sensor_aliases()
+                    .get(&sensor.id)
+                    .cloned()
+                    .unwrap_or_default(),
+            )
+            .placeholder_text(sensor.default_name())
+            .activates_default(true)
+            .build();
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&window)
+            .modal(true)
+            .heading(i18n("Rename Sensor"))
+            .body(i18n_f(
+                "Give {} of {} a name that is easier to recognize. Leave the name empty to use the original one.",
+                &[&sensor.default_name(), &sensor.chip],
+            ))
+            .extra_child(&entry)
+            .build();
+
+        dialog.add_response("cancel", &i18n("Cancel"));
+        dialog.add_response("rename", &i18n("Rename"));
+        dialog.set_response_appearance("rename", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("rename"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            Some("rename"),
+            clone!(@weak entry, @strong sensor => move |_, _| {
+                let mut aliases = SETTINGS.sensor_aliases();
+                let alias = entry.text().trim().to_string();
+                if alias.is_empty() {
+                    aliases.remove(&sensor.id);
+                } else {
+                    aliases.insert(sensor.id.clone(), alias);
+                }
+
+                if let Err(error) = SETTINGS.set_sensor_aliases(&aliases) {
+                    log::warn!("Unable to save sensor names: {error}");
+                }
+            }),
+        );
+
+        dialog.present();
+    }
+
+    fn apply_sensor_aliases(&self) {
+        let aliases = SETTINGS.sensor_aliases();
+
+        for sensor_row in self
+            .imp()
+            .groups
+            .borrow()
+            .iter()
+            .flat_map(|group| group.rows.iter())
+        {
+            let sensor = &sensor_row.sensor;
+            let name = aliases
+                .get(&sensor.id)
+                .cloned()
+                .unwrap_or_else(|| sensor.default_name());
+            sensor_row.row.set_title(&glib::markup_escape_text(&name));
+
+            // the original name is still needed to e.g. look up the sensor in
+            // the documentation of the mainboard
+            let tooltip = (name != sensor.default_name())
+                .then(|| i18n_f("Originally {}", &[&sensor.default_name()]));
+            sensor_row.row.set_tooltip_text(tooltip.as_deref());
+        }
+    }
+
+    fn apply_hidden_sensors(&self) {
+        let imp = self.imp();
+
+        let hidden_sensors = SETTINGS.hidden_sensors();
+        let show_hidden = imp.show_hidden_row.is_active();
+
+        for group in imp.groups.borrow().iter() {
+            let mut any_visible = false;
+
+            for sensor_row in &group.rows {
+                let hidden = hidden_sensors.contains(&sensor_row.sensor.id);
+                sensor_row.hide_button.set_active(hidden);
+                sensor_row.row.set_visible(show_hidden || !hidden);
+                if hidden {
+                    sensor_row.row.add_css_class("dim-label");
+                } else {
+                    sensor_row.row.remove_css_class("dim-label");
+                    any_visible = true;
+                }
+            }
+
+            group.group.set_visible(show_hidden || any_visible);
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::glib::{self, clone};
+use hashbrown::HashMap;
+
+use crate::config::PROFILE;
+use crate::i18n::i18n;
+use crate::ui::copy::setup_copy_actions;
+use crate::ui::dialogs::journal_dialog::ResJournalDialog;
+use crate::utils::journal::JournalSource;
+use crate::utils::systemd::{SystemdUnit, UnitStatus};
+
+#[derive(Debug)]
+pub struct ServiceRow {
+    row: adw::ActionRow,
+    state_label: gtk::Label,
+}
+
+impl ServiceRow {
+    fn new(page: &ResServices, unit: &SystemdUnit) -> Self {
+        let state_label = gtk::Label::builder()
+            .valign(gtk::Align::Center)
+            .css_classes(vec!["dim-label"])
+            .build();
+
+        let row = adw::ActionRow::builder()
+            .title(glib::markup_escape_text(&unit.name))
+            .activatable(true)
+            .build();
+        row.add_css_class("property");
+        row.add_suffix(&state_label);
+        row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+        row.connect_activated(
+            clone!(@weak page, @strong unit => move |_| page.show_journal(&unit)),
+        );
+
+        Self { row, state_label }
+    }
+
+    fn refresh(&self, status: &UnitStatus) {
+        self.row
+            .set_subtitle(&glib::markup_escape_text(&status.description));
+        self.state_label
+            .set_label(&format!("{} ({})", status.active_state, status.sub_state));
+
+        // a failed service is what people come here for, so make it stand out
+        if status.is_failed() {
+            self.state_label.remove_css_class("dim-label");
+            self.state_label.add_css_class("error");
+        } else {
+            self.state_label.remove_css_class("error");
+            self.state_label.add_css_class("dim-label");
+        }
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    use gtk::{
+        gio::{Icon, ThemedIcon},
+        glib::{ParamSpec, Properties, Value},
+        CompositeTemplate,
+    };
+
+    #[derive(CompositeTemplate, Properties)]
+    #[template(resource = "/net/nokyan/Resources/ui/pages/services.ui")]
+    #[properties(wrapper_type = super::ResServices)]
+    pub struct ResServices {
+        #[template_child]
+        pub system_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub user_list: TemplateChild<gtk::ListBox>,
+
+        pub system_rows: RefCell<HashMap<String, ServiceRow>>,
+        pub user_rows: RefCell<HashMap<String, ServiceRow>>,
+
+        #[property(get)]
+        uses_progress_bar: Cell<bool>,
+
+        #[property(get)]
+        icon: RefCell<Icon>,
+
+        #[property(get = Self::tab_name, type = glib::GString)]
+        tab_name: Cell<glib::GString>,
+    }
+
+    impl ResServices {
+        pub fn tab_name(&self) -> glib::GString {
+            let tab_name = self.tab_name.take();
+            let result = tab_name.clone();
+            self.tab_name.set(tab_name);
+            result
+        }
+    }
+
+    impl Default for ResServices {
+        fn default() -> Self {
+            Self {
+                system_list: Default::default(),
+                user_list: Default::default(),
+                system_rows: Default::default(),
+                user_rows: Default::default(),
+                uses_progress_bar: Cell::new(false),
+                icon: RefCell::new(ThemedIcon::new("system-run-symbolic").into()),
+                tab_name: Cell::from(glib::GString::from(i18n("Services"))),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResServices {
+        const NAME: &'static str = "ResServices";
+        type Type = super::ResServices;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        // You must call `Widget`'s `init_template()` within `instance_init()`.
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ResServices {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // Devel Profile
+            if PROFILE == "Devel" {
+                obj.add_css_class("devel");
+            }
+        }
+
+        fn properties() -> &'static [ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn set_property(&self, id: usize, value: &Value, pspec: &ParamSpec) {
+            self.derived_set_property(id, value, pspec);
+        }
+
+        fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
+            self.derived_property(id, pspec)
+        }
+    }
+
+    impl WidgetImpl for ResServices {}
+    impl BinImpl for ResServices {}
+}
+
+glib::wrapper! {
+    pub struct ResServices(ObjectSubclass<imp::ResServices>)
+        @extends gtk::Widget, adw::Bin;
+}
+
+impl Default for ResServices {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResServices {
+    pub fn new() -> Self {
+        glib::Object::new::<Self>()
+    }
+
+    pub fn init(&self) {
+        let imp = self.imp();
+
+        for list in [&*imp.system_list, &*imp.user_list] {
+            list.set_placeholder(Some(
+                &gtk::Label::builder()
+                    .label(i18n("No Services"))
+                    .margin_top(12)
+                    .margin_bottom(12)
+                    .css_classes(vec!["dim-label"])
+                    .build(),
+            ));
+        }
+
+        setup_copy_actions(self);
+    }
+
+    /// Shows `services`, which all have to belong to the same service manager
+    pub fn refresh_services(&self, user: bool, mut services: Vec<UnitStatus>) {
+        let imp = self.imp();
+        let (list, mut rows) = if user {
+            (&imp.user_list, imp.user_rows.borrow_mut())
+        } else {
+            (&imp.system_list, imp.system_rows.borrow_mut())
+        };
+
+        rows.retain(|name, service_row| {
+            let present = services.iter().any(|status| status.unit.name == *name);
+            if !present {
+                list.remove(&service_row.row);
+            }
+            present
+        });
+
+        // failed services first, the rest by name
+        services.sort_by(|a, b| {
+            b.is_failed()
+                .cmp(&a.is_failed())
+                .then_with(|| a.unit.name.cmp(&b.unit.name))
+        });
+
+        for (i, status) in services.iter().enumerate() {
+            let service_row = rows.entry(status.unit.name.clone()).or_insert_with(|| {
+                let service_row = ServiceRow::new(self, &status.unit);
+                list.append(&service_row.row);
+                service_row
+            });
+            service_row.refresh(status);
+
+            // keep the order of the rows in line with `services`
+            if service_row.row.index() != i as i32 {
+                list.remove(&service_row.row);
+                list.insert(&service_row.row, i as i32);
+            }
+        }
+    }
+
+    fn show_journal(&self, unit: &SystemdUnit) {
+        let journal_dialog = ResJournalDialog::new();
+        journal_dialog.set_transient_for(self.root().and_downcast_ref::<gtk::Window>());
+        journal_dialog.init(JournalSource::Unit(unit.clone()));
+        journal_dialog.present();
+    }
+}
@@ -0,0 +1,285 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::glib::{self, clone, MainContext};
+use hashbrown::HashMap;
+
+use crate::config::PROFILE;
+use crate::i18n::{i18n, i18n_f};
+use crate::ui::copy::setup_copy_actions;
+use crate::utils::app::AppsContext;
+use crate::utils::logind::Session;
+use crate::utils::units::convert_storage;
+
+#[derive(Debug)]
+pub struct SessionRow {
+    row: adw::ActionRow,
+    cpu_label: gtk::Label,
+    memory_label: gtk::Label,
+}
+
+impl SessionRow {
+    fn new(page: &ResSessions, session: &Session) -> Self {
+        let cpu_label = gtk::Label::builder()
+            .valign(gtk::Align::Center)
+            .width_chars(7)
+            .xalign(1.0)
+            .css_classes(vec!["numeric"])
+            .tooltip_text(i18n("Processor"))
+            .build();
+
+        let memory_label = gtk::Label::builder()
+            .valign(gtk::Align::Center)
+            .width_chars(9)
+            .xalign(1.0)
+            .css_classes(vec!["numeric", "dim-label"])
+            .tooltip_text(i18n("Memory"))
+            .build();
+
+        let terminate_button = gtk::Button::builder()
+            .icon_name("system-log-out-symbolic")
+            .tooltip_text(i18n("Terminate Session"))
+            .valign(gtk::Align::Center)
+            .css_classes(vec!["flat"])
+            .build();
+        terminate_button.connect_clicked(
+            clone!(@weak page, @strong session => move |_| page.show_terminate_dialog(&session)),
+        );
+
+        let row = adw::ActionRow::builder()
+            .title(glib::markup_escape_text(&session.user))
+            .subtitle_selectable(true)
+            .build();
+        row.add_css_class("property");
+        row.add_suffix(&memory_label);
+        row.add_suffix(&cpu_label);
+        row.add_suffix(&terminate_button);
+
+        Self {
+            row,
+            cpu_label,
+            memory_label,
+        }
+    }
+
+    fn refresh(&self, session: &Session, apps_context: &AppsContext) {
+        let mut subtitle_parts = vec![i18n_f("Session {}", &[&session.id])];
+        if let Some(seat) = &session.seat {
+            subtitle_parts.push(seat.clone());
+        }
+        if let Some(tty) = &session.tty {
+            subtitle_parts.push(tty.clone());
+        }
+        match session.session_type.as_str() {
+            "wayland" => subtitle_parts.push(i18n("Wayland")),
+            "x11" => subtitle_parts.push(i18n("X11")),
+            "tty" => subtitle_parts.push(i18n("Terminal")),
+            "mir" => subtitle_parts.push(i18n("Mir")),
+            _ => {}
+        }
+        if session.remote {
+            subtitle_parts.push(session.remote_host.as_ref().map_or_else(
+                || i18n("Remote"),
+                |remote_host| i18n_f("Remote from {}", &[remote_host.as_str()]),
+            ));
+        }
+        self.row
+            .set_subtitle(&glib::markup_escape_text(&subtitle_parts.join(" · ")));
+
+        let (cpu_time_ratio, memory_usage) = session
+            .pids()
+            .into_iter()
+            .filter_map(|pid| apps_context.get_process(pid))
+            .fold((0.0, 0), |(cpu, memory), process| {
+                (
+                    cpu + process.cpu_time_ratio(),
+                    memory + process.data.memory_usage,
+                )
+            });
+
+        self.cpu_label
+            .set_label(&format!("{:.1} %", cpu_time_ratio * 100.0));
+        self.memory_label
+            .set_label(&convert_storage(memory_usage as f64, false));
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    use gtk::{
+        gio::{Icon, ThemedIcon},
+        glib::{ParamSpec, Properties, Value},
+        CompositeTemplate,
+    };
+
+    #[derive(CompositeTemplate, Properties)]
+    #[template(resource = "/net/nokyan/Resources/ui/pages/sessions.ui")]
+    #[properties(wrapper_type = super::ResSessions)]
+    pub struct ResSessions {
+        #[template_child]
+        pub sessions_list: TemplateChild<gtk::ListBox>,
+
+        pub rows: RefCell<HashMap<String, SessionRow>>,
+
+        #[property(get)]
+        uses_progress_bar: Cell<bool>,
+
+        #[property(get)]
+        icon: RefCell<Icon>,
+
+        #[property(get = Self::tab_name, type = glib::GString)]
+        tab_name: Cell<glib::GString>,
+    }
+
+    impl ResSessions {
+        pub fn tab_name(&self) -> glib::GString {
+            let tab_name = self.tab_name.take();
+            let result = tab_name.clone();
+            self.tab_name.set(tab_name);
+            result
+        }
+    }
+
+    impl Default for ResSessions {
+        fn default() -> Self {
+            Self {
+                sessions_list: Default::default(),
+                rows: Default::default(),
+                uses_progress_bar: Cell::new(false),
+                icon: RefCell::new(ThemedIcon::new("system-log-out-symbolic").into()),
+                tab_name: Cell::from(glib::GString::from(i18n("Sessions"))),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResSessions {
+        const NAME: &'static str = "ResSessions";
+        type Type = super::ResSessions;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        // You must call `Widget`'s `init_template()` within `instance_init()`.
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ResSessions {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // Devel Profile
+            if PROFILE == "Devel" {
+                obj.add_css_class("devel");
+            }
+        }
+
+        fn properties() -> &'static [ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn set_property(&self, id: usize, value: &Value, pspec: &ParamSpec) {
+            self.derived_set_property(id, value, pspec);
+        }
+
+        fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
+            self.derived_property(id, pspec)
+        }
+    }
+
+    impl WidgetImpl for ResSessions {}
+    impl BinImpl for ResSessions {}
+}
+
+glib::wrapper! {
+    pub struct ResSessions(ObjectSubclass<imp::ResSessions>)
+        @extends gtk::Widget, adw::Bin;
+}
+
+impl Default for ResSessions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResSessions {
+    pub fn new() -> Self {
+        glib::Object::new::<Self>()
+    }
+
+    pub fn init(&self) {
+        self.imp().sessions_list.set_placeholder(Some(
+            &gtk::Label::builder()
+                .label(i18n("No Sessions"))
+                .margin_top(12)
+                .margin_bottom(12)
+                .css_classes(vec!["dim-label"])
+                .build(),
+        ));
+
+        setup_copy_actions(self);
+    }
+
+    pub fn refresh_sessions(&self, sessions: Vec<Session>, apps_context: &AppsContext) {
+        let imp = self.imp();
+        let mut rows = imp.rows.borrow_mut();
+
+        rows.retain(|id, session_row| {
+            let present = sessions.iter().any(|session| session.id == *id);
+            if !present {
+                imp.sessions_list.remove(&session_row.row);
+            }
+            present
+        });
+
+        for session in &sessions {
+            let session_row = rows.entry(session.id.clone()).or_insert_with(|| {
+                let session_row = SessionRow::new(self, session);
+                imp.sessions_list.append(&session_row.row);
+                session_row
+            });
+            session_row.refresh(session, apps_context);
+        }
+    }
+
+    fn show_terminate_dialog(&self, session: &Session) {
+        let Some(window) = self.root().and_downcast::<gtk::Window>() else {
+            return;
+        };
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&window)
+            .modal(true)
+            .heading(i18n_f("Terminate Session of {}?", &[&session.user]))
+            .body(i18n(
+                "All processes of this session will be killed. Unsaved data will be lost.",
+            ))
+            .build();
+
+        dialog.add_response("cancel", &i18n("Cancel"));
+        dialog.add_response("terminate", &i18n("Terminate Session"));
+        dialog.set_response_appearance("terminate", adw::ResponseAppearance::Destructive);
+        dialog.set_default_response(Some("cancel"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            Some("terminate"),
+            clone!(@strong session => move |_, _| {
+                let session = session.clone();
+                MainContext::default().spawn_local(async move {
+                    if let Err(error) = session.terminate().await {
+                        log::warn!("Unable to terminate session {}: {error}", session.id);
+                    }
+                });
+            }),
+        );
+
+        dialog.present();
+    }
+}
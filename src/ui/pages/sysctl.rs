@@ -0,0 +1,411 @@
+use std::cell::Cell;
+use std::collections::BTreeMap;
+
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::glib::{self, clone, MainContext};
+
+use crate::config::PROFILE;
+use crate::i18n::{i18n, i18n_f, ni18n_f};
+use crate::ui::copy::setup_copy_actions;
+use crate::utils::helper;
+use crate::utils::settings::SETTINGS;
+use crate::utils::sysctl::SysctlParameter;
+
+/// Searching for something short like "a" would otherwise add thousands of rows
+/// at once
+const MAX_RESULTS: usize = 250;
+
+mod imp {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    use gtk::{
+        gio::{Icon, ThemedIcon},
+        glib::{ParamSpec, Properties, Value},
+        CompositeTemplate,
+    };
+
+    #[derive(CompositeTemplate, Properties)]
+    #[template(resource = "/net/nokyan/Resources/ui/pages/sysctl.ui")]
+    #[properties(wrapper_type = super::ResSysctl)]
+    pub struct ResSysctl {
+        #[template_child]
+        pub toast_overlay: TemplateChild<adw::ToastOverlay>,
+        #[template_child]
+        pub search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub reload_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub subsystems_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub subsystems_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub results_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub results_list: TemplateChild<gtk::ListBox>,
+
+        /// All parameters, grouped by their subsystem
+        pub parameters: RefCell<BTreeMap<String, Vec<SysctlParameter>>>,
+
+        #[property(get)]
+        uses_progress_bar: Cell<bool>,
+
+        #[property(get)]
+        icon: RefCell<Icon>,
+
+        #[property(get = Self::tab_name, type = glib::GString)]
+        tab_name: Cell<glib::GString>,
+    }
+
+    impl ResSysctl {
+        pub fn tab_name(&self) -> glib::GString {
+            let tab_name = self.tab_name.take();
+            let result = tab_name.clone();
+            self.tab_name.set(tab_name);
+            result
+        }
+    }
+
+    impl Default for ResSysctl {
+        fn default() -> Self {
+            Self {
+                toast_overlay: Default::default(),
+                search_entry: Default::default(),
+                reload_button: Default::default(),
+                subsystems_group: Default::default(),
+                subsystems_list: Default::default(),
+                results_group: Default::default(),
+                results_list: Default::default(),
+                parameters: Default::default(),
+                uses_progress_bar: Cell::new(false),
+                icon: RefCell::new(ThemedIcon::new("preferences-other-symbolic").into()),
+                tab_name: Cell::from(glib::GString::from(i18n("Kernel Parameters"))),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResSysctl {
+        const NAME: &'static str = "ResSysctl";
+        type Type = super::ResSysctl;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        // You must call `Widget`'s `init_template()` within `instance_init()`.
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ResSysctl {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // Devel Profile
+            if PROFILE == "Devel" {
+                obj.add_css_class("devel");
+            }
+        }
+
+        fn properties() -> &'static [ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn set_property(&self, id: usize, value: &Value, pspec: &ParamSpec) {
+            self.derived_set_property(id, value, pspec);
+        }
+
+        fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
+            self.derived_property(id, pspec)
+        }
+    }
+
+    impl WidgetImpl for ResSysctl {}
+    impl BinImpl for ResSysctl {}
+}
+
+glib::wrapper! {
+    pub struct ResSysctl(ObjectSubclass<imp::ResSysctl>)
+        @extends gtk::Widget, adw::Bin;
+}
+
+impl Default for ResSysctl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResSysctl {
+    pub fn new() -> Self {
+        glib::Object::new::<Self>()
+    }
+
+    pub fn init(&self) {
+        let imp = self.imp();
+
+        imp.results_list.set_placeholder(Some(
+            &gtk::Label::builder()
+                .label(i18n("No Parameters Found"))
+                .margin_top(12)
+                .margin_bottom(12)
+                .css_classes(vec!["dim-label"])
+                .build(),
+        ));
+
+        imp.search_entry
+            .connect_search_changed(clone!(@weak self as this => move |_| {
+                this.refresh_results();
+            }));
+
+        imp.reload_button
+            .connect_clicked(clone!(@weak self as this => move |_| {
+                this.reload();
+            }));
+
+        // the edit buttons are only added to rows while editing is allowed
+        SETTINGS.connect_sysctl_editing(clone!(@weak self as this => move |_| {
+            this.rebuild();
+        }));
+
+        self.reload();
+
+        setup_copy_actions(self);
+    }
+
+    /// Reads all kernel parameters in the background and rebuilds the page
+    /// afterwards. There are a few thousand of them and they rarely change, so
+    /// this is only done on request rather than on every refresh.
+    pub fn reload(&self) {
+        MainContext::default().spawn_local(clone!(@weak self as this => async move {
+            let parameters = match async_std::task::spawn_blocking(SysctlParameter::all).await {
+                Ok(parameters) => parameters,
+                Err(error) => {
+                    log::warn!("Unable to read kernel parameters: {error}");
+                    Vec::new()
+                }
+            };
+
+            let mut grouped_parameters: BTreeMap<String, Vec<SysctlParameter>> = BTreeMap::new();
+            for parameter in parameters {
+                grouped_parameters
+                    .entry(parameter.subsystem().to_string())
+                    .or_default()
+                    .push(parameter);
+            }
+            *this.imp().parameters.borrow_mut() = grouped_parameters;
+
+            this.rebuild();
+        }));
+    }
+
+    fn rebuild(&self) {
+        let imp = self.imp();
+
+        while let Some(row) = imp.subsystems_list.row_at_index(0) {
+            imp.subsystems_list.remove(&row);
+        }
+
+        for (subsystem, parameters) in imp.parameters.borrow().iter() {
+            let expander_row = adw::ExpanderRow::builder()
+                .title(glib::markup_escape_text(&subsystem_title(subsystem)))
+                .subtitle(glib::markup_escape_text(&format!(
+                    "{subsystem} · {}",
+                    ni18n_f(
+                        "{} parameter",
+                        "{} parameters",
+                        parameters.len() as u32,
+                        &[&parameters.len().to_string()],
+                    )
+                )))
+                .build();
+
+            // creating rows for all parameters up front would take a while, so
+            // they are only added once the subsystem is expanded for the first
+            // time
+            let subsystem = subsystem.clone();
+            let populated = Cell::new(false);
+            expander_row.connect_expanded_notify(
+                clone!(@weak self as this => move |expander_row| {
+                    if !expander_row.is_expanded() || populated.replace(true) {
+                        return;
+                    }
+
+                    let parameters = this
+                        .imp()
+                        .parameters
+                        .borrow()
+                        .get(&subsystem)
+                        .cloned()
+                        .unwrap_or_default();
+                    for parameter in &parameters {
+                        expander_row.add_row(&this.parameter_row(parameter));
+                    }
+                }),
+            );
+
+            imp.subsystems_list.append(&expander_row);
+        }
+
+        self.refresh_results();
+    }
+
+    fn refresh_results(&self) {
+        let imp = self.imp();
+
+        let query = imp.search_entry.text().to_lowercase();
+        let searching = !query.is_empty();
+        imp.subsystems_group.set_visible(!searching);
+        imp.results_group.set_visible(searching);
+
+        while let Some(row) = imp.results_list.row_at_index(0) {
+            imp.results_list.remove(&row);
+        }
+
+        if !searching {
+            return;
+        }
+
+        let results: Vec<SysctlParameter> = imp
+            .parameters
+            .borrow()
+            .values()
+            .flatten()
+            .filter(|parameter| parameter.name().to_lowercase().contains(&query))
+            .cloned()
+            .collect();
+
+        if results.len() > MAX_RESULTS {
+            imp.results_group.set_description(Some(&i18n_f(
+                "Showing the first {} of {} results",
+                &[&MAX_RESULTS.to_string(), &results.len().to_string()],
+            )));
+        } else {
+            imp.results_group.set_description(None);
+        }
+
+        for parameter in results.iter().take(MAX_RESULTS) {
+            imp.results_list.append(&self.parameter_row(parameter));
+        }
+    }
+
+    fn parameter_row(&self, parameter: &SysctlParameter) -> adw::ActionRow {
+        let row = adw::ActionRow::builder()
+            .title(glib::markup_escape_text(&parameter.name()))
+            .subtitle(glib::markup_escape_text(
+                &parameter.value.clone().unwrap_or_else(|| i18n("N/A")),
+            ))
+            .subtitle_selectable(true)
+            .build();
+        row.add_css_class("property");
+
+        if parameter.writable && SETTINGS.sysctl_editing() {
+            let edit_button = gtk::Button::builder()
+                .icon_name("document-edit-symbolic")
+                .tooltip_text(i18n("Change Value"))
+                .valign(gtk::Align::Center)
+                .css_classes(vec!["flat"])
+                .build();
+            edit_button.connect_clicked(
+                clone!(@weak self as this, @weak row, @strong parameter => move |_| {
+                    this.show_edit_dialog(&parameter, &row);
+                }),
+            );
+            row.add_suffix(&edit_button);
+        }
+
+        row
+    }
+
+    fn show_edit_dialog(&self, parameter: &SysctlParameter, row: &adw::ActionRow) {
+        let Some(window) = self.root().and_downcast::<gtk::Window>() else {
+            return;
+        };
+
+        let entry = gtk::Entry::builder()
+            .text(parameter.value.clone().unwrap_or_default())
+            .activates_default(true)
+            .build();
+
+        let dialog = adw::MessageDialog::builder()
+            .transient_for(&window)
+            .modal(true)
+            .heading(i18n_f("Change {}?", &[&parameter.name()]))
+            .body(i18n(
+                "The new value takes effect immediately and lasts until the next reboot.",
+            ))
+            .extra_child(&entry)
+            .build();
+
+        dialog.add_response("cancel", &i18n("Cancel"));
+        dialog.add_response("apply", &i18n("Change Value"));
+        dialog.set_response_appearance("apply", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("apply"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            Some("apply"),
+            clone!(@weak self as this, @weak row, @weak entry, @strong parameter => move |_, _| {
+                let value = entry.text().to_string();
+                MainContext::default().spawn_local(clone!(@weak this, @weak row, @strong parameter => async move {
+                    if let Err(error) = helper::set_sysctl(&parameter.path, &value).await {
+                        log::warn!("Unable to set kernel parameter {}: {error}", parameter.name());
+                        this.imp().toast_overlay.add_toast(adw::Toast::new(&i18n_f(
+                            "Unable to change {}",
+                            &[&parameter.name()],
+                        )));
+                    }
+                    this.refresh_parameter(&parameter.path, &row);
+                }));
+            }),
+        );
+
+        dialog.present();
+    }
+
+    /// Reads the parameter at `path` again, e.g. after it has been changed, and
+    /// updates `row` accordingly
+    fn refresh_parameter(&self, path: &str, row: &adw::ActionRow) {
+        let parameter = match SysctlParameter::read(path) {
+            Ok(parameter) => parameter,
+            Err(error) => {
+                log::warn!("Unable to read kernel parameter {path}: {error}");
+                return;
+            }
+        };
+
+        row.set_subtitle(&glib::markup_escape_text(
+            &parameter.value.clone().unwrap_or_else(|| i18n("N/A")),
+        ));
+
+        if let Some(old_parameter) = self
+            .imp()
+            .parameters
+            .borrow_mut()
+            .get_mut(parameter.subsystem())
+            .and_then(|parameters| parameters.iter_mut().find(|old| old.path == path))
+        {
+            *old_parameter = parameter;
+        }
+    }
+}
+
+/// Returns a human-readable name for a top-level directory of /proc/sys
+fn subsystem_title(subsystem: &str) -> String {
+    match subsystem {
+        "abi" => i18n("Execution Domains"),
+        "crypto" => i18n("Cryptography"),
+        "debug" => i18n("Debugging"),
+        "dev" => i18n("Devices"),
+        "fs" => i18n("File Systems"),
+        "kernel" => i18n("Kernel"),
+        "net" => i18n("Networking"),
+        "user" => i18n("User Namespaces"),
+        "vm" => i18n("Virtual Memory"),
+        _ => subsystem.to_string(),
+    }
+}
@@ -0,0 +1,259 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::glib;
+use hashbrown::HashMap;
+
+use crate::config::PROFILE;
+use crate::i18n::{i18n, ni18n_f};
+use crate::ui::copy::setup_copy_actions;
+use crate::utils::app::{AppsContext, UserItem};
+use crate::utils::demo;
+use crate::utils::units::convert_storage;
+
+#[derive(Debug)]
+pub struct UserRow {
+    row: adw::ActionRow,
+    cpu_label: gtk::Label,
+    memory_label: gtk::Label,
+}
+
+impl UserRow {
+    fn new(user_name: &str) -> Self {
+        let cpu_label = gtk::Label::builder()
+            .valign(gtk::Align::Center)
+            .width_chars(7)
+            .xalign(1.0)
+            .css_classes(vec!["numeric"])
+            .tooltip_text(i18n("Processor"))
+            .build();
+
+        let memory_label = gtk::Label::builder()
+            .valign(gtk::Align::Center)
+            .width_chars(9)
+            .xalign(1.0)
+            .css_classes(vec!["numeric", "dim-label"])
+            .tooltip_text(i18n("Memory"))
+            .build();
+
+        let row = adw::ActionRow::builder()
+            .title(glib::markup_escape_text(user_name))
+            .subtitle_selectable(true)
+            .build();
+        row.add_css_class("property");
+        row.add_suffix(&memory_label);
+        row.add_suffix(&cpu_label);
+
+        Self {
+            row,
+            cpu_label,
+            memory_label,
+        }
+    }
+
+    fn refresh(&self, user_item: &UserItem) {
+        self.row.set_subtitle(&ni18n_f(
+            "{} process",
+            "{} processes",
+            user_item.processes_amount as u32,
+            &[&user_item.processes_amount.to_string()],
+        ));
+        self.cpu_label
+            .set_label(&format!("{:.1} %", user_item.cpu_time_ratio * 100.0));
+        self.memory_label
+            .set_label(&convert_storage(user_item.memory_usage as f64, false));
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    use super::*;
+
+    use gtk::{
+        gio::{Icon, ThemedIcon},
+        glib::{ParamSpec, Properties, Value},
+        CompositeTemplate,
+    };
+
+    #[derive(CompositeTemplate, Properties)]
+    #[template(resource = "/net/nokyan/Resources/ui/pages/users.ui")]
+    #[properties(wrapper_type = super::ResUsers)]
+    pub struct ResUsers {
+        #[template_child]
+        pub users_list: TemplateChild<gtk::ListBox>,
+
+        pub rows: RefCell<HashMap<u32, UserRow>>,
+        pub user_items: Rc<RefCell<HashMap<gtk::ListBoxRow, UserItem>>>,
+        pub username_cache: RefCell<HashMap<u32, String>>,
+
+        #[property(get)]
+        uses_progress_bar: Cell<bool>,
+
+        #[property(get)]
+        icon: RefCell<Icon>,
+
+        #[property(get = Self::tab_name, type = glib::GString)]
+        tab_name: Cell<glib::GString>,
+    }
+
+    impl ResUsers {
+        pub fn tab_name(&self) -> glib::GString {
+            let tab_name = self.tab_name.take();
+            let result = tab_name.clone();
+            self.tab_name.set(tab_name);
+            result
+        }
+    }
+
+    impl Default for ResUsers {
+        fn default() -> Self {
+            Self {
+                users_list: Default::default(),
+                rows: Default::default(),
+                user_items: Default::default(),
+                username_cache: Default::default(),
+                uses_progress_bar: Cell::new(false),
+                icon: RefCell::new(ThemedIcon::new("system-users-symbolic").into()),
+                tab_name: Cell::from(glib::GString::from(i18n("Users"))),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResUsers {
+        const NAME: &'static str = "ResUsers";
+        type Type = super::ResUsers;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        // You must call `Widget`'s `init_template()` within `instance_init()`.
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ResUsers {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // Devel Profile
+            if PROFILE == "Devel" {
+                obj.add_css_class("devel");
+            }
+        }
+
+        fn properties() -> &'static [ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn set_property(&self, id: usize, value: &Value, pspec: &ParamSpec) {
+            self.derived_set_property(id, value, pspec);
+        }
+
+        fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
+            self.derived_property(id, pspec)
+        }
+    }
+
+    impl WidgetImpl for ResUsers {}
+    impl BinImpl for ResUsers {}
+}
+
+glib::wrapper! {
+    pub struct ResUsers(ObjectSubclass<imp::ResUsers>)
+        @extends gtk::Widget, adw::Bin;
+}
+
+impl Default for ResUsers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResUsers {
+    pub fn new() -> Self {
+        glib::Object::new::<Self>()
+    }
+
+    pub fn init(&self) {
+        let imp = self.imp();
+
+        // heaviest users first
+        let user_items = imp.user_items.clone();
+        imp.users_list.set_sort_func(move |a, b| {
+            let user_items = user_items.borrow();
+            let (cpu_a, memory_a) = user_items
+                .get(a)
+                .map_or((0.0, 0), |item| (item.cpu_time_ratio, item.memory_usage));
+            let (cpu_b, memory_b) = user_items
+                .get(b)
+                .map_or((0.0, 0), |item| (item.cpu_time_ratio, item.memory_usage));
+            cpu_b.total_cmp(&cpu_a).then(memory_b.cmp(&memory_a)).into()
+        });
+
+        setup_copy_actions(self);
+    }
+
+    pub fn refresh_users_list(&self, apps_context: &AppsContext) {
+        let imp = self.imp();
+
+        let new_user_items = apps_context.user_items();
+
+        let mut rows = imp.rows.borrow_mut();
+
+        // users whose last process has exited
+        rows.retain(|uid, user_row| {
+            let present = new_user_items.contains_key(uid);
+            if !present {
+                imp.user_items
+                    .borrow_mut()
+                    .remove(user_row.row.upcast_ref::<gtk::ListBoxRow>());
+                imp.users_list.remove(&user_row.row);
+            }
+            present
+        });
+
+        for (uid, user_item) in new_user_items {
+            // appending a row runs the sort function, so `user_items` must not be
+            // borrowed at that point
+            let user_row = rows.entry(uid).or_insert_with(|| {
+                let user_row = UserRow::new(&self.get_user_name_by_uid(uid));
+                imp.users_list.append(&user_row.row);
+                user_row
+            });
+
+            user_row.refresh(&user_item);
+            imp.user_items
+                .borrow_mut()
+                .insert(user_row.row.clone().upcast(), user_item);
+        }
+
+        drop(rows);
+
+        imp.users_list.invalidate_sort();
+    }
+
+    fn get_user_name_by_uid(&self, uid: u32) -> String {
+        let imp = self.imp();
+        // cache all the user names so we don't have
+        // to do expensive lookups all the time
+        (*(imp
+            .username_cache
+            .borrow_mut()
+            .entry(uid)
+            .or_insert_with(|| {
+                if demo::is_enabled() {
+                    return demo::user_name(uid);
+                }
+                uzers::get_user_by_uid(uid).map_or_else(
+                    || uid.to_string(),
+                    |user| user.name().to_string_lossy().to_string(),
+                )
+            })))
+        .to_string()
+    }
+}
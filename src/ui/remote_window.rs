@@ -0,0 +1,250 @@
+use std::collections::hash_map::Entry;
+use std::ffi::OsString;
+use std::time::Duration;
+
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::gio;
+use gtk::glib::{self, timeout_future, MainContext};
+
+use crate::config::PROFILE;
+use crate::i18n::{i18n, i18n_f};
+use crate::ui::pages::drive::ResDrive;
+use crate::ui::pages::network::ResNetwork;
+use crate::utils::drive::{Drive, DriveData, DriveExt};
+use crate::utils::network::{NetworkData, NetworkInterface, NetworkInterfaceExt};
+use crate::utils::remote::RemoteHost;
+use crate::utils::settings::SETTINGS;
+
+mod imp {
+    use std::{
+        cell::{OnceCell, RefCell},
+        collections::HashMap,
+    };
+
+    use crate::ui::pages::{cpu::ResCPU, memory::ResMemory};
+
+    use super::*;
+
+    use gtk::CompositeTemplate;
+
+    #[derive(Debug, CompositeTemplate, Default)]
+    #[template(resource = "/net/nokyan/Resources/ui/remote_window.ui")]
+    pub struct ResRemoteWindow {
+        #[template_child]
+        pub status_banner: TemplateChild<adw::Banner>,
+        #[template_child]
+        pub stack: TemplateChild<adw::ViewStack>,
+        #[template_child]
+        pub cpu: TemplateChild<ResCPU>,
+        #[template_child]
+        pub memory: TemplateChild<ResMemory>,
+
+        pub drive_pages: RefCell<HashMap<String, ResDrive>>,
+        pub network_pages: RefCell<HashMap<OsString, ResNetwork>>,
+
+        pub host: OnceCell<RemoteHost>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResRemoteWindow {
+        const NAME: &'static str = "ResRemoteWindow";
+        type Type = super::ResRemoteWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        // You must call `Widget`'s `init_template()` within `instance_init()`.
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ResRemoteWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // Devel Profile
+            if PROFILE == "Devel" {
+                obj.add_css_class("devel");
+            }
+        }
+    }
+
+    impl WidgetImpl for ResRemoteWindow {}
+
+    impl WindowImpl for ResRemoteWindow {}
+
+    impl AdwWindowImpl for ResRemoteWindow {}
+}
+
+glib::wrapper! {
+    pub struct ResRemoteWindow(ObjectSubclass<imp::ResRemoteWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window,
+        @implements gtk::Root;
+}
+
+impl ResRemoteWindow {
+    pub fn new(app: &impl IsA<gtk::Application>) -> Self {
+        glib::Object::builder::<Self>()
+            .property("application", app)
+            .build()
+    }
+
+    /// Starts monitoring `host`. The pages are refreshed for as long as this
+    /// window is open.
+    pub fn init(&self, host: RemoteHost) {
+        let imp = self.imp();
+
+        self.set_title(Some(&i18n_f("{} (Remote)", &[host.destination()])));
+        self.show_status(&i18n_f("Connecting to {}…", &[host.destination()]));
+
+        imp.host.set(host).unwrap_or_default();
+
+        MainContext::default().spawn_local(Self::refresh_loop(self.downgrade()));
+    }
+
+    fn show_status(&self, status: &str) {
+        let imp = self.imp();
+        imp.status_banner.set_title(status);
+        imp.status_banner.set_revealed(true);
+    }
+
+    // only holds a weak reference between refreshes so that closing the window
+    // also ends the loop
+    async fn refresh_loop(this: glib::WeakRef<Self>) {
+        let Some(host) = this
+            .upgrade()
+            .and_then(|this| this.imp().host.get().cloned())
+        else {
+            return;
+        };
+
+        let cpu_info = host.cpu_info().await;
+        let hostname = host.hostname().await;
+
+        let Some(window) = this.upgrade() else {
+            return;
+        };
+        match cpu_info {
+            Ok(cpu_info) => window.imp().cpu.init_remote(cpu_info),
+            Err(error) => {
+                log::warn!("Unable to connect to {}: {error}", host.destination());
+                window.show_status(&i18n_f("Unable to connect to {}", &[host.destination()]));
+                return;
+            }
+        }
+        window.imp().memory.init_remote();
+        if let Ok(hostname) = hostname {
+            window.set_title(Some(&i18n_f("{} (Remote)", &[&hostname])));
+        }
+        let logical_cpus = window.imp().cpu.logical_cpus_amount();
+        drop(window);
+
+        loop {
+            let data = host.data(logical_cpus).await;
+
+            let Some(window) = this.upgrade() else {
+                return;
+            };
+            let imp = window.imp();
+            match data {
+                Ok(data) => {
+                    imp.cpu.refresh_page(data.cpu);
+                    imp.memory.refresh_page(data.memory);
+                    window.refresh_drives(data.drives);
+                    window.refresh_network_interfaces(data.network_interfaces);
+                    imp.status_banner.set_revealed(false);
+                }
+                Err(error) => {
+                    log::warn!("Unable to refresh {}: {error}", host.destination());
+                    window.show_status(&i18n("Connection lost, trying to reconnect…"));
+                }
+            }
+            drop(window);
+
+            timeout_future(Duration::from_secs_f32(
+                SETTINGS.refresh_speed().ui_refresh_interval(),
+            ))
+            .await;
+        }
+    }
+
+    /// Adds pages for drives that have appeared, removes the pages of drives
+    /// that are gone and refreshes the rest
+    fn refresh_drives(&self, drives: Vec<(Drive, DriveData)>) {
+        let imp = self.imp();
+        let mut pages = imp.drive_pages.borrow_mut();
+
+        pages.retain(|block_device, page| {
+            let still_there = drives
+                .iter()
+                .any(|(drive, _)| drive.block_device == *block_device);
+            if !still_there {
+                imp.stack.remove(&*page);
+            }
+            still_there
+        });
+
+        for (drive, data) in drives {
+            pages
+                .entry(drive.block_device.clone())
+                .or_insert_with(|| {
+                    let page = ResDrive::new();
+                    let title = drive
+                        .model
+                        .clone()
+                        .unwrap_or_else(|| drive.block_device.clone());
+                    let name = format!("drive-{}", drive.block_device);
+                    self.add_page(&page, &name, &title, &drive.icon());
+                    page.init_remote(drive);
+                    page
+                })
+                .refresh_page(data);
+        }
+    }
+
+    /// Adds pages for network interfaces that have appeared, removes the pages
+    /// of interfaces that are gone and refreshes the rest
+    fn refresh_network_interfaces(&self, interfaces: Vec<(NetworkInterface, NetworkData)>) {
+        let imp = self.imp();
+        let mut pages = imp.network_pages.borrow_mut();
+
+        pages.retain(|interface_name, page| {
+            let still_there = interfaces
+                .iter()
+                .any(|(interface, _)| interface.interface_name == *interface_name);
+            if !still_there {
+                imp.stack.remove(&*page);
+            }
+            still_there
+        });
+
+        for (interface, data) in interfaces {
+            match pages.entry(interface.interface_name.clone()) {
+                Entry::Occupied(entry) => entry.get().refresh_page(data),
+                Entry::Vacant(entry) => {
+                    // the first counters only serve as the baseline for the next
+                    // refresh
+                    let page = ResNetwork::new();
+                    let name = format!("network-{}", interface.interface_name.to_string_lossy());
+                    self.add_page(&page, &name, &interface.display_name(), &interface.icon());
+                    page.init_remote(interface, data.received_bytes, data.sent_bytes);
+                    entry.insert(page);
+                }
+            }
+        }
+    }
+
+    fn add_page(&self, page: &impl IsA<gtk::Widget>, name: &str, title: &str, icon: &gio::Icon) {
+        let icon_name = icon
+            .downcast_ref::<gio::ThemedIcon>()
+            .and_then(|icon| icon.names().first().cloned())
+            .unwrap_or_default();
+        self.imp()
+            .stack
+            .add_titled_with_icon(page, Some(name), title, &icon_name);
+    }
+}
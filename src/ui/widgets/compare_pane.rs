@@ -0,0 +1,214 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::glib::{self, clone};
+
+use crate::config::PROFILE;
+use crate::ui::widgets::graph::ResGraph;
+use crate::ui::widgets::graph_box::ResGraphBox;
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    use gtk::{glib::SignalHandlerId, CompositeTemplate};
+
+    #[derive(Debug, CompositeTemplate, Default)]
+    #[template(resource = "/net/nokyan/Resources/ui/widgets/compare_pane.ui")]
+    pub struct ResComparePane {
+        #[template_child]
+        pub page_dropdown: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub graphs_box: TemplateChild<gtk::Box>,
+
+        pub pages: RefCell<Vec<gtk::Widget>>,
+        pub compared_page: RefCell<Option<gtk::Widget>>,
+        /// The graph boxes of the compared page along with our handlers that
+        /// copy their data points
+        pub sources: RefCell<Vec<(glib::WeakRef<ResGraphBox>, SignalHandlerId)>>,
+        pub updating_pages: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResComparePane {
+        const NAME: &'static str = "ResComparePane";
+        type Type = super::ResComparePane;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        // You must call `Widget`'s `init_template()` within `instance_init()`.
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ResComparePane {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // Devel Profile
+            if PROFILE == "Devel" {
+                obj.add_css_class("devel");
+            }
+
+            self.page_dropdown
+                .connect_selected_notify(clone!(@weak obj => move |_| {
+                    if !obj.imp().updating_pages.get() {
+                        obj.show_selected_page();
+                    }
+                }));
+        }
+
+        fn dispose(&self) {
+            // the pages outlive this pane, so don't leave our handlers connected to them
+            self.obj().disconnect_sources();
+        }
+    }
+
+    impl WidgetImpl for ResComparePane {}
+
+    impl BinImpl for ResComparePane {}
+}
+
+glib::wrapper! {
+    pub struct ResComparePane(ObjectSubclass<imp::ResComparePane>)
+        @extends gtk::Widget, adw::Bin;
+}
+
+impl Default for ResComparePane {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResComparePane {
+    pub fn new() -> Self {
+        glib::Object::new::<Self>()
+    }
+
+    /// Sets the pages the user can choose from. The currently compared page stays
+    /// selected if it's still among `pages`.
+    pub fn set_pages(&self, pages: Vec<gtk::Widget>) {
+        let imp = self.imp();
+
+        let selected = imp
+            .compared_page
+            .borrow()
+            .as_ref()
+            .and_then(|current_page| pages.iter().position(|page| page == current_page))
+            .unwrap_or(0);
+
+        let names: Vec<String> = pages
+            .iter()
+            .map(|page| page.property::<String>("tab_name"))
+            .collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+
+        *imp.pages.borrow_mut() = pages;
+
+        // replacing the model resets the selection, so don't follow it until we're done
+        // or the graphs of the compared page would be thrown away
+        imp.updating_pages.set(true);
+        imp.page_dropdown
+            .set_model(Some(&gtk::StringList::new(&names)));
+        imp.page_dropdown.set_selected(selected as u32);
+        imp.updating_pages.set(false);
+        self.show_selected_page();
+    }
+
+    /// Shows every graph of the selected page that is currently shown on the page
+    /// itself. The graphs follow the page's graphs from now on, along with their
+    /// titles and subtitles.
+    fn show_selected_page(&self) {
+        let imp = self.imp();
+
+        let selected = imp.page_dropdown.selected() as usize;
+        let Some(page) = imp.pages.borrow().get(selected).cloned() else {
+            return;
+        };
+
+        if imp.compared_page.borrow().as_ref() == Some(&page) {
+            // still comparing the same page, keep its graphs
+            return;
+        }
+
+        self.disconnect_sources();
+        while let Some(child) = imp.graphs_box.first_child() {
+            imp.graphs_box.remove(&child);
+        }
+
+        let mut sources = Vec::new();
+        for graph_box in shown_graph_boxes(&page) {
+            let title_label = gtk::Label::builder()
+                .halign(gtk::Align::Start)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .build();
+
+            let info_label = gtk::Label::builder()
+                .halign(gtk::Align::Start)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .css_classes(vec!["dim-label"])
+                .build();
+
+            let graph = ResGraph::new();
+            graph.set_data_points_max_amount(60);
+            graph.set_height_request(72);
+            graph.set_hexpand(true);
+            let (r, g, b) = graph_box.graph_color();
+            graph.set_graph_color(r, g, b);
+            graph.set_locked_max_y(graph_box.locked_max_y());
+
+            let card = gtk::Box::new(gtk::Orientation::Vertical, 6);
+            card.set_margin_top(12);
+            card.set_margin_bottom(12);
+            card.set_margin_start(12);
+            card.set_margin_end(12);
+            card.append(&title_label);
+            card.append(&graph);
+            card.append(&info_label);
+
+            let frame = gtk::Frame::builder()
+                .child(&card)
+                .css_classes(vec!["card"])
+                .build();
+            imp.graphs_box.append(&frame);
+
+            graph_box.bind_labels(&title_label, &info_label);
+            let handler = graph_box.connect_data_point_pushed(
+                clone!(@weak graph => move |data_point| graph.push_data_point(data_point)),
+            );
+            sources.push((graph_box.downgrade(), handler));
+        }
+
+        *imp.sources.borrow_mut() = sources;
+        imp.compared_page.replace(Some(page));
+    }
+
+    fn disconnect_sources(&self) {
+        for (graph_box, handler) in self.imp().sources.take() {
+            if let Some(graph_box) = graph_box.upgrade() {
+                graph_box.disconnect(handler);
+            }
+        }
+    }
+}
+
+/// Returns the graph boxes below `widget` that are shown, in the order they
+/// appear on the page
+fn shown_graph_boxes(widget: &gtk::Widget) -> Vec<ResGraphBox> {
+    let mut graph_boxes = Vec::new();
+    let mut child = widget.first_child();
+    while let Some(current) = child {
+        if current.is_visible() {
+            match current.downcast_ref::<ResGraphBox>() {
+                Some(graph_box) => graph_boxes.push(graph_box.clone()),
+                None => graph_boxes.extend(shown_graph_boxes(&current)),
+            }
+        }
+        child = current.next_sibling();
+    }
+    graph_boxes
+}
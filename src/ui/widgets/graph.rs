@@ -3,15 +3,88 @@ use gtk::subclass::prelude::*;
 use gtk::traits::WidgetExt;
 use plotters::style::RGBColor;
 
+use std::cell::{Cell, RefCell};
 use std::f64;
+use std::time::Instant;
+
+/// How much of the previous, already smoothed data point carries over into the
+/// next one when graph smoothing is enabled
+const SMOOTHING_FACTOR: f64 = 0.25;
+
+/// The color of the vertical lines that mark noteworthy data points
+const MARKER_COLOR: RGBColor = RGBColor(230, 97, 0);
+
+/// How many data points every graph keeps around so that its history can be
+/// browsed, regardless of how many of them are shown at once
+pub const HISTORY_LENGTH: usize = 3600;
+
+thread_local! {
+    // `None` means that the graphs show the latest data points, `Some(x)` means
+    // that they show the data points up to the moment `x`
+    static HISTORY_MOMENT: Cell<Option<Instant>> = Cell::new(None);
+
+    static GRAPHS: RefCell<Vec<glib::WeakRef<ResGraph>>> = RefCell::new(Vec::new());
+}
+
+/// Moves all graphs back to `moment`, or back to the live view if `moment` is
+/// `None`. Since every data point is stored along with the moment it has been
+/// pushed, all graphs show the same moment regardless of how often they are
+/// refreshed. The graphs keep recording new data points meanwhile.
+pub fn set_history_moment(moment: Option<Instant>) {
+    HISTORY_MOMENT.with(|history_moment| history_moment.set(moment));
+    redraw_all();
+}
+
+fn history_moment() -> Option<Instant> {
+    HISTORY_MOMENT.with(Cell::get)
+}
+
+/// Returns when the oldest data point that any graph still keeps has been
+/// pushed, i.e. how far back the history can be browsed
+pub fn oldest_data_point_moment() -> Option<Instant> {
+    let mut oldest = None;
+    for_each_graph(|graph| {
+        let graph_oldest = graph.imp().timestamps.borrow().front().copied();
+        oldest = match (oldest, graph_oldest) {
+            (Some(oldest), Some(graph_oldest)) => Some(Instant::min(oldest, graph_oldest)),
+            (oldest, graph_oldest) => oldest.or(graph_oldest),
+        };
+    });
+    oldest
+}
+
+/// Redraws all graphs, e.g. because a setting that affects how they are drawn
+/// has changed
+pub fn redraw_all() {
+    for_each_graph(|graph| graph.queue_draw());
+}
+
+fn for_each_graph<F: FnMut(&ResGraph)>(mut f: F) {
+    GRAPHS.with(|graphs| {
+        graphs.borrow_mut().retain(|graph| {
+            if let Some(graph) = graph.upgrade() {
+                f(&graph);
+                true
+            } else {
+                false
+            }
+        });
+    });
+}
 
 mod imp {
-    use std::{cell::RefCell, collections::VecDeque, error::Error};
+    use std::{
+        cell::{Cell, RefCell},
+        collections::VecDeque,
+        error::Error,
+        time::Instant,
+    };
 
     use gtk::{
         glib,
+        prelude::ObjectExt,
         subclass::{
-            prelude::{ObjectImpl, ObjectSubclass, ObjectSubclassExt},
+            prelude::{ObjectImpl, ObjectImplExt, ObjectSubclass, ObjectSubclassExt},
             widget::WidgetImpl,
         },
         traits::{SnapshotExt, WidgetExt},
@@ -23,12 +96,22 @@ mod imp {
     };
     use plotters_cairo::CairoBackend;
 
+    use crate::utils::settings::SETTINGS;
+
     #[derive(Debug)]
     pub struct ResGraph {
         pub data_points: RefCell<VecDeque<f64>>,
         pub data_points_max_amount: RefCell<usize>,
         pub max_y: RefCell<Option<f64>>,
         pub graph_color: RefCell<RGBColor>,
+        // when each of `data_points` has been pushed
+        pub timestamps: RefCell<VecDeque<Instant>>,
+        // how many data points have been pushed in total, used to keep markers in
+        // place even though old data points are dropped
+        pub total_data_points: Cell<usize>,
+        // data points that are highlighted with a vertical line, counted like
+        // `total_data_points`
+        pub markers: RefCell<VecDeque<usize>>,
     }
 
     impl Default for ResGraph {
@@ -38,6 +121,9 @@ mod imp {
                 data_points_max_amount: RefCell::default(),
                 max_y: RefCell::new(Some(1.0)),
                 graph_color: RefCell::default(),
+                timestamps: RefCell::default(),
+                total_data_points: Cell::default(),
+                markers: RefCell::default(),
             }
         }
     }
@@ -49,7 +135,13 @@ mod imp {
         type ParentType = gtk::Widget;
     }
 
-    impl ObjectImpl for ResGraph {}
+    impl ObjectImpl for ResGraph {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+            super::GRAPHS.with(|graphs| graphs.borrow_mut().push(obj.downgrade()));
+        }
+    }
 
     impl WidgetImpl for ResGraph {
         fn snapshot(&self, snapshot: &gtk::Snapshot) {
@@ -67,6 +159,42 @@ mod imp {
     }
 
     impl ResGraph {
+        /// Returns the range of data points that is currently supposed to be
+        /// shown, taking history browsing into account. The indices are counted
+        /// like `total_data_points`.
+        fn visible_range(&self, amount: usize) -> std::ops::Range<usize> {
+            let total = self.total_data_points.get();
+
+            let end = match super::history_moment() {
+                Some(moment) => {
+                    // everything up to the last data point pushed before `moment`
+                    let timestamps = self.timestamps.borrow();
+                    let first = total - timestamps.len();
+                    first + timestamps.partition_point(|timestamp| *timestamp <= moment)
+                }
+                None => total,
+            };
+
+            end.saturating_sub(amount)..end
+        }
+
+        /// Returns the data points that are currently supposed to be shown, taking
+        /// history browsing into account
+        pub fn visible_data_points(&self, data_points: &VecDeque<f64>, amount: usize) -> Vec<f64> {
+            let total = self.total_data_points.get();
+            let visible_range = self.visible_range(amount);
+
+            // the index of the oldest data point we still have, counted like `total`
+            let first = total - data_points.len();
+
+            data_points
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| visible_range.contains(&(first + i)))
+                .map(|(_, data_point)| *data_point)
+                .collect()
+        }
+
         pub fn plot_graph<'a, DB>(&self, backend: DB) -> Result<(), Box<dyn Error + 'a>>
         where
             DB: DrawingBackend + 'a,
@@ -83,9 +211,16 @@ mod imp {
             // (because the program hasn't been running long enough e.g.),
             // fill it from the front with zeros until we have just enough
             // "space" for the actual data points
-            let mut filled_data_points = vec![0.0; *data_points_max_amount - data_points.len()];
-            for i in data_points.iter() {
-                filled_data_points.push(*i);
+            let visible_data_points =
+                self.visible_data_points(&data_points, *data_points_max_amount);
+            let mut filled_data_points =
+                vec![0.0; *data_points_max_amount - visible_data_points.len()];
+            if SETTINGS.smooth_graphs() {
+                // smooth at drawing time so that the stored data points stay exact and
+                // toggling the setting applies to the whole graph
+                filled_data_points.extend(super::smooth(visible_data_points));
+            } else {
+                filled_data_points.extend(visible_data_points);
             }
 
             let y_max = self.max_y.borrow().unwrap_or_else(|| {
@@ -109,6 +244,21 @@ mod imp {
                 .border_style(*color),
             )?;
 
+            // markers are placed relative to the right edge since the graph is
+            // filled with zeros from the front
+            let visible_range = self.visible_range(*data_points_max_amount);
+            let padding = *data_points_max_amount - visible_range.len();
+            for marker in self.markers.borrow().iter() {
+                if !visible_range.contains(marker) {
+                    continue;
+                }
+                let x = (padding + marker - visible_range.start) as f64;
+                chart.draw_series(LineSeries::new(
+                    [(x, 0.0), (x, y_max)],
+                    super::MARKER_COLOR.stroke_width(2),
+                ))?;
+            }
+
             root.present()?;
             Ok(())
         }
@@ -136,28 +286,83 @@ impl ResGraph {
         imp.obj().queue_draw();
     }
 
+    pub fn graph_color(&self) -> (u8, u8, u8) {
+        let RGBColor(r, g, b) = *self.imp().graph_color.borrow();
+        (r, g, b)
+    }
+
+    pub fn locked_max_y(&self) -> Option<f64> {
+        *self.imp().max_y.borrow()
+    }
+
     pub fn set_locked_max_y(&self, y_max: Option<f64>) {
         let imp = self.imp();
         *imp.max_y.borrow_mut() = y_max;
         imp.obj().queue_draw();
     }
 
+    pub fn latest_data_point(&self) -> Option<f64> {
+        self.imp().data_points.borrow().back().copied()
+    }
+
+    /// Returns the highest of the data points that are currently shown, which
+    /// are older ones while the history is being browsed
     pub fn get_highest_value(&self) -> f64 {
         let imp = self.imp();
-        *imp.data_points
-            .borrow()
-            .iter()
-            .max_by(|x, y| x.total_cmp(y))
-            .unwrap_or(&0.0)
+        imp.visible_data_points(
+            &imp.data_points.borrow(),
+            *imp.data_points_max_amount.borrow(),
+        )
+        .into_iter()
+        .max_by(|x, y| x.total_cmp(y))
+        .unwrap_or(0.0)
     }
 
     pub fn push_data_point(&self, data: f64) {
         let imp = self.imp();
         let mut data_points = imp.data_points.borrow_mut();
-        if data_points.len() >= *imp.data_points_max_amount.borrow() {
+        let mut timestamps = imp.timestamps.borrow_mut();
+        if data_points.len() >= HISTORY_LENGTH.max(*imp.data_points_max_amount.borrow()) {
             data_points.pop_front();
+            timestamps.pop_front();
         }
         data_points.push_back(data);
+        timestamps.push_back(Instant::now());
+        imp.total_data_points.set(imp.total_data_points.get() + 1);
+
+        // forget markers whose data points are gone
+        let first = imp.total_data_points.get() - data_points.len();
+        let mut markers = imp.markers.borrow_mut();
+        while markers.front().map_or(false, |marker| *marker < first) {
+            markers.pop_front();
+        }
+
+        imp.obj().queue_draw();
+    }
+
+    /// Highlights the most recently pushed data point with a vertical line,
+    /// e.g. because something noteworthy happened at that moment
+    pub fn mark_latest_data_point(&self) {
+        let imp = self.imp();
+        let Some(latest) = imp.total_data_points.get().checked_sub(1) else {
+            return;
+        };
+        let mut markers = imp.markers.borrow_mut();
+        if markers.back() != Some(&latest) {
+            markers.push_back(latest);
+        }
         imp.obj().queue_draw();
     }
 }
+
+/// Applies exponential smoothing to `data_points`
+fn smooth<I: IntoIterator<Item = f64>>(data_points: I) -> Vec<f64> {
+    let mut smoothed: Vec<f64> = Vec::new();
+    for data_point in data_points {
+        let value = smoothed.last().map_or(data_point, |previous| {
+            (1.0 - SMOOTHING_FACTOR) * data_point + SMOOTHING_FACTOR * previous
+        });
+        smoothed.push(value);
+    }
+    smoothed
+}
@@ -1,11 +1,45 @@
+use std::cell::RefCell;
+
 use adw::{prelude::*, subclass::prelude::*};
 use gtk::glib;
+use hashbrown::HashMap;
 
 use crate::config::PROFILE;
 
+thread_local! {
+    // graph boxes that can be pinned to the dashboard, by their dashboard key
+    static PINNABLE: RefCell<HashMap<String, glib::WeakRef<ResGraphBox>>> = RefCell::new(HashMap::new());
+}
+
+/// Returns the graph box that has been given `dashboard_key`, if it (still)
+/// exists
+pub fn pinnable_graph_box(dashboard_key: &str) -> Option<ResGraphBox> {
+    PINNABLE.with(|pinnable| {
+        pinnable
+            .borrow()
+            .get(dashboard_key)
+            .and_then(glib::WeakRef::upgrade)
+    })
+}
+
+/// Returns the dashboard keys of all graph boxes that currently exist
+pub fn pinnable_keys() -> Vec<String> {
+    PINNABLE.with(|pinnable| {
+        pinnable
+            .borrow()
+            .iter()
+            .filter(|(_, graph_box)| graph_box.upgrade().is_some())
+            .map(|(key, _)| key.clone())
+            .collect()
+    })
+}
+
 mod imp {
     use crate::ui::widgets::graph::ResGraph;
 
+    use gtk::glib::subclass::Signal;
+    use once_cell::sync::Lazy;
+
     use super::*;
 
     use gtk::CompositeTemplate;
@@ -19,6 +53,8 @@ mod imp {
         pub title_label: TemplateChild<gtk::Label>,
         #[template_child]
         pub info_label: TemplateChild<gtk::Label>,
+
+        pub dashboard_key: RefCell<Option<String>>,
     }
 
     #[glib::object_subclass]
@@ -47,6 +83,15 @@ mod imp {
                 obj.add_css_class("devel");
             }
         }
+
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![Signal::builder("data-point-pushed")
+                    .param_types([f64::static_type()])
+                    .build()]
+            });
+            SIGNALS.as_ref()
+        }
     }
 
     impl WidgetImpl for ResGraphBox {}
@@ -84,6 +129,68 @@ impl ResGraphBox {
     pub fn push_data_point(&self, data: f64) {
         let imp = self.imp();
         imp.graph.push_data_point(data);
+        self.emit_by_name::<()>("data-point-pushed", &[&data]);
+    }
+
+    /// Highlights the most recently pushed data point in the graph
+    pub fn mark_latest_data_point(&self) {
+        self.imp().graph.mark_latest_data_point();
+    }
+
+    pub fn connect_data_point_pushed<F: Fn(f64) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_local("data-point-pushed", false, move |values| {
+            f(values[1].get::<f64>().unwrap_or_default());
+            None
+        })
+    }
+
+    /// Makes this graph box available to be pinned to the dashboard under
+    /// `dashboard_key`. The key is stored in the settings, so it has to stay the
+    /// same across sessions.
+    pub fn set_dashboard_key(&self, dashboard_key: &str) {
+        let imp = self.imp();
+        *imp.dashboard_key.borrow_mut() = Some(dashboard_key.to_string());
+        PINNABLE.with(|pinnable| {
+            pinnable
+                .borrow_mut()
+                .insert(dashboard_key.to_string(), self.downgrade());
+        });
+    }
+
+    pub fn dashboard_key(&self) -> Option<String> {
+        self.imp().dashboard_key.borrow().clone()
+    }
+
+    /// Keeps the labels of a copy of this graph box (e.g. a dashboard tile) in
+    /// sync with the labels of this graph box
+    pub fn bind_labels(&self, title_label: &gtk::Label, info_label: &gtk::Label) {
+        let imp = self.imp();
+        imp.title_label
+            .bind_property("label", title_label, "label")
+            .sync_create()
+            .build();
+        imp.info_label
+            .bind_property("label", info_label, "label")
+            .sync_create()
+            .build();
+    }
+
+    pub fn graph_color(&self) -> (u8, u8, u8) {
+        self.imp().graph.graph_color()
+    }
+
+    pub fn locked_max_y(&self) -> Option<f64> {
+        self.imp().graph.locked_max_y()
+    }
+
+    /// Whether the values of this graph are fractions between 0.0 and 1.0 rather
+    /// than absolute values like bytes per second
+    pub fn shows_fractions(&self) -> bool {
+        self.locked_max_y() == Some(1.0)
+    }
+
+    pub fn latest_data_point(&self) -> Option<f64> {
+        self.imp().graph.latest_data_point()
     }
 
     pub fn set_title_label(&self, str: &str) {
@@ -115,4 +222,18 @@ impl ResGraphBox {
         let imp = self.imp();
         imp.graph.get_highest_value()
     }
+
+    /// Returns the labels of this box as a single line of text, e.g. for
+    /// copying them to the clipboard
+    pub fn copy_text(&self) -> Option<String> {
+        let imp = self.imp();
+        let text = [imp.title_label.label(), imp.info_label.label()]
+            .iter()
+            .map(|label| label.trim())
+            .filter(|label| !label.is_empty())
+            .collect::<Vec<_>>()
+            .join(" · ");
+
+        (!text.is_empty()).then_some(text)
+    }
 }
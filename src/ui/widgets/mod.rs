@@ -1,3 +1,4 @@
+pub mod compare_pane;
 pub mod graph;
 pub mod graph_box;
 pub mod stack_sidebar;
@@ -142,6 +142,11 @@ impl ResStackSidebar {
                 .can_target(true)
                 .build();
 
+            // some pages are only shown if there's something to show on them
+            page.bind_property("visible", &row, "visible")
+                .sync_create()
+                .build();
+
             imp.list_box.append(&row);
 
             if let Some(visible_page) = imp.stack.borrow().visible_child() {
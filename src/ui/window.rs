@@ -1,6 +1,6 @@
 use hashbrown::HashMap;
 use std::path::PathBuf;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use adw::{prelude::*, subclass::prelude::*};
 use adw::{Toast, ToastOverlay};
@@ -12,34 +12,75 @@ use crate::application::Application;
 use crate::config::PROFILE;
 use crate::i18n::{i18n, i18n_f, ni18n_f};
 use crate::ui::pages::drive::ResDrive;
+use crate::utils::alerts::{self, AlertAction, AlertEngine, AlertRule};
 use crate::utils::app::AppsContext;
-use crate::utils::cpu;
-use crate::utils::drive::{Drive, DriveType};
-use crate::utils::gpu::GPU;
-use crate::utils::network::{InterfaceType, NetworkInterface};
-use crate::utils::process::ProcessAction;
+use crate::utils::battery;
+use crate::utils::cpu::{self, CpuData};
+use crate::utils::demo;
+use crate::utils::drive::{Drive, DriveData, DriveType};
+use crate::utils::gamemode::GameModeStatus;
+use crate::utils::gpu::{GpuData, GPU};
+use crate::utils::gpu_resets;
+use crate::utils::interrupts::InterruptData;
+use crate::utils::logind::Session;
+use crate::utils::memory::MemoryData;
+use crate::utils::modules::KernelModule;
+use crate::utils::mqtt::{self, MqttPublisher};
+use crate::utils::network::{InterfaceType, NetworkData, NetworkInterface};
+use crate::utils::oom::{self, OomKiller, OomdStatus};
+use crate::utils::process::{Process, ProcessAction};
+use crate::utils::sensors::{self, Sensor};
 use crate::utils::settings::SETTINGS;
+use crate::utils::systemd::SystemdUnit;
 use crate::utils::units::convert_storage;
 
+use super::mini_window::ResMiniWindow;
 use super::pages::gpu::ResGPU;
 use super::pages::network::ResNetwork;
+use super::widgets::graph;
+use super::widgets::graph_box::{pinnable_graph_box, pinnable_keys, ResGraphBox};
+
+// UDisks only polls SMART data every 10 minutes by itself
+const SMART_REFRESH_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+// resets are rare and reading the kernel log isn't free, so there's no need to
+// look for them as often as for everything else
+const GPU_RESETS_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+// the same goes for processes killed because the system ran out of memory
+const OOM_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+// how long each page is shown in kiosk mode if nothing has been pinned to the
+// dashboard
+const KIOSK_CYCLE_INTERVAL: Duration = Duration::from_secs(15);
 
 #[derive(Debug, Clone)]
 pub enum Action {
     ManipulateProcess(ProcessAction, i32, String, ToastOverlay),
     ManipulateApp(ProcessAction, String, ToastOverlay),
+    /// Switches to the processes page, only showing the processes of the app
+    /// with the given ID (or the system processes if it's `None`) and display
+    /// name
+    ShowAppProcesses(Option<String>, String),
+    /// Switches to the applications page and selects the app with the given ID
+    ShowApplication(String),
+    /// Switches to the processes page and selects the process with the given
+    /// PID
+    ShowProcess(i32),
 }
 
 mod imp {
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
 
     use crate::{
         ui::{
             pages::{
-                applications::ResApplications, cpu::ResCPU, memory::ResMemory,
-                processes::ResProcesses,
+                applications::ResApplications, cpu::ResCPU, dashboard::ResDashboard,
+                interrupts::ResInterrupts, memory::ResMemory, modules::ResModules, pods::ResPods,
+                processes::ResProcesses, sensors::ResSensors, services::ResServices,
+                sessions::ResSessions, sysctl::ResSysctl, users::ResUsers,
             },
-            widgets::stack_sidebar::ResStackSidebar,
+            widgets::{compare_pane::ResComparePane, stack_sidebar::ResStackSidebar},
         },
         utils::app::AppsContext,
     };
@@ -75,9 +116,53 @@ mod imp {
         #[template_child]
         pub processes_page: TemplateChild<gtk::StackPage>,
         #[template_child]
+        pub users: TemplateChild<ResUsers>,
+        #[template_child]
+        pub users_page: TemplateChild<gtk::StackPage>,
+        #[template_child]
+        pub pods: TemplateChild<ResPods>,
+        #[template_child]
+        pub pods_page: TemplateChild<gtk::StackPage>,
+        #[template_child]
+        pub sessions: TemplateChild<ResSessions>,
+        #[template_child]
+        pub sessions_page: TemplateChild<gtk::StackPage>,
+        #[template_child]
+        pub services: TemplateChild<ResServices>,
+        #[template_child]
+        pub services_page: TemplateChild<gtk::StackPage>,
+        #[template_child]
+        pub dashboard: TemplateChild<ResDashboard>,
+        #[template_child]
+        pub dashboard_page: TemplateChild<gtk::StackPage>,
+        #[template_child]
+        pub interrupts: TemplateChild<ResInterrupts>,
+        #[template_child]
+        pub interrupts_page: TemplateChild<gtk::StackPage>,
+        #[template_child]
+        pub modules: TemplateChild<ResModules>,
+        #[template_child]
+        pub modules_page: TemplateChild<gtk::StackPage>,
+        #[template_child]
+        pub sysctl: TemplateChild<ResSysctl>,
+        #[template_child]
+        pub sysctl_page: TemplateChild<gtk::StackPage>,
+        #[template_child]
         pub memory: TemplateChild<ResMemory>,
         #[template_child]
         pub memory_page: TemplateChild<gtk::StackPage>,
+        #[template_child]
+        pub sensors: TemplateChild<ResSensors>,
+        #[template_child]
+        pub sensors_page: TemplateChild<gtk::StackPage>,
+        #[template_child]
+        pub history_revealer: TemplateChild<gtk::Revealer>,
+        #[template_child]
+        pub history_scale: TemplateChild<gtk::Scale>,
+        #[template_child]
+        pub history_adjustment: TemplateChild<gtk::Adjustment>,
+        #[template_child]
+        pub live_button: TemplateChild<gtk::Button>,
 
         pub drive_pages: RefCell<HashMap<PathBuf, adw::ToolbarView>>,
 
@@ -85,6 +170,27 @@ mod imp {
 
         pub gpu_pages: RefCell<Vec<adw::ToolbarView>>,
 
+        pub mini_window: RefCell<Option<ResMiniWindow>>,
+
+        pub kiosk_mode: Cell<bool>,
+        /// The page that was visible and whether the sidebar was shown before
+        /// entering kiosk mode
+        pub kiosk_previous: RefCell<Option<(Option<Widget>, bool)>>,
+        pub kiosk_cycle: RefCell<Option<glib::SourceId>>,
+        pub kiosk_inhibit_cookie: Cell<u32>,
+
+        pub alert_engine: RefCell<AlertEngine>,
+
+        /// Not part of the template because it's put next to the pages in code
+        pub compare_pane: ResComparePane,
+
+        /// When browsing the history began, the history scrubber counts back
+        /// from there
+        pub history_start: Cell<Option<Instant>>,
+
+        /// Refresh loops that are waiting for the window to be shown again
+        pub refresh_waiters: RefCell<Vec<async_std::channel::Sender<()>>>,
+
         pub apps_context: RefCell<AppsContext>,
 
         pub sender: Sender<Action>,
@@ -108,13 +214,44 @@ mod imp {
                 processes_page: TemplateChild::default(),
                 cpu: TemplateChild::default(),
                 cpu_page: TemplateChild::default(),
+                users: TemplateChild::default(),
+                users_page: TemplateChild::default(),
+                pods: TemplateChild::default(),
+                pods_page: TemplateChild::default(),
+                sessions: TemplateChild::default(),
+                sessions_page: TemplateChild::default(),
+                services: TemplateChild::default(),
+                services_page: TemplateChild::default(),
+                dashboard: TemplateChild::default(),
+                dashboard_page: TemplateChild::default(),
+                interrupts: TemplateChild::default(),
+                interrupts_page: TemplateChild::default(),
+                modules: TemplateChild::default(),
+                modules_page: TemplateChild::default(),
+                sysctl: TemplateChild::default(),
+                sysctl_page: TemplateChild::default(),
                 memory: TemplateChild::default(),
                 memory_page: TemplateChild::default(),
+                sensors: TemplateChild::default(),
+                sensors_page: TemplateChild::default(),
+                history_revealer: TemplateChild::default(),
+                history_scale: TemplateChild::default(),
+                history_adjustment: TemplateChild::default(),
+                live_button: TemplateChild::default(),
                 apps_context: RefCell::default(),
                 sender,
                 receiver,
                 processor_window_title: TemplateChild::default(),
                 gpu_pages: RefCell::default(),
+                mini_window: RefCell::default(),
+                kiosk_mode: Cell::default(),
+                kiosk_previous: RefCell::default(),
+                kiosk_cycle: RefCell::default(),
+                kiosk_inhibit_cookie: Cell::default(),
+                alert_engine: RefCell::default(),
+                compare_pane: ResComparePane::default(),
+                history_start: Cell::default(),
+                refresh_waiters: RefCell::default(),
             }
         }
     }
@@ -197,10 +334,71 @@ impl MainWindow {
 
         imp.resources_sidebar.set_stack(&imp.content_stack);
 
+        // the compare pane sits next to whatever page is shown, so it shares the
+        // content of the split view with the pages
+        let content_stack = imp.content_stack.get();
+        imp.split_view.set_content(None::<&gtk::Widget>);
+        let compare_paned = gtk::Paned::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .start_child(&content_stack)
+            .end_child(&imp.compare_pane)
+            .resize_end_child(false)
+            .shrink_end_child(false)
+            .build();
+        imp.compare_pane.set_visible(false);
+        imp.split_view.set_content(Some(&compare_paned));
+
+        // leaving fullscreen by other means, e.g. through the window manager, also
+        // ends kiosk mode
+        self.connect_fullscreened_notify(|window| {
+            if !window.is_fullscreened() && window.is_kiosk_mode() {
+                window.set_kiosk_mode(false);
+            }
+        });
+
+        // wake up the refresh loops as soon as somebody can see the data again
+        self.connect_suspended_notify(|window| window.resume_refreshes());
+        self.connect_visible_notify(|window| window.resume_refreshes());
+        SETTINGS.connect_run_in_background(
+            clone!(@weak self as this => move |_| this.resume_refreshes()),
+        );
+        SETTINGS
+            .connect_alert_rules(clone!(@weak self as this => move |_| this.resume_refreshes()));
+        SETTINGS
+            .connect_mqtt_enabled(clone!(@weak self as this => move |_| this.resume_refreshes()));
+
+        // smoothing is applied while drawing, so the graphs only need a redraw
+        SETTINGS.connect_smooth_graphs(|_| graph::redraw_all());
+
         imp.applications.init(imp.sender.clone());
         imp.processes.init(imp.sender.clone());
-        imp.cpu.init();
+        imp.users.init();
+        imp.pods.init();
+        imp.sessions.init();
+        imp.services.init();
+        imp.cpu.init(imp.sender.clone());
         imp.memory.init();
+        imp.sensors.init();
+        imp.interrupts.init();
+        imp.modules.init();
+        imp.sysctl.init();
+        imp.dashboard.init();
+
+        // these pages have no synthetic counterpart and would show the actual system
+        if demo::is_enabled() {
+            for page in [
+                &imp.sessions_page,
+                &imp.services_page,
+                &imp.interrupts_page,
+                &imp.modules_page,
+                &imp.sysctl_page,
+                &imp.sensors_page,
+            ] {
+                page.set_visible(false);
+            }
+        }
+
+        self.setup_history();
 
         let main_context = MainContext::default();
         main_context.spawn_local(clone!(@strong self as this => async move {
@@ -219,7 +417,11 @@ impl MainWindow {
                 imp.processor_window_title.set_subtitle(&i18n("Processor"));
             }
 
-            let gpus = GPU::get_gpus().await.unwrap_or_default();
+            let gpus = if demo::is_enabled() {
+                vec![demo::gpu()]
+            } else {
+                GPU::get_gpus().await.unwrap_or_default()
+            };
             for (i, gpu) in gpus.iter().enumerate() {
                 let page = ResGPU::new();
                 page.init(gpu.clone(), i);
@@ -247,64 +449,791 @@ impl MainWindow {
             futures_util::join!(
             async {
                 loop {
-                    imp.apps_context.borrow_mut().refresh().await;
+                    // counting open files is expensive, so only do it if they are shown
+                    let with_open_files = SETTINGS.processes_show_open_files();
+                    let (process_data, discharge_rate) = async_std::task::spawn(async move {
+                        let process_data = if demo::is_enabled() {
+                            demo::process_data()
+                        } else {
+                            Process::all_data(with_open_files).await.unwrap_or_default()
+                        };
+                        let discharge_rate = if demo::is_enabled() {
+                            None
+                        } else {
+                            battery::discharge_rate()
+                        };
+                        (process_data, discharge_rate)
+                    }).await;
+                    imp.apps_context.borrow_mut().refresh(process_data);
+                    imp.applications.set_discharge_rate(discharge_rate);
                     imp.applications.refresh_apps_list(&imp.apps_context.borrow());
                     imp.processes.refresh_processes_list(&imp.apps_context.borrow());
-                    timeout_future(Duration::from_secs_f32(SETTINGS.refresh_speed().process_refresh_interval())).await;
+                    imp.cpu.refresh_top_processes(&imp.apps_context.borrow());
+                    imp.users.refresh_users_list(&imp.apps_context.borrow());
+                    // only Kubernetes nodes have pods, so don't bother everyone else with the page
+                    imp.pods_page.set_visible(imp.pods.refresh_pods_list(&imp.apps_context.borrow()));
+                    this.wait_for_next_refresh(SETTINGS.refresh_speed().process_refresh_interval()).await;
                 }
             },
             async {
                 loop {
-                    imp.cpu.refresh_page().await;
-                    timeout_future(Duration::from_secs_f32(SETTINGS.refresh_speed().ui_refresh_interval())).await;
+                    let logical_cpus = imp.cpu.logical_cpus_amount();
+                    let cpu_data = if demo::is_enabled() {
+                        demo::cpu_data(logical_cpus)
+                    } else {
+                        async_std::task::spawn(CpuData::new(logical_cpus)).await
+                    };
+                    imp.cpu.refresh_page(cpu_data);
+                    this.wait_for_next_sample(SETTINGS.refresh_speed().ui_refresh_interval()).await;
+                }
+            },
+            async {
+                if demo::is_enabled() {
+                    return;
+                }
+                loop {
+                    // GIO's D-Bus calls are asynchronous already, so this doesn't need a worker task
+                    let gamemode_status = GameModeStatus::current().await.ok().flatten();
+                    imp.cpu.refresh_gamemode(gamemode_status);
+                    this.wait_for_next_refresh(SETTINGS.refresh_speed().process_refresh_interval()).await;
+                }
+            },
+            async {
+                if demo::is_enabled() {
+                    return;
+                }
+                loop {
+                    // GIO's D-Bus calls are asynchronous already, so this doesn't need a worker task
+                    let sessions = Session::all().await.unwrap_or_default();
+                    imp.sessions.refresh_sessions(sessions, &imp.apps_context.borrow());
+                    this.wait_for_next_refresh(SETTINGS.refresh_speed().process_refresh_interval()).await;
                 }
             },
             async {
+                if demo::is_enabled() {
+                    return;
+                }
                 loop {
-                    imp.memory.refresh_page().await;
+                    // GIO's D-Bus calls are asynchronous already, so this doesn't need a worker task
+                    for user in [false, true] {
+                        match SystemdUnit::services(user).await {
+                            Ok(services) => imp.services.refresh_services(user, services),
+                            Err(error) => log::debug!("Unable to list services: {error}"),
+                        }
+                    }
+                    this.wait_for_next_refresh(SETTINGS.refresh_speed().process_refresh_interval()).await;
+                }
+            },
+            async {
+                loop {
+                    imp.dashboard.attach_tiles();
+                    this.wait_for_next_refresh(SETTINGS.refresh_speed().ui_refresh_interval()).await;
+                }
+            },
+            async {
+                // keeps checking while suspended, a minimized window is when alerts matter most
+                loop {
+                    this.check_alerts();
                     timeout_future(Duration::from_secs_f32(SETTINGS.refresh_speed().ui_refresh_interval())).await;
                 }
             },
             async {
+                if demo::is_enabled() {
+                    return;
+                }
+                loop {
+                    let interrupt_data = async_std::task::spawn_blocking(InterruptData::new).await;
+                    imp.interrupts.refresh_page(interrupt_data);
+                    this.wait_for_next_refresh(SETTINGS.refresh_speed().ui_refresh_interval()).await;
+                }
+            },
+            async {
+                if demo::is_enabled() {
+                    return;
+                }
+                loop {
+                    let modules = async_std::task::spawn_blocking(KernelModule::all).await.unwrap_or_default();
+                    imp.modules.refresh_page(modules);
+                    this.wait_for_next_refresh(SETTINGS.refresh_speed().process_refresh_interval()).await;
+                }
+            },
+            async {
+                loop {
+                    let memory_data = if demo::is_enabled() {
+                        demo::memory_data()
+                    } else {
+                        async_std::task::spawn_blocking(MemoryData::new).await
+                    };
+                    imp.memory.refresh_page(memory_data);
+                    this.wait_for_next_sample(SETTINGS.refresh_speed().ui_refresh_interval()).await;
+                }
+            },
+            async {
+                // older versions kept the password in the broker URL
+                if let Ok((broker, Some(password))) = mqtt::split_password(&SETTINGS.mqtt_broker()) {
+                    match mqtt::set_broker_password(&broker, &password).await {
+                        Ok(()) => {
+                            let _ = SETTINGS.set_mqtt_broker(&broker);
+                        }
+                        Err(error) => {
+                            log::warn!("Unable to move the MQTT broker's password into the keyring: {error}");
+                        }
+                    }
+                }
+
+                let mut publisher = MqttPublisher::default();
                 loop {
-                    for gpu_page_toolbar in imp.gpu_pages.borrow().iter() {
-                        gpu_page_toolbar.content().and_downcast::<ResGPU>().unwrap().refresh_page().await;
+                    if SETTINGS.mqtt_enabled() && !SETTINGS.mqtt_broker().is_empty() {
+                        let messages = this.mqtt_messages();
+                        if let Err(error) = publisher.publish(&SETTINGS.mqtt_broker(), &messages).await {
+                            log::warn!("Unable to publish metrics via MQTT: {error}");
+                        }
+                    } else {
+                        publisher.disconnect();
+                    }
+                    // brokers are fed while the window is hidden as well
+                    timeout_future(mqtt::PUBLISH_INTERVAL).await;
+                }
+            },
+            async {
+                if demo::is_enabled() {
+                    return;
+                }
+                let all_sensors = async_std::task::spawn_blocking(Sensor::all).await;
+                // virtual machines usually have no hardware sensors at all
+                imp.sensors_page.set_visible(!all_sensors.is_empty());
+                imp.sensors.set_sensors(all_sensors.clone());
+                loop {
+                    let all_sensors = all_sensors.clone();
+                    let values = async_std::task::spawn_blocking(move || sensors::read_all(&all_sensors)).await;
+                    imp.sensors.refresh_values(&values);
+                    this.wait_for_next_sample(SETTINGS.refresh_speed().ui_refresh_interval()).await;
+                }
+            },
+            async {
+                loop {
+                    let gpu_pages: Vec<ResGPU> = imp.gpu_pages.borrow().iter().map(|toolbar| toolbar.content().and_downcast::<ResGPU>().unwrap()).collect();
+                    for gpu_page in gpu_pages {
+                        let gpu = gpu_page.gpu();
+                        let gpu_data = if demo::is_enabled() {
+                            demo::gpu_data()
+                        } else {
+                            async_std::task::spawn(async move { GpuData::new(&gpu).await }).await
+                        };
+                        gpu_page.refresh_page(gpu_data);
+                    }
+                    this.wait_for_next_sample(SETTINGS.refresh_speed().ui_refresh_interval()).await;
+                }
+            },
+            async {
+                // keeps looking while suspended, the notification is meant for when nobody's watching
+                let mut old_throttle_counts: Option<Vec<Option<u64>>> = None;
+                let mut was_throttling = false;
+                loop {
+                    if SETTINGS.notify_thermal_throttling() && !demo::is_enabled() {
+                        let logical_cpus = imp.cpu.logical_cpus_amount();
+                        let throttle_counts = async_std::task::spawn_blocking(move || cpu::get_throttle_counts(logical_cpus)).await;
+                        let is_throttling = old_throttle_counts.as_ref().map_or(false, |old_throttle_counts| cpu::is_throttling(old_throttle_counts, &throttle_counts));
+                        if is_throttling && !was_throttling {
+                            this.notify_thermal_throttling();
+                        }
+                        was_throttling = is_throttling;
+                        old_throttle_counts = Some(throttle_counts);
+                    } else {
+                        old_throttle_counts = None;
+                        was_throttling = false;
                     }
                     timeout_future(Duration::from_secs_f32(SETTINGS.refresh_speed().ui_refresh_interval())).await;
                 }
             },
             async {
+                if demo::is_enabled() {
+                    return;
+                }
+                // keeps looking while suspended so that resets are notified about in the background
+                let mut cursor: Option<String> = None;
+                let mut initial = true;
                 loop {
+                    match gpu_resets::gpu_resets(cursor.as_deref()).await {
+                        Ok((resets, next_cursor)) => {
+                            let gpu_pages: Vec<ResGPU> = imp.gpu_pages.borrow().iter().map(|toolbar| toolbar.content().and_downcast::<ResGPU>().unwrap()).collect();
+                            for gpu_page in gpu_pages {
+                                let added = gpu_page.add_resets(&resets);
+                                // the history that's there already isn't news
+                                if !initial && !added.is_empty() && SETTINGS.notify_gpu_resets() {
+                                    this.notify_gpu_reset(&gpu_page);
+                                }
+                            }
+                            cursor = next_cursor;
+                            initial = false;
+                        }
+                        Err(error) => log::debug!("Unable to look for GPU resets: {error}"),
+                    }
+                    timeout_future(GPU_RESETS_REFRESH_INTERVAL).await;
+                }
+            },
+            async {
+                if demo::is_enabled() {
+                    return;
+                }
+                // keeps looking while suspended so that no kills are missed
+                let mut kernel_cursor: Option<String> = None;
+                let mut oomd_cursor: Option<String> = None;
+                loop {
+                    let oomd_status = OomdStatus::current().await;
+                    if let Err(error) = &oomd_status {
+                        log::debug!("Not showing systemd-oomd status: {error}");
+                    }
+                    imp.memory.set_oomd_status(oomd_status.ok().as_ref());
+
+                    for (killer, cursor) in [(OomKiller::Kernel, &mut kernel_cursor), (OomKiller::Oomd, &mut oomd_cursor)] {
+                        match oom::oom_kills(killer, cursor.as_deref()).await {
+                            Ok((kills, next_cursor)) => {
+                                imp.memory.add_oom_kills(killer, &kills);
+                                *cursor = next_cursor;
+                            }
+                            Err(error) => log::debug!("Unable to look for OOM kills: {error}"),
+                        }
+                    }
+                    timeout_future(OOM_REFRESH_INTERVAL).await;
+                }
+            },
+            async {
+                loop {
+                    // keep looking for added or removed drives even while suspended so that the
+                    // list of pages is up to date once the window is shown again
                     this.refresh_drives().await;
-                    for drive_page_toolbar in imp.drive_pages.borrow().values() {
-                        drive_page_toolbar.content().and_downcast::<ResDrive>().unwrap().refresh_page().await;
+                    if !this.is_sampling_suspended() {
+                        let drive_pages: Vec<ResDrive> = imp.drive_pages.borrow().values().map(|toolbar| toolbar.content().and_downcast::<ResDrive>().unwrap()).collect();
+                        for drive_page in drive_pages {
+                            let drive = drive_page.drive();
+                            let drive_data = if demo::is_enabled() {
+                                demo::drive_data()
+                            } else {
+                                async_std::task::spawn(async move { DriveData::new(&drive).await }).await
+                            };
+                            drive_page.refresh_page(drive_data);
+                        }
                     }
                     timeout_future(Duration::from_secs_f32(SETTINGS.refresh_speed().ui_refresh_interval())).await;
                 }
+            },
+            async {
+                if demo::is_enabled() {
+                    return;
+                }
+                loop {
+                    // GIO's D-Bus calls are asynchronous already, so this doesn't need a worker task
+                    let drive_pages: Vec<ResDrive> = imp.drive_pages.borrow().values().map(|toolbar| toolbar.content().and_downcast::<ResDrive>().unwrap()).collect();
+                    for drive_page in drive_pages {
+                        drive_page.refresh_smart().await;
+                    }
+                    // keep recording while suspended so that the history doesn't get gaps
+                    timeout_future(SMART_REFRESH_INTERVAL).await;
+                }
             }, async {
                 loop {
                     this.refresh_network_interfaces().await;
-                    for network_page_toolbar in imp.network_pages.borrow().values() {
-                        network_page_toolbar.content().and_downcast::<ResNetwork>().unwrap().refresh_page().await;
+                    let network_pages: Vec<ResNetwork> = imp.network_pages.borrow().values().map(|toolbar| toolbar.content().and_downcast::<ResNetwork>().unwrap()).collect();
+                    for network_page in network_pages {
+                        let network_interface = network_page.network_interface();
+                        let network_data = if demo::is_enabled() {
+                            demo::network_data()
+                        } else {
+                            async_std::task::spawn(async move { NetworkData::new(&network_interface).await }).await
+                        };
+                        network_page.refresh_page(network_data);
                     }
-                    timeout_future(Duration::from_secs_f32(SETTINGS.refresh_speed().ui_refresh_interval())).await;
+                    this.wait_for_next_sample(SETTINGS.refresh_speed().ui_refresh_interval()).await;
                 }
             });
         }));
     }
 
+    /// Returns whether the refresh loops should currently be paused, i.e. when the
+    /// window is minimized, fully obscured or not shown at all and mini mode isn't
+    /// active either
+    fn is_refresh_suspended(&self) -> bool {
+        let window_suspended = self.is_suspended() || !self.is_visible();
+        let mini_window_suspended = self
+            .imp()
+            .mini_window
+            .borrow()
+            .as_ref()
+            .map_or(true, |mini_window| {
+                mini_window.is_suspended() || !mini_window.is_visible()
+            });
+        window_suspended && mini_window_suspended
+    }
+
+    /// Returns whether the loops that feed the graphs should currently be paused.
+    /// They keep going while suspended if alert rules or the MQTT publisher rely on
+    /// their values or when running in the background to record the history.
+    fn is_sampling_suspended(&self) -> bool {
+        self.is_refresh_suspended()
+            && !SETTINGS.run_in_background()
+            && SETTINGS.alert_rules().is_empty()
+            && !SETTINGS.mqtt_enabled()
+    }
+
+    fn check_alerts(&self) {
+        let rules = SETTINGS.alert_rules();
+        if rules.is_empty() {
+            return;
+        }
+
+        let triggered_rules = self.imp().alert_engine.borrow_mut().evaluate(
+            &rules,
+            |metric| {
+                pinnable_graph_box(metric)
+                    .filter(ResGraphBox::shows_fractions)
+                    .and_then(|graph_box| graph_box.latest_data_point())
+            },
+            Instant::now(),
+        );
+
+        for (rule, value) in triggered_rules {
+            self.run_alert_action(&rule, value);
+        }
+    }
+
+    fn run_alert_action(&self, rule: &AlertRule, value: f64) {
+        match rule.action {
+            AlertAction::Notify => {
+                let Some(app) = self.application() else {
+                    return;
+                };
+                let notification = gio::Notification::new(&i18n("Resource Alert"));
+                notification.set_body(Some(&rule.description()));
+                notification.set_priority(gio::NotificationPriority::High);
+                app.send_notification(Some(&format!("alert-{}", rule.metric)), &notification);
+            }
+            AlertAction::RunCommand => {
+                let command = rule.target.clone();
+                let metric = rule.metric.clone();
+                let payload = rule.payload(value);
+                async_std::task::spawn(async move {
+                    if let Err(error) =
+                        alerts::run_command(&command, &metric, value, &payload).await
+                    {
+                        log::warn!("Unable to run alert command for {metric}: {error}");
+                    }
+                });
+            }
+            AlertAction::PostWebhook => {
+                let url = rule.target.clone();
+                let metric = rule.metric.clone();
+                let payload = rule.payload(value);
+                // GIO's networking is asynchronous already, so this doesn't need a worker task
+                MainContext::default().spawn_local(async move {
+                    if let Err(error) = alerts::post_webhook(&url, &payload).await {
+                        log::warn!("Unable to post alert for {metric} to webhook: {error}");
+                    }
+                });
+            }
+        }
+    }
+
+    /// Collects the latest values of the metrics that are to be published via
+    /// MQTT as pairs of topic and payload. Graphs are published by their
+    /// dashboard key, fractions as percentages.
+    fn mqtt_messages(&self) -> Vec<(String, String)> {
+        let categories = SETTINGS.mqtt_metrics();
+        let prefix = SETTINGS.mqtt_topic();
+
+        let mut keys: Vec<String> = pinnable_keys()
+            .into_iter()
+            .filter(|key| {
+                categories
+                    .iter()
+                    .any(|category| category == mqtt::category_of(key))
+            })
+            .collect();
+        keys.sort();
+
+        let mut messages: Vec<(String, String)> = keys
+            .into_iter()
+            .filter_map(|key| {
+                let graph_box = pinnable_graph_box(&key)?;
+                let value = graph_box.latest_data_point()?;
+                let value = if graph_box.shows_fractions() {
+                    value * 100.0
+                } else {
+                    value
+                };
+                Some((format!("{prefix}/{key}"), format!("{value:.1}")))
+            })
+            .collect();
+
+        if categories.iter().any(|category| category == "temperature") {
+            for (name, celsius) in self.imp().sensors.temperatures() {
+                messages.push((
+                    format!("{prefix}/temperature-{}", mqtt::topic_level(&name)),
+                    format!("{celsius:.1}"),
+                ));
+            }
+        }
+
+        messages
+    }
+
+    fn notify_thermal_throttling(&self) {
+        let Some(app) = self.application() else {
+            return;
+        };
+        let notification = gio::Notification::new(&i18n("Processor Is Being Throttled"));
+        notification.set_body(Some(&i18n(
+            "Your processor is running slower than usual because it is too hot",
+        )));
+        app.send_notification(Some("thermal-throttling"), &notification);
+    }
+
+    fn notify_gpu_reset(&self, gpu_page: &ResGPU) {
+        let Some(app) = self.application() else {
+            return;
+        };
+        let gpu = gpu_page.gpu();
+        let name = gpu
+            .get_name()
+            .unwrap_or_else(|_| gpu_page.tab_name().to_string());
+        let notification = gio::Notification::new(&i18n("GPU Has Been Reset"));
+        notification.set_body(Some(&i18n_f(
+            "{} stopped responding and had to be reset by its driver",
+            &[&name],
+        )));
+        notification.set_priority(gio::NotificationPriority::High);
+        app.send_notification(Some(&format!("gpu-reset-{}", gpu.pci_slot)), &notification);
+    }
+
+    fn setup_history(&self) {
+        let imp = self.imp();
+
+        imp.history_scale.set_format_value_func(
+            clone!(@weak self as this => @default-return String::new(), move |_, value| {
+                match this.history_moment(value) {
+                    Some(moment) => format_moment(moment),
+                    None => i18n("Live"),
+                }
+            }),
+        );
+
+        imp.history_adjustment.connect_value_changed(
+            clone!(@weak self as this => move |adjustment| {
+                graph::set_history_moment(this.history_moment(adjustment.value()));
+            }),
+        );
+
+        imp.live_button
+            .connect_clicked(clone!(@weak self as this => move |_| {
+                this.imp().history_adjustment.set_value(0.0);
+            }));
+    }
+
+    /// Returns the moment that the history scrubber points to at `value`, which
+    /// counts the seconds before browsing began, or `None` for the live view
+    fn history_moment(&self, value: f64) -> Option<Instant> {
+        let seconds_ago = -value.round();
+        if seconds_ago < 1.0 {
+            return None;
+        }
+        self.imp()
+            .history_start
+            .get()?
+            .checked_sub(Duration::from_secs_f64(seconds_ago))
+    }
+
+    pub fn is_browsing_history(&self) -> bool {
+        self.imp().history_revealer.reveals_child()
+    }
+
+    /// Shows or hides the history scrubber. Hiding it always returns the graphs
+    /// to the live view.
+    pub fn set_browsing_history(&self, enabled: bool) {
+        let imp = self.imp();
+        imp.history_adjustment.set_value(0.0);
+        if enabled {
+            // the scrubber covers everything from the oldest data point any graph
+            // still keeps up to now
+            let start = Instant::now();
+            let seconds_kept = graph::oldest_data_point_moment()
+                .map_or(0.0, |oldest| start.duration_since(oldest).as_secs_f64());
+            imp.history_start.set(Some(start));
+            imp.history_adjustment.set_lower(-seconds_kept.floor());
+            imp.history_adjustment.set_upper(0.0);
+        } else {
+            imp.history_start.set(None);
+        }
+        imp.history_revealer.set_reveal_child(enabled);
+    }
+
+    pub fn is_comparing(&self) -> bool {
+        self.imp().compare_pane.is_visible()
+    }
+
+    /// Shows or hides a pane next to the current page that follows the graphs of
+    /// another page, e.g. to see whether a game is limited by the processor or
+    /// the GPU
+    pub fn set_comparing(&self, enabled: bool) {
+        let imp = self.imp();
+        if enabled {
+            // pages for drives and network interfaces come and go, so gather them anew
+            imp.compare_pane.set_pages(self.comparable_pages());
+        }
+        imp.compare_pane.set_visible(enabled);
+    }
+
+    fn comparable_pages(&self) -> Vec<gtk::Widget> {
+        let imp = self.imp();
+
+        let mut pages = vec![imp.cpu.clone().upcast(), imp.memory.clone().upcast()];
+
+        let toolbars = imp
+            .gpu_pages
+            .borrow()
+            .iter()
+            .cloned()
+            .chain(imp.drive_pages.borrow().values().cloned())
+            .chain(imp.network_pages.borrow().values().cloned())
+            .collect::<Vec<adw::ToolbarView>>();
+        pages.extend(toolbars.iter().filter_map(adw::ToolbarView::content));
+
+        pages
+    }
+
+    pub fn is_kiosk_mode(&self) -> bool {
+        self.imp().kiosk_mode.get()
+    }
+
+    /// Shows this window in fullscreen without the sidebar and header bars, meant
+    /// for a display that's dedicated to monitoring. Pinned metrics are tiled on
+    /// the dashboard, otherwise the pages of the processor, memory, GPUs, drives
+    /// and network interfaces take turns.
+    pub fn set_kiosk_mode(&self, enabled: bool) {
+        let imp = self.imp();
+
+        if imp.kiosk_mode.replace(enabled) == enabled {
+            return;
+        }
+
+        self.set_top_bars_revealed(!enabled);
+
+        if enabled {
+            imp.kiosk_previous.replace(Some((
+                imp.content_stack.visible_child(),
+                imp.split_view.shows_sidebar(),
+            )));
+            imp.split_view.set_show_sidebar(false);
+
+            if SETTINGS.dashboard_tiles().is_empty() {
+                imp.content_stack.set_visible_child(&imp.cpu_page.child());
+                let source = glib::timeout_add_local(
+                    KIOSK_CYCLE_INTERVAL,
+                    clone!(@weak self as this => @default-return glib::ControlFlow::Break, move || {
+                        this.show_next_kiosk_page();
+                        glib::ControlFlow::Continue
+                    }),
+                );
+                imp.kiosk_cycle.replace(Some(source));
+            } else {
+                imp.content_stack
+                    .set_visible_child(&imp.dashboard_page.child());
+            }
+
+            // nobody's going to touch the machine, so keep the screen on
+            if let Some(app) = self.application() {
+                imp.kiosk_inhibit_cookie.set(app.inhibit(
+                    Some(self),
+                    gtk::ApplicationInhibitFlags::IDLE,
+                    Some(&i18n("Showing the kiosk mode of Resources")),
+                ));
+            }
+
+            self.fullscreen();
+        } else {
+            if let Some(source) = imp.kiosk_cycle.take() {
+                source.remove();
+            }
+
+            if let Some((page, showed_sidebar)) = imp.kiosk_previous.take() {
+                if let Some(page) = page {
+                    imp.content_stack.set_visible_child(&page);
+                }
+                imp.split_view.set_show_sidebar(showed_sidebar);
+            }
+
+            let cookie = imp.kiosk_inhibit_cookie.replace(0);
+            if cookie != 0 {
+                if let Some(app) = self.application() {
+                    app.uninhibit(cookie);
+                }
+            }
+
+            self.unfullscreen();
+        }
+    }
+
+    fn set_top_bars_revealed(&self, revealed: bool) {
+        let mut child = self.imp().content_stack.first_child();
+        while let Some(widget) = child {
+            if let Some(toolbar) = widget.downcast_ref::<adw::ToolbarView>() {
+                toolbar.set_reveal_top_bars(revealed);
+            }
+            child = widget.next_sibling();
+        }
+    }
+
+    fn show_next_kiosk_page(&self) {
+        let imp = self.imp();
+
+        let mut pages: Vec<Widget> = vec![imp.cpu_page.child(), imp.memory_page.child()];
+        pages.extend(
+            imp.gpu_pages
+                .borrow()
+                .iter()
+                .map(|toolbar| toolbar.clone().upcast()),
+        );
+        pages.extend(
+            imp.drive_pages
+                .borrow()
+                .values()
+                .map(|toolbar| toolbar.clone().upcast()),
+        );
+        pages.extend(
+            imp.network_pages
+                .borrow()
+                .values()
+                .map(|toolbar| toolbar.clone().upcast()),
+        );
+
+        let next = imp
+            .content_stack
+            .visible_child()
+            .and_then(|visible| pages.iter().position(|page| *page == visible))
+            .map_or(0, |position| (position + 1) % pages.len());
+
+        imp.content_stack.set_visible_child(&pages[next]);
+    }
+
+    pub fn is_mini_mode(&self) -> bool {
+        self.imp().mini_window.borrow().is_some()
+    }
+
+    /// Replaces this window with a small window that only shows tiny graphs of
+    /// the processor, memory, GPUs and network interfaces or restores it again.
+    pub fn set_mini_mode(&self, enabled: bool) {
+        let imp = self.imp();
+
+        if !enabled {
+            // closing the mini window brings this window back, see below
+            let mini_window = imp.mini_window.borrow().clone();
+            if let Some(mini_window) = mini_window {
+                mini_window.close();
+            }
+            return;
+        }
+
+        if self.is_mini_mode() {
+            return;
+        }
+
+        let Some(app) = self.application() else {
+            return;
+        };
+
+        let mini_window = ResMiniWindow::new(&app);
+
+        mini_window.add_page(&*imp.cpu, (28, 113, 216), true);
+        mini_window.add_page(&*imp.memory, (129, 61, 156), true);
+        for toolbar in imp.gpu_pages.borrow().iter() {
+            if let Some(page) = toolbar.content() {
+                mini_window.add_page(&page, (230, 97, 0), true);
+            }
+        }
+        for toolbar in imp.network_pages.borrow().values() {
+            if let Some(page) = toolbar.content() {
+                mini_window.add_page(&page, (52, 170, 175), false);
+            }
+        }
+
+        mini_window.connect_close_request(
+            clone!(@weak self as this => @default-return glib::Propagation::Proceed, move |_| {
+                this.imp().mini_window.take();
+                this.present();
+                glib::Propagation::Proceed
+            }),
+        );
+
+        mini_window.connect_suspended_notify(
+            clone!(@weak self as this => move |_| this.resume_refreshes()),
+        );
+        mini_window
+            .connect_visible_notify(clone!(@weak self as this => move |_| this.resume_refreshes()));
+
+        *imp.mini_window.borrow_mut() = Some(mini_window.clone());
+
+        mini_window.present();
+        self.set_visible(false);
+    }
+
+    /// Waits `interval` seconds until the next refresh should happen. If the window
+    /// is suspended at that point, keep waiting until it isn't anymore.
+    async fn wait_for_next_refresh(&self, interval: f32) {
+        timeout_future(Duration::from_secs_f32(interval)).await;
+        self.wait_while(Self::is_refresh_suspended).await;
+    }
+
+    /// Like `wait_for_next_refresh`, but for the loops that feed the graphs, which
+    /// may have to keep going while the window is suspended
+    async fn wait_for_next_sample(&self, interval: f32) {
+        timeout_future(Duration::from_secs_f32(interval)).await;
+        self.wait_while(Self::is_sampling_suspended).await;
+    }
+
+    async fn wait_while(&self, suspended: fn(&Self) -> bool) {
+        while suspended(self) {
+            let (sender, receiver) = async_std::channel::bounded(1);
+            self.imp().refresh_waiters.borrow_mut().push(sender);
+            // an error only means that the window is gone, so there's nothing
+            // left to refresh anyway
+            if receiver.recv().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Lets all refresh loops that are waiting for the window to be shown again
+    /// check whether they may continue
+    fn resume_refreshes(&self) {
+        for sender in self.imp().refresh_waiters.take() {
+            let _ = sender.try_send(());
+        }
+    }
+
     async fn refresh_drives(&self) {
         let imp = self.imp();
         let mut still_active_drives = Vec::with_capacity(imp.drive_pages.borrow().len());
-        for path in Drive::get_sysfs_paths(true).await.unwrap_or_default() {
+        let paths = if demo::is_enabled() {
+            vec![demo::drive().sys_fs_path]
+        } else {
+            Drive::get_sysfs_paths(true).await.unwrap_or_default()
+        };
+        for path in paths {
             // ignore drive pages that are already listed
             if imp.drive_pages.borrow().contains_key(&path) {
                 still_active_drives.push(path);
                 continue;
             }
-            if let Ok(drive) = Drive::from_sysfs(&path).await {
-                let capacity =
-                    drive.capacity().await.unwrap_or(0) * drive.sector_size().await.unwrap_or(512);
+            let drive = if demo::is_enabled() {
+                Ok(demo::drive())
+            } else {
+                Drive::from_sysfs(&path).await
+            };
+            if let Ok(drive) = drive {
+                let capacity = if demo::is_enabled() {
+                    demo::drive_capacity()
+                } else {
+                    drive.capacity().await.unwrap_or(0) * drive.sector_size().await.unwrap_or(512)
+                };
                 let capacity_formatted = convert_storage(capacity as f64, true);
                 let sidebar_title = match drive.drive_type {
                     DriveType::CdDvdBluray => i18n("CD/DVD/Blu-ray Drive"),
@@ -337,16 +1266,25 @@ impl MainWindow {
     async fn refresh_network_interfaces(&self) {
         let imp = self.imp();
         let mut still_active_interfaces = Vec::with_capacity(imp.network_pages.borrow().len());
-        for path in NetworkInterface::get_sysfs_paths()
-            .await
-            .unwrap_or_default()
-        {
+        let paths = if demo::is_enabled() {
+            vec![demo::network_interface().sysfs_path]
+        } else {
+            NetworkInterface::get_sysfs_paths()
+                .await
+                .unwrap_or_default()
+        };
+        for path in paths {
             // ignore network pages that are already listed
             if imp.network_pages.borrow().contains_key(&path) {
                 still_active_interfaces.push(path);
                 continue;
             }
-            if let Ok(interface) = NetworkInterface::from_sysfs(&path).await {
+            let interface = if demo::is_enabled() {
+                Ok(demo::network_interface())
+            } else {
+                NetworkInterface::from_sysfs(&path).await
+            };
+            if let Ok(interface) = interface {
                 let sidebar_title = match interface.interface_type {
                     InterfaceType::Ethernet => i18n("Ethernet Connection"),
                     InterfaceType::InfiniBand => i18n("InfiniBand Connection"),
@@ -382,6 +1320,17 @@ impl MainWindow {
             .for_each(|(_, v)| imp.content_stack.remove(&v)); // remove page from the UI
     }
 
+    /// Switches to the processes page, only showing the process with the given
+    /// PID
+    pub fn reveal_process(&self, pid: i32) {
+        let imp = self.imp();
+
+        imp.processes
+            .reveal_process(pid, &imp.apps_context.borrow());
+        imp.content_stack
+            .set_visible_child(&imp.processes_page.child());
+    }
+
     fn process_action(&self, action: Action) -> glib::ControlFlow {
         let imp = self.imp();
 
@@ -423,6 +1372,25 @@ impl MainWindow {
 
                 toast_overlay.add_toast(Toast::new(&toast_message));
             }
+
+            Action::ShowAppProcesses(id, display_name) => {
+                imp.processes
+                    .set_app_filter(id, display_name, &imp.apps_context.borrow());
+                imp.content_stack
+                    .set_visible_child(&imp.processes_page.child());
+            }
+
+            Action::ShowApplication(id) => {
+                imp.applications.select_app(&id);
+                imp.content_stack
+                    .set_visible_child(&imp.applications_page.child());
+            }
+
+            Action::ShowProcess(pid) => {
+                imp.processes.select_process(pid);
+                imp.content_stack
+                    .set_visible_child(&imp.processes_page.child());
+            }
         };
 
         glib::ControlFlow::Continue
@@ -482,6 +1450,7 @@ impl MainWindow {
         let toolbar = adw::ToolbarView::new();
         toolbar.add_top_bar(&header_bar);
         toolbar.set_content(Some(widget));
+        toolbar.set_reveal_top_bars(!self.is_kiosk_mode());
 
         imp.content_stack.add_titled(&toolbar, None, sidebar_title);
 
@@ -499,6 +1468,15 @@ impl Default for MainWindow {
     }
 }
 
+/// Formats `moment` as the local time of day it happened at, e.g. "14:03:27"
+fn format_moment(moment: Instant) -> String {
+    glib::DateTime::now_local()
+        .and_then(|now| now.add_seconds(-moment.elapsed().as_secs_f64()))
+        .and_then(|then| then.format("%X"))
+        .map(|formatted| formatted.to_string())
+        .unwrap_or_default()
+}
+
 pub fn get_action_name(action: ProcessAction, args: &[&str]) -> String {
     match action {
         ProcessAction::TERM => i18n_f("End {}?", args),
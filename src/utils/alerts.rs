@@ -0,0 +1,600 @@
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context, Result};
+use async_std::io::WriteExt;
+use gtk::{
+    gio::{self, prelude::*},
+    glib,
+};
+use strum_macros::{Display, EnumString, FromRepr};
+
+use crate::i18n::{i18n, i18n_f};
+
+use super::async_host_command;
+
+/// Webhooks that take longer than this to accept a connection are given up on
+const WEBHOOK_TIMEOUT_SECS: u32 = 10;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumString, Display, Hash, FromRepr)]
+pub enum Comparator {
+    #[default]
+    Above,
+    Below,
+}
+
+impl Comparator {
+    pub fn matches(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparator::Above => value > threshold,
+            Comparator::Below => value < threshold,
+        }
+    }
+}
+
+/// What happens once a rule has been triggered
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumString, Display, Hash, FromRepr)]
+pub enum AlertAction {
+    #[default]
+    Notify,
+    /// Runs the rule's target as a shell command
+    RunCommand,
+    /// Sends a POST request to the rule's target URL
+    PostWebhook,
+}
+
+impl AlertAction {
+    /// Whether the action needs a command or URL to do its job
+    pub fn needs_target(self) -> bool {
+        self != AlertAction::Notify
+    }
+}
+
+/// An alert that is triggered once `metric` has been above or below
+/// `threshold` for at least `duration`.
+///
+/// `metric` is the dashboard key of a graph, `threshold` is a fraction between
+/// 0.0 and 1.0 just like the values of that graph.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AlertRule {
+    pub metric: String,
+    pub comparator: Comparator,
+    pub threshold: f64,
+    pub duration: Duration,
+    pub action: AlertAction,
+    /// The command to run or the URL to post to, depending on `action`
+    pub target: String,
+}
+
+/// How an `AlertRule` is stored in the settings
+pub type StoredAlertRule = (String, String, f64, u32, String, String);
+
+/// How an `AlertRule` was stored before actions could have a target
+pub type LegacyStoredAlertRule = (String, String, f64, u32, String);
+
+impl AlertRule {
+    pub fn from_stored(stored: StoredAlertRule) -> Option<Self> {
+        let (metric, comparator, threshold, duration, action, target) = stored;
+        Some(Self {
+            metric,
+            comparator: Comparator::from_str(&comparator).ok()?,
+            threshold,
+            duration: Duration::from_secs(duration.into()),
+            action: AlertAction::from_str(&action).ok()?,
+            target,
+        })
+    }
+
+    pub fn from_legacy_stored(stored: LegacyStoredAlertRule) -> Option<Self> {
+        let (metric, comparator, threshold, duration, action) = stored;
+        Self::from_stored((
+            metric,
+            comparator,
+            threshold,
+            duration,
+            action,
+            String::new(),
+        ))
+    }
+
+    pub fn to_stored(&self) -> StoredAlertRule {
+        (
+            self.metric.clone(),
+            self.comparator.to_string(),
+            self.threshold,
+            self.duration.as_secs().try_into().unwrap_or(u32::MAX),
+            self.action.to_string(),
+            self.target.clone(),
+        )
+    }
+
+    /// Returns a human-readable description, e.g. "Processor above 90 % for
+    /// 30 s"
+    pub fn description(&self) -> String {
+        let threshold = format!("{} %", (self.threshold * 100.0).round());
+        let duration = self.duration.as_secs().to_string();
+        let metric = metric_name(&self.metric);
+
+        match self.comparator {
+            Comparator::Above => i18n_f("{} above {} for {} s", &[&metric, &threshold, &duration]),
+            Comparator::Below => i18n_f("{} below {} for {} s", &[&metric, &threshold, &duration]),
+        }
+    }
+
+    /// Returns the JSON document that tells commands and webhooks which rule
+    /// has been triggered by which `value`
+    pub fn payload(&self, value: f64) -> String {
+        serde_json::json!({
+            "metric": self.metric,
+            "name": metric_name(&self.metric),
+            "description": self.description(),
+            "comparator": self.comparator.to_string(),
+            "threshold": self.threshold,
+            "duration": self.duration.as_secs(),
+            "value": value,
+        })
+        .to_string()
+    }
+}
+
+/// Runs `command` using `sh` on the host. The metric's dashboard key and the
+/// current value are passed as `$1` and `$2`, the full `payload` is written to
+/// the command's standard input.
+///
+/// # Errors
+///
+/// Will return `Err` if the command can't be started or doesn't exit
+/// successfully
+pub async fn run_command(command: &str, metric: &str, value: f64, payload: &str) -> Result<()> {
+    let mut child = async_host_command("sh")
+        .arg("-c")
+        .arg(command)
+        .arg("resources-alert")
+        .arg(metric)
+        .arg(value.to_string())
+        .stdin(async_process::Stdio::piped())
+        .spawn()
+        .with_context(|| format!("unable to start `{command}`"))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // commands are free to ignore the payload, so a closed pipe is fine
+        let _ = stdin.write_all(payload.as_bytes()).await;
+    }
+
+    let status = child
+        .status()
+        .await
+        .with_context(|| format!("unable to wait for `{command}`"))?;
+    if !status.success() {
+        bail!("`{command}` exited with {status}");
+    }
+
+    Ok(())
+}
+
+/// Where a webhook is posted to
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WebhookUrl {
+    https: bool,
+    /// The value of the `Host` header, the port is only included if the URL
+    /// has one
+    host: String,
+    /// The path along with the query
+    target: String,
+}
+
+impl WebhookUrl {
+    fn parse(url: &str) -> Result<Self> {
+        let uri = glib::Uri::parse(url, glib::UriFlags::NONE).context("invalid webhook URL")?;
+        let https = match uri.scheme().as_str() {
+            "http" => false,
+            "https" => true,
+            scheme => bail!("unsupported webhook URL scheme {scheme}"),
+        };
+
+        let host = uri.host().context("webhook URL has no host")?;
+        // IPv6 literals come without their brackets
+        let host = if host.contains(':') {
+            format!("[{host}]")
+        } else {
+            host.to_string()
+        };
+        let host = if uri.port() > 0 {
+            format!("{host}:{}", uri.port())
+        } else {
+            host
+        };
+
+        let target = match (uri.path().as_str(), uri.query()) {
+            ("", None) => "/".to_string(),
+            ("", Some(query)) => format!("/?{query}"),
+            (path, None) => path.to_string(),
+            (path, Some(query)) => format!("{path}?{query}"),
+        };
+
+        Ok(Self {
+            https,
+            host,
+            target,
+        })
+    }
+
+    fn request(&self, payload: &str) -> String {
+        format!(
+            "POST {} HTTP/1.1\r\n\
+             Host: {}\r\n\
+             User-Agent: Resources\r\n\
+             Content-Type: application/json\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\
+             \r\n\
+             {payload}",
+            self.target,
+            self.host,
+            payload.len()
+        )
+    }
+}
+
+/// Returns the status code of an HTTP response by its status line, e.g. 204 for
+/// "HTTP/1.1 204 No Content"
+fn parse_status_line(line: &str) -> Result<u16> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let mut parts = line.splitn(3, ' ');
+
+    if !parts.next().unwrap_or_default().starts_with("HTTP/") {
+        bail!("invalid response");
+    }
+
+    let status = parts.next().context("invalid response")?;
+    if status.len() != 3 || !status.bytes().all(|byte| byte.is_ascii_digit()) {
+        bail!("invalid status {status}");
+    }
+
+    Ok(status.parse()?)
+}
+
+/// Sends `payload` to `url` as the body of a POST request with JSON content.
+/// HTTPS uses GIO's TLS support and therefore needs glib-networking.
+///
+/// This uses GIO's networking and therefore has to be awaited on the main
+/// context.
+///
+/// # Errors
+///
+/// Will return `Err` if `url` is no valid HTTP(S) URL, the server can't be
+/// reached or it doesn't answer with a 2xx status
+pub async fn post_webhook(url: &str, payload: &str) -> Result<()> {
+    let webhook_url = WebhookUrl::parse(url)?;
+    let host = &webhook_url.host;
+
+    if webhook_url.https && !gio::TlsBackend::default().supports_tls() {
+        bail!("unable to post to {host}, HTTPS needs glib-networking");
+    }
+
+    let client = gio::SocketClient::new();
+    client.set_tls(webhook_url.https);
+    client.set_timeout(WEBHOOK_TIMEOUT_SECS);
+    let connection = client
+        .connect_to_uri_future(url, if webhook_url.https { 443 } else { 80 })
+        .await
+        .with_context(|| format!("unable to connect to {host}"))?;
+
+    connection
+        .output_stream()
+        .write_all_future(
+            webhook_url.request(payload).into_bytes(),
+            glib::Priority::DEFAULT,
+        )
+        .await
+        .map_err(|(_, error)| error)
+        .with_context(|| format!("unable to send request to {host}"))?;
+
+    // only the status line is of interest
+    let status_line = gio::DataInputStream::new(&connection.input_stream())
+        .read_line_utf8_future(glib::Priority::DEFAULT)
+        .await
+        .with_context(|| format!("unable to read response from {host}"))?
+        .with_context(|| format!("{host} closed the connection without answering"))?;
+    let status = parse_status_line(&status_line)?;
+    if !(200..300).contains(&status) {
+        bail!("{host} answered with status {status}");
+    }
+
+    Ok(())
+}
+
+/// Returns a human-readable name for the graph with `dashboard_key`
+pub fn metric_name(dashboard_key: &str) -> String {
+    let parts: Vec<&str> = dashboard_key.split('-').collect();
+    let number = |part: &str, offset: usize| {
+        part.parse::<usize>()
+            .map_or_else(|_| part.to_string(), |i| (i + offset).to_string())
+    };
+
+    match parts.as_slice() {
+        ["cpu"] => i18n("Processor"),
+        ["cpu", thread] => i18n_f("CPU {}", &[&number(thread, 1)]),
+        ["memory"] => i18n("Memory"),
+        ["swap"] => i18n("Swap"),
+        ["gpu", gpu, "usage"] => i18n_f("GPU {} Usage", &[&number(gpu, 0)]),
+        ["gpu", gpu, "vram"] => i18n_f("GPU {} Video Memory Usage", &[&number(gpu, 0)]),
+        _ => match dashboard_key.split_once('-') {
+            Some(("drive", device)) => i18n_f("Drive {}", &[device]),
+            _ => dashboard_key.to_string(),
+        },
+    }
+}
+
+#[derive(Debug)]
+struct RuleState {
+    rule: AlertRule,
+    since: Option<Instant>,
+    triggered: bool,
+}
+
+/// Keeps track of how long the conditions of the alert rules have been met
+#[derive(Debug, Default)]
+pub struct AlertEngine {
+    states: Vec<RuleState>,
+}
+
+impl AlertEngine {
+    /// Checks `rules` against the current values of their metrics, as returned
+    /// by `value_of`, and returns the rules that have just been triggered along
+    /// with the values that triggered them.
+    ///
+    /// A rule is triggered once per episode, it has to stop matching before it
+    /// can be triggered again. Rules that are unchanged since the last call keep
+    /// their progress.
+    pub fn evaluate<F: Fn(&str) -> Option<f64>>(
+        &mut self,
+        rules: &[AlertRule],
+        value_of: F,
+        now: Instant,
+    ) -> Vec<(AlertRule, f64)> {
+        let mut old_states = std::mem::take(&mut self.states);
+        let mut triggered_rules = Vec::new();
+
+        for rule in rules {
+            let mut state = old_states
+                .iter()
+                .position(|state| state.rule == *rule)
+                .map(|i| old_states.swap_remove(i))
+                .unwrap_or_else(|| RuleState {
+                    rule: rule.clone(),
+                    since: None,
+                    triggered: false,
+                });
+
+            let value = value_of(&rule.metric)
+                .filter(|value| rule.comparator.matches(*value, rule.threshold));
+
+            if let Some(value) = value {
+                let since = *state.since.get_or_insert(now);
+                if !state.triggered && now.duration_since(since) >= rule.duration {
+                    state.triggered = true;
+                    triggered_rules.push((rule.clone(), value));
+                }
+            } else {
+                state.since = None;
+                state.triggered = false;
+            }
+
+            self.states.push(state);
+        }
+
+        triggered_rules
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(comparator: Comparator, threshold: f64, duration: u64) -> AlertRule {
+        AlertRule {
+            metric: "cpu".into(),
+            comparator,
+            threshold,
+            duration: Duration::from_secs(duration),
+            ..Default::default()
+        }
+    }
+
+    fn evaluate_at(
+        engine: &mut AlertEngine,
+        rules: &[AlertRule],
+        value: Option<f64>,
+        start: Instant,
+        seconds: u64,
+    ) -> Vec<(AlertRule, f64)> {
+        engine.evaluate(rules, |_| value, start + Duration::from_secs(seconds))
+    }
+
+    #[test]
+    fn test_comparator() {
+        assert!(Comparator::Above.matches(0.91, 0.9));
+        assert!(!Comparator::Above.matches(0.9, 0.9));
+        assert!(Comparator::Below.matches(0.09, 0.1));
+        assert!(!Comparator::Below.matches(0.1, 0.1));
+    }
+
+    #[test]
+    fn test_evaluate_crossing() {
+        let rules = [rule(Comparator::Above, 0.9, 0)];
+        let mut engine = AlertEngine::default();
+        let start = Instant::now();
+
+        assert!(evaluate_at(&mut engine, &rules, Some(0.5), start, 0).is_empty());
+        assert_eq!(
+            evaluate_at(&mut engine, &rules, Some(0.95), start, 1),
+            [(rules[0].clone(), 0.95)]
+        );
+        // staying above doesn't trigger again
+        assert!(evaluate_at(&mut engine, &rules, Some(0.97), start, 2).is_empty());
+    }
+
+    #[test]
+    fn test_evaluate_hold_time() {
+        let rules = [rule(Comparator::Above, 0.9, 30)];
+        let mut engine = AlertEngine::default();
+        let start = Instant::now();
+
+        assert!(evaluate_at(&mut engine, &rules, Some(0.95), start, 0).is_empty());
+        assert!(evaluate_at(&mut engine, &rules, Some(0.95), start, 29).is_empty());
+        assert_eq!(
+            evaluate_at(&mut engine, &rules, Some(0.96), start, 30),
+            [(rules[0].clone(), 0.96)]
+        );
+        assert!(evaluate_at(&mut engine, &rules, Some(0.96), start, 60).is_empty());
+    }
+
+    #[test]
+    fn test_evaluate_interrupted_hold_time() {
+        let rules = [rule(Comparator::Below, 0.1, 30)];
+        let mut engine = AlertEngine::default();
+        let start = Instant::now();
+
+        assert!(evaluate_at(&mut engine, &rules, Some(0.05), start, 0).is_empty());
+        // the episode ends before the hold time is over
+        assert!(evaluate_at(&mut engine, &rules, Some(0.5), start, 20).is_empty());
+        assert!(evaluate_at(&mut engine, &rules, Some(0.05), start, 25).is_empty());
+        assert!(evaluate_at(&mut engine, &rules, Some(0.05), start, 50).is_empty());
+        assert_eq!(
+            evaluate_at(&mut engine, &rules, Some(0.05), start, 55),
+            [(rules[0].clone(), 0.05)]
+        );
+    }
+
+    #[test]
+    fn test_evaluate_rearm() {
+        let rules = [rule(Comparator::Above, 0.9, 10)];
+        let mut engine = AlertEngine::default();
+        let start = Instant::now();
+
+        assert!(evaluate_at(&mut engine, &rules, Some(0.95), start, 0).is_empty());
+        assert_eq!(
+            evaluate_at(&mut engine, &rules, Some(0.95), start, 10).len(),
+            1
+        );
+
+        // dropping below the threshold re-arms the rule
+        assert!(evaluate_at(&mut engine, &rules, Some(0.5), start, 11).is_empty());
+        assert!(evaluate_at(&mut engine, &rules, Some(0.95), start, 12).is_empty());
+        assert_eq!(
+            evaluate_at(&mut engine, &rules, Some(0.95), start, 22).len(),
+            1
+        );
+
+        // so does the metric vanishing
+        assert!(evaluate_at(&mut engine, &rules, None, start, 23).is_empty());
+        assert!(evaluate_at(&mut engine, &rules, Some(0.95), start, 24).is_empty());
+        assert_eq!(
+            evaluate_at(&mut engine, &rules, Some(0.95), start, 34).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_evaluate_changed_rule() {
+        let mut engine = AlertEngine::default();
+        let start = Instant::now();
+
+        let rules = [rule(Comparator::Above, 0.9, 10)];
+        assert!(evaluate_at(&mut engine, &rules, Some(0.95), start, 0).is_empty());
+
+        // a changed rule starts over
+        let rules = [rule(Comparator::Above, 0.8, 10)];
+        assert!(evaluate_at(&mut engine, &rules, Some(0.95), start, 5).is_empty());
+        assert!(evaluate_at(&mut engine, &rules, Some(0.95), start, 10).is_empty());
+        assert_eq!(
+            evaluate_at(&mut engine, &rules, Some(0.95), start, 15).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_webhook_url() {
+        assert_eq!(
+            WebhookUrl::parse("http://example.com").unwrap(),
+            WebhookUrl {
+                https: false,
+                host: "example.com".into(),
+                target: "/".into()
+            }
+        );
+        assert_eq!(
+            WebhookUrl::parse("https://example.com:8443/hooks/alert?token=abc").unwrap(),
+            WebhookUrl {
+                https: true,
+                host: "example.com:8443".into(),
+                target: "/hooks/alert?token=abc".into()
+            }
+        );
+        assert_eq!(
+            WebhookUrl::parse("http://example.com?token=abc")
+                .unwrap()
+                .target,
+            "/?token=abc"
+        );
+    }
+
+    #[test]
+    fn test_webhook_url_ipv6() {
+        assert_eq!(
+            WebhookUrl::parse("http://[::1]:8123/api/webhook/resources")
+                .unwrap()
+                .host,
+            "[::1]:8123"
+        );
+        assert_eq!(
+            WebhookUrl::parse("http://[fe80::1]/").unwrap().host,
+            "[fe80::1]"
+        );
+    }
+
+    #[test]
+    fn test_webhook_url_invalid() {
+        assert!(WebhookUrl::parse("ftp://example.com/hook").is_err());
+        assert!(WebhookUrl::parse("example.com/hook").is_err());
+    }
+
+    #[test]
+    fn test_webhook_request() {
+        let url = WebhookUrl::parse("http://[::1]:8123/hook?id=1").unwrap();
+        assert_eq!(
+            url.request("{\"value\":0.5}"),
+            "POST /hook?id=1 HTTP/1.1\r\n\
+             Host: [::1]:8123\r\n\
+             User-Agent: Resources\r\n\
+             Content-Type: application/json\r\n\
+             Content-Length: 13\r\n\
+             Connection: close\r\n\
+             \r\n\
+             {\"value\":0.5}"
+        );
+    }
+
+    #[test]
+    fn test_parse_status_line() {
+        assert_eq!(
+            parse_status_line("HTTP/1.1 204 No Content\r\n").unwrap(),
+            204
+        );
+        assert_eq!(parse_status_line("HTTP/1.0 200 OK\r").unwrap(), 200);
+        assert_eq!(parse_status_line("HTTP/1.1 404 Not Found").unwrap(), 404);
+        assert_eq!(parse_status_line("HTTP/1.1 200 ").unwrap(), 200);
+
+        assert!(parse_status_line("").is_err());
+        assert!(parse_status_line("SSH-2.0-OpenSSH_9.6").is_err());
+        assert!(parse_status_line("HTTP/1.1 2000 OK").is_err());
+        assert!(parse_status_line("HTTP/1.1 +20 OK").is_err());
+        assert!(parse_status_line("HTTP/1.1").is_err());
+        assert!(parse_status_line("<html>HTTP/1.1 200 OK").is_err());
+    }
+}
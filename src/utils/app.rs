@@ -4,11 +4,12 @@ use anyhow::{Context, Result};
 use gtk::gio::{Icon, ThemedIcon};
 use hashbrown::{HashMap, HashSet};
 use once_cell::sync::Lazy;
-use process_data::Containerization;
+use process_data::{Containerization, ProcessData};
 
-use crate::i18n::i18n;
+use crate::i18n::{i18n, i18n_f};
 
 use super::process::{Process, ProcessAction, ProcessItem};
+use super::{host_path, steam, IS_FLATPAK};
 
 // Adapted from Mission Center: https://gitlab.com/mission-center-devs/mission-center/
 static DATA_DIRS: Lazy<Vec<PathBuf>> = Lazy::new(|| {
@@ -19,6 +20,11 @@ static DATA_DIRS: Lazy<Vec<PathBuf>> = Lazy::new(|| {
         .map(PathBuf::from)
         .collect();
     data_dirs.push(PathBuf::from(format!("{}/.local/share", home)));
+    // make sure that the host's applications are found even inside the Flatpak sandbox
+    if *IS_FLATPAK {
+        data_dirs.push(host_path("/usr/share"));
+        data_dirs.push(host_path("/usr/local/share"));
+    }
     data_dirs
 });
 
@@ -33,7 +39,9 @@ static KNOWN_EXECUTABLE_NAME_EXCEPTIONS: Lazy<HashMap<String, String>> =
 pub struct AppsContext {
     apps: HashMap<String, App>,
     processes: HashMap<i32, Process>,
-    processes_assigned_to_apps: HashSet<i32>,
+    /// The ID of the application each process has been assigned to, system
+    /// processes are missing
+    app_ids_of_processes: HashMap<i32, String>,
 }
 
 /// Convenience struct for displaying running applications and
@@ -48,6 +56,27 @@ pub struct AppItem {
     pub cpu_time_ratio: f32,
     pub processes_amount: usize,
     pub containerization: Containerization,
+    pub pids: Vec<i32>,
+}
+
+/// Convenience struct for displaying how many resources the processes of a
+/// single user account use altogether
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UserItem {
+    pub uid: u32,
+    pub memory_usage: usize,
+    pub cpu_time_ratio: f32,
+    pub processes_amount: usize,
+}
+
+/// Convenience struct for displaying how many resources the processes of a
+/// single Kubernetes pod use altogether
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PodItem {
+    pub uid: String,
+    pub memory_usage: usize,
+    pub cpu_time_ratio: f32,
+    pub processes_amount: usize,
 }
 
 /// Represents an application installed on the system. It doesn't
@@ -193,7 +222,7 @@ impl App {
     #[must_use]
     pub fn cpu_time_ratio(&self, apps: &AppsContext) -> f32 {
         self.processes_iter(apps)
-            .map(Process::cpu_time_ratio)
+            .map(|process| process.cpu_time_ratio())
             .sum::<f32>()
             .clamp(0.0, 1.0)
     }
@@ -222,7 +251,7 @@ impl AppsContext {
         AppsContext {
             apps,
             processes: HashMap::new(),
-            processes_assigned_to_apps: HashSet::new(),
+            app_ids_of_processes: HashMap::new(),
         }
     }
 
@@ -268,6 +297,36 @@ impl AppsContext {
         }
     }
 
+    /// Returns the ID of the app that represents the Steam game with
+    /// `steam_app_id`. That's the game's desktop shortcut if Steam has created
+    /// one, otherwise an app is made up from the game's Steam manifest.
+    fn steam_game_app(&mut self, steam_app_id: u32) -> String {
+        let rungameid = format!("steam://rungameid/{steam_app_id}");
+        if let Some(app) = self.apps.values().find(|app| {
+            app.commandline
+                .as_ref()
+                .map_or(false, |commandline| commandline.contains(&rungameid))
+        }) {
+            return app.id.clone();
+        }
+
+        let id = format!("steam_app_{steam_app_id}");
+        self.apps.entry(id.clone()).or_insert_with(|| App {
+            processes: Vec::new(),
+            commandline: Some(format!("steam {rungameid}")),
+            display_name: steam::game_name(steam_app_id)
+                .unwrap_or_else(|| i18n_f("Steam Game {}", &[&steam_app_id.to_string()])),
+            description: None,
+            icon: ThemedIcon::from_names(&[
+                &steam::game_icon_name(steam_app_id),
+                "applications-games",
+            ])
+            .into(),
+            id: id.clone(),
+        });
+        id
+    }
+
     pub fn get_process(&self, pid: i32) -> Option<&Process> {
         self.processes.get(&pid)
     }
@@ -303,6 +362,7 @@ impl AppsContext {
             } else {
                 process.data.comm.clone()
             };
+            let (minor_faults_per_second, major_faults_per_second) = process.page_fault_rates();
             ProcessItem {
                 pid: process.data.pid,
                 display_name: full_comm,
@@ -313,10 +373,47 @@ impl AppsContext {
                 containerization: process.data.containerization.clone(),
                 cgroup: process.data.cgroup.clone(),
                 uid: process.data.uid,
+                app_id: self.app_id_of_process(process.data.pid),
+                memory_trend: process.memory_trend(),
+                open_files: process.data.open_files,
+                open_files_limit: process.data.open_files_limit,
+                scheduling_policy: process.data.scheduling_policy,
+                realtime_priority: process.data.realtime_priority,
+                minor_faults_per_second,
+                major_faults_per_second,
             }
         })
     }
 
+    /// Returns the ID of the application the process with the given `pid` has
+    /// been assigned to, or `None` if it's a system process
+    pub fn app_id_of_process(&self, pid: i32) -> Option<String> {
+        self.app_ids_of_processes.get(&pid).cloned()
+    }
+
+    /// Returns the PIDs of the running processes of the application with the
+    /// given `id`. If `id` is `None`, the PIDs of all system processes (i.e.
+    /// processes that don't belong to any application) are returned.
+    pub fn pids_of_app(&self, id: Option<&str>) -> HashSet<i32> {
+        match id {
+            Some(id) => self
+                .all_processes()
+                .filter(|process| {
+                    self.app_ids_of_processes
+                        .get(&process.data.pid)
+                        .map(String::as_str)
+                        == Some(id)
+                })
+                .map(|process| process.data.pid)
+                .collect(),
+            None => self
+                .all_processes()
+                .filter(|process| !self.app_ids_of_processes.contains_key(&process.data.pid))
+                .map(|process| process.data.pid)
+                .collect(),
+        }
+    }
+
     /// Returns a `HashMap` of running graphical applications. For more info,
     /// refer to `AppItem`.
     #[must_use]
@@ -356,6 +453,10 @@ impl AppsContext {
                         cpu_time_ratio: app.cpu_time_ratio(self),
                         processes_amount: app.processes_iter(self).count(),
                         containerization,
+                        pids: app
+                            .processes_iter(self)
+                            .map(|process| process.data.pid)
+                            .collect(),
                     },
                 )
             })
@@ -364,7 +465,7 @@ impl AppsContext {
         let system_cpu_ratio = self
             .all_processes()
             .filter(|process| !app_pids.contains(&process.data.pid) && process.alive)
-            .map(Process::cpu_time_ratio)
+            .map(|process| process.cpu_time_ratio())
             .sum();
 
         let system_memory_usage: usize = self
@@ -384,40 +485,93 @@ impl AppsContext {
                 cpu_time_ratio: system_cpu_ratio,
                 processes_amount: self.processes.len(),
                 containerization: Containerization::None,
+                pids: Vec::new(),
             },
         );
         return_map
     }
 
-    /// Refreshes the statistics about the running applications and processes.
-    pub async fn refresh(&mut self) {
-        let newly_gathered_processes = Process::all().await.unwrap_or_default();
+    /// Returns a `HashMap` of the users that have running processes, indexed
+    /// by their UID. For more info, refer to `UserItem`.
+    #[must_use]
+    pub fn user_items(&self) -> HashMap<u32, UserItem> {
+        let mut user_items: HashMap<u32, UserItem> = HashMap::new();
+
+        for process in self.all_processes().filter(|process| process.alive) {
+            let user_item = user_items
+                .entry(process.data.uid)
+                .or_insert_with(|| UserItem {
+                    uid: process.data.uid,
+                    ..Default::default()
+                });
+            user_item.memory_usage += process.data.memory_usage;
+            user_item.cpu_time_ratio += process.cpu_time_ratio();
+            user_item.processes_amount += 1;
+        }
+
+        user_items
+    }
+
+    /// Returns a `HashMap` of the Kubernetes pods that have running processes
+    /// on this machine, indexed by their UID. For more info, refer to `PodItem`.
+    #[must_use]
+    pub fn pod_items(&self) -> HashMap<String, PodItem> {
+        let mut pod_items: HashMap<String, PodItem> = HashMap::new();
+
+        for process in self.all_processes().filter(|process| process.alive) {
+            let Some(pod_uid) = &process.data.kubernetes_pod else {
+                continue;
+            };
+            let pod_item = pod_items
+                .entry_ref(pod_uid.as_str())
+                .or_insert_with(|| PodItem {
+                    uid: pod_uid.clone(),
+                    ..Default::default()
+                });
+            pod_item.memory_usage += process.data.memory_usage;
+            pod_item.cpu_time_ratio += process.cpu_time_ratio();
+            pod_item.processes_amount += 1;
+        }
+
+        pod_items
+    }
+
+    /// Refreshes the statistics about the running applications and processes
+    /// using `process_data` that has been gathered beforehand, e.g. using
+    /// `Process::all_data()`.
+    pub fn refresh(&mut self, process_data: Vec<ProcessData>) {
+        let newly_gathered_processes = process_data.into_iter().map(Process::from_process_data);
         let mut updated_processes = HashSet::new();
 
         for mut refreshed_process in newly_gathered_processes {
             updated_processes.insert(refreshed_process.data.pid);
             // refresh our old processes
             if let Some(old_process) = self.processes.get_mut(&refreshed_process.data.pid) {
-                old_process.cpu_time_before = old_process.data.cpu_time;
-                old_process.cpu_time_before_timestamp = old_process.data.cpu_time_timestamp;
-                old_process.data = refreshed_process.data.clone();
+                old_process.update(refreshed_process.sampled.data);
             } else {
                 // this is a new process, see if it belongs to a graphical app
 
                 if self
-                    .processes_assigned_to_apps
-                    .contains(&refreshed_process.data.pid)
+                    .app_ids_of_processes
+                    .contains_key(&refreshed_process.data.pid)
                 {
                     continue;
                 }
 
-                if let Some(app_id) = self.app_associated_with_process(&refreshed_process) {
-                    self.processes_assigned_to_apps
-                        .insert(refreshed_process.data.pid);
+                // games started by Steam would otherwise end up as a bunch of
+                // anonymous system processes (or even as part of Steam itself)
+                let app_id = match refreshed_process.data.steam_app_id {
+                    Some(steam_app_id) => Some(self.steam_game_app(steam_app_id)),
+                    None => self.app_associated_with_process(&refreshed_process),
+                };
+
+                if let Some(app_id) = app_id {
                     self.apps
                         .get_mut(&app_id)
                         .unwrap()
                         .add_process(&mut refreshed_process);
+                    self.app_ids_of_processes
+                        .insert(refreshed_process.data.pid, app_id);
                 }
 
                 self.processes
@@ -429,6 +583,7 @@ impl AppsContext {
         for process in self.processes.values_mut() {
             if !updated_processes.contains(&process.data.pid) {
                 process.alive = false;
+                self.app_ids_of_processes.remove(&process.data.pid);
             }
         }
     }
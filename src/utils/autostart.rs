@@ -0,0 +1,150 @@
+use anyhow::{bail, Context, Result};
+use gtk::{
+    gio::{self, prelude::*},
+    glib::{self, VariantDict},
+};
+
+use crate::config::APP_ID;
+use crate::i18n::i18n;
+
+use super::IS_FLATPAK;
+
+const PORTAL_BUS_NAME: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_OBJECT_PATH: &str = "/org/freedesktop/portal/desktop";
+const PORTAL_BACKGROUND_INTERFACE: &str = "org.freedesktop.portal.Background";
+const PORTAL_REQUEST_INTERFACE: &str = "org.freedesktop.portal.Request";
+
+/// The command line option that starts Resources without showing a window
+pub const BACKGROUND_OPTION: &str = "background";
+
+/// Makes Resources start in the background at login, or stops it from doing so.
+/// Outside of Flatpak, this is done using an XDG autostart entry, inside of
+/// Flatpak the Background portal takes care of that.
+///
+/// There's deliberately no systemd user service: Resources needs the graphical
+/// session to be up, which XDG autostart waits for on every desktop, and
+/// systemd-based sessions turn autostart entries into units by themselves.
+///
+/// # Errors
+///
+/// Will return `Err` if the autostart entry can't be written or removed or if
+/// the portal request fails or is denied
+pub async fn set_autostart(enabled: bool) -> Result<()> {
+    if *IS_FLATPAK {
+        request_background_portal(enabled).await
+    } else {
+        set_autostart_entry(enabled)
+    }
+}
+
+fn set_autostart_entry(enabled: bool) -> Result<()> {
+    let autostart_dir = glib::user_config_dir().join("autostart");
+    let path = autostart_dir.join(format!("{APP_ID}.desktop"));
+
+    if !enabled {
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("unable to remove {}", path.display()))?;
+        }
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(&autostart_dir)
+        .with_context(|| format!("unable to create {}", autostart_dir.display()))?;
+
+    let entry = format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name={}\n\
+         Exec=resources --{BACKGROUND_OPTION}\n\
+         Icon={APP_ID}\n\
+         NoDisplay=true\n\
+         X-GNOME-Autostart-enabled=true\n",
+        i18n("Resources")
+    );
+
+    std::fs::write(&path, entry).with_context(|| format!("unable to write {}", path.display()))
+}
+
+async fn request_background_portal(enabled: bool) -> Result<()> {
+    let proxy = gio::DBusProxy::for_bus_future(
+        gio::BusType::Session,
+        gio::DBusProxyFlags::DO_NOT_LOAD_PROPERTIES | gio::DBusProxyFlags::DO_NOT_CONNECT_SIGNALS,
+        None,
+        PORTAL_BUS_NAME,
+        PORTAL_OBJECT_PATH,
+        PORTAL_BACKGROUND_INTERFACE,
+    )
+    .await
+    .context("unable to connect to the Background portal")?;
+
+    let options = VariantDict::new(None);
+    options.insert(
+        "reason",
+        i18n("Record history and check alerts while the window is closed"),
+    );
+    options.insert("autostart", enabled);
+    options.insert(
+        "commandline",
+        vec!["resources".to_string(), format!("--{BACKGROUND_OPTION}")],
+    );
+
+    // the portal answers with a signal of a Request object whose path depends
+    // on our token, so subscribe before asking to not miss the answer
+    let connection = proxy.connection();
+    let token = format!("resources{}", glib::random_int());
+    let sender = connection
+        .unique_name()
+        .context("no unique name on the session bus")?
+        .trim_start_matches(':')
+        .replace('.', "_");
+    let request_path = format!("{PORTAL_OBJECT_PATH}/request/{sender}/{token}");
+    options.insert("handle_token", &token);
+
+    let (response_sender, response_receiver) = async_std::channel::bounded(1);
+    let subscription = connection.signal_subscribe(
+        Some(PORTAL_BUS_NAME),
+        Some(PORTAL_REQUEST_INTERFACE),
+        Some("Response"),
+        Some(&request_path),
+        None,
+        gio::DBusSignalFlags::NO_MATCH_RULE,
+        move |_, _, _, _, _, parameters| {
+            let _ = response_sender.try_send(parameters.clone());
+        },
+    );
+
+    let result = proxy
+        .call_future(
+            "RequestBackground",
+            Some(&glib::Variant::tuple_from_iter([
+                "".to_variant(),
+                options.end(),
+            ])),
+            gio::DBusCallFlags::NONE,
+            -1,
+        )
+        .await
+        .context("unable to request running in the background");
+    let response = match result {
+        Ok(_) => response_receiver
+            .recv()
+            .await
+            .context("no answer from the Background portal"),
+        Err(error) => Err(error),
+    };
+    connection.signal_unsubscribe(subscription);
+    let response = response?;
+
+    // 0 means granted, 1 that the user said no and 2 that something else went wrong
+    if response.child_value(0).get::<u32>() != Some(0) {
+        bail!("running in the background has been denied");
+    }
+
+    let results = VariantDict::new(Some(&response.child_value(1)));
+    if enabled && results.lookup::<bool>("autostart").ok().flatten() == Some(false) {
+        bail!("starting at login has been denied");
+    }
+
+    Ok(())
+}
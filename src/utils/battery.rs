@@ -0,0 +1,48 @@
+use std::path::Path;
+
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+/// Returns the rate at which the batteries of this machine are currently
+/// being drained in W, or `None` if the machine isn't running on battery
+pub fn discharge_rate() -> Option<f64> {
+    let mut discharging = false;
+    let mut rate = 0.0;
+
+    for entry in std::fs::read_dir(POWER_SUPPLY_DIR).ok()?.flatten() {
+        let path = entry.path();
+        if read_trimmed(&path.join("type")).as_deref() != Some("Battery") {
+            continue;
+        }
+        if read_trimmed(&path.join("status")).as_deref() != Some("Discharging") {
+            continue;
+        }
+
+        discharging = true;
+        rate += battery_power(&path).unwrap_or_default();
+    }
+
+    (discharging && rate > 0.0).then_some(rate)
+}
+
+/// The power drawn from a single battery in W. Some batteries report the power
+/// directly in µW, others only report current (µA) and voltage (µV).
+fn battery_power(path: &Path) -> Option<f64> {
+    if let Some(power_now) = read_number(&path.join("power_now")) {
+        return Some(power_now / 1_000_000.0);
+    }
+
+    let current_now = read_number(&path.join("current_now"))?;
+    let voltage_now = read_number(&path.join("voltage_now"))?;
+    Some(current_now * voltage_now / 1_000_000_000_000.0)
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|content| content.trim().to_string())
+}
+
+fn read_number(path: &Path) -> Option<f64> {
+    // some drivers report negative values while discharging
+    read_trimmed(path)?.parse::<f64>().ok().map(f64::abs)
+}
@@ -3,11 +3,9 @@ use async_process::Command;
 use async_std::path::PathBuf;
 use glob::glob;
 use nparse::KVStrToJson;
-use regex::bytes::Regex;
-use serde_json::Value;
 use std::sync::OnceLock;
 
-static PROC_STAT_REGEX: OnceLock<Regex> = OnceLock::new();
+use super::{FLATPAK_SPAWN, IS_FLATPAK};
 
 static ZENPOWER: OnceLock<PathBuf> = OnceLock::new();
 static CORETEMP: OnceLock<PathBuf> = OnceLock::new();
@@ -27,18 +25,93 @@ pub struct CPUInfo {
     pub max_speed: Option<f32>,
 }
 
-async fn lscpu() -> Result<Value> {
-    String::from_utf8(
-        Command::new("lscpu")
-            .env("LC_ALL", "C")
+/// Everything the CPU page needs for a single refresh. Gathered off the GTK
+/// main thread so that reading procfs and sysfs never blocks the UI.
+#[derive(Debug)]
+pub struct CpuData {
+    pub new_total_usage: (u64, u64),
+    /// Time since boot in which a hypervisor ran something else while this
+    /// virtual machine wanted to run, always 0 on bare metal
+    pub new_total_steal: u64,
+    pub new_thread_usages: Vec<(u64, u64)>,
+    pub frequencies: Vec<Option<u64>>,
+    pub idle_states: Vec<Vec<IdleState>>,
+    pub throttle_counts: Vec<Option<u64>>,
+    pub temperature: Result<f32>,
+}
+
+/// An idle state (C-state) of a logical CPU as reported by cpuidle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdleState {
+    pub name: String,
+    /// Total time spent in this state since boot in microseconds
+    pub time: u64,
+}
+
+impl CpuData {
+    pub async fn new(logical_cpus: usize) -> Self {
+        // read /proc/stat once for all threads instead of once per thread
+        let (new_total_usage, new_total_steal, new_thread_usages) =
+            async_std::fs::read_to_string("/proc/stat")
+                .await
+                .map(|proc_stat| parse_proc_stat(&proc_stat, logical_cpus))
+                .unwrap_or_else(|_| ((0, 0), 0, vec![(0, 0); logical_cpus]));
+
+        let mut frequencies = Vec::with_capacity(logical_cpus);
+        let mut idle_states = Vec::with_capacity(logical_cpus);
+        for i in 0..logical_cpus {
+            frequencies.push(get_cpu_freq(i).ok());
+            idle_states.push(get_idle_states(i).unwrap_or_default());
+        }
+        let throttle_counts = get_throttle_counts(logical_cpus);
+
+        let temperature = get_temperature().await;
+
+        Self {
+            new_total_usage,
+            new_total_steal,
+            new_thread_usages,
+            frequencies,
+            idle_states,
+            throttle_counts,
+            temperature,
+        }
+    }
+
+    /// Builds `CpuData` from the contents of a /proc/stat file that doesn't
+    /// necessarily belong to this machine. Frequencies, idle states, throttling
+    /// and the temperature can't be known from that file alone, so they are
+    /// left empty.
+    pub fn from_proc_stat(proc_stat: &str, logical_cpus: usize) -> Self {
+        let (new_total_usage, new_total_steal, new_thread_usages) =
+            parse_proc_stat(proc_stat, logical_cpus);
+
+        Self {
+            new_total_usage,
+            new_total_steal,
+            new_thread_usages,
+            frequencies: vec![None; logical_cpus],
+            idle_states: vec![Vec::new(); logical_cpus],
+            throttle_counts: vec![None; logical_cpus],
+            temperature: Err(anyhow!("temperature is unknown")),
+        }
+    }
+}
+
+async fn lscpu() -> Result<String> {
+    let output = match Command::new("lscpu").env("LC_ALL", "C").output().await {
+        Ok(output) => output,
+        // the Flatpak runtime doesn't necessarily ship lscpu, so try the host's one
+        Err(_) if *IS_FLATPAK => Command::new(FLATPAK_SPAWN)
+            .args(["--host", "--env=LC_ALL=C", "lscpu"])
             .output()
             .await
-            .with_context(|| "unable to run lscpu, is util-linux installed?")?
-            .stdout,
-    )
-    .with_context(|| "unable to parse lscpu output to UTF-8")?
-    .kv_str_to_json()
-    .map_err(|x| anyhow!("{}", x))
+            .with_context(|| "unable to run lscpu on the host, is util-linux installed?")?,
+        Err(err) => {
+            return Err(err).with_context(|| "unable to run lscpu, is util-linux installed?")
+        }
+    };
+    String::from_utf8(output.stdout).with_context(|| "unable to parse lscpu output to UTF-8")
 }
 
 /// Returns a `CPUInfo` struct populated with values gathered from `lscpu`.
@@ -48,44 +121,119 @@ async fn lscpu() -> Result<Value> {
 /// Will return `Err` if the are problems during reading or parsing
 /// of the `lscpu` command
 pub async fn cpu_info() -> Result<CPUInfo> {
-    let lscpu_output = lscpu().await?;
-
-    let vendor_id = lscpu_output["Vendor ID"]
-        .as_str()
-        .map(std::string::ToString::to_string);
-    let model_name = lscpu_output["Model name"]
-        .as_str()
-        .map(std::string::ToString::to_string);
-    let architecture = lscpu_output["Architecture"]
-        .as_str()
-        .map(std::string::ToString::to_string);
-    let logical_cpus = lscpu_output["CPU(s)"]
-        .as_str()
-        .and_then(|x| x.parse::<usize>().ok());
-    let sockets = lscpu_output["Socket(s)"]
-        .as_str()
-        .and_then(|x| x.parse::<usize>().ok());
-    let physical_cpus = lscpu_output["Core(s) per socket"]
-        .as_str()
-        .and_then(|x| x.parse::<usize>().ok().map(|y| y * sockets.unwrap_or(1)));
-    let virtualization = lscpu_output["Virtualization"]
-        .as_str()
-        .map(std::string::ToString::to_string);
-    let max_speed = lscpu_output["CPU max MHz"]
-        .as_str()
-        .and_then(|x| x.parse::<f32>().ok())
-        .map(|y| y * 1_000_000.0);
-
-    Ok(CPUInfo {
-        vendor_id,
-        model_name,
-        architecture,
-        logical_cpus,
-        physical_cpus,
-        sockets,
-        virtualization,
-        max_speed,
-    })
+    CPUInfo::from_lscpu(&lscpu().await?)
+}
+
+impl CPUInfo {
+    /// Parses the (non-JSON) output of `LC_ALL=C lscpu`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `lscpu_output` couldn't be parsed
+    pub fn from_lscpu(lscpu_output: &str) -> Result<Self> {
+        let lscpu_output = lscpu_output
+            .kv_str_to_json()
+            .map_err(|x| anyhow!("{}", x))?;
+
+        let vendor_id = lscpu_output["Vendor ID"]
+            .as_str()
+            .map(std::string::ToString::to_string);
+        let model_name = lscpu_output["Model name"]
+            .as_str()
+            .map(std::string::ToString::to_string);
+        let architecture = lscpu_output["Architecture"]
+            .as_str()
+            .map(std::string::ToString::to_string);
+        let logical_cpus = lscpu_output["CPU(s)"]
+            .as_str()
+            .and_then(|x| x.parse::<usize>().ok());
+        let sockets = lscpu_output["Socket(s)"]
+            .as_str()
+            .and_then(|x| x.parse::<usize>().ok());
+        let physical_cpus = lscpu_output["Core(s) per socket"]
+            .as_str()
+            .and_then(|x| x.parse::<usize>().ok().map(|y| y * sockets.unwrap_or(1)));
+        let virtualization = lscpu_output["Virtualization"]
+            .as_str()
+            .map(std::string::ToString::to_string);
+        let max_speed = lscpu_output["CPU max MHz"]
+            .as_str()
+            .and_then(|x| x.parse::<f32>().ok())
+            .map(|y| y * 1_000_000.0);
+
+        Ok(CPUInfo {
+            vendor_id,
+            model_name,
+            architecture,
+            logical_cpus,
+            physical_cpus,
+            sockets,
+            virtualization,
+            max_speed,
+        })
+    }
+}
+
+/// How the kernel scales the frequency of the CPU, as reported by cpufreq for
+/// the first logical CPU
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrequencyScaling {
+    pub driver: String,
+    /// The operation mode of drivers that have one, e.g. "active" or "passive"
+    /// for intel_pstate and amd-pstate
+    pub driver_mode: Option<String>,
+    pub governor: Option<String>,
+    pub available_governors: Vec<String>,
+    pub energy_performance_preference: Option<String>,
+    pub available_energy_performance_preferences: Vec<String>,
+}
+
+impl FrequencyScaling {
+    /// Reads the current frequency scaling configuration from sysfs
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the CPU has no cpufreq driver
+    pub fn current() -> Result<Self> {
+        let cpufreq = std::path::Path::new("/sys/devices/system/cpu/cpu0/cpufreq");
+        let read = |file: &str| {
+            std::fs::read_to_string(cpufreq.join(file))
+                .ok()
+                .map(|content| content.trim().to_string())
+                .filter(|content| !content.is_empty())
+        };
+        let read_list = |file: &str| {
+            read(file)
+                .map(|content| content.split_whitespace().map(str::to_string).collect())
+                .unwrap_or_default()
+        };
+
+        let driver = read("scaling_driver").context("no cpufreq driver in use")?;
+
+        // intel_cpufreq is what intel_pstate calls itself in passive mode
+        let status_path = if driver.starts_with("intel") {
+            Some("/sys/devices/system/cpu/intel_pstate/status")
+        } else if driver.starts_with("amd-pstate") {
+            Some("/sys/devices/system/cpu/amd_pstate/status")
+        } else {
+            None
+        };
+        let driver_mode = status_path
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|status| status.trim().to_string())
+            .filter(|status| !status.is_empty());
+
+        Ok(Self {
+            driver,
+            driver_mode,
+            governor: read("scaling_governor"),
+            available_governors: read_list("scaling_available_governors"),
+            energy_performance_preference: read("energy_performance_preference"),
+            available_energy_performance_preferences: read_list(
+                "energy_performance_available_preferences",
+            ),
+        })
+    }
 }
 
 /// Returns the frequency of the given CPU `core`
@@ -105,30 +253,134 @@ pub fn get_cpu_freq(core: usize) -> Result<u64> {
     .map(|x| x * 1000)
 }
 
-fn parse_proc_stat_line(line: &[u8]) -> Result<(u64, u64)> {
-    let captures = PROC_STAT_REGEX
-        .get_or_init(|| Regex::new(r"cpu[0-9]* *(?P<user>[0-9]*) *(?P<nice>[0-9]*) *(?P<system>[0-9]*) *(?P<idle>[0-9]*) *(?P<iowait>[0-9]*) *(?P<irq>[0-9]*) *(?P<softirq>[0-9]*) *(?P<steal>[0-9]*) *(?P<guest>[0-9]*) *(?P<guest_nice>[0-9]*)").unwrap())
-        .captures(line)
-        .ok_or_else(|| anyhow!("using regex to parse /proc/stat failed"))?;
-    let idle_time = captures
-        .name("idle")
-        .and_then(|x| String::from_utf8_lossy(x.as_bytes()).parse::<u64>().ok())
-        .ok_or_else(|| anyhow!("unable to get idle time"))?
-        + captures
-            .name("iowait")
-            .and_then(|x| String::from_utf8_lossy(x.as_bytes()).parse::<u64>().ok())
-            .ok_or_else(|| anyhow!("unable to get iowait time"))?;
-    let sum = captures
+/// Returns the idle states (C-states) of the given CPU `core` in the order the
+/// kernel numbers them, i.e. from the shallowest to the deepest state
+///
+/// # Errors
+///
+/// Will return `Err` if cpuidle isn't available for `core`
+pub fn get_idle_states(core: usize) -> Result<Vec<IdleState>> {
+    let cpuidle = std::path::Path::new("/sys/devices/system/cpu")
+        .join(format!("cpu{core}"))
+        .join("cpuidle");
+
+    let mut idle_states = Vec::new();
+    for state in 0.. {
+        let state_path = cpuidle.join(format!("state{state}"));
+        let Ok(name) = std::fs::read_to_string(state_path.join("name")) else {
+            break;
+        };
+        let time = std::fs::read_to_string(state_path.join("time"))
+            .with_context(|| format!("unable to read time of idle state {state} for core {core}"))?
+            .trim()
+            .parse::<u64>()
+            .with_context(|| "can't parse idle state time to u64")?;
+        idle_states.push(IdleState {
+            name: name.trim().to_string(),
+            time,
+        });
+    }
+
+    if idle_states.is_empty() {
+        bail!("no idle states for core {core}");
+    }
+
+    Ok(idle_states)
+}
+
+/// Returns how often the given CPU `core` has been thermally throttled since
+/// boot, counting throttling of both the core itself and its whole package.
+/// These counters are only provided by Intel CPUs.
+///
+/// # Errors
+///
+/// Will return `Err` if there are no throttle counters for `core`
+pub fn get_throttle_count(core: usize) -> Result<u64> {
+    let thermal_throttle = std::path::Path::new("/sys/devices/system/cpu")
+        .join(format!("cpu{core}"))
+        .join("thermal_throttle");
+
+    let counts: Vec<u64> = ["core_throttle_count", "package_throttle_count"]
+        .iter()
+        .filter_map(|file| std::fs::read_to_string(thermal_throttle.join(file)).ok())
+        .filter_map(|count| count.trim().parse().ok())
+        .collect();
+
+    if counts.is_empty() {
+        bail!("no thermal throttle counters for core {core}");
+    }
+
+    Ok(counts.iter().sum())
+}
+
+/// Returns the throttle counters of all `logical_cpus`, see `get_throttle_count`
+pub fn get_throttle_counts(logical_cpus: usize) -> Vec<Option<u64>> {
+    (0..logical_cpus)
+        .map(|core| get_throttle_count(core).ok())
+        .collect()
+}
+
+/// Returns whether any CPU has been thermally throttled between reading
+/// `old_counts` and `new_counts`
+pub fn is_throttling(old_counts: &[Option<u64>], new_counts: &[Option<u64>]) -> bool {
+    old_counts
         .iter()
-        .skip(1)
-        .flat_map(|cap| {
-            cap.and_then(|x| String::from_utf8_lossy(x.as_bytes()).parse::<u64>().ok())
-                .ok_or_else(|| anyhow!("unable to sum CPU times from /proc/stat"))
+        .zip(new_counts)
+        .any(|(old_count, new_count)| match (old_count, new_count) {
+            (Some(old_count), Some(new_count)) => new_count > old_count,
+            _ => false,
         })
-        .sum();
+}
+
+/// Parses a "cpu" line of /proc/stat without allocating. The tuple's layout is
+/// `(idle_time, total_time)`.
+fn parse_proc_stat_line(line: &str) -> Result<(u64, u64)> {
+    let mut idle_time = 0;
+    let mut sum = 0;
+
+    // the fields are user, nice, system, idle, iowait, irq, softirq, steal,
+    // guest and guest_nice, in that order
+    for (i, field) in line.split_ascii_whitespace().skip(1).take(10).enumerate() {
+        let value = field
+            .parse::<u64>()
+            .with_context(|| "unable to sum CPU times from /proc/stat")?;
+        if i == 3 || i == 4 {
+            idle_time += value;
+        }
+        sum += value;
+    }
+
     Ok((idle_time, sum))
 }
 
+/// Returns the steal time from a "cpu" line of /proc/stat
+fn parse_proc_stat_steal(line: &str) -> Result<u64> {
+    line.split_ascii_whitespace()
+        .nth(8)
+        .context("no steal time in /proc/stat")?
+        .parse::<u64>()
+        .context("unable to parse steal time from /proc/stat")
+}
+
+/// Parses the combined usage, the combined steal time and the per-thread
+/// usages from the contents of /proc/stat
+fn parse_proc_stat(proc_stat: &str, logical_cpus: usize) -> ((u64, u64), u64, Vec<(u64, u64)>) {
+    let mut cpu_lines = proc_stat.lines().filter(|line| line.starts_with("cpu"));
+
+    let total_line = cpu_lines.next().unwrap_or_default();
+    let total_usage = parse_proc_stat_line(total_line).unwrap_or((0, 0));
+    // kernels older than 2.6.11 don't report steal time
+    let total_steal = parse_proc_stat_steal(total_line).unwrap_or(0);
+
+    let mut thread_usages: Vec<(u64, u64)> = cpu_lines
+        .take(logical_cpus)
+        .map(|line| parse_proc_stat_line(line).unwrap_or((0, 0)))
+        .collect();
+    thread_usages.resize(logical_cpus, (0, 0));
+
+    (total_usage, total_steal, thread_usages)
+}
+
 async fn get_proc_stat(core: Option<usize>) -> Result<String> {
     // the combined stats are in line 0, the other cores are in the following lines,
     // since our `core` argument starts with 0, we must add 1 to it if it's not `None`.
@@ -136,13 +388,13 @@ async fn get_proc_stat(core: Option<usize>) -> Result<String> {
     let proc_stat_raw = async_std::fs::read_to_string("/proc/stat")
         .await
         .with_context(|| "unable to read /proc/stat")?;
-    let mut proc_stat = proc_stat_raw.split('\n').collect::<Vec<&str>>();
-    proc_stat.retain(|x| x.starts_with("cpu"));
     // return an `Error` if `core` is greater than the number of cores
-    if selected_line_number > proc_stat.len() {
-        bail!("`core` argument greater than amount of cores")
-    }
-    Ok(proc_stat[selected_line_number].to_string())
+    proc_stat_raw
+        .lines()
+        .filter(|x| x.starts_with("cpu"))
+        .nth(selected_line_number)
+        .map(str::to_string)
+        .with_context(|| "`core` argument greater than amount of cores")
 }
 
 /// Returns the CPU usage of either all cores combined (if supplied argument is `None`),
@@ -155,7 +407,7 @@ async fn get_proc_stat(core: Option<usize>) -> Result<String> {
 /// Will return `Err` if the are problems during reading or parsing
 /// of /proc/stat
 pub async fn get_cpu_usage(core: Option<usize>) -> Result<(u64, u64)> {
-    parse_proc_stat_line(get_proc_stat(core).await?.as_bytes())
+    parse_proc_stat_line(&get_proc_stat(core).await?)
 }
 
 /// Returns the CPU temperature.
@@ -0,0 +1,364 @@
+//! Synthetic but plausible data for `--demo`, which feeds the hardware pages
+//! and the process list without looking at the actual system. This is useful
+//! for screenshots and for working on pages for hardware that isn't there,
+//! e.g. a GPU or a second drive.
+//!
+//! Pages without a synthetic counterpart, like sessions, services, sensors or
+//! kernel modules, are hidden in demo mode and nothing is read for them.
+//!
+//! All values are smooth functions of the time since demo mode has been
+//! enabled, so there's no state to keep and the data can be generated on any
+//! thread.
+
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::{Instant, SystemTime};
+
+use process_data::ProcessData;
+
+use super::cpu::CpuData;
+use super::drive::{Drive, DriveData, DriveType, IoScheduler};
+use super::gpu::{GpuData, GPU};
+use super::memory::{MemoryData, MemoryDevice};
+use super::network::{InterfaceType, NetworkData, NetworkInterface};
+
+/// The command line option that starts Resources in demo mode
+pub const DEMO_OPTION: &str = "demo";
+
+/// The clock ticks per second that /proc uses for CPU times
+const USER_HZ: f64 = 100.0;
+
+const MIB: f64 = 1024.0 * 1024.0;
+const GIB: f64 = 1024.0 * MIB;
+
+const DRIVE_SECTOR_SIZE: u64 = 512;
+const DRIVE_CAPACITY: u64 = 1_000_204_886_016;
+
+static START: OnceLock<Instant> = OnceLock::new();
+
+struct DemoProcess {
+    pid: i32,
+    uid: u32,
+    comm: &'static str,
+    commandline: &'static str,
+    /// The app ID the process is assigned to if that app is installed
+    app_id: Option<&'static str>,
+    /// Average share of a single CPU
+    cpu: f64,
+    memory_mib: f64,
+}
+
+const PROCESSES: &[DemoProcess] = &[
+    DemoProcess {
+        pid: 1,
+        uid: 0,
+        comm: "systemd",
+        commandline: "/usr/lib/systemd/systemd --switched-root --system",
+        app_id: None,
+        cpu: 0.002,
+        memory_mib: 14.0,
+    },
+    DemoProcess {
+        pid: 812,
+        uid: 0,
+        comm: "NetworkManager",
+        commandline: "/usr/sbin/NetworkManager --no-daemon",
+        app_id: None,
+        cpu: 0.004,
+        memory_mib: 19.0,
+    },
+    DemoProcess {
+        pid: 1604,
+        uid: 1000,
+        comm: "gnome-shell",
+        commandline: "/usr/bin/gnome-shell",
+        app_id: None,
+        cpu: 0.08,
+        memory_mib: 310.0,
+    },
+    DemoProcess {
+        pid: 1688,
+        uid: 1000,
+        comm: "pipewire",
+        commandline: "/usr/bin/pipewire",
+        app_id: None,
+        cpu: 0.01,
+        memory_mib: 22.0,
+    },
+    DemoProcess {
+        pid: 2310,
+        uid: 1000,
+        comm: "firefox",
+        commandline: "/usr/lib64/firefox/firefox",
+        app_id: Some("org.mozilla.firefox"),
+        cpu: 0.22,
+        memory_mib: 1240.0,
+    },
+    DemoProcess {
+        pid: 2377,
+        uid: 1000,
+        comm: "Isolated Web Co",
+        commandline: "/usr/lib64/firefox/firefox -contentproc -isForBrowser",
+        app_id: Some("org.mozilla.firefox"),
+        cpu: 0.12,
+        memory_mib: 420.0,
+    },
+    DemoProcess {
+        pid: 2954,
+        uid: 1000,
+        comm: "nautilus",
+        commandline: "/usr/bin/nautilus --gapplication-service",
+        app_id: Some("org.gnome.Nautilus"),
+        cpu: 0.005,
+        memory_mib: 160.0,
+    },
+    DemoProcess {
+        pid: 3121,
+        uid: 1000,
+        comm: "gnome-text-edit",
+        commandline: "/usr/bin/gnome-text-editor",
+        app_id: Some("org.gnome.TextEditor"),
+        cpu: 0.01,
+        memory_mib: 95.0,
+    },
+    DemoProcess {
+        pid: 3480,
+        uid: 1000,
+        comm: "cargo",
+        commandline: "cargo build --release",
+        app_id: None,
+        cpu: 0.6,
+        memory_mib: 540.0,
+    },
+];
+
+/// Switches to demo data for the rest of the session
+pub fn enable() {
+    START.get_or_init(Instant::now);
+}
+
+pub fn is_enabled() -> bool {
+    START.get().is_some()
+}
+
+fn elapsed() -> f64 {
+    START
+        .get()
+        .map_or(0.0, |start| start.elapsed().as_secs_f64())
+}
+
+/// A value that oscillates around `mean` by `amplitude` every `period` seconds
+fn wave(t: f64, mean: f64, amplitude: f64, period: f64, phase: f64) -> f64 {
+    mean + amplitude * (TAU * t / period + phase).sin()
+}
+
+/// The sum of `wave` from 0 to `t`, for counters like CPU times that only ever
+/// grow. `amplitude` must not exceed `mean`.
+fn integral(t: f64, mean: f64, amplitude: f64, period: f64, phase: f64) -> f64 {
+    mean * t + amplitude * period / TAU * (phase.cos() - (TAU * t / period + phase).cos())
+}
+
+pub fn cpu_data(logical_cpus: usize) -> CpuData {
+    let t = elapsed();
+    let total_ticks = t * USER_HZ;
+
+    // each thread is given its own rhythm so that the graphs don't look alike
+    let new_thread_usages: Vec<(u64, u64)> = (0..logical_cpus)
+        .map(|i| {
+            let busy_ticks = integral(t, 0.35, 0.3, 20.0 + 7.0 * i as f64, i as f64) * USER_HZ;
+            ((total_ticks - busy_ticks) as u64, total_ticks as u64)
+        })
+        .collect();
+
+    let new_total_usage = new_thread_usages
+        .iter()
+        .fold((0, 0), |(idle, sum), (thread_idle, thread_sum)| {
+            (idle + thread_idle, sum + thread_sum)
+        });
+
+    CpuData {
+        new_total_usage,
+        new_total_steal: 0,
+        new_thread_usages,
+        frequencies: (0..logical_cpus)
+            .map(|i| Some(wave(t, 3.2e9, 0.8e9, 9.0 + i as f64, i as f64) as u64))
+            .collect(),
+        idle_states: vec![Vec::new(); logical_cpus],
+        throttle_counts: vec![None; logical_cpus],
+        temperature: Ok(wave(t, 58.0, 9.0, 45.0, 0.0) as f32),
+    }
+}
+
+pub fn memory_data() -> MemoryData {
+    let t = elapsed();
+
+    let total_mem = 16.0 * GIB;
+    let total_swap = 8.0 * GIB;
+
+    MemoryData {
+        total_mem: total_mem as usize,
+        available_mem: (total_mem - wave(t, 7.5 * GIB, 1.5 * GIB, 120.0, 0.0)) as usize,
+        total_swap: total_swap as usize,
+        free_swap: (total_swap - wave(t, 600.0 * MIB, 200.0 * MIB, 300.0, 1.0)) as usize,
+        ksm: None,
+    }
+}
+
+/// The name to show for `uid` instead of the name of whoever actually has it
+pub fn user_name(uid: u32) -> String {
+    match uid {
+        0 => "root".to_string(),
+        _ => "demo".to_string(),
+    }
+}
+
+/// Two DIMMs in four slots
+pub fn memory_devices() -> Vec<MemoryDevice> {
+    (0..4)
+        .map(|slot| MemoryDevice {
+            speed: Some(5600),
+            form_factor: "DIMM".to_string(),
+            r#type: "DDR5".to_string(),
+            type_detail: "Synchronous".to_string(),
+            installed: slot % 2 == 1,
+        })
+        .collect()
+}
+
+pub fn process_data() -> Vec<ProcessData> {
+    let t = elapsed();
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+
+    PROCESSES
+        .iter()
+        .enumerate()
+        .map(|(i, process)| {
+            let phase = i as f64;
+            let mut data = ProcessData::default();
+            data.pid = process.pid;
+            data.uid = process.uid;
+            data.comm = process.comm.to_string();
+            data.commandline = process.commandline.to_string();
+            data.cgroup = process.app_id.map(str::to_string);
+            // the same unit as utime and stime in /proc/<pid>/stat
+            data.cpu_time = (integral(t, process.cpu, process.cpu * 0.8, 15.0 + 4.0 * phase, phase)
+                * USER_HZ) as u64;
+            data.cpu_time_timestamp = timestamp;
+            data.memory_usage = (wave(
+                t,
+                process.memory_mib,
+                process.memory_mib * 0.1,
+                60.0 + 10.0 * phase,
+                phase,
+            ) * MIB) as usize;
+            data.minor_faults =
+                integral(t, 500.0 * process.cpu, 400.0 * process.cpu, 20.0, phase) as u64;
+            data.major_faults =
+                integral(t, 2.0 * process.cpu, 2.0 * process.cpu, 45.0, phase) as u64;
+            data.open_files = Some(20 + 7 * i);
+            data.open_files_limit = Some(1024);
+            data
+        })
+        .collect()
+}
+
+pub fn gpu() -> GPU {
+    let mut gpu = GPU::default();
+    gpu.pci_slot = "0000:03:00.0".to_string();
+    gpu.driver = "amdgpu".to_string();
+    gpu
+}
+
+pub fn gpu_data() -> GpuData {
+    let t = elapsed();
+
+    GpuData {
+        usage: Ok(wave(t, 45.0, 40.0, 30.0, 0.0).round() as isize),
+        total_vram: Ok((8.0 * GIB) as isize),
+        used_vram: Ok(wave(t, 3.0 * GIB, 1.0 * GIB, 90.0, 2.0) as isize),
+        temp: Ok(wave(t, 62.0, 12.0, 30.0, 0.5)),
+        power_usage: Ok(wave(t, 120.0, 90.0, 30.0, 0.0)),
+        gpu_speed: Ok(wave(t, 1.9e9, 0.6e9, 30.0, 0.0)),
+        vram_speed: Ok(1.0e9),
+        power_cap: Ok(220.0),
+        power_cap_max: Ok(250.0),
+    }
+}
+
+pub fn drive() -> Drive {
+    // a block device that doesn't exist, so that nothing is read from the
+    // actual drives, e.g. SMART data
+    Drive {
+        model: Some("Demo NVMe SSD".to_string()),
+        drive_type: DriveType::Nvme,
+        block_device: "demo0".to_string(),
+        sys_fs_path: PathBuf::from("/sys/block/demo0"),
+    }
+}
+
+pub fn drive_capacity() -> u64 {
+    DRIVE_CAPACITY
+}
+
+pub fn drive_data() -> DriveData {
+    let t = elapsed();
+    let sector_size = DRIVE_SECTOR_SIZE as f64;
+
+    // ticks are milliseconds spent doing I/O
+    let disk_stats: HashMap<String, usize> = [
+        ("read_ticks", integral(t, 0.08, 0.07, 25.0, 0.0) * 1000.0),
+        ("write_ticks", integral(t, 0.05, 0.05, 40.0, 2.0) * 1000.0),
+        (
+            "read_sectors",
+            integral(t, 40.0 * MIB, 35.0 * MIB, 25.0, 0.0) / sector_size,
+        ),
+        (
+            "write_sectors",
+            integral(t, 15.0 * MIB, 15.0 * MIB, 40.0, 2.0) / sector_size,
+        ),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value as usize))
+    .collect();
+
+    DriveData {
+        disk_stats,
+        capacity: DRIVE_CAPACITY / DRIVE_SECTOR_SIZE,
+        sector_size: DRIVE_SECTOR_SIZE,
+        writable: true,
+        removable: false,
+        io_scheduler: Some(IoScheduler {
+            active: "none".to_string(),
+            available: ["none", "mq-deadline", "kyber", "bfq"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        }),
+    }
+}
+
+pub fn network_interface() -> NetworkInterface {
+    let mut interface = NetworkInterface::default();
+    interface.interface_name = "demo0".into();
+    interface.interface_type = InterfaceType::Wlan;
+    interface.driver_name = Some("iwlwifi".to_string());
+    interface.vendor = Some("Demo Wireless".to_string());
+    interface.device_name = Some("Wi-Fi 6 Adapter".to_string());
+    interface.hw_address = Some("00:00:5e:00:53:01".to_string());
+    interface.sysfs_path = PathBuf::from("/sys/class/net/demo0");
+    interface
+}
+
+pub fn network_data() -> NetworkData {
+    let t = elapsed();
+
+    NetworkData {
+        received_bytes: integral(t, 2.0 * MIB, 1.8 * MIB, 30.0, 0.0) as usize,
+        sent_bytes: integral(t, 300.0 * 1024.0, 250.0 * 1024.0, 50.0, 1.0) as usize,
+    }
+}
@@ -0,0 +1,95 @@
+use anyhow::{Context, Result};
+use gtk::gio::{self, prelude::*};
+
+const GAMEMODE_BUS_NAME: &str = "com.feralinteractive.GameMode";
+const GAMEMODE_OBJECT_PATH: &str = "/com/feralinteractive/GameMode";
+const GAMEMODE_INTERFACE: &str = "com.feralinteractive.GameMode";
+const GAMEMODE_GAME_INTERFACE: &str = "com.feralinteractive.GameMode.Game";
+
+/// A game that has registered itself with Feral's GameMode
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GameModeGame {
+    pub pid: i32,
+    pub executable: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GameModeStatus {
+    pub client_count: i32,
+    pub games: Vec<GameModeGame>,
+}
+
+impl GameModeStatus {
+    /// Asks the GameMode daemon about its current state. Returns `Ok(None)` if
+    /// GameMode isn't running.
+    ///
+    /// This uses GIO's D-Bus machinery and therefore has to be awaited on the
+    /// main context.
+    pub async fn current() -> Result<Option<Self>> {
+        let proxy = gio::DBusProxy::for_bus_future(
+            gio::BusType::Session,
+            gio::DBusProxyFlags::DO_NOT_AUTO_START | gio::DBusProxyFlags::DO_NOT_CONNECT_SIGNALS,
+            None,
+            GAMEMODE_BUS_NAME,
+            GAMEMODE_OBJECT_PATH,
+            GAMEMODE_INTERFACE,
+        )
+        .await
+        .context("unable to connect to GameMode")?;
+
+        if proxy.name_owner().is_none() {
+            return Ok(None);
+        }
+
+        let client_count = proxy
+            .cached_property("ClientCount")
+            .and_then(|variant| variant.get::<i32>())
+            .unwrap_or(0);
+
+        let reply = proxy
+            .call_future("ListGames", None, gio::DBusCallFlags::NONE, -1)
+            .await
+            .context("unable to list GameMode's games")?;
+
+        // ListGames returns a(io), i.e. the PIDs and object paths of the games
+        let games_variant = reply.child_value(0);
+        let mut games = Vec::with_capacity(games_variant.n_children());
+        for game in games_variant.iter() {
+            let Some(pid) = game.child_value(0).get::<i32>() else {
+                continue;
+            };
+            let object_path = game.child_value(1);
+            let executable = match object_path.str() {
+                Some(object_path) => Self::game_executable(object_path).await,
+                None => None,
+            };
+            games.push(GameModeGame { pid, executable });
+        }
+
+        Ok(Some(Self {
+            client_count,
+            games,
+        }))
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.client_count > 0
+    }
+
+    async fn game_executable(object_path: &str) -> Option<String> {
+        let proxy = gio::DBusProxy::for_bus_future(
+            gio::BusType::Session,
+            gio::DBusProxyFlags::DO_NOT_AUTO_START | gio::DBusProxyFlags::DO_NOT_CONNECT_SIGNALS,
+            None,
+            GAMEMODE_BUS_NAME,
+            object_path,
+            GAMEMODE_GAME_INTERFACE,
+        )
+        .await
+        .ok()?;
+
+        proxy
+            .cached_property("Executable")
+            .and_then(|variant| variant.str().map(str::to_string))
+    }
+}
@@ -0,0 +1,75 @@
+use anyhow::Result;
+
+use super::journal::{self, JournalEntry};
+
+/// Kernel messages of amdgpu, i915 and xe that mark a GPU hang or reset, e.g.
+/// "amdgpu 0000:03:00.0: amdgpu: ring gfx_0.0.0 timeout" or
+/// "i915 0000:00:02.0: [drm] GPU HANG: ecode 12:1:85dffffb, in Xorg [1234]"
+const PATTERN: &str = "GPU reset begin|ring [^ ]+ timeout|GPU HANG|Resetting chip|Engine reset";
+
+/// How many past events are loaded initially
+const HISTORY_LENGTH: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuResetKind {
+    /// The GPU stopped responding
+    Hang,
+    /// The driver reset the GPU or one of its engines to recover
+    Reset,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpuReset {
+    pub kind: GpuResetKind,
+    /// Microseconds since the UNIX epoch
+    pub timestamp: i64,
+    /// The PCI slot of the affected GPU, e.g. "0000:03:00.0"
+    pub pci_slot: String,
+    /// The message of the driver without the device prefix
+    pub message: String,
+}
+
+impl GpuReset {
+    fn from_entry(entry: &JournalEntry) -> Option<Self> {
+        // messages of drivers are prefixed with the driver and device, e.g.
+        // "amdgpu 0000:03:00.0: "
+        let (prefix, message) = entry.message.split_once(": ")?;
+        let pci_slot = prefix.split_whitespace().nth(1)?.to_string();
+
+        let lowercase = message.to_lowercase();
+        let kind = if lowercase.contains("gpu hang") || lowercase.contains("timeout") {
+            GpuResetKind::Hang
+        } else {
+            GpuResetKind::Reset
+        };
+
+        Some(Self {
+            kind,
+            timestamp: entry.timestamp,
+            pci_slot,
+            message: message.trim().to_string(),
+        })
+    }
+}
+
+/// Looks for GPU hangs and resets in the kernel log of all boots, oldest first.
+/// If `after_cursor` is given, only events after it are returned, otherwise
+/// the latest ones. Also returns the cursor to pass next time.
+///
+/// # Errors
+///
+/// Will return `Err` if the journal can't be read, e.g. because the user isn't
+/// allowed to read kernel messages
+pub async fn gpu_resets(after_cursor: Option<&str>) -> Result<(Vec<GpuReset>, Option<String>)> {
+    let entries = journal::kernel_entries(PATTERN, HISTORY_LENGTH, after_cursor).await?;
+
+    let cursor = entries
+        .last()
+        .map(|entry| entry.cursor.clone())
+        .or_else(|| after_cursor.map(str::to_string));
+
+    Ok((
+        entries.iter().filter_map(GpuReset::from_entry).collect(),
+        cursor,
+    ))
+}
@@ -0,0 +1,144 @@
+//! Client side of `resources-helper`, a small D-Bus activated service that
+//! runs as root and performs the few things the GUI can't do on its own.
+//!
+//! Every method of the helper is bound to a `Capability`, and every
+//! capability is guarded by its own polkit action. The GUI never runs
+//! privileged code itself, it only asks the helper to do so.
+
+use anyhow::{bail, Context, Result};
+use gtk::{
+    gio,
+    glib::{self, ToVariant},
+};
+use process_data::SchedulingPolicy;
+
+use super::helper_interface::{Capability, HELPER_BUS_NAME, HELPER_INTERFACE, HELPER_OBJECT_PATH};
+
+/// Waiting for the user to authenticate can take a while, so we don't want
+/// D-Bus' default timeout of 25 seconds here.
+const CALL_TIMEOUT: i32 = i32::MAX;
+
+async fn proxy() -> Result<gio::DBusProxy> {
+    gio::DBusProxy::for_bus_future(
+        gio::BusType::System,
+        gio::DBusProxyFlags::DO_NOT_LOAD_PROPERTIES | gio::DBusProxyFlags::DO_NOT_CONNECT_SIGNALS,
+        None,
+        HELPER_BUS_NAME,
+        HELPER_OBJECT_PATH,
+        HELPER_INTERFACE,
+    )
+    .await
+    .context("unable to connect to resources-helper")
+}
+
+async fn call(method_name: &str, parameters: Option<&glib::Variant>) -> Result<glib::Variant> {
+    proxy()
+        .await?
+        .call_future(
+            method_name,
+            parameters,
+            gio::DBusCallFlags::ALLOW_INTERACTIVE_AUTHORIZATION,
+            CALL_TIMEOUT,
+        )
+        .await
+        .with_context(|| format!("calling {method_name} on resources-helper failed"))
+}
+
+/// Returns the read and written bytes of any process, including those of
+/// other users
+pub async fn process_io(pid: i32) -> Result<(u64, u64)> {
+    let reply = call(
+        Capability::ProcessIo.method_name(),
+        Some(&(pid,).to_variant()),
+    )
+    .await?;
+    reply
+        .get::<(u64, u64)>()
+        .context("unexpected reply from resources-helper")
+}
+
+/// Returns the output of `smartctl --json --all` for the block device
+/// `device` (e.g. `sda`)
+pub async fn smart_data<S: AsRef<str>>(device: S) -> Result<String> {
+    let reply = call(
+        Capability::SmartData.method_name(),
+        Some(&(device.as_ref(),).to_variant()),
+    )
+    .await?;
+    reply
+        .get::<(String,)>()
+        .map(|(json,)| json)
+        .context("unexpected reply from resources-helper")
+}
+
+/// Drops the kernel's caches, `level` has the same meaning as in
+/// /proc/sys/vm/drop_caches (1: page cache, 2: dentries and inodes, 3: both)
+pub async fn drop_caches(level: u32) -> Result<()> {
+    if !(1..=3).contains(&level) {
+        bail!("invalid drop_caches level {level}");
+    }
+    call(
+        Capability::DropCaches.method_name(),
+        Some(&(level,).to_variant()),
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Sets the cpufreq governor of logical CPU `cpu`
+pub async fn set_governor<S: AsRef<str>>(cpu: u32, governor: S) -> Result<()> {
+    call(
+        Capability::SetGovernor.method_name(),
+        Some(&(cpu, governor.as_ref()).to_variant()),
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Sets the energy-performance preference (EPP) of logical CPU `cpu`, e.g.
+/// "balance_power"
+pub async fn set_energy_performance_preference<S: AsRef<str>>(
+    cpu: u32,
+    preference: S,
+) -> Result<()> {
+    call(
+        Capability::SetEnergyPerformancePreference.method_name(),
+        Some(&(cpu, preference.as_ref()).to_variant()),
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Writes `value` to the kernel parameter at `path`, which is relative to
+/// /proc/sys (e.g. "vm/swappiness")
+pub async fn set_sysctl<S: AsRef<str>, T: AsRef<str>>(path: S, value: T) -> Result<()> {
+    call(
+        Capability::SetSysctl.method_name(),
+        Some(&(path.as_ref(), value.as_ref()).to_variant()),
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Changes the scheduling policy and realtime priority of the process with
+/// `pid`. `priority` has to be 1 to 99 for realtime policies and 0 otherwise.
+/// `SchedulingPolicy::Deadline` needs further parameters and isn't supported.
+pub async fn set_scheduling(pid: i32, policy: SchedulingPolicy, priority: u32) -> Result<()> {
+    call(
+        Capability::SetScheduling.method_name(),
+        Some(&(pid, policy.name(), priority).to_variant()),
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Sets the block I/O scheduler of the block device `device` (e.g. `sda`) to
+/// `scheduler`, e.g. "bfq"
+pub async fn set_io_scheduler<S: AsRef<str>, T: AsRef<str>>(device: S, scheduler: T) -> Result<()> {
+    call(
+        Capability::SetIoScheduler.method_name(),
+        Some(&(device.as_ref(), scheduler.as_ref()).to_variant()),
+    )
+    .await
+    .map(|_| ())
+}
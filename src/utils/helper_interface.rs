@@ -0,0 +1,107 @@
+//! The D-Bus interface of `resources-helper` that is shared between the
+//! helper itself and its client in `utils::helper`.
+//!
+//! The helper runs as root, so this module must not pull in anything but the
+//! standard library and strum. The helper binary includes it directly instead
+//! of linking the GUI library.
+
+use strum_macros::Display;
+
+pub const HELPER_BUS_NAME: &str = "net.nokyan.Resources.Helper";
+pub const HELPER_OBJECT_PATH: &str = "/net/nokyan/Resources/Helper";
+pub const HELPER_INTERFACE: &str = "net.nokyan.Resources.Helper";
+
+pub const HELPER_INTROSPECTION_XML: &str = r#"
+<node>
+  <interface name="net.nokyan.Resources.Helper">
+    <method name="GetProcessIo">
+      <arg type="i" name="pid" direction="in"/>
+      <arg type="t" name="read_bytes" direction="out"/>
+      <arg type="t" name="write_bytes" direction="out"/>
+    </method>
+    <method name="GetSmartData">
+      <arg type="s" name="device" direction="in"/>
+      <arg type="s" name="json" direction="out"/>
+    </method>
+    <method name="DropCaches">
+      <arg type="u" name="level" direction="in"/>
+    </method>
+    <method name="SetGovernor">
+      <arg type="u" name="cpu" direction="in"/>
+      <arg type="s" name="governor" direction="in"/>
+    </method>
+    <method name="SetEnergyPerformancePreference">
+      <arg type="u" name="cpu" direction="in"/>
+      <arg type="s" name="preference" direction="in"/>
+    </method>
+    <method name="SetSysctl">
+      <arg type="s" name="path" direction="in"/>
+      <arg type="s" name="value" direction="in"/>
+    </method>
+    <method name="SetScheduling">
+      <arg type="i" name="pid" direction="in"/>
+      <arg type="s" name="policy" direction="in"/>
+      <arg type="u" name="priority" direction="in"/>
+    </method>
+    <method name="SetIoScheduler">
+      <arg type="s" name="device" direction="in"/>
+      <arg type="s" name="scheduler" direction="in"/>
+    </method>
+  </interface>
+</node>
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display)]
+pub enum Capability {
+    #[strum(serialize = "process-io")]
+    ProcessIo,
+    #[strum(serialize = "smart-data")]
+    SmartData,
+    #[strum(serialize = "drop-caches")]
+    DropCaches,
+    #[strum(serialize = "set-governor")]
+    SetGovernor,
+    #[strum(serialize = "set-epp")]
+    SetEnergyPerformancePreference,
+    #[strum(serialize = "set-sysctl")]
+    SetSysctl,
+    #[strum(serialize = "set-scheduling")]
+    SetScheduling,
+    #[strum(serialize = "set-io-scheduler")]
+    SetIoScheduler,
+}
+
+impl Capability {
+    /// The D-Bus method of the helper that belongs to this capability
+    pub fn method_name(self) -> &'static str {
+        match self {
+            Capability::ProcessIo => "GetProcessIo",
+            Capability::SmartData => "GetSmartData",
+            Capability::DropCaches => "DropCaches",
+            Capability::SetGovernor => "SetGovernor",
+            Capability::SetEnergyPerformancePreference => "SetEnergyPerformancePreference",
+            Capability::SetSysctl => "SetSysctl",
+            Capability::SetScheduling => "SetScheduling",
+            Capability::SetIoScheduler => "SetIoScheduler",
+        }
+    }
+
+    pub fn from_method_name<S: AsRef<str>>(method_name: S) -> Option<Self> {
+        match method_name.as_ref() {
+            "GetProcessIo" => Some(Capability::ProcessIo),
+            "GetSmartData" => Some(Capability::SmartData),
+            "DropCaches" => Some(Capability::DropCaches),
+            "SetGovernor" => Some(Capability::SetGovernor),
+            "SetEnergyPerformancePreference" => Some(Capability::SetEnergyPerformancePreference),
+            "SetSysctl" => Some(Capability::SetSysctl),
+            "SetScheduling" => Some(Capability::SetScheduling),
+            "SetIoScheduler" => Some(Capability::SetIoScheduler),
+            _ => None,
+        }
+    }
+
+    /// The polkit action that the caller needs to be authorized for
+    pub fn polkit_action(self) -> String {
+        format!("net.nokyan.Resources.helper.{self}")
+    }
+}
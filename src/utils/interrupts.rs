@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InterruptKind {
+    Hardware,
+    Software,
+}
+
+/// A single line of /proc/interrupts or /proc/softirqs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interrupt {
+    pub kind: InterruptKind,
+    /// The IRQ number or the kernel's short name for it, e.g. "16" or "LOC"
+    pub id: String,
+    /// The controller and the devices behind the interrupt or, for
+    /// architecture-specific interrupts, what they are used for
+    pub description: Option<String>,
+    /// How often this interrupt has been handled by each logical CPU since boot
+    pub counts: Vec<u64>,
+}
+
+impl Interrupt {
+    /// A key that is unique among all interrupts of both kinds
+    pub fn key(&self) -> String {
+        match self.kind {
+            InterruptKind::Hardware => format!("irq-{}", self.id),
+            InterruptKind::Software => format!("softirq-{}", self.id),
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+}
+
+/// Everything the interrupts page needs for a single refresh
+#[derive(Debug, Default)]
+pub struct InterruptData {
+    pub interrupts: Vec<Interrupt>,
+}
+
+impl InterruptData {
+    pub fn new() -> Self {
+        let mut interrupts = std::fs::read_to_string("/proc/interrupts")
+            .context("unable to read /proc/interrupts")
+            .map(|content| parse_interrupts(&content, InterruptKind::Hardware))
+            .unwrap_or_else(|error| {
+                log::debug!("{error}");
+                Vec::new()
+            });
+
+        interrupts.extend(
+            std::fs::read_to_string("/proc/softirqs")
+                .context("unable to read /proc/softirqs")
+                .map(|content| parse_interrupts(&content, InterruptKind::Software))
+                .unwrap_or_else(|error| {
+                    log::debug!("{error}");
+                    Vec::new()
+                }),
+        );
+
+        Self { interrupts }
+    }
+}
+
+/// Parses the contents of /proc/interrupts or /proc/softirqs. Both start with
+/// a header naming the CPUs, followed by one line per interrupt with its ID,
+/// one counter per CPU and (for /proc/interrupts) a description.
+pub fn parse_interrupts(content: &str, kind: InterruptKind) -> Vec<Interrupt> {
+    let mut lines = content.lines();
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let cpus = header.split_whitespace().count();
+
+    lines
+        .filter_map(|line| parse_interrupt_line(line, cpus, kind).ok())
+        .collect()
+}
+
+fn parse_interrupt_line(line: &str, cpus: usize, kind: InterruptKind) -> Result<Interrupt> {
+    let (id, rest) = line.split_once(':').context("missing interrupt ID")?;
+
+    let mut tokens = rest.split_whitespace().peekable();
+    let mut counts = Vec::with_capacity(cpus);
+    // some lines, e.g. ERR and MIS on x86, only have a single counter
+    while counts.len() < cpus {
+        let Some(count) = tokens.peek().and_then(|token| token.parse::<u64>().ok()) else {
+            break;
+        };
+        counts.push(count);
+        tokens.next();
+    }
+
+    let description = tokens.collect::<Vec<_>>().join(" ");
+
+    Ok(Interrupt {
+        kind,
+        id: id.trim().to_string(),
+        description: (!description.is_empty()).then_some(description),
+        counts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROC_INTERRUPTS: &str = "            CPU0       CPU1
+   1:          0          9  IO-APIC   1-edge      i8042
+  24:          1          0  IO-APIC   5-edge      ACPI:Ged
+ LOC:     339301     312876   Local timer interrupts
+ ERR:          0
+";
+
+    const PROC_SOFTIRQS: &str = "                    CPU0       CPU1
+          HI:          4          0
+       TIMER:      31802      29107
+";
+
+    #[test]
+    fn test_parse_interrupts() {
+        let interrupts = parse_interrupts(PROC_INTERRUPTS, InterruptKind::Hardware);
+
+        assert_eq!(interrupts.len(), 4);
+        assert_eq!(
+            interrupts[1],
+            Interrupt {
+                kind: InterruptKind::Hardware,
+                id: "24".into(),
+                description: Some("IO-APIC 5-edge ACPI:Ged".into()),
+                counts: vec![1, 0],
+            }
+        );
+        assert_eq!(interrupts[2].id, "LOC");
+        assert_eq!(interrupts[2].total(), 652_177);
+        assert_eq!(interrupts[3].counts, vec![0]);
+        assert_eq!(interrupts[3].description, None);
+    }
+
+    #[test]
+    fn test_parse_softirqs() {
+        let softirqs = parse_interrupts(PROC_SOFTIRQS, InterruptKind::Software);
+
+        assert_eq!(softirqs.len(), 2);
+        assert_eq!(softirqs[1].key(), "softirq-TIMER");
+        assert_eq!(softirqs[1].counts, vec![31802, 29107]);
+        assert_eq!(softirqs[1].description, None);
+    }
+
+    #[test]
+    fn test_parse_interrupts_empty() {
+        assert!(parse_interrupts("", InterruptKind::Hardware).is_empty());
+    }
+}
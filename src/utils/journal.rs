@@ -0,0 +1,222 @@
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use strum_macros::{Display, EnumString, FromRepr};
+
+use super::{async_host_command, systemd::SystemdUnit};
+
+/// The syslog priorities used by the journal, from most to least important
+#[repr(u8)]
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, EnumString, Display, Hash, FromRepr,
+)]
+pub enum Priority {
+    Emergency,
+    Alert,
+    Critical,
+    Error,
+    Warning,
+    Notice,
+    #[default]
+    Informational,
+    Debug,
+}
+
+/// Whose journal entries to show
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JournalSource {
+    /// A systemd unit, e.g. `sshd.service` or an app's scope
+    Unit(SystemdUnit),
+    /// A single process that doesn't belong to a service
+    Pid(i32),
+}
+
+impl JournalSource {
+    /// Returns the service or scope `pid` belongs to, or the process itself if
+    /// it isn't part of either
+    pub fn for_pid(pid: i32) -> Self {
+        SystemdUnit::of_pid(pid).map_or(Self::Pid(pid), Self::Unit)
+    }
+
+    fn match_arg(&self) -> String {
+        match self {
+            JournalSource::Unit(unit) if unit.user => format!("--user-unit={}", unit.name),
+            JournalSource::Unit(unit) => format!("--unit={}", unit.name),
+            JournalSource::Pid(pid) => format!("_PID={pid}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct JournalEntry {
+    pub cursor: String,
+    /// Microseconds since the UNIX epoch
+    pub timestamp: i64,
+    pub priority: Priority,
+    pub identifier: Option<String>,
+    pub message: String,
+}
+
+impl JournalEntry {
+    /// Parses a single line of `journalctl --output=json`
+    pub fn from_json(line: &str) -> Result<Self> {
+        let json: Value = serde_json::from_str(line).context("invalid journal entry")?;
+
+        let field = |name: &str| json.get(name).and_then(Value::as_str);
+
+        // messages that aren't valid UTF-8 are given as an array of bytes
+        let message = match json.get("MESSAGE") {
+            Some(Value::String(message)) => message.clone(),
+            Some(Value::Array(bytes)) => String::from_utf8_lossy(
+                &bytes
+                    .iter()
+                    .filter_map(|byte| byte.as_u64().and_then(|byte| u8::try_from(byte).ok()))
+                    .collect::<Vec<u8>>(),
+            )
+            .to_string(),
+            _ => String::new(),
+        };
+
+        Ok(Self {
+            cursor: field("__CURSOR")
+                .context("journal entry has no cursor")?
+                .to_string(),
+            timestamp: field("__REALTIME_TIMESTAMP")
+                .and_then(|timestamp| timestamp.parse().ok())
+                .unwrap_or_default(),
+            priority: field("PRIORITY")
+                .and_then(|priority| priority.parse::<u8>().ok())
+                .and_then(Priority::from_repr)
+                .unwrap_or_default(),
+            identifier: field("SYSLOG_IDENTIFIER").map(str::to_string),
+            message,
+        })
+    }
+}
+
+/// Returns up to `lines` of the latest journal entries of `source` that are at
+/// least as important as `priority`. If `after_cursor` is given, only entries
+/// after that one are returned.
+pub async fn entries(
+    source: &JournalSource,
+    priority: Priority,
+    lines: usize,
+    after_cursor: Option<&str>,
+) -> Result<Vec<JournalEntry>> {
+    let mut command = async_host_command("journalctl");
+    command
+        .args(["--output=json", "--no-pager", "--quiet"])
+        .arg(format!("--lines={lines}"))
+        .arg(format!("--priority={}", priority as u8));
+
+    if let Some(cursor) = after_cursor {
+        command.arg(format!("--after-cursor={cursor}"));
+    }
+
+    command.arg(source.match_arg());
+
+    run(command).await
+}
+
+/// Returns up to `lines` of the latest kernel messages of all boots that match
+/// the regular expression `pattern`, regardless of case. If `after_cursor` is
+/// given, only entries after that one are returned.
+pub async fn kernel_entries(
+    pattern: &str,
+    lines: usize,
+    after_cursor: Option<&str>,
+) -> Result<Vec<JournalEntry>> {
+    matching_entries("_TRANSPORT=kernel", pattern, lines, after_cursor).await
+}
+
+/// Like `kernel_entries`, but for the messages of the program that logs with
+/// the syslog identifier `identifier`, e.g. "systemd-oomd"
+pub async fn identifier_entries(
+    identifier: &str,
+    pattern: &str,
+    lines: usize,
+    after_cursor: Option<&str>,
+) -> Result<Vec<JournalEntry>> {
+    matching_entries(
+        &format!("SYSLOG_IDENTIFIER={identifier}"),
+        pattern,
+        lines,
+        after_cursor,
+    )
+    .await
+}
+
+async fn matching_entries(
+    field_match: &str,
+    pattern: &str,
+    lines: usize,
+    after_cursor: Option<&str>,
+) -> Result<Vec<JournalEntry>> {
+    let mut command = async_host_command("journalctl");
+    command
+        .args(["--output=json", "--no-pager", "--quiet", field_match])
+        .arg(format!("--lines={lines}"))
+        .arg(format!("--grep={pattern}"))
+        .arg("--case-sensitive=false");
+
+    if let Some(cursor) = after_cursor {
+        command.arg(format!("--after-cursor={cursor}"));
+    }
+
+    run(command).await
+}
+
+async fn run(mut command: async_process::Command) -> Result<Vec<JournalEntry>> {
+    let output = command.output().await.context("unable to run journalctl")?;
+
+    // journalctl fails without saying why if --grep doesn't match anything
+    if !output.status.success() && !output.stderr.is_empty() {
+        bail!(
+            "journalctl failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| JournalEntry::from_json(line).ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_from_json() {
+        let entry = JournalEntry::from_json(
+            r#"{"__CURSOR":"s=1;i=2","__REALTIME_TIMESTAMP":"1712345678901234","PRIORITY":"4","SYSLOG_IDENTIFIER":"sshd","MESSAGE":"Connection closed"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            entry,
+            JournalEntry {
+                cursor: "s=1;i=2".into(),
+                timestamp: 1_712_345_678_901_234,
+                priority: Priority::Warning,
+                identifier: Some("sshd".into()),
+                message: "Connection closed".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_entry_from_json_byte_message() {
+        let entry = JournalEntry::from_json(r#"{"__CURSOR":"c","MESSAGE":[104,105,255]}"#).unwrap();
+
+        assert_eq!(entry.message, "hi\u{FFFD}");
+        assert_eq!(entry.priority, Priority::Informational);
+        assert_eq!(entry.identifier, None);
+    }
+
+    #[test]
+    fn test_entry_from_json_invalid() {
+        assert!(JournalEntry::from_json("not json").is_err());
+        assert!(JournalEntry::from_json(r#"{"MESSAGE":"no cursor"}"#).is_err());
+    }
+}
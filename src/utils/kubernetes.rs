@@ -0,0 +1,38 @@
+use std::path::Path;
+
+use hashbrown::HashMap;
+
+/// kubelet keeps the logs of each pod in a directory named
+/// `<namespace>_<name>_<uid>` below this one, which is the only place on the
+/// node that maps pod UIDs to names without talking to the API server
+const POD_LOGS_DIR: &str = "/var/log/pods";
+
+/// The human-readable identity of a Kubernetes pod
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PodName {
+    pub namespace: String,
+    pub name: String,
+}
+
+/// Returns the names of the pods kubelet knows about on this node, indexed by
+/// their UID. Returns an empty map if this isn't a Kubernetes node or the pod
+/// logs aren't readable.
+pub fn pod_names() -> HashMap<String, PodName> {
+    let Ok(entries) = std::fs::read_dir(Path::new(POD_LOGS_DIR)) else {
+        return HashMap::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name();
+            // namespaces and pod names can't contain underscores, so splitting
+            // on them is unambiguous
+            let mut parts = file_name.to_str()?.splitn(3, '_');
+            let namespace = parts.next()?.to_string();
+            let name = parts.next()?.to_string();
+            let uid = parts.next()?.to_string();
+            Some((uid, PodName { namespace, name }))
+        })
+        .collect()
+}
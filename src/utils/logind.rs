@@ -0,0 +1,148 @@
+use anyhow::{Context, Result};
+use gtk::{
+    gio::{self, prelude::*},
+    glib,
+};
+
+const LOGIND_BUS_NAME: &str = "org.freedesktop.login1";
+const LOGIND_OBJECT_PATH: &str = "/org/freedesktop/login1";
+const LOGIND_MANAGER_INTERFACE: &str = "org.freedesktop.login1.Manager";
+const LOGIND_SESSION_INTERFACE: &str = "org.freedesktop.login1.Session";
+
+/// A login session that is managed by systemd-logind
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Session {
+    pub id: String,
+    pub uid: u32,
+    pub user: String,
+    pub seat: Option<String>,
+    pub tty: Option<String>,
+    /// e.g. "wayland", "x11" or "tty"
+    pub session_type: String,
+    /// e.g. "user" or "greeter"
+    pub class: String,
+    /// e.g. "active", "online" or "closing"
+    pub state: String,
+    pub remote: bool,
+    pub remote_host: Option<String>,
+    /// The systemd scope the processes of this session run in, e.g.
+    /// `session-2.scope`
+    pub scope: Option<String>,
+}
+
+async fn manager() -> Result<gio::DBusProxy> {
+    gio::DBusProxy::for_bus_future(
+        gio::BusType::System,
+        gio::DBusProxyFlags::DO_NOT_LOAD_PROPERTIES | gio::DBusProxyFlags::DO_NOT_CONNECT_SIGNALS,
+        None,
+        LOGIND_BUS_NAME,
+        LOGIND_OBJECT_PATH,
+        LOGIND_MANAGER_INTERFACE,
+    )
+    .await
+    .context("unable to connect to systemd-logind")
+}
+
+impl Session {
+    /// Asks systemd-logind for all current sessions
+    ///
+    /// This uses GIO's D-Bus machinery and therefore has to be awaited on the
+    /// main context.
+    pub async fn all() -> Result<Vec<Self>> {
+        let reply = manager()
+            .await?
+            .call_future("ListSessions", None, gio::DBusCallFlags::NONE, -1)
+            .await
+            .context("unable to list sessions")?;
+
+        // ListSessions returns a(susso), i.e. the session ID, UID, user name,
+        // seat and object path of each session
+        let sessions_variant = reply.child_value(0);
+        let mut sessions = Vec::with_capacity(sessions_variant.n_children());
+        for session in sessions_variant.iter() {
+            let Some((id, uid, user, seat, object_path)) =
+                session.get::<(String, u32, String, String, glib::variant::ObjectPath)>()
+            else {
+                continue;
+            };
+
+            let mut session = Self {
+                id,
+                uid,
+                user,
+                seat: (!seat.is_empty()).then_some(seat),
+                ..Default::default()
+            };
+            if let Err(error) = session.load_properties(object_path.as_str()).await {
+                log::debug!("Unable to get details of session {}: {error}", session.id);
+            }
+            sessions.push(session);
+        }
+
+        Ok(sessions)
+    }
+
+    async fn load_properties(&mut self, object_path: &str) -> Result<()> {
+        let proxy = gio::DBusProxy::for_bus_future(
+            gio::BusType::System,
+            gio::DBusProxyFlags::DO_NOT_CONNECT_SIGNALS,
+            None,
+            LOGIND_BUS_NAME,
+            object_path,
+            LOGIND_SESSION_INTERFACE,
+        )
+        .await
+        .context("unable to connect to systemd-logind")?;
+
+        let string = |name: &str| {
+            proxy
+                .cached_property(name)
+                .and_then(|value| value.get::<String>())
+                .filter(|value| !value.is_empty())
+        };
+
+        self.tty = string("TTY");
+        self.session_type = string("Type").unwrap_or_default();
+        self.class = string("Class").unwrap_or_default();
+        self.state = string("State").unwrap_or_default();
+        self.remote_host = string("RemoteHost");
+        self.scope = string("Scope");
+        self.remote = proxy
+            .cached_property("Remote")
+            .and_then(|value| value.get::<bool>())
+            .unwrap_or_default();
+
+        Ok(())
+    }
+
+    /// Returns the PIDs of all processes that run in this session's scope
+    pub fn pids(&self) -> Vec<i32> {
+        let Some(scope) = &self.scope else {
+            return Vec::new();
+        };
+
+        std::fs::read_to_string(format!(
+            "/sys/fs/cgroup/user.slice/user-{}.slice/{scope}/cgroup.procs",
+            self.uid
+        ))
+        .map(|procs| procs.lines().filter_map(|pid| pid.parse().ok()).collect())
+        .unwrap_or_default()
+    }
+
+    /// Asks systemd-logind to terminate this session, killing all of its
+    /// processes. logind asks polkit whether we're allowed to do that, which
+    /// might involve the user having to authenticate.
+    pub async fn terminate(&self) -> Result<()> {
+        manager()
+            .await?
+            .call_future(
+                "TerminateSession",
+                Some(&(self.id.as_str(),).to_variant()),
+                gio::DBusCallFlags::ALLOW_INTERACTIVE_AUTHORIZATION,
+                i32::MAX,
+            )
+            .await
+            .with_context(|| format!("unable to terminate session {}", self.id))
+            .map(|_| ())
+    }
+}
@@ -1,57 +1,145 @@
 use std::{process::Command, sync::OnceLock};
 
 use anyhow::{bail, Context, Result};
-use nparse::KVStrToJson;
 use regex::Regex;
-use serde_json::Value;
 
-use super::{FLATPAK_APP_PATH, FLATPAK_SPAWN, IS_FLATPAK};
+use super::{host_command, FLATPAK_APP_PATH, IS_FLATPAK};
 
 static RE_SPEED: OnceLock<Regex> = OnceLock::new();
 static RE_FORMFACTOR: OnceLock<Regex> = OnceLock::new();
 static RE_TYPE: OnceLock<Regex> = OnceLock::new();
 static RE_TYPE_DETAIL: OnceLock<Regex> = OnceLock::new();
 
-fn proc_meminfo() -> Result<Value, anyhow::Error> {
-    std::fs::read_to_string("/proc/meminfo")
-        .with_context(|| "unable to read /proc/meminfo")?
-        .kv_str_to_json()
-        .map_err(anyhow::Error::msg)
+const KSM_PATH: &str = "/sys/kernel/mm/ksm";
+
+fn proc_meminfo() -> Result<String> {
+    std::fs::read_to_string("/proc/meminfo").with_context(|| "unable to read /proc/meminfo")
 }
 
-pub fn get_total_memory() -> Option<usize> {
-    proc_meminfo().ok()?["MemTotal"]
-        .as_str()
-        .and_then(|x| x.split(' ').collect::<Vec<&str>>()[0].parse::<usize>().ok())
+/// Looks up `key` (e.g. "MemTotal") in the contents of /proc/meminfo and
+/// returns its value in bytes, without allocating
+fn meminfo_value(meminfo: &str, key: &str) -> Option<usize> {
+    meminfo
+        .lines()
+        .find_map(|line| {
+            line.strip_prefix(key)
+                .and_then(|rest| rest.strip_prefix(':'))
+        })
+        .and_then(|value| value.split_ascii_whitespace().next()?.parse::<usize>().ok())
         .map(|y| y * 1000)
 }
 
+pub fn get_total_memory() -> Option<usize> {
+    meminfo_value(&proc_meminfo().ok()?, "MemTotal")
+}
+
 pub fn get_available_memory() -> Option<usize> {
-    proc_meminfo().ok()?["MemAvailable"]
-        .as_str()
-        .and_then(|x| x.split(' ').collect::<Vec<&str>>()[0].parse::<usize>().ok())
-        .map(|y| y * 1000)
+    meminfo_value(&proc_meminfo().ok()?, "MemAvailable")
 }
 
 pub fn get_free_memory() -> Option<usize> {
-    proc_meminfo().ok()?["MemFree"]
-        .as_str()
-        .and_then(|x| x.split(' ').collect::<Vec<&str>>()[0].parse::<usize>().ok())
-        .map(|y| y * 1000)
+    meminfo_value(&proc_meminfo().ok()?, "MemFree")
 }
 
 pub fn get_total_swap() -> Option<usize> {
-    proc_meminfo().ok()?["SwapTotal"]
-        .as_str()
-        .and_then(|x| x.split(' ').collect::<Vec<&str>>()[0].parse::<usize>().ok())
-        .map(|y| y * 1000)
+    meminfo_value(&proc_meminfo().ok()?, "SwapTotal")
 }
 
 pub fn get_free_swap() -> Option<usize> {
-    proc_meminfo().ok()?["SwapFree"]
-        .as_str()
-        .and_then(|x| x.split(' ').collect::<Vec<&str>>()[0].parse::<usize>().ok())
-        .map(|y| y * 1000)
+    meminfo_value(&proc_meminfo().ok()?, "SwapFree")
+}
+
+/// Everything the memory page needs for a single refresh. Gathered off the
+/// GTK main thread so that reading procfs never blocks the UI.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemoryData {
+    pub total_mem: usize,
+    pub available_mem: usize,
+    pub total_swap: usize,
+    pub free_swap: usize,
+    pub ksm: Option<KsmStats>,
+}
+
+/// Statistics of kernel samepage merging (KSM), which merges identical pages
+/// of processes that allow it, e.g. of virtual machines, into one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KsmStats {
+    /// Whether the KSM daemon is currently merging pages
+    pub running: bool,
+    /// How many merged pages there are
+    pub pages_shared: usize,
+    /// How many more places share the merged pages, i.e. how many pages are
+    /// saved
+    pub pages_sharing: usize,
+    /// How many pages are unique but are repeatedly checked for merging
+    pub pages_unshared: usize,
+}
+
+impl KsmStats {
+    /// Reads the current KSM statistics. Returns `None` if the kernel has been
+    /// built without KSM.
+    pub fn current() -> Option<Self> {
+        let read = |file: &str| -> Option<usize> {
+            std::fs::read_to_string(format!("{KSM_PATH}/{file}"))
+                .ok()?
+                .trim()
+                .parse()
+                .ok()
+        };
+
+        Some(Self {
+            // 2 means that KSM is unmerging all pages and stopping afterwards
+            running: read("run")? == 1,
+            pages_shared: read("pages_shared")?,
+            pages_sharing: read("pages_sharing")?,
+            pages_unshared: read("pages_unshared").unwrap_or_default(),
+        })
+    }
+
+    /// Whether KSM is running or pages that it has merged earlier are still
+    /// merged
+    pub fn is_active(&self) -> bool {
+        self.running || self.pages_sharing > 0
+    }
+
+    /// Estimates the memory saved by KSM in bytes
+    pub fn saved_bytes(&self) -> usize {
+        self.pages_sharing * sysconf::pagesize()
+    }
+}
+
+impl MemoryData {
+    pub fn new() -> Self {
+        // read /proc/meminfo once for all values instead of once per value
+        let meminfo = proc_meminfo().unwrap_or_default();
+        Self {
+            total_mem: meminfo_value(&meminfo, "MemTotal").unwrap_or_default(),
+            available_mem: meminfo_value(&meminfo, "MemAvailable").unwrap_or_default(),
+            total_swap: meminfo_value(&meminfo, "SwapTotal").unwrap_or_default(),
+            free_swap: meminfo_value(&meminfo, "SwapFree").unwrap_or_default(),
+            ksm: KsmStats::current(),
+        }
+    }
+
+    /// Builds `MemoryData` from the contents of a /proc/meminfo file that
+    /// doesn't necessarily belong to this machine
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `meminfo` couldn't be parsed
+    pub fn from_meminfo(meminfo: &str) -> Result<Self> {
+        let value = |key: &str| {
+            meminfo_value(meminfo, key).with_context(|| format!("unable to find {key} in meminfo"))
+        };
+
+        Ok(Self {
+            total_mem: value("MemTotal")?,
+            available_mem: value("MemAvailable")?,
+            total_swap: value("SwapTotal").unwrap_or_default(),
+            free_swap: value("SwapFree").unwrap_or_default(),
+            ksm: None,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -113,22 +201,14 @@ pub fn get_memory_devices() -> Result<Vec<MemoryDevice>> {
 }
 
 pub fn pkexec_get_memory_devices() -> Result<Vec<MemoryDevice>> {
-    let output = if *IS_FLATPAK {
-        Command::new(FLATPAK_SPAWN)
-            .args([
-                "--host",
-                "/usr/bin/pkexec",
-                "--disable-internal-agent",
-                &format!("{}/bin/dmidecode", FLATPAK_APP_PATH.as_str()),
-                "-t",
-                "17",
-                "-q",
-            ])
-            .output()?
+    // inside the Flatpak sandbox, we have to use the dmidecode shipped with us
+    let dmidecode = if *IS_FLATPAK {
+        format!("{}/bin/dmidecode", FLATPAK_APP_PATH.as_str())
     } else {
-        Command::new("pkexec")
-            .args(["--disable-internal-agent", "dmidecode", "-t", "17", "-q"])
-            .output()?
+        "dmidecode".to_string()
     };
+    let output = host_command("pkexec")
+        .args(["--disable-internal-agent", &dmidecode, "-t", "17", "-q"])
+        .output()?;
     Ok(parse_dmidecode(String::from_utf8(output.stdout)?.as_str()))
 }
@@ -1,19 +1,49 @@
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
 use anyhow::{Context, Result};
 use ini::Ini;
 use once_cell::sync::Lazy;
 
+use crate::config::LIBEXECDIR;
+
+pub mod alerts;
 pub mod app;
+pub mod autostart;
+pub mod battery;
 pub mod cpu;
+pub mod demo;
 pub mod drive;
+pub mod gamemode;
 pub mod gpu;
+pub mod gpu_resets;
+pub mod helper;
+pub mod helper_interface;
+pub mod interrupts;
+pub mod journal;
+pub mod kubernetes;
+pub mod logind;
 pub mod memory;
+pub mod modules;
+pub mod mqtt;
 pub mod network;
+pub mod oom;
 pub mod process;
+pub mod profile;
+pub mod remote;
+pub mod sensors;
 pub mod settings;
+pub mod smart;
+pub mod steam;
+pub mod sysctl;
+pub mod systemd;
 pub mod units;
 
 // Adapted from Mission Center: https://gitlab.com/mission-center-devs/mission-center/
-static IS_FLATPAK: Lazy<bool> = Lazy::new(|| std::path::Path::new("/.flatpak-info").exists());
+pub static IS_FLATPAK: Lazy<bool> = Lazy::new(|| std::path::Path::new("/.flatpak-info").exists());
 
 static FLATPAK_APP_PATH: Lazy<String> =
     Lazy::new(|| flatpak_app_path().unwrap_or_else(|_| String::new()));
@@ -35,6 +65,55 @@ pub fn flatpak_app_path() -> Result<String> {
         .to_string())
 }
 
+/// Returns a `Command` that runs `program` on the host system. Inside the
+/// Flatpak sandbox `flatpak-spawn --host` is used for that, otherwise
+/// `program` is run directly.
+pub fn host_command<S: AsRef<OsStr>>(program: S) -> Command {
+    if *IS_FLATPAK {
+        let mut command = Command::new(FLATPAK_SPAWN);
+        command.arg("--host").arg(program);
+        command
+    } else {
+        Command::new(program)
+    }
+}
+
+/// Async version of `host_command()`
+pub fn async_host_command<S: AsRef<OsStr>>(program: S) -> async_process::Command {
+    if *IS_FLATPAK {
+        let mut command = async_process::Command::new(FLATPAK_SPAWN);
+        command.arg("--host").arg(program);
+        command
+    } else {
+        async_process::Command::new(program)
+    }
+}
+
+/// Returns the path where the host's file at `path` can be found. Inside the
+/// Flatpak sandbox, the host's /usr and /etc are mounted below /run/host.
+pub fn host_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    if *IS_FLATPAK && (path.starts_with("/usr") || path.starts_with("/etc")) {
+        Path::new("/run/host").join(path.strip_prefix("/").unwrap_or(path))
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Returns the path of one of our own helper binaries (e.g. `resources-kill`)
+/// as it can be found from the host system.
+pub fn libexec_path<S: AsRef<str>>(binary: S) -> String {
+    if *IS_FLATPAK {
+        format!(
+            "{}/libexec/resources/{}",
+            FLATPAK_APP_PATH.as_str(),
+            binary.as_ref()
+        )
+    } else {
+        format!("{LIBEXECDIR}/{}", binary.as_ref())
+    }
+}
+
 pub trait NaNDefault {
     /// Returns the given `default` value if the variable is NaN,
     /// and returns itself otherwise.
@@ -0,0 +1,97 @@
+use anyhow::{Context, Result};
+
+/// A kernel module that is currently loaded, as listed in /proc/modules
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct KernelModule {
+    pub name: String,
+    /// Memory used by the module in bytes
+    pub size: usize,
+    /// How many other modules and users currently hold a reference to it
+    pub use_count: usize,
+    /// The modules that depend on this module
+    pub used_by: Vec<String>,
+    /// "Live", "Loading" or "Unloading"
+    pub state: String,
+}
+
+impl KernelModule {
+    /// Returns all loaded kernel modules
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if /proc/modules can't be read
+    pub fn all() -> Result<Vec<Self>> {
+        std::fs::read_to_string("/proc/modules")
+            .context("unable to read /proc/modules")
+            .map(|modules| Self::from_proc_modules(&modules))
+    }
+
+    /// Parses the contents of /proc/modules. Every line looks like
+    /// `name size use_count used_by state address`, `used_by` being a
+    /// comma-separated list or "-" if it's empty.
+    pub fn from_proc_modules(proc_modules: &str) -> Vec<Self> {
+        proc_modules
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let name = fields.next()?.to_string();
+                let size = fields.next()?.parse().ok()?;
+                let use_count = fields.next()?.parse().ok()?;
+                let used_by = fields
+                    .next()?
+                    .split(',')
+                    .filter(|module| !module.is_empty() && *module != "-")
+                    .map(str::to_string)
+                    .collect();
+                let state = fields.next().unwrap_or_default().to_string();
+
+                Some(Self {
+                    name,
+                    size,
+                    use_count,
+                    used_by,
+                    state,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROC_MODULES: &str = "\
+snd_hda_codec_realtek 188416 1 - Live 0x0000000000000000
+snd_hda_codec_generic 110592 1 snd_hda_codec_realtek, Live 0x0000000000000000
+ledtrig_audio 12288 2 snd_hda_codec_realtek,snd_hda_codec_generic, Live 0x0000000000000000
+";
+
+    #[test]
+    fn test_from_proc_modules() {
+        let modules = KernelModule::from_proc_modules(PROC_MODULES);
+
+        assert_eq!(modules.len(), 3);
+        assert_eq!(
+            modules[0],
+            KernelModule {
+                name: "snd_hda_codec_realtek".into(),
+                size: 188_416,
+                use_count: 1,
+                used_by: Vec::new(),
+                state: "Live".into(),
+            }
+        );
+        assert_eq!(modules[1].used_by, vec!["snd_hda_codec_realtek"]);
+        assert_eq!(
+            modules[2].used_by,
+            vec!["snd_hda_codec_realtek", "snd_hda_codec_generic"]
+        );
+    }
+
+    #[test]
+    fn test_from_proc_modules_malformed() {
+        assert!(KernelModule::from_proc_modules("").is_empty());
+        assert!(KernelModule::from_proc_modules("broken not_a_number 1 - Live").is_empty());
+    }
+}
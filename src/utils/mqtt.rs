@@ -0,0 +1,492 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use gtk::{
+    gio::{self, prelude::*},
+    glib,
+};
+
+use crate::i18n::i18n_f;
+
+/// Home automation systems don't need metrics more often than this
+pub const PUBLISH_INTERVAL: Duration = Duration::from_secs(10);
+
+const DEFAULT_PORT: u16 = 1883;
+const DEFAULT_TLS_PORT: u16 = 8883;
+const CONNECT_TIMEOUT_SECS: u32 = 10;
+
+/// The broker closes the connection if it hasn't heard from us for 1.5 times
+/// this long, which happens e.g. while refreshing is suspended
+const KEEP_ALIVE: Duration = Duration::from_secs(60);
+
+const PROTOCOL_LEVEL_3_1_1: u8 = 4;
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const CLEAN_SESSION: u8 = 0x02;
+const PASSWORD_FLAG: u8 = 0x40;
+const USERNAME_FLAG: u8 = 0x80;
+
+/// Identifies our items in the keyring, deliberately not the app ID so that the
+/// development profile finds the same passwords
+const SECRET_SCHEMA: &str = "net.nokyan.Resources.MqttBroker";
+
+/// Returns the category a graph belongs to by its dashboard key, e.g. "network"
+/// for "network-wlan0-receiving"
+pub fn category_of(dashboard_key: &str) -> &str {
+    match dashboard_key.split('-').next().unwrap_or_default() {
+        "swap" => "memory",
+        category => category,
+    }
+}
+
+/// Turns `name` into something that can be used within a single topic level,
+/// e.g. "CPU Package" into "cpu_package"
+pub fn topic_level(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| match c {
+            '+' | '#' | '/' => '_',
+            c if c.is_whitespace() => '_',
+            c => c.to_ascii_lowercase(),
+        })
+        .collect()
+}
+
+/// Splits the password off `broker`, returning the URL without it (with the
+/// scheme added if it was omitted) and the password if there was one.
+///
+/// # Errors
+///
+/// Will return `Err` if `broker` is not a valid URL
+pub fn split_password(broker: &str) -> Result<(String, Option<String>)> {
+    let uri = parse_broker(broker)?;
+    Ok((
+        uri.to_string_partial(glib::UriHideFlags::PASSWORD)
+            .to_string(),
+        uri.password().map(|password| password.to_string()),
+    ))
+}
+
+/// Returns the password that is stored in the keyring for `broker`, which is
+/// the broker URL without password
+///
+/// # Errors
+///
+/// Will return `Err` if the keyring can't be opened or read
+pub async fn broker_password(broker: &str) -> Result<Option<String>> {
+    let items = keyring()
+        .await?
+        .search_items(secret_attributes(broker))
+        .await
+        .context("unable to search the keyring")?;
+    let Some(item) = items.first() else {
+        return Ok(None);
+    };
+
+    let secret = item
+        .secret()
+        .await
+        .context("unable to read the password from the keyring")?;
+    Ok(Some(String::from_utf8(secret.to_vec())?))
+}
+
+/// Stores `password` for `broker` in the keyring or removes it from there if
+/// it's empty
+///
+/// # Errors
+///
+/// Will return `Err` if the keyring can't be opened or written
+pub async fn set_broker_password(broker: &str, password: &str) -> Result<()> {
+    let keyring = keyring().await?;
+    if password.is_empty() {
+        keyring
+            .delete(secret_attributes(broker))
+            .await
+            .context("unable to remove the password from the keyring")
+    } else {
+        keyring
+            .create_item(
+                &i18n_f("MQTT broker {}", &[broker]),
+                secret_attributes(broker),
+                password,
+                true,
+            )
+            .await
+            .context("unable to store the password in the keyring")
+    }
+}
+
+async fn keyring() -> Result<oo7::Keyring> {
+    let keyring = oo7::Keyring::new()
+        .await
+        .context("unable to open the keyring")?;
+    keyring
+        .unlock()
+        .await
+        .context("unable to unlock the keyring")?;
+    Ok(keyring)
+}
+
+fn secret_attributes(broker: &str) -> HashMap<&str, &str> {
+    HashMap::from([("xdg:schema", SECRET_SCHEMA), ("broker", broker)])
+}
+
+/// A minimal MQTT 3.1.1 client that is only able to publish messages with QoS
+/// 0, which is all that's needed to feed metrics into e.g. Home Assistant.
+///
+/// This uses GIO's networking and therefore has to be used on the main context.
+#[derive(Debug, Default)]
+pub struct MqttPublisher {
+    broker: String,
+    connection: Option<gio::SocketConnection>,
+    last_sent: Option<Instant>,
+}
+
+impl MqttPublisher {
+    /// Publishes `messages`, pairs of topic and payload, to `broker`. Connects to
+    /// the broker first unless there's a connection already that's still
+    /// alive.
+    ///
+    /// `broker` is a URL like "mqtt://user@host:1883", "mqtts://" is used for
+    /// TLS and the scheme may be omitted entirely. The password is taken from
+    /// the keyring, see `broker_password()`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the broker can't be reached, refuses the connection
+    /// or the messages can't be sent
+    pub async fn publish(&mut self, broker: &str, messages: &[(String, String)]) -> Result<()> {
+        let expired = self
+            .last_sent
+            .map_or(true, |last_sent| last_sent.elapsed() >= KEEP_ALIVE);
+        if self.broker != broker || expired {
+            self.disconnect();
+        }
+
+        let connection = match &self.connection {
+            Some(connection) => connection.clone(),
+            None => {
+                let connection = connect(broker).await?;
+                self.broker = broker.to_string();
+                self.connection = Some(connection.clone());
+                connection
+            }
+        };
+
+        let mut packets = Vec::new();
+        for (topic, payload) in messages {
+            packets.extend(publish_packet(topic, payload)?);
+        }
+
+        let result = connection
+            .output_stream()
+            .write_all_future(packets, glib::Priority::DEFAULT)
+            .await
+            .map_err(|(_, error)| error)
+            .context("unable to send messages to the MQTT broker");
+
+        if result.is_ok() {
+            self.last_sent = Some(Instant::now());
+        } else {
+            // try again with a new connection next time
+            self.disconnect();
+        }
+
+        result.map(|_| ())
+    }
+
+    pub fn disconnect(&mut self) {
+        if let Some(connection) = self.connection.take() {
+            let _ = connection.close(None::<&gio::Cancellable>);
+        }
+        self.last_sent = None;
+    }
+}
+
+fn parse_broker(broker: &str) -> Result<glib::Uri> {
+    let broker = if broker.contains("://") {
+        broker.to_string()
+    } else {
+        format!("mqtt://{broker}")
+    };
+
+    glib::Uri::parse(&broker, glib::UriFlags::HAS_PASSWORD).context("invalid MQTT broker URL")
+}
+
+async fn connect(broker: &str) -> Result<gio::SocketConnection> {
+    let uri = parse_broker(broker)?;
+    let tls = match uri.scheme().as_str() {
+        "mqtt" | "tcp" => false,
+        "mqtts" | "ssl" => true,
+        scheme => bail!("unsupported MQTT broker URL scheme {scheme}"),
+    };
+
+    let client = gio::SocketClient::new();
+    client.set_tls(tls);
+    client.set_timeout(CONNECT_TIMEOUT_SECS);
+    let connection = client
+        .connect_to_uri_future(
+            &uri.to_str(),
+            if tls { DEFAULT_TLS_PORT } else { DEFAULT_PORT },
+        )
+        .await
+        .context("unable to connect to the MQTT broker")?;
+
+    let username = uri.user().map(|user| user.to_string());
+    let password = match uri.password() {
+        Some(password) => Some(password.to_string()),
+        None if username.is_some() => {
+            let (broker, _) = split_password(broker)?;
+            // the broker might just as well not want a password at all
+            broker_password(&broker).await.unwrap_or_else(|error| {
+                log::warn!("Unable to get the MQTT broker's password: {error}");
+                None
+            })
+        }
+        None => None,
+    };
+
+    connection
+        .output_stream()
+        .write_all_future(
+            connect_packet(username.as_deref(), password.as_deref())?,
+            glib::Priority::DEFAULT,
+        )
+        .await
+        .map_err(|(_, error)| error)
+        .context("unable to send CONNECT to the MQTT broker")?;
+
+    let (connack, length, _) = connection
+        .input_stream()
+        .read_all_future(vec![0; 4], glib::Priority::DEFAULT)
+        .await
+        .map_err(|(_, error)| error)
+        .context("unable to receive CONNACK from the MQTT broker")?;
+
+    check_connack(&connack[..length])?;
+
+    Ok(connection)
+}
+
+/// Checks the broker's answer to our CONNECT
+fn check_connack(connack: &[u8]) -> Result<()> {
+    if connack.len() < 4 || connack[0] != CONNACK || connack[1] != 2 {
+        bail!("MQTT broker sent an invalid answer");
+    }
+
+    match connack[3] {
+        0 => Ok(()),
+        1 => bail!("MQTT broker doesn't support protocol version 3.1.1"),
+        4 | 5 => bail!("MQTT broker refused the username or password"),
+        code => bail!("MQTT broker refused the connection (return code {code})"),
+    }
+}
+
+fn connect_packet(username: Option<&str>, password: Option<&str>) -> Result<Vec<u8>> {
+    let mut flags = CLEAN_SESSION;
+    if username.is_some() {
+        flags |= USERNAME_FLAG;
+        // the protocol doesn't allow a password without username
+        if password.is_some() {
+            flags |= PASSWORD_FLAG;
+        }
+    }
+
+    let mut body = Vec::new();
+    push_string(&mut body, "MQTT")?;
+    body.push(PROTOCOL_LEVEL_3_1_1);
+    body.push(flags);
+    body.extend_from_slice(&(KEEP_ALIVE.as_secs() as u16).to_be_bytes());
+
+    // an empty client identifier makes the broker assign one, which is allowed
+    // for clean sessions
+    push_string(&mut body, "")?;
+    if let Some(username) = username {
+        push_string(&mut body, username)?;
+        if let Some(password) = password {
+            push_string(&mut body, password)?;
+        }
+    }
+
+    packet(CONNECT, &body)
+}
+
+fn publish_packet(topic: &str, payload: &str) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    push_string(&mut body, topic)?;
+    body.extend_from_slice(payload.as_bytes());
+
+    packet(PUBLISH, &body)
+}
+
+/// Prefixes `body` with the fixed header of a packet of type `header`
+fn packet(header: u8, body: &[u8]) -> Result<Vec<u8>> {
+    let mut packet = vec![header];
+    push_remaining_length(&mut packet, body.len())?;
+    packet.extend_from_slice(body);
+    Ok(packet)
+}
+
+/// Appends the remaining length of a packet to `buffer`, which is encoded
+/// using 7 bits per byte with the highest bit marking that another byte follows
+fn push_remaining_length(buffer: &mut Vec<u8>, length: usize) -> Result<()> {
+    if length > 268_435_455 {
+        bail!("MQTT packet is too large");
+    }
+
+    let mut remaining = length;
+    loop {
+        let mut byte = (remaining % 128) as u8;
+        remaining /= 128;
+        if remaining > 0 {
+            byte |= 0x80;
+        }
+        buffer.push(byte);
+        if remaining == 0 {
+            return Ok(());
+        }
+    }
+}
+
+/// Appends `string` to `buffer` with the length prefix that MQTT uses
+fn push_string(buffer: &mut Vec<u8>, string: &str) -> Result<()> {
+    let length = u16::try_from(string.len()).context("string is too long for MQTT")?;
+    buffer.extend_from_slice(&length.to_be_bytes());
+    buffer.extend_from_slice(string.as_bytes());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic_level() {
+        assert_eq!(topic_level("CPU Package"), "cpu_package");
+        assert_eq!(topic_level(" Tctl "), "tctl");
+        assert_eq!(topic_level("a+b#c/d"), "a_b_c_d");
+    }
+
+    #[test]
+    fn test_push_string() {
+        let mut buffer = Vec::new();
+        push_string(&mut buffer, "").unwrap();
+        assert_eq!(buffer, [0x00, 0x00]);
+
+        // example from section 1.5.3 of the MQTT 3.1.1 specification
+        let mut buffer = Vec::new();
+        push_string(&mut buffer, "A\u{2A6D4}").unwrap();
+        assert_eq!(buffer, [0x00, 0x05, 0x41, 0xF0, 0xAA, 0x9B, 0x94]);
+
+        let mut buffer = Vec::new();
+        assert!(push_string(&mut buffer, &"a".repeat(65536)).is_err());
+    }
+
+    #[test]
+    fn test_push_remaining_length() {
+        // the boundaries from section 2.2.3 of the MQTT 3.1.1 specification
+        let expected: [(usize, &[u8]); 8] = [
+            (0, &[0x00]),
+            (127, &[0x7F]),
+            (128, &[0x80, 0x01]),
+            (16_383, &[0xFF, 0x7F]),
+            (16_384, &[0x80, 0x80, 0x01]),
+            (2_097_151, &[0xFF, 0xFF, 0x7F]),
+            (2_097_152, &[0x80, 0x80, 0x80, 0x01]),
+            (268_435_455, &[0xFF, 0xFF, 0xFF, 0x7F]),
+        ];
+        for (length, bytes) in expected {
+            let mut buffer = Vec::new();
+            push_remaining_length(&mut buffer, length).unwrap();
+            assert_eq!(buffer, bytes, "remaining length {length}");
+        }
+
+        let mut buffer = Vec::new();
+        assert!(push_remaining_length(&mut buffer, 268_435_456).is_err());
+    }
+
+    #[test]
+    fn test_packet() {
+        // PINGREQ, section 3.12 of the MQTT 3.1.1 specification
+        assert_eq!(packet(0xC0, &[]).unwrap(), [0xC0, 0x00]);
+
+        let body = [0x2A; 200];
+        let packet = packet(PUBLISH, &body).unwrap();
+        assert_eq!(packet[..3], [0x30, 0xC8, 0x01]);
+        assert_eq!(packet[3..], body);
+    }
+
+    #[test]
+    fn test_connect_packet_anonymous() {
+        let expected = [
+            0x10, 0x0C, // fixed header
+            0x00, 0x04, b'M', b'Q', b'T', b'T', // protocol name
+            0x04, // protocol level
+            0x02, // clean session
+            0x00, 0x3C, // keep alive of 60 seconds
+            0x00, 0x00, // empty client identifier
+        ];
+        assert_eq!(connect_packet(None, None).unwrap(), expected);
+        // a password without username is dropped
+        assert_eq!(connect_packet(None, Some("pass")).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_connect_packet_credentials() {
+        let expected = [
+            0x10, 0x18, // fixed header
+            0x00, 0x04, b'M', b'Q', b'T', b'T', // protocol name
+            0x04, // protocol level
+            0xC2, // username, password and clean session
+            0x00, 0x3C, // keep alive of 60 seconds
+            0x00, 0x00, // empty client identifier
+            0x00, 0x04, b'u', b's', b'e', b'r', // username
+            0x00, 0x04, b'p', b'a', b's', b's', // password
+        ];
+        assert_eq!(
+            connect_packet(Some("user"), Some("pass")).unwrap(),
+            expected
+        );
+
+        let expected = [
+            0x10, 0x12, // fixed header
+            0x00, 0x04, b'M', b'Q', b'T', b'T', // protocol name
+            0x04, // protocol level
+            0x82, // username and clean session
+            0x00, 0x3C, // keep alive of 60 seconds
+            0x00, 0x00, // empty client identifier
+            0x00, 0x04, b'u', b's', b'e', b'r', // username
+        ];
+        assert_eq!(connect_packet(Some("user"), None).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_publish_packet() {
+        let expected = [
+            0x30, 0x0C, // fixed header, QoS 0
+            0x00, 0x03, b'a', b'/', b'b', // topic name
+            b'4', b'2', b'.', b'0', b' ', b'%', b'!', // payload
+        ];
+        assert_eq!(publish_packet("a/b", "42.0 %!").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_check_connack() {
+        assert!(check_connack(&[0x20, 0x02, 0x00, 0x00]).is_ok());
+        // session present
+        assert!(check_connack(&[0x20, 0x02, 0x01, 0x00]).is_ok());
+
+        // unacceptable protocol version
+        assert!(check_connack(&[0x20, 0x02, 0x00, 0x01]).is_err());
+        // bad user name or password
+        assert!(check_connack(&[0x20, 0x02, 0x00, 0x04]).is_err());
+        // not authorized
+        assert!(check_connack(&[0x20, 0x02, 0x00, 0x05]).is_err());
+
+        assert!(check_connack(&[0x30, 0x02, 0x00, 0x00]).is_err());
+        assert!(check_connack(&[0x20, 0x03, 0x00, 0x00]).is_err());
+        assert!(check_connack(&[0x20, 0x02, 0x00]).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+use anyhow::{bail, Context, Result};
+
+use super::async_host_command;
+use super::journal::{self, JournalEntry};
+
+/// e.g. "Out of memory: Killed process 1234 (firefox) total-vm:…" or "Memory
+/// cgroup out of memory: Killed process 1234 (firefox) …"
+const KERNEL_PATTERN: &str = "Killed process [0-9]+";
+
+/// e.g. "Killed /user.slice/…/app-firefox-1234.scope due to memory pressure
+/// for /user.slice/user-1000.slice/user@1000.service being 87.43% > 50.00% for
+/// > 20s with reclaim activity"
+const OOMD_PATTERN: &str = "^Killed /";
+const OOMD_IDENTIFIER: &str = "systemd-oomd";
+
+/// How many past kills are loaded initially
+const HISTORY_LENGTH: usize = 20;
+
+/// What killed a process because the system was running out of memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OomKiller {
+    /// The kernel's OOM killer, which only steps in once there's no memory left
+    /// at all
+    Kernel,
+    /// systemd-oomd, which kills whole cgroups before that happens
+    Oomd,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OomKill {
+    pub killer: OomKiller,
+    /// Microseconds since the UNIX epoch
+    pub timestamp: i64,
+    /// The name of the killed process or the unit of the killed cgroup
+    pub name: String,
+    /// Why the process has been killed, only known for systemd-oomd
+    pub reason: Option<String>,
+    pub message: String,
+}
+
+impl OomKill {
+    fn from_entry(killer: OomKiller, entry: &JournalEntry) -> Option<Self> {
+        let (name, reason) = match killer {
+            OomKiller::Kernel => {
+                let (_, rest) = entry.message.split_once("Killed process ")?;
+                let name = rest.split_once('(')?.1.split_once(')')?.0;
+                (name.to_string(), None)
+            }
+            OomKiller::Oomd => {
+                let rest = entry.message.strip_prefix("Killed ")?;
+                let (cgroup, reason) = rest
+                    .split_once(" due to ")
+                    .map_or((rest, None), |(cgroup, reason)| (cgroup, Some(reason)));
+                let name = cgroup.trim_end_matches('/').rsplit('/').next()?;
+                (
+                    name.to_string(),
+                    reason.map(|reason| reason.trim().to_string()),
+                )
+            }
+        };
+
+        Some(Self {
+            killer,
+            timestamp: entry.timestamp,
+            name,
+            reason,
+            message: entry.message.trim().to_string(),
+        })
+    }
+}
+
+/// Looks for processes that `killer` has killed in the journal of all boots,
+/// oldest first. If `after_cursor` is given, only kills after it are returned,
+/// otherwise the latest ones. Also returns the cursor to pass next time.
+///
+/// # Errors
+///
+/// Will return `Err` if the journal can't be read, e.g. because the user isn't
+/// allowed to read kernel messages
+pub async fn oom_kills(
+    killer: OomKiller,
+    after_cursor: Option<&str>,
+) -> Result<(Vec<OomKill>, Option<String>)> {
+    let entries = match killer {
+        OomKiller::Kernel => {
+            journal::kernel_entries(KERNEL_PATTERN, HISTORY_LENGTH, after_cursor).await?
+        }
+        OomKiller::Oomd => {
+            journal::identifier_entries(OOMD_IDENTIFIER, OOMD_PATTERN, HISTORY_LENGTH, after_cursor)
+                .await?
+        }
+    };
+
+    let cursor = entries
+        .last()
+        .map(|entry| entry.cursor.clone())
+        .or_else(|| after_cursor.map(str::to_string));
+
+    Ok((
+        entries
+            .iter()
+            .filter_map(|entry| OomKill::from_entry(killer, entry))
+            .collect(),
+        cursor,
+    ))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OomdMonitor {
+    /// The cgroup is killed once the system's swap usage exceeds the swap used
+    /// limit
+    Swap,
+    /// The cgroup is killed once its memory pressure exceeds `limit` for
+    /// longer than the memory pressure duration
+    MemoryPressure {
+        limit: Option<f64>,
+        /// The share of the last 10 seconds the cgroup's processes have been
+        /// stalled waiting for memory
+        pressure: Option<f64>,
+    },
+}
+
+/// A cgroup that systemd-oomd is watching
+#[derive(Debug, Clone, PartialEq)]
+pub struct OomdCgroup {
+    pub path: String,
+    pub monitor: OomdMonitor,
+}
+
+/// What systemd-oomd is currently doing. All limits are fractions between 0.0
+/// and 1.0.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OomdStatus {
+    pub dry_run: bool,
+    pub swap_used_limit: Option<f64>,
+    pub default_memory_pressure_limit: Option<f64>,
+    pub default_memory_pressure_duration: Option<String>,
+    pub cgroups: Vec<OomdCgroup>,
+}
+
+impl OomdStatus {
+    /// Asks systemd-oomd for its current state using `oomctl`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if systemd-oomd isn't running or `oomctl` isn't
+    /// available
+    pub async fn current() -> Result<Self> {
+        let output = async_host_command("oomctl")
+            .arg("dump")
+            .output()
+            .await
+            .context("unable to run oomctl")?;
+
+        if !output.status.success() {
+            bail!(
+                "oomctl failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Ok(Self::from_dump(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Parses the output of `oomctl dump`
+    fn from_dump(dump: &str) -> Self {
+        #[derive(PartialEq)]
+        enum Section {
+            General,
+            Swap,
+            MemoryPressure,
+        }
+
+        // "50.00%" to 0.5
+        let percentage = |value: &str| {
+            value
+                .trim()
+                .trim_end_matches('%')
+                .parse::<f64>()
+                .ok()
+                .map(|percentage| percentage / 100.0)
+        };
+
+        let mut status = Self::default();
+        let mut section = Section::General;
+
+        for line in dump.lines() {
+            let trimmed = line.trim();
+            let Some((key, value)) = trimmed.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key {
+                "Dry Run" => status.dry_run = value == "yes",
+                "Swap Used Limit" => status.swap_used_limit = percentage(value),
+                "Default Memory Pressure Limit" => {
+                    status.default_memory_pressure_limit = percentage(value);
+                }
+                "Default Memory Pressure Duration" => {
+                    status.default_memory_pressure_duration = Some(value.to_string());
+                }
+                "Swap Monitored CGroups" => section = Section::Swap,
+                "Memory Pressure Monitored CGroups" => section = Section::MemoryPressure,
+                "Path" if section != Section::General => status.cgroups.push(OomdCgroup {
+                    path: value.to_string(),
+                    monitor: if section == Section::Swap {
+                        OomdMonitor::Swap
+                    } else {
+                        OomdMonitor::MemoryPressure {
+                            limit: None,
+                            pressure: None,
+                        }
+                    },
+                }),
+                "Memory Pressure Limit" => {
+                    if let Some(OomdCgroup {
+                        monitor: OomdMonitor::MemoryPressure { limit, .. },
+                        ..
+                    }) = status.cgroups.last_mut()
+                    {
+                        *limit = percentage(value);
+                    }
+                }
+                // e.g. "Pressure: Avg10: 0.00 Avg60: 0.00 Avg300: 0.00 Total: 0"
+                "Pressure" => {
+                    if let Some(OomdCgroup {
+                        monitor: OomdMonitor::MemoryPressure { pressure, .. },
+                        ..
+                    }) = status.cgroups.last_mut()
+                    {
+                        *pressure = value
+                            .strip_prefix("Avg10:")
+                            .and_then(|rest| rest.split_whitespace().next())
+                            .and_then(percentage);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        status
+    }
+}
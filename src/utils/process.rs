@@ -1,27 +1,25 @@
+use std::ops::{Deref, DerefMut};
+
 use anyhow::{bail, Context, Result};
-use config::LIBEXECDIR;
-use glob::glob;
-use process_data::{Containerization, ProcessData};
-use std::process::Command;
-
-use async_std::sync::Arc;
-use async_std::sync::Mutex;
-use futures_util::future::join_all;
+use process_data::{Containerization, FileActivity, ProcessData, SchedulingPolicy};
+use resources_collector::process::SampledProcess;
+
 use gtk::gio::{Icon, ThemedIcon};
 
-use crate::config;
+use crate::i18n::{i18n, i18n_f};
 
-use super::{FLATPAK_APP_PATH, FLATPAK_SPAWN, IS_FLATPAK};
+use super::{async_host_command, demo, host_command, libexec_path, IS_FLATPAK};
 
-/// Represents a process that can be found within procfs.
+pub use resources_collector::process::MemoryTrend;
+
+/// Represents a process that can be found within procfs. Everything that is
+/// sampled from procfs is part of the `SampledProcess` it dereferences to.
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct Process {
-    pub data: ProcessData,
+    pub sampled: SampledProcess,
     pub executable_path: String,
     pub executable_name: String,
     pub icon: Icon,
-    pub cpu_time_before: u64,
-    pub cpu_time_before_timestamp: u64,
     pub alive: bool,
 }
 
@@ -45,62 +43,68 @@ pub struct ProcessItem {
     pub commandline: String,
     pub containerization: Containerization,
     pub cgroup: Option<String>,
+    /// The ID of the application this process belongs to, if any
+    pub app_id: Option<String>,
+    pub memory_trend: MemoryTrend,
+    pub open_files: Option<usize>,
+    pub open_files_limit: Option<usize>,
+    pub scheduling_policy: SchedulingPolicy,
+    pub realtime_priority: u32,
+    pub minor_faults_per_second: f32,
+    pub major_faults_per_second: f32,
 }
 
 impl Process {
-    /// Returns a `Vec` containing all currently running processes.
+    /// Returns a `Vec` containing the `ProcessData` of all currently running
+    /// processes. Unlike `Process`, `ProcessData` is `Send`, so this can be
+    /// called away from the GTK main thread. Open files are only counted if
+    /// `with_open_files` is set because that's expensive.
     ///
     /// # Errors
     ///
     /// Will return `Err` if there are problems traversing and
     /// parsing procfs
-    pub async fn all() -> Result<Vec<Self>> {
-        let mut return_vec = Vec::new();
-
+    pub async fn all_data(with_open_files: bool) -> Result<Vec<ProcessData>> {
         if *IS_FLATPAK {
-            let proxy_path = format!(
-                "{}/libexec/resources/resources-processes",
-                FLATPAK_APP_PATH.as_str()
-            );
-            let command = async_process::Command::new(FLATPAK_SPAWN)
-                .args(["--host", proxy_path.as_str()])
-                .output()
-                .await?;
+            let mut command = async_host_command(libexec_path("resources-processes"));
+            if with_open_files {
+                command.arg("--with-open-files");
+            }
+            let command = command.output().await?;
             let output = command.stdout;
             let proxy_output: Vec<ProcessData> =
                 rmp_serde::from_slice::<Vec<ProcessData>>(&output)?;
 
-            for process_data in proxy_output {
-                return_vec.push(Self::from_process_data(process_data));
-            }
+            Ok(proxy_output)
         } else {
-            let vec: Arc<Mutex<Vec<ProcessData>>> = Arc::new(Mutex::new(Vec::new()));
-
-            let mut handles = vec![];
-            for entry in glob("/proc/[0-9]*/").context("unable to glob")?.flatten() {
-                let vec = Arc::clone(&vec);
-
-                let handle = async_std::task::spawn(async move {
-                    if let Ok(process_data) = ProcessData::try_from_path(entry).await {
-                        vec.lock().await.push(process_data);
-                    }
-                });
+            ProcessData::all(with_open_files).await
+        }
+    }
 
-                handles.push(handle);
+    /// Returns a snapshot of the files the process with `pid` has opened, see
+    /// `FileActivity`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the open files of the process can't be read
+    pub async fn file_activity(pid: i32) -> Result<FileActivity> {
+        if *IS_FLATPAK {
+            let command = async_host_command(libexec_path("resources-processes"))
+                .arg("--file-activity")
+                .arg(pid.to_string())
+                .output()
+                .await?;
+            if !command.status.success() {
+                bail!("unable to read file activity of process {pid}");
             }
-            join_all(handles).await;
-
-            let vec = vec.lock().await;
 
-            for process_data in vec.iter() {
-                return_vec.push(Self::from_process_data(process_data.clone()));
-            }
+            Ok(rmp_serde::from_slice::<FileActivity>(&command.stdout)?)
+        } else {
+            async_std::task::spawn_blocking(move || FileActivity::of_pid(pid)).await
         }
-
-        Ok(return_vec)
     }
 
-    fn from_process_data(process_data: ProcessData) -> Self {
+    pub fn from_process_data(process_data: ProcessData) -> Self {
         let executable_path = process_data
             .commandline
             .split('\0')
@@ -115,17 +119,20 @@ impl Process {
             .to_string();
 
         Self {
+            sampled: SampledProcess::new(process_data),
             executable_path,
             executable_name,
-            data: process_data,
             icon: ThemedIcon::new("generic-process").into(),
-            cpu_time_before: 0,
-            cpu_time_before_timestamp: 0,
             alive: true,
         }
     }
 
     pub fn execute_process_action(&self, action: ProcessAction) -> Result<()> {
+        // the demo PIDs belong to whatever actually runs under them
+        if demo::is_enabled() {
+            bail!("not sending signals to processes in demo mode");
+        }
+
         let action_str = match action {
             ProcessAction::TERM => "TERM",
             ProcessAction::STOP => "STOP",
@@ -133,37 +140,14 @@ impl Process {
             ProcessAction::CONT => "CONT",
         };
 
-        // TODO: tidy this mess up
+        let kill_path = libexec_path("resources-kill");
 
-        let kill_path = if *IS_FLATPAK {
-            format!(
-                "{}/libexec/resources/resources-kill",
-                FLATPAK_APP_PATH.as_str()
-            )
-        } else {
-            format!("{LIBEXECDIR}/resources-kill")
-        };
-
-        let status_code = if *IS_FLATPAK {
-            Command::new(FLATPAK_SPAWN)
-                .args([
-                    "--host",
-                    kill_path.as_str(),
-                    action_str,
-                    self.data.pid.to_string().as_str(),
-                ])
-                .output()?
-                .status
-                .code()
-                .with_context(|| "no status code?")?
-        } else {
-            Command::new(kill_path.as_str())
-                .args([action_str, self.data.pid.to_string().as_str()])
-                .output()?
-                .status
-                .code()
-                .with_context(|| "no status code?")?
-        };
+        let status_code = host_command(&kill_path)
+            .args([action_str, self.data.pid.to_string().as_str()])
+            .output()?
+            .status
+            .code()
+            .with_context(|| "no status code?")?;
 
         if status_code == 0 || status_code == 3 {
             // 0 := successful; 3 := process not found which we don't care
@@ -184,33 +168,17 @@ impl Process {
     }
 
     fn pkexec_execute_process_action(&self, action: &str, kill_path: &str) -> Result<()> {
-        let status_code = if *IS_FLATPAK {
-            Command::new(FLATPAK_SPAWN)
-                .args([
-                    "--host",
-                    "pkexec",
-                    "--disable-internal-agent",
-                    kill_path,
-                    action,
-                    self.data.pid.to_string().as_str(),
-                ])
-                .output()?
-                .status
-                .code()
-                .with_context(|| "no status code?")?
-        } else {
-            Command::new("pkexec")
-                .args([
-                    "--disable-internal-agent",
-                    kill_path,
-                    action,
-                    self.data.pid.to_string().as_str(),
-                ])
-                .output()?
-                .status
-                .code()
-                .with_context(|| "no status code?")?
-        };
+        let status_code = host_command("pkexec")
+            .args([
+                "--disable-internal-agent",
+                kill_path,
+                action,
+                self.data.pid.to_string().as_str(),
+            ])
+            .output()?
+            .status
+            .code()
+            .with_context(|| "no status code?")?;
 
         if status_code == 0 || status_code == 3 {
             // 0 := successful; 3 := process not found which we don't care
@@ -226,21 +194,49 @@ impl Process {
         }
     }
 
-    #[must_use]
-    pub fn cpu_time_ratio(&self) -> f32 {
-        if self.cpu_time_before == 0 {
-            0.0
-        } else {
-            (self.data.cpu_time.saturating_sub(self.cpu_time_before) as f32
-                / (self
-                    .data
-                    .cpu_time_timestamp
-                    .saturating_sub(self.cpu_time_before_timestamp)) as f32)
-                .clamp(0.0, 1.0)
-        }
-    }
-
     pub fn sanitize_cmdline<S: AsRef<str>>(cmdline: S) -> String {
         cmdline.as_ref().replace('\0', " ")
     }
 }
+
+impl Deref for Process {
+    type Target = SampledProcess;
+
+    fn deref(&self) -> &Self::Target {
+        &self.sampled
+    }
+}
+
+impl DerefMut for Process {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.sampled
+    }
+}
+
+/// Returns a human readable name of `policy`
+pub fn scheduling_policy_name(policy: SchedulingPolicy) -> String {
+    match policy {
+        SchedulingPolicy::Other => i18n("Normal"),
+        SchedulingPolicy::Fifo => i18n("Realtime (FIFO)"),
+        SchedulingPolicy::RoundRobin => i18n("Realtime (Round Robin)"),
+        SchedulingPolicy::Batch => i18n("Batch"),
+        SchedulingPolicy::Idle => i18n("Idle"),
+        SchedulingPolicy::Deadline => i18n("Deadline"),
+    }
+}
+
+/// Describes how a process is scheduled, including its priority if it has a
+/// realtime policy
+pub fn describe_scheduling(policy: SchedulingPolicy, realtime_priority: u32) -> String {
+    if policy.is_realtime() {
+        i18n_f(
+            "{}, priority {}",
+            &[
+                &scheduling_policy_name(policy),
+                &realtime_priority.to_string(),
+            ],
+        )
+    } else {
+        scheduling_policy_name(policy)
+    }
+}
@@ -0,0 +1,189 @@
+use anyhow::{bail, Context, Result};
+use gtk::{gio::prelude::*, glib};
+use serde_json::{Map, Value};
+
+use super::{
+    alerts::{AlertAction, AlertRule, StoredAlertRule},
+    settings::SETTINGS,
+};
+
+/// Keys that only make sense on the machine they've been set on and are
+/// therefore neither exported nor imported
+const MACHINE_SPECIFIC_KEYS: &[&str] = &[
+    "window-width",
+    "window-height",
+    "is-maximized",
+    "run-in-background",
+    "sensor-aliases",
+    "hidden-sensors",
+    // may contain credentials
+    "mqtt-broker",
+];
+
+/// Keys that are only read to migrate them to newer keys
+const DEPRECATED_KEYS: &[&str] = &["alert-rules"];
+
+const FORMAT_VERSION: u64 = 1;
+
+/// What `import_profile` has done
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ImportedProfile {
+    /// How many settings have been applied
+    pub settings: usize,
+    /// How many alerts have been changed to show a notification instead of
+    /// running a command or posting to a webhook
+    pub disarmed_alerts: usize,
+}
+
+/// Serializes all portable settings into a JSON document. Values are stored in
+/// GVariant text format so that every key type round-trips without loss, except
+/// for the commands and URLs of alerts, which are left out.
+pub fn export_profile() -> String {
+    let mut settings = Map::new();
+
+    for key in portable_keys() {
+        let value = SETTINGS.value(&key);
+        // commands and webhook URLs may contain credentials, importing would
+        // disarm these alerts anyway
+        let value = if key.as_str() == "alerts" {
+            strip_alert_targets(&value)
+        } else {
+            value
+        };
+
+        settings.insert(
+            key.to_string(),
+            Value::String(value.print(false).to_string()),
+        );
+    }
+
+    let mut profile = Map::new();
+    profile.insert("version".into(), Value::from(FORMAT_VERSION));
+    profile.insert("settings".into(), Value::Object(settings));
+
+    serde_json::to_string_pretty(&Value::Object(profile)).unwrap_or_default()
+}
+
+/// Applies a profile created by `export_profile`. Keys unknown to this version
+/// of Resources are skipped, keys missing from the profile keep their current
+/// value. Nothing is changed if any of the values is invalid.
+///
+/// Alerts that would run a command or post to a webhook only show a
+/// notification after the import, so that a profile from someone else can't
+/// run anything on this machine.
+///
+/// # Errors
+///
+/// Will return `Err` if `profile` is not a valid settings profile
+pub fn import_profile<S: AsRef<str>>(profile: S) -> Result<ImportedProfile> {
+    let profile: Value =
+        serde_json::from_str(profile.as_ref()).context("profile is not valid JSON")?;
+
+    let version = profile
+        .get("version")
+        .and_then(Value::as_u64)
+        .context("profile has no version")?;
+    if version > FORMAT_VERSION {
+        bail!("profile has been created by a newer version of Resources");
+    }
+
+    let settings = profile
+        .get("settings")
+        .and_then(Value::as_object)
+        .context("profile contains no settings")?;
+
+    let schema = SETTINGS
+        .settings_schema()
+        .context("unable to get settings schema")?;
+
+    let mut values = Vec::new();
+    let mut disarmed_alerts = 0;
+    for key in portable_keys() {
+        let Some(text) = settings.get(key.as_str()).and_then(Value::as_str) else {
+            continue;
+        };
+
+        let schema_key = schema.key(&key);
+        let value = glib::Variant::parse(Some(schema_key.value_type().as_ref()), text)
+            .with_context(|| format!("invalid value for {key}"))?;
+        if !schema_key.range_check(&value) {
+            bail!("value for {key} is out of range");
+        }
+
+        let value = if key.as_str() == "alerts" {
+            let (alerts, disarmed) = disarm_alerts(&value);
+            disarmed_alerts += disarmed;
+            alerts
+        } else {
+            value
+        };
+
+        values.push((key, value));
+    }
+
+    for (key, value) in &values {
+        SETTINGS
+            .set_value(key, value)
+            .with_context(|| format!("unable to set {key}"))?;
+    }
+
+    Ok(ImportedProfile {
+        settings: values.len(),
+        disarmed_alerts,
+    })
+}
+
+/// Changes every alert in `alerts` that runs a command or posts to a webhook
+/// to show a notification instead. Returns the changed alerts along with how
+/// many of them have been disarmed.
+fn disarm_alerts(alerts: &glib::Variant) -> (glib::Variant, usize) {
+    let mut disarmed = 0;
+
+    let alerts: Vec<StoredAlertRule> = alerts
+        .get::<Vec<StoredAlertRule>>()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(AlertRule::from_stored)
+        .map(|mut rule| {
+            if rule.action.needs_target() {
+                rule.action = AlertAction::Notify;
+                rule.target.clear();
+                disarmed += 1;
+            }
+            rule.to_stored()
+        })
+        .collect();
+
+    (alerts.to_variant(), disarmed)
+}
+
+/// Removes the command or URL from every alert in `alerts` that runs a command
+/// or posts to a webhook
+fn strip_alert_targets(alerts: &glib::Variant) -> glib::Variant {
+    alerts
+        .get::<Vec<StoredAlertRule>>()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(AlertRule::from_stored)
+        .map(|mut rule| {
+            if rule.action.needs_target() {
+                rule.target.clear();
+            }
+            rule.to_stored()
+        })
+        .collect::<Vec<_>>()
+        .to_variant()
+}
+
+fn portable_keys() -> Vec<glib::GString> {
+    SETTINGS
+        .settings_schema()
+        .map(|schema| schema.list_keys())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|key| {
+            !MACHINE_SPECIFIC_KEYS.contains(&key.as_str())
+                && !DEPRECATED_KEYS.contains(&key.as_str())
+        })
+        .collect()
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use gtk::glib;
+
+use super::async_host_command;
+use super::cpu::{CPUInfo, CpuData};
+use super::drive::{self, Drive, DriveData, DriveType};
+use super::memory::MemoryData;
+use super::network::{InterfaceType, NetworkData, NetworkInterface};
+
+// separates the contents of the different files we read in a single SSH call
+const SEPARATOR: &str = "--- resources ---";
+
+const SYS_BLOCK: &str = "/sys/block";
+
+/// The files of each drive in /sys/block that are read on every refresh
+const DRIVE_ATTRIBUTES: &[&str] = &[
+    "stat",
+    "size",
+    "ro",
+    "removable",
+    "queue/rotational",
+    "queue/hw_sector_size",
+    "device/model",
+];
+
+/// Everything a remote window needs for a single refresh
+#[derive(Debug)]
+pub struct RemoteData {
+    pub cpu: CpuData,
+    pub memory: MemoryData,
+    pub drives: Vec<(Drive, DriveData)>,
+    pub network_interfaces: Vec<(NetworkInterface, NetworkData)>,
+}
+
+/// A machine that is monitored by reading its procfs over SSH. Only key-based
+/// authentication is supported since there is no terminal to ask for a
+/// password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteHost {
+    destination: String,
+}
+
+impl RemoteHost {
+    /// Creates a new `RemoteHost` for an SSH `destination` such as
+    /// `user@example.com`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `destination` is empty or looks like an option
+    pub fn new<S: AsRef<str>>(destination: S) -> Result<Self> {
+        let destination = destination.as_ref().trim();
+        if destination.is_empty()
+            || destination.starts_with('-')
+            || destination.contains(char::is_whitespace)
+        {
+            bail!("invalid SSH destination {destination}");
+        }
+        Ok(Self {
+            destination: destination.to_string(),
+        })
+    }
+
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+
+    /// Runs `remote_command` on the remote host and returns its stdout. SSH
+    /// connections are shared between calls so that refreshing doesn't need a
+    /// new handshake every time.
+    async fn run(&self, remote_command: &str) -> Result<String> {
+        let control_path = glib::user_runtime_dir().join("resources-ssh-%C");
+
+        let output = async_host_command("ssh")
+            .args(["-o", "BatchMode=yes", "-o", "ConnectTimeout=10"])
+            .args(["-o", "ControlMaster=auto", "-o", "ControlPersist=60"])
+            .arg("-o")
+            .arg(format!("ControlPath={}", control_path.to_string_lossy()))
+            .arg("--")
+            .arg(&self.destination)
+            .arg(remote_command)
+            .output()
+            .await
+            .context("unable to run ssh, is it installed?")?;
+
+        if !output.status.success() {
+            bail!(
+                "ssh to {} failed: {}",
+                self.destination,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        String::from_utf8(output.stdout).context("remote output is not UTF-8")
+    }
+
+    pub async fn hostname(&self) -> Result<String> {
+        self.run("cat /proc/sys/kernel/hostname")
+            .await
+            .map(|hostname| hostname.trim().to_string())
+    }
+
+    pub async fn cpu_info(&self) -> Result<CPUInfo> {
+        CPUInfo::from_lscpu(&self.run("LC_ALL=C lscpu").await?)
+    }
+
+    pub async fn data(&self, logical_cpus: usize) -> Result<RemoteData> {
+        // grep -H prints every line as "<path>:<contents>", which lets us read
+        // all drive attributes at once. Not every drive has all of them, so
+        // grep's exit status is ignored.
+        let drive_attributes: Vec<String> = DRIVE_ATTRIBUTES
+            .iter()
+            .map(|attribute| format!("{SYS_BLOCK}/*/{attribute}"))
+            .collect();
+        let output = self
+            .run(&format!(
+                "cat /proc/stat; echo '{SEPARATOR}'; cat /proc/meminfo; echo '{SEPARATOR}'; \
+                 cat /proc/net/dev; echo '{SEPARATOR}'; grep -H . {} 2>/dev/null; true",
+                drive_attributes.join(" ")
+            ))
+            .await?;
+
+        let sections: Vec<&str> = output.split(SEPARATOR).collect();
+        let [proc_stat, meminfo, net_dev, sys_block] = sections[..] else {
+            bail!("unexpected output from remote host");
+        };
+
+        Ok(RemoteData {
+            cpu: CpuData::from_proc_stat(proc_stat, logical_cpus),
+            memory: MemoryData::from_meminfo(meminfo)?,
+            drives: parse_drives(sys_block),
+            network_interfaces: parse_network_interfaces(net_dev),
+        })
+    }
+}
+
+/// Parses the output of `grep -H . /sys/block/*/<attribute>…` into drives
+/// and their current stats. Virtual block devices are skipped, like they are
+/// for the local machine.
+fn parse_drives(sys_block: &str) -> Vec<(Drive, DriveData)> {
+    let mut attributes: HashMap<&str, HashMap<&str, &str>> = HashMap::new();
+    for line in sys_block.lines() {
+        let Some((path, value)) = line.split_once(':') else {
+            continue;
+        };
+        let Some((block_device, attribute)) = path
+            .strip_prefix(SYS_BLOCK)
+            .and_then(|path| path.strip_prefix('/'))
+            .and_then(|path| path.split_once('/'))
+        else {
+            continue;
+        };
+        attributes
+            .entry(block_device)
+            .or_default()
+            .insert(attribute, value.trim());
+    }
+
+    let mut drives: Vec<(Drive, DriveData)> = attributes
+        .into_iter()
+        .filter(|(block_device, _)| !drive::is_virtual_block_device(block_device))
+        .filter_map(|(block_device, attributes)| {
+            let flag = |attribute| attributes.get(attribute).map(|value| *value != "0");
+            let removable = flag("removable").unwrap_or(false);
+
+            let drive = Drive {
+                model: attributes
+                    .get("device/model")
+                    .map(|model| model.to_string()),
+                drive_type: DriveType::guess(block_device, flag("queue/rotational"), removable),
+                block_device: block_device.to_string(),
+                sys_fs_path: Path::new(SYS_BLOCK).join(block_device),
+            };
+
+            let data = DriveData {
+                disk_stats: drive::parse_sys_stats(attributes.get("stat")?).ok()?,
+                capacity: attributes.get("size")?.parse().ok()?,
+                sector_size: attributes
+                    .get("queue/hw_sector_size")
+                    .and_then(|sector_size| sector_size.parse().ok())
+                    .unwrap_or(512),
+                writable: !flag("ro").unwrap_or(false),
+                removable,
+                // the I/O scheduler could only be changed on this machine
+                io_scheduler: None,
+            };
+
+            Some((drive, data))
+        })
+        .collect();
+    drives.sort_by(|(a, _), (b, _)| a.block_device.cmp(&b.block_device));
+    drives
+}
+
+/// Parses /proc/net/dev into network interfaces and their current stats. The
+/// loopback interface is skipped, like it is for the local machine.
+fn parse_network_interfaces(net_dev: &str) -> Vec<(NetworkInterface, NetworkData)> {
+    net_dev
+        .lines()
+        .filter_map(|line| {
+            let (interface_name, counters) = line.split_once(':')?;
+            let interface_name = interface_name.trim();
+            if interface_name.starts_with("lo") {
+                return None;
+            }
+
+            // received bytes are the first counter, sent bytes the ninth
+            let counters: Vec<usize> = counters
+                .split_whitespace()
+                .map(|counter| counter.parse().ok())
+                .collect::<Option<_>>()?;
+            let data = NetworkData {
+                received_bytes: *counters.first()?,
+                sent_bytes: *counters.get(8)?,
+            };
+
+            let mut interface = NetworkInterface::default();
+            interface.interface_name = interface_name.into();
+            interface.interface_type = InterfaceType::from_interface_name(interface_name);
+            interface.sysfs_path = PathBuf::from("/sys/class/net").join(interface_name);
+
+            Some((interface, data))
+        })
+        .collect()
+}
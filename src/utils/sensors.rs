@@ -0,0 +1,165 @@
+use std::path::{Path, PathBuf};
+
+use crate::i18n::i18n_f;
+
+use super::units::convert_temperature;
+
+const HWMON_PATH: &str = "/sys/class/hwmon";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorKind {
+    Temperature,
+    Fan,
+    Voltage,
+}
+
+impl SensorKind {
+    const ALL: [Self; 3] = [Self::Temperature, Self::Fan, Self::Voltage];
+
+    /// The prefix of the kind's channel files in hwmon, e.g. "temp" for
+    /// temp1_input
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::Temperature => "temp",
+            Self::Fan => "fan",
+            Self::Voltage => "in",
+        }
+    }
+
+    /// Converts a raw hwmon reading into °C, RPM or V respectively
+    fn from_raw(self, raw: i64) -> f64 {
+        match self {
+            Self::Temperature | Self::Voltage => raw as f64 / 1000.0,
+            Self::Fan => raw as f64,
+        }
+    }
+}
+
+/// A single channel of a hardware monitoring chip, e.g. the third temperature
+/// input of a motherboard's Super I/O chip
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sensor {
+    /// Identifies the sensor across reboots, unlike the hwmon index, which
+    /// depends on the order in which the drivers have been loaded
+    pub id: String,
+    /// The name of the chip as reported by its driver, e.g. "nct6798"
+    pub chip: String,
+    /// The device the chip belongs to, e.g. "0000:00:18.3"
+    pub device: Option<String>,
+    /// The name of the channel, e.g. "temp3"
+    pub channel: String,
+    /// The label the driver gave the channel, e.g. "SYSTIN"
+    pub label: Option<String>,
+    pub kind: SensorKind,
+    input_path: PathBuf,
+}
+
+impl Sensor {
+    /// Lists the channels of all hardware monitoring chips, sorted by chip
+    pub fn all() -> Vec<Self> {
+        let Ok(entries) = std::fs::read_dir(HWMON_PATH) else {
+            return Vec::new();
+        };
+
+        let mut sensors: Vec<Self> = entries
+            .flatten()
+            .flat_map(|entry| Self::of_chip(&entry.path()))
+            .collect();
+        sensors.sort_by(|a, b| {
+            (&a.chip, &a.device, a.kind as u8, channel_index(&a.channel)).cmp(&(
+                &b.chip,
+                &b.device,
+                b.kind as u8,
+                channel_index(&b.channel),
+            ))
+        });
+        sensors
+    }
+
+    fn of_chip(hwmon_path: &Path) -> Vec<Self> {
+        let read = |path: &Path| {
+            std::fs::read_to_string(path)
+                .ok()
+                .map(|content| content.trim().to_string())
+                .filter(|content| !content.is_empty())
+        };
+
+        let Some(chip) = read(&hwmon_path.join("name")) else {
+            return Vec::new();
+        };
+        let device = std::fs::canonicalize(hwmon_path.join("device"))
+            .ok()
+            .and_then(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+            });
+
+        let Ok(entries) = std::fs::read_dir(hwmon_path) else {
+            return Vec::new();
+        };
+
+        entries
+            .flatten()
+            .filter_map(|entry| {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                let channel = file_name.strip_suffix("_input")?;
+                let kind = SensorKind::ALL.into_iter().find(|kind| {
+                    channel
+                        .strip_prefix(kind.prefix())
+                        .map_or(false, |index| index.parse::<u32>().is_ok())
+                })?;
+
+                let id = match &device {
+                    Some(device) => format!("{chip}@{device}/{channel}"),
+                    None => format!("{chip}/{channel}"),
+                };
+
+                Some(Self {
+                    id,
+                    chip: chip.clone(),
+                    device: device.clone(),
+                    channel: channel.to_string(),
+                    label: read(&hwmon_path.join(format!("{channel}_label"))),
+                    kind,
+                    input_path: entry.path(),
+                })
+            })
+            .collect()
+    }
+
+    /// The name the sensor is known by if the user hasn't given it one
+    pub fn default_name(&self) -> String {
+        self.label.clone().unwrap_or_else(|| self.channel.clone())
+    }
+
+    /// Reads the current value of the sensor in °C, RPM or V depending on its
+    /// kind. Returns `None` if the sensor can't be read at the moment, which
+    /// e.g. happens for fans that aren't connected.
+    pub fn read(&self) -> Option<f64> {
+        std::fs::read_to_string(&self.input_path)
+            .ok()
+            .and_then(|raw| raw.trim().parse::<i64>().ok())
+            .map(|raw| self.kind.from_raw(raw))
+    }
+
+    pub fn format_value(&self, value: f64) -> String {
+        match self.kind {
+            SensorKind::Temperature => convert_temperature(value),
+            SensorKind::Fan => i18n_f("{} RPM", &[&format!("{value:.0}")]),
+            SensorKind::Voltage => i18n_f("{} V", &[&format!("{value:.3}")]),
+        }
+    }
+}
+
+/// Reads the current values of `sensors`, see `Sensor::read`
+pub fn read_all(sensors: &[Sensor]) -> Vec<Option<f64>> {
+    sensors.iter().map(Sensor::read).collect()
+}
+
+/// Makes temp10 come after temp9 instead of after temp1
+fn channel_index(channel: &str) -> u32 {
+    channel
+        .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+        .parse()
+        .unwrap_or_default()
+}
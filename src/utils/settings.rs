@@ -1,4 +1,4 @@
-use std::{ops::Deref, str::FromStr};
+use std::{collections::HashMap, ops::Deref, str::FromStr};
 
 use adw::prelude::*;
 
@@ -8,6 +8,8 @@ use strum_macros::{Display, EnumString, FromRepr};
 
 use crate::config::APP_ID;
 
+use super::alerts::{AlertRule, LegacyStoredAlertRule, StoredAlertRule};
+
 pub static SETTINGS: Lazy<Settings> = Lazy::new(Settings::default);
 
 #[repr(u8)]
@@ -118,6 +120,331 @@ impl Settings {
         })
     }
 
+    pub fn smooth_graphs(&self) -> bool {
+        self.boolean("smooth-graphs")
+    }
+
+    pub fn set_smooth_graphs(&self, smooth_graphs: bool) -> Result<(), glib::error::BoolError> {
+        self.set_boolean("smooth-graphs", smooth_graphs)
+    }
+
+    pub fn connect_smooth_graphs<F: Fn(bool) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_changed(Some("smooth-graphs"), move |settings, _key| {
+            f(settings.boolean("smooth-graphs"))
+        })
+    }
+
+    pub fn notify_thermal_throttling(&self) -> bool {
+        self.boolean("notify-thermal-throttling")
+    }
+
+    pub fn set_notify_thermal_throttling(
+        &self,
+        notify_thermal_throttling: bool,
+    ) -> Result<(), glib::error::BoolError> {
+        self.set_boolean("notify-thermal-throttling", notify_thermal_throttling)
+    }
+
+    pub fn connect_notify_thermal_throttling<F: Fn(bool) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_changed(Some("notify-thermal-throttling"), move |settings, _key| {
+            f(settings.boolean("notify-thermal-throttling"))
+        })
+    }
+
+    pub fn notify_gpu_resets(&self) -> bool {
+        self.boolean("notify-gpu-resets")
+    }
+
+    pub fn set_notify_gpu_resets(
+        &self,
+        notify_gpu_resets: bool,
+    ) -> Result<(), glib::error::BoolError> {
+        self.set_boolean("notify-gpu-resets", notify_gpu_resets)
+    }
+
+    pub fn connect_notify_gpu_resets<F: Fn(bool) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_changed(Some("notify-gpu-resets"), move |settings, _key| {
+            f(settings.boolean("notify-gpu-resets"))
+        })
+    }
+
+    pub fn processes_show_open_files(&self) -> bool {
+        self.boolean("processes-show-open-files")
+    }
+
+    pub fn set_processes_show_open_files(
+        &self,
+        processes_show_open_files: bool,
+    ) -> Result<(), glib::error::BoolError> {
+        self.set_boolean("processes-show-open-files", processes_show_open_files)
+    }
+
+    pub fn connect_processes_show_open_files<F: Fn(bool) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_changed(Some("processes-show-open-files"), move |settings, _key| {
+            f(settings.boolean("processes-show-open-files"))
+        })
+    }
+
+    pub fn processes_show_scheduling(&self) -> bool {
+        self.boolean("processes-show-scheduling")
+    }
+
+    pub fn set_processes_show_scheduling(
+        &self,
+        processes_show_scheduling: bool,
+    ) -> Result<(), glib::error::BoolError> {
+        self.set_boolean("processes-show-scheduling", processes_show_scheduling)
+    }
+
+    pub fn connect_processes_show_scheduling<F: Fn(bool) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_changed(Some("processes-show-scheduling"), move |settings, _key| {
+            f(settings.boolean("processes-show-scheduling"))
+        })
+    }
+
+    pub fn processes_show_page_faults(&self) -> bool {
+        self.boolean("processes-show-page-faults")
+    }
+
+    pub fn set_processes_show_page_faults(
+        &self,
+        processes_show_page_faults: bool,
+    ) -> Result<(), glib::error::BoolError> {
+        self.set_boolean("processes-show-page-faults", processes_show_page_faults)
+    }
+
+    pub fn connect_processes_show_page_faults<F: Fn(bool) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_changed(Some("processes-show-page-faults"), move |settings, _key| {
+            f(settings.boolean("processes-show-page-faults"))
+        })
+    }
+
+    pub fn run_in_background(&self) -> bool {
+        self.boolean("run-in-background")
+    }
+
+    pub fn set_run_in_background(
+        &self,
+        run_in_background: bool,
+    ) -> Result<(), glib::error::BoolError> {
+        self.set_boolean("run-in-background", run_in_background)
+    }
+
+    pub fn connect_run_in_background<F: Fn(bool) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_changed(Some("run-in-background"), move |settings, _key| {
+            f(settings.boolean("run-in-background"))
+        })
+    }
+
+    pub fn sysctl_editing(&self) -> bool {
+        self.boolean("sysctl-editing")
+    }
+
+    pub fn set_sysctl_editing(&self, sysctl_editing: bool) -> Result<(), glib::error::BoolError> {
+        self.set_boolean("sysctl-editing", sysctl_editing)
+    }
+
+    pub fn connect_sysctl_editing<F: Fn(bool) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_changed(Some("sysctl-editing"), move |settings, _key| {
+            f(settings.boolean("sysctl-editing"))
+        })
+    }
+
+    pub fn sensor_aliases(&self) -> HashMap<String, String> {
+        self.value("sensor-aliases").get().unwrap_or_default()
+    }
+
+    pub fn set_sensor_aliases(
+        &self,
+        aliases: &HashMap<String, String>,
+    ) -> Result<(), glib::error::BoolError> {
+        self.set_value("sensor-aliases", &aliases.to_variant())
+    }
+
+    pub fn connect_sensor_aliases<F: Fn(HashMap<String, String>) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_changed(Some("sensor-aliases"), move |settings, _key| {
+            f(settings.value("sensor-aliases").get().unwrap_or_default())
+        })
+    }
+
+    pub fn hidden_sensors(&self) -> Vec<String> {
+        self.strv("hidden-sensors")
+            .iter()
+            .map(|sensor| sensor.to_string())
+            .collect()
+    }
+
+    pub fn set_hidden_sensors(&self, sensors: &[String]) -> Result<(), glib::error::BoolError> {
+        self.set_strv("hidden-sensors", sensors)
+    }
+
+    pub fn connect_hidden_sensors<F: Fn(Vec<String>) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_changed(Some("hidden-sensors"), move |settings, _key| {
+            f(settings
+                .strv("hidden-sensors")
+                .iter()
+                .map(|sensor| sensor.to_string())
+                .collect())
+        })
+    }
+
+    pub fn mqtt_enabled(&self) -> bool {
+        self.boolean("mqtt-enabled")
+    }
+
+    pub fn set_mqtt_enabled(&self, mqtt_enabled: bool) -> Result<(), glib::error::BoolError> {
+        self.set_boolean("mqtt-enabled", mqtt_enabled)
+    }
+
+    pub fn connect_mqtt_enabled<F: Fn(bool) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_changed(Some("mqtt-enabled"), move |settings, _key| {
+            f(settings.boolean("mqtt-enabled"))
+        })
+    }
+
+    pub fn mqtt_broker(&self) -> String {
+        self.string("mqtt-broker").to_string()
+    }
+
+    pub fn set_mqtt_broker(&self, mqtt_broker: &str) -> Result<(), glib::error::BoolError> {
+        self.set_string("mqtt-broker", mqtt_broker)
+    }
+
+    pub fn connect_mqtt_broker<F: Fn(String) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_changed(Some("mqtt-broker"), move |settings, _key| {
+            f(settings.string("mqtt-broker").to_string())
+        })
+    }
+
+    pub fn mqtt_topic(&self) -> String {
+        self.string("mqtt-topic").to_string()
+    }
+
+    pub fn set_mqtt_topic(&self, mqtt_topic: &str) -> Result<(), glib::error::BoolError> {
+        self.set_string("mqtt-topic", mqtt_topic)
+    }
+
+    pub fn connect_mqtt_topic<F: Fn(String) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_changed(Some("mqtt-topic"), move |settings, _key| {
+            f(settings.string("mqtt-topic").to_string())
+        })
+    }
+
+    pub fn mqtt_metrics(&self) -> Vec<String> {
+        self.strv("mqtt-metrics")
+            .iter()
+            .map(|metric| metric.to_string())
+            .collect()
+    }
+
+    pub fn set_mqtt_metrics(&self, metrics: &[String]) -> Result<(), glib::error::BoolError> {
+        self.set_strv("mqtt-metrics", metrics)
+    }
+
+    pub fn connect_mqtt_metrics<F: Fn(Vec<String>) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_changed(Some("mqtt-metrics"), move |settings, _key| {
+            f(settings
+                .strv("mqtt-metrics")
+                .iter()
+                .map(|metric| metric.to_string())
+                .collect())
+        })
+    }
+
+    pub fn dashboard_tiles(&self) -> Vec<String> {
+        self.strv("dashboard-tiles")
+            .iter()
+            .map(|tile| tile.to_string())
+            .collect()
+    }
+
+    pub fn set_dashboard_tiles(&self, tiles: &[String]) -> Result<(), glib::error::BoolError> {
+        self.set_strv("dashboard-tiles", tiles)
+    }
+
+    pub fn connect_dashboard_tiles<F: Fn(Vec<String>) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_changed(Some("dashboard-tiles"), move |settings, _key| {
+            f(settings
+                .strv("dashboard-tiles")
+                .iter()
+                .map(|tile| tile.to_string())
+                .collect())
+        })
+    }
+
+    pub fn alert_rules(&self) -> Vec<AlertRule> {
+        Self::alert_rules_of(self)
+    }
+
+    pub fn set_alert_rules(&self, rules: &[AlertRule]) -> Result<(), glib::error::BoolError> {
+        let stored: Vec<StoredAlertRule> = rules.iter().map(AlertRule::to_stored).collect();
+        self.set_value("alerts", &stored.to_variant())
+    }
+
+    pub fn connect_alert_rules<F: Fn(Vec<AlertRule>) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_changed(Some("alerts"), move |settings, _key| {
+            f(Self::alert_rules_of(settings))
+        })
+    }
+
+    fn alert_rules_of(settings: &gio::Settings) -> Vec<AlertRule> {
+        settings
+            .value("alerts")
+            .get::<Vec<StoredAlertRule>>()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(AlertRule::from_stored)
+            .collect()
+    }
+
+    /// Moves the rules saved in the deprecated alert-rules key over to the
+    /// alerts key
+    pub fn migrate_alert_rules(&self) {
+        if self.user_value("alert-rules").is_none() {
+            return;
+        }
+
+        let mut rules = self.alert_rules();
+        rules.extend(
+            self.value("alert-rules")
+                .get::<Vec<LegacyStoredAlertRule>>()
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(AlertRule::from_legacy_stored),
+        );
+
+        if self.set_alert_rules(&rules).is_ok() {
+            self.reset("alert-rules");
+        }
+    }
+
     pub fn window_width(&self) -> i32 {
         self.int("window-width")
     }
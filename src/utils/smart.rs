@@ -0,0 +1,406 @@
+use std::{
+    io::Write,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use gtk::{
+    gio::{self, prelude::*},
+    glib::{self, VariantDict},
+};
+use serde::{Deserialize, Serialize};
+
+use super::helper;
+
+const UDISKS_BUS_NAME: &str = "org.freedesktop.UDisks2";
+const UDISKS_BLOCK_DEVICES_PATH: &str = "/org/freedesktop/UDisks2/block_devices";
+const UDISKS_BLOCK_INTERFACE: &str = "org.freedesktop.UDisks2.Block";
+const UDISKS_DRIVE_INTERFACE: &str = "org.freedesktop.UDisks2.Drive";
+const UDISKS_ATA_INTERFACE: &str = "org.freedesktop.UDisks2.Drive.Ata";
+const UDISKS_NVME_INTERFACE: &str = "org.freedesktop.UDisks2.NVMe.Controller";
+
+/// SMART values barely change from minute to minute, so only one sample per
+/// hour is recorded
+pub const RECORD_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// A little more than a year's worth of hourly samples
+const MAX_SAMPLES: usize = 24 * 400;
+
+const ATA_REALLOCATED_SECTOR_COUNT: u8 = 5;
+/// Different vendors report the remaining endurance of SSDs in different
+/// attributes, but all of them use the normalized value counting down from 100
+const ATA_WEAR_ATTRIBUTES: [u8; 3] = [177, 231, 233];
+
+/// The key SMART attributes of a drive at a certain point in time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SmartSample {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    /// In °C
+    pub temperature: Option<f64>,
+    pub reallocated_sectors: Option<u64>,
+    /// How much of the drive's rated endurance has been used up, 1.0 meaning
+    /// all of it. Only reported by SSDs.
+    pub wear: Option<f64>,
+}
+
+impl SmartSample {
+    /// Asks UDisks for the current SMART attributes of `block_device` (e.g.
+    /// "sda"). Returns the UDisks ID of the drive alongside the sample, which
+    /// unlike the block device stays the same across reboots.
+    ///
+    /// This uses GIO's D-Bus machinery and therefore has to be awaited on the
+    /// main context.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if UDisks is not available or the drive doesn't
+    /// support SMART
+    pub async fn current(block_device: &str) -> Result<(String, Self)> {
+        let block = proxy(
+            &format!("{UDISKS_BLOCK_DEVICES_PATH}/{block_device}"),
+            UDISKS_BLOCK_INTERFACE,
+        )
+        .await?;
+        let drive_path = block
+            .cached_property("Drive")
+            .and_then(|value| value.get::<glib::variant::ObjectPath>())
+            .filter(|path| path.as_str() != "/")
+            .with_context(|| format!("UDisks doesn't know a drive for {block_device}"))?;
+
+        let drive_id = proxy(drive_path.as_str(), UDISKS_DRIVE_INTERFACE)
+            .await?
+            .cached_property("Id")
+            .and_then(|value| value.get::<String>())
+            .filter(|id| !id.is_empty())
+            .with_context(|| format!("drive of {block_device} has no ID"))?;
+
+        let timestamp = now();
+
+        let ata = proxy(drive_path.as_str(), UDISKS_ATA_INTERFACE).await?;
+        if ata
+            .cached_property("SmartEnabled")
+            .and_then(|value| value.get::<bool>())
+            .unwrap_or(false)
+        {
+            let sample = Self::from_ata(&ata, timestamp).await?;
+            return Ok((drive_id, sample));
+        }
+
+        let nvme = proxy(drive_path.as_str(), UDISKS_NVME_INTERFACE).await?;
+        if nvme
+            .cached_property("SmartUpdated")
+            .and_then(|value| value.get::<u64>())
+            .map_or(false, |updated| updated > 0)
+        {
+            let sample = Self::from_nvme(&nvme, timestamp).await?;
+            return Ok((drive_id, sample));
+        }
+
+        bail!("{block_device} doesn't support SMART")
+    }
+
+    /// Reads the SMART attributes of `block_device` with smartctl through
+    /// resources-helper, for drives whose SMART data UDisks doesn't offer.
+    /// Returns an ID made up from the drive's model and serial number alongside
+    /// the sample. The helper asks for authentication first.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the helper isn't available, authentication fails
+    /// or smartctl doesn't know the drive's model and serial number
+    pub async fn current_from_smartctl(block_device: &str) -> Result<(String, Self)> {
+        let json = helper::smart_data(block_device).await?;
+        Self::from_smartctl_json(&json, now())
+    }
+
+    /// Parses the output of `smartctl --json --all`
+    fn from_smartctl_json(json: &str, timestamp: u64) -> Result<(String, Self)> {
+        let json: serde_json::Value =
+            serde_json::from_str(json).context("smartctl output is not valid JSON")?;
+
+        let model = json["model_name"]
+            .as_str()
+            .context("smartctl doesn't know the model")?;
+        let serial = json["serial_number"]
+            .as_str()
+            .context("smartctl doesn't know the serial number")?;
+        let drive_id = format!("{model}-{serial}").replace(' ', "-");
+
+        let temperature = json["temperature"]["current"].as_f64();
+
+        let ata_attributes = json["ata_smart_attributes"]["table"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let ata_attribute = |ids: &[u8]| {
+            ata_attributes.iter().find(|attribute| {
+                attribute["id"]
+                    .as_u64()
+                    .and_then(|id| u8::try_from(id).ok())
+                    .map_or(false, |id| ids.contains(&id))
+            })
+        };
+
+        let reallocated_sectors = ata_attribute(&[ATA_REALLOCATED_SECTOR_COUNT])
+            .and_then(|attribute| attribute["raw"]["value"].as_u64());
+
+        // NVMe drives report their wear directly, ATA SSDs like with UDisks
+        let wear = json["nvme_smart_health_information_log"]["percentage_used"]
+            .as_f64()
+            .map(|percent_used| percent_used / 100.0)
+            .or_else(|| {
+                ata_attribute(&ATA_WEAR_ATTRIBUTES)
+                    .and_then(|attribute| attribute["value"].as_f64())
+                    .filter(|value| (0.0..=100.0).contains(value))
+                    .map(|value| (100.0 - value) / 100.0)
+            });
+
+        Ok((
+            drive_id,
+            Self {
+                timestamp,
+                temperature,
+                reallocated_sectors,
+                wear,
+            },
+        ))
+    }
+
+    async fn from_ata(ata: &gio::DBusProxy, timestamp: u64) -> Result<Self> {
+        let temperature = ata
+            .cached_property("SmartTemperature")
+            .and_then(|value| value.get::<f64>())
+            .filter(|kelvin| *kelvin > 0.0)
+            .map(|kelvin| kelvin - 273.15);
+
+        // SmartGetAttributes returns a(ysqiiixia{sv}), i.e. the ID, name,
+        // flags, normalized value, worst value, threshold, "pretty" value,
+        // unit of the pretty value and expansion of each attribute
+        let reply = ata
+            .call_future(
+                "SmartGetAttributes",
+                Some(&glib::Variant::tuple_from_iter([
+                    VariantDict::new(None).end()
+                ])),
+                gio::DBusCallFlags::NONE,
+                -1,
+            )
+            .await
+            .context("unable to get SMART attributes")?;
+
+        let mut reallocated_sectors = None;
+        let mut wear = None;
+        for attribute in reply.child_value(0).iter() {
+            let Some(id) = attribute.child_value(0).get::<u8>() else {
+                continue;
+            };
+            if id == ATA_REALLOCATED_SECTOR_COUNT {
+                reallocated_sectors = attribute
+                    .child_value(6)
+                    .get::<i64>()
+                    .and_then(|pretty| u64::try_from(pretty).ok());
+            } else if ATA_WEAR_ATTRIBUTES.contains(&id) {
+                wear = attribute
+                    .child_value(3)
+                    .get::<i32>()
+                    .filter(|value| (0..=100).contains(value))
+                    .map(|value| f64::from(100 - value) / 100.0);
+            }
+        }
+
+        Ok(Self {
+            timestamp,
+            temperature,
+            reallocated_sectors,
+            wear,
+        })
+    }
+
+    async fn from_nvme(nvme: &gio::DBusProxy, timestamp: u64) -> Result<Self> {
+        let temperature = nvme
+            .cached_property("SmartTemperature")
+            .and_then(|value| value.get::<u16>())
+            .filter(|kelvin| *kelvin > 0)
+            .map(|kelvin| f64::from(kelvin) - 273.15);
+
+        // NVMe drives remap bad blocks internally and only report their spare
+        // capacity, so there's no equivalent of reallocated sectors
+        let reply = nvme
+            .call_future(
+                "SmartGetAttributes",
+                Some(&glib::Variant::tuple_from_iter([
+                    VariantDict::new(None).end()
+                ])),
+                gio::DBusCallFlags::NONE,
+                -1,
+            )
+            .await
+            .context("unable to get SMART attributes")?;
+        let wear = VariantDict::new(Some(&reply.child_value(0)))
+            .lookup_value("percent_used", None)
+            .and_then(|value| value.get::<u8>())
+            .map(|percent_used| f64::from(percent_used) / 100.0);
+
+        Ok(Self {
+            timestamp,
+            temperature,
+            reallocated_sectors: None,
+            wear,
+        })
+    }
+}
+
+/// Seconds since the Unix epoch
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+async fn proxy(object_path: &str, interface: &str) -> Result<gio::DBusProxy> {
+    gio::DBusProxy::for_bus_future(
+        gio::BusType::System,
+        gio::DBusProxyFlags::DO_NOT_CONNECT_SIGNALS,
+        None,
+        UDISKS_BUS_NAME,
+        object_path,
+        interface,
+    )
+    .await
+    .context("unable to connect to UDisks")
+}
+
+/// The SMART samples of a single drive that have been recorded so far. They
+/// are stored as one JSON object per line in the user's data directory.
+#[derive(Debug, Clone, Default)]
+pub struct SmartHistory {
+    path: PathBuf,
+    pub samples: Vec<SmartSample>,
+}
+
+impl SmartHistory {
+    /// Loads the history of the drive with the given UDisks ID. Returns an
+    /// empty history if nothing has been recorded yet.
+    pub fn load(drive_id: &str) -> Self {
+        let path = glib::user_data_dir()
+            .join("resources")
+            .join("smart")
+            .join(format!("{}.jsonl", drive_id.replace('/', "_")));
+
+        let samples = std::fs::read_to_string(&path)
+            .map(|history| {
+                history
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self { path, samples }
+    }
+
+    /// Adds `sample` to the history unless the last sample has been recorded
+    /// less than `RECORD_INTERVAL` ago. Returns whether the sample was added.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the history can't be written to disk
+    pub fn record(&mut self, sample: SmartSample) -> Result<bool> {
+        if self.samples.last().map_or(false, |last| {
+            sample.timestamp.saturating_sub(last.timestamp) < RECORD_INTERVAL.as_secs()
+        }) {
+            return Ok(false);
+        }
+
+        self.samples.push(sample);
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("unable to create {}", parent.display()))?;
+        }
+
+        if self.samples.len() > MAX_SAMPLES {
+            // dropping the oldest samples means rewriting the whole file
+            self.samples.drain(..self.samples.len() - MAX_SAMPLES);
+            let history: String = self
+                .samples
+                .iter()
+                .filter_map(|sample| serde_json::to_string(sample).ok())
+                .map(|line| line + "\n")
+                .collect();
+            std::fs::write(&self.path, history)
+                .with_context(|| format!("unable to write {}", self.path.display()))?;
+        } else {
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .with_context(|| format!("unable to open {}", self.path.display()))?;
+            writeln!(file, "{}", serde_json::to_string(&sample)?)
+                .with_context(|| format!("unable to write {}", self.path.display()))?;
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_smartctl_json_ata() {
+        // trimmed down from `smartctl --json --all /dev/sda`
+        let json = r#"{
+            "model_name": "Samsung SSD 860 EVO 500GB",
+            "serial_number": "S3Z1NB0K123456A",
+            "temperature": { "current": 31 },
+            "ata_smart_attributes": {
+                "table": [
+                    { "id": 5, "name": "Reallocated_Sector_Ct", "value": 100, "raw": { "value": 2 } },
+                    { "id": 177, "name": "Wear_Leveling_Count", "value": 97, "raw": { "value": 24 } }
+                ]
+            }
+        }"#;
+
+        let (drive_id, sample) = SmartSample::from_smartctl_json(json, 1000).unwrap();
+
+        assert_eq!(drive_id, "Samsung-SSD-860-EVO-500GB-S3Z1NB0K123456A");
+        assert_eq!(
+            sample,
+            SmartSample {
+                timestamp: 1000,
+                temperature: Some(31.0),
+                reallocated_sectors: Some(2),
+                wear: Some(0.03),
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_smartctl_json_nvme() {
+        let json = r#"{
+            "model_name": "WD_BLACK SN770 1TB",
+            "serial_number": "22123A456789",
+            "temperature": { "current": 42 },
+            "nvme_smart_health_information_log": { "percentage_used": 5, "temperature": 42 }
+        }"#;
+
+        let (drive_id, sample) = SmartSample::from_smartctl_json(json, 1000).unwrap();
+
+        assert_eq!(drive_id, "WD_BLACK-SN770-1TB-22123A456789");
+        assert_eq!(sample.temperature, Some(42.0));
+        assert_eq!(sample.reallocated_sectors, None);
+        assert_eq!(sample.wear, Some(0.05));
+    }
+
+    #[test]
+    fn test_from_smartctl_json_without_serial() {
+        let json = r#"{ "model_name": "QEMU HARDDISK", "temperature": { "current": 20 } }"#;
+        assert!(SmartSample::from_smartctl_json(json, 1000).is_err());
+        assert!(SmartSample::from_smartctl_json("not json", 1000).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+
+use gtk::glib;
+
+/// The places Steam keeps its data in, depending on how it has been installed
+const STEAM_ROOTS: &[&str] = &[
+    ".steam/steam",
+    ".local/share/Steam",
+    ".var/app/com.valvesoftware.Steam/.local/share/Steam",
+];
+
+/// Returns the title of the Steam game with the given app ID by looking at
+/// the app manifests of all Steam libraries. Returns `None` if the game
+/// couldn't be found, e.g. because it's a non-Steam game added to Steam.
+pub fn game_name(app_id: u32) -> Option<String> {
+    let manifest_name = format!("appmanifest_{app_id}.acf");
+    library_folders()
+        .iter()
+        .filter_map(|library| {
+            std::fs::read_to_string(library.join("steamapps").join(&manifest_name)).ok()
+        })
+        .find_map(|manifest| vdf_value(&manifest, "name"))
+}
+
+/// Steam installs an icon for each game it has created a shortcut for
+pub fn game_icon_name(app_id: u32) -> String {
+    format!("steam_icon_{app_id}")
+}
+
+fn library_folders() -> Vec<PathBuf> {
+    let home = glib::home_dir();
+    let mut libraries: Vec<PathBuf> = Vec::new();
+
+    for root in STEAM_ROOTS.iter().map(|root| home.join(root)) {
+        if !root.exists() {
+            continue;
+        }
+
+        let additional_libraries =
+            std::fs::read_to_string(root.join("steamapps").join("libraryfolders.vdf"))
+                .map(|folders| vdf_values(&folders, "path").map(PathBuf::from).collect())
+                .unwrap_or_else(|_| Vec::new());
+
+        for library in std::iter::once(root).chain(additional_libraries) {
+            // ~/.steam/steam is usually a symlink to one of the others
+            let library = library.canonicalize().unwrap_or(library);
+            if !libraries.contains(&library) {
+                libraries.push(library);
+            }
+        }
+    }
+
+    libraries
+}
+
+/// Returns all values of `key` in a Valve KeyValues ("VDF") document, without
+/// bothering to parse its structure
+fn vdf_values<'a>(vdf: &'a str, key: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    vdf.lines().filter_map(move |line| {
+        let mut quoted = line.split('"').skip(1).step_by(2);
+        (quoted.next()? == key).then(|| quoted.next()).flatten()
+    })
+}
+
+fn vdf_value(vdf: &str, key: &str) -> Option<String> {
+    vdf_values(vdf, key).next().map(str::to_string)
+}
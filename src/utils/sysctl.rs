@@ -0,0 +1,107 @@
+use std::{os::unix::fs::PermissionsExt, path::Path};
+
+use anyhow::{Context, Result};
+
+const PROC_SYS: &str = "/proc/sys";
+
+/// A kernel parameter that is exposed in /proc/sys
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SysctlParameter {
+    /// The path relative to /proc/sys, e.g. "vm/swappiness"
+    pub path: String,
+    /// `None` if the parameter couldn't be read, which happens for some
+    /// parameters even if they're supposed to be readable
+    pub value: Option<String>,
+    /// Whether root is allowed to write to this parameter
+    pub writable: bool,
+}
+
+impl SysctlParameter {
+    /// Returns all readable kernel parameters, sorted by their path
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if /proc/sys can't be read
+    pub fn all() -> Result<Vec<Self>> {
+        let mut parameters = Vec::new();
+        collect_parameters(Path::new(PROC_SYS), &mut parameters)
+            .context("unable to read /proc/sys")?;
+        parameters.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(parameters)
+    }
+
+    /// Reads the kernel parameter at `path`, which is relative to /proc/sys
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the parameter doesn't exist
+    pub fn read<S: AsRef<str>>(path: S) -> Result<Self> {
+        let path = path.as_ref();
+        let full_path = Path::new(PROC_SYS).join(path);
+        let mode = std::fs::metadata(&full_path)
+            .with_context(|| format!("unable to find kernel parameter {path}"))?
+            .permissions()
+            .mode();
+
+        Ok(Self {
+            path: path.to_string(),
+            value: read_value(&full_path),
+            writable: mode & 0o200 != 0,
+        })
+    }
+
+    /// The name of the parameter as `sysctl` would display it, e.g.
+    /// "vm.swappiness"
+    pub fn name(&self) -> String {
+        self.path.replace('/', ".")
+    }
+
+    /// The top-level directory of the parameter, e.g. "vm" or "net"
+    pub fn subsystem(&self) -> &str {
+        self.path.split('/').next().unwrap_or_default()
+    }
+}
+
+fn collect_parameters(dir: &Path, parameters: &mut Vec<SysctlParameter>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)?.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+
+        let path = entry.path();
+        if metadata.is_dir() {
+            // some subdirectories are only accessible to root, that's fine
+            let _ = collect_parameters(&path, parameters);
+            continue;
+        }
+
+        let mode = metadata.permissions().mode();
+        // write-only parameters like vm.drop_caches are triggers rather than
+        // settings, sysctl doesn't display them either
+        if mode & 0o444 == 0 {
+            continue;
+        }
+
+        let Some(relative_path) = path
+            .strip_prefix(PROC_SYS)
+            .ok()
+            .and_then(|relative_path| relative_path.to_str())
+        else {
+            continue;
+        };
+
+        parameters.push(SysctlParameter {
+            path: relative_path.to_string(),
+            value: read_value(&path),
+            writable: mode & 0o200 != 0,
+        });
+    }
+
+    Ok(())
+}
+
+fn read_value(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|value| value.trim_end().replace('\t', " "))
+}
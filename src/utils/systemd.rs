@@ -0,0 +1,213 @@
+use anyhow::{Context, Result};
+use gtk::{
+    gio::{self, prelude::*},
+    glib,
+};
+
+const SYSTEMD_BUS_NAME: &str = "org.freedesktop.systemd1";
+const SYSTEMD_OBJECT_PATH: &str = "/org/freedesktop/systemd1";
+const SYSTEMD_MANAGER_INTERFACE: &str = "org.freedesktop.systemd1.Manager";
+
+// systemd uses this for "not set" or "infinity" in all of the properties below
+const UNSET: u64 = u64::MAX;
+
+/// A systemd unit whose cgroup a process lives in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemdUnit {
+    /// e.g. `app-gnome-firefox-1234.scope` or `sshd.service`
+    pub name: String,
+    /// Whether the unit belongs to the user's service manager instead of the
+    /// system's
+    pub user: bool,
+}
+
+/// Resource limits of a unit. `None` means that there is no limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ResourceLimits {
+    /// Relative share of CPU time, between 1 and 10000 (systemd's default is 100)
+    pub cpu_weight: Option<u64>,
+    /// Percentage of a single CPU's time, 200 means two full CPUs
+    pub cpu_quota: Option<u64>,
+    /// Maximum memory usage in bytes
+    pub memory_max: Option<u64>,
+}
+
+/// A unit as listed by its service manager
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitStatus {
+    pub unit: SystemdUnit,
+    pub description: String,
+    /// e.g. `active`, `inactive` or `failed`
+    pub active_state: String,
+    /// e.g. `running`, `exited` or `dead`
+    pub sub_state: String,
+}
+
+impl UnitStatus {
+    pub fn is_failed(&self) -> bool {
+        self.active_state == "failed"
+    }
+}
+
+impl SystemdUnit {
+    /// Returns the service or scope `pid` belongs to
+    pub fn of_pid(pid: i32) -> Option<Self> {
+        let cgroup = std::fs::read_to_string(format!("/proc/{pid}/cgroup")).ok()?;
+        let path = cgroup.lines().find_map(|line| line.strip_prefix("0::"))?;
+
+        let name = path
+            .rsplit('/')
+            .find(|segment| segment.ends_with(".service") || segment.ends_with(".scope"))?;
+
+        // the user's service manager itself runs in user@UID.service, so that one
+        // belongs to the system
+        let user = path
+            .split('/')
+            .any(|segment| segment.starts_with("user@") && segment.ends_with(".service"))
+            && !name.starts_with("user@");
+
+        Some(Self {
+            name: name.to_string(),
+            user,
+        })
+    }
+
+    /// Returns the services that are currently loaded by the system's or, if
+    /// `user` is true, the user's service manager
+    pub async fn services(user: bool) -> Result<Vec<UnitStatus>> {
+        let reply = manager(bus_type(user))
+            .await?
+            .call_future(
+                "ListUnitsByPatterns",
+                Some(&(Vec::<String>::new(), vec!["*.service"]).to_variant()),
+                gio::DBusCallFlags::NONE,
+                -1,
+            )
+            .await
+            .context("unable to list units")?;
+
+        Ok(reply
+            .child_value(0)
+            .iter()
+            .filter_map(|unit| {
+                let field = |i| unit.child_value(i).str().map(str::to_string);
+                Some(UnitStatus {
+                    unit: SystemdUnit {
+                        name: field(0)?,
+                        user,
+                    },
+                    description: field(1)?,
+                    active_state: field(3)?,
+                    sub_state: field(4)?,
+                })
+            })
+            .collect())
+    }
+
+    fn bus_type(&self) -> gio::BusType {
+        bus_type(self.user)
+    }
+
+    fn unit_interface(&self) -> &'static str {
+        if self.name.ends_with(".scope") {
+            "org.freedesktop.systemd1.Scope"
+        } else {
+            "org.freedesktop.systemd1.Service"
+        }
+    }
+
+    async fn manager(&self) -> Result<gio::DBusProxy> {
+        manager(self.bus_type()).await
+    }
+
+    /// Returns the limits that are currently in place for this unit
+    pub async fn resource_limits(&self) -> Result<ResourceLimits> {
+        let reply = self
+            .manager()
+            .await?
+            .call_future(
+                "GetUnit",
+                Some(&(self.name.as_str(),).to_variant()),
+                gio::DBusCallFlags::NONE,
+                -1,
+            )
+            .await
+            .with_context(|| format!("unable to find unit {}", self.name))?;
+
+        let object_path = reply.child_value(0);
+        let object_path = object_path.str().context("unexpected reply from systemd")?;
+
+        let unit = gio::DBusProxy::for_bus_future(
+            self.bus_type(),
+            gio::DBusProxyFlags::DO_NOT_CONNECT_SIGNALS,
+            None,
+            SYSTEMD_BUS_NAME,
+            object_path,
+            self.unit_interface(),
+        )
+        .await
+        .context("unable to connect to systemd")?;
+
+        let property = |name: &str| {
+            unit.cached_property(name)
+                .and_then(|value| value.get::<u64>())
+                .filter(|value| *value != UNSET)
+        };
+
+        Ok(ResourceLimits {
+            cpu_weight: property("CPUWeight"),
+            cpu_quota: property("CPUQuotaPerSecUSec").map(|usec| usec / 10_000),
+            memory_max: property("MemoryMax"),
+        })
+    }
+
+    /// Applies `limits` to this unit until the next reboot. For system units
+    /// polkit will ask the user to authenticate.
+    pub async fn set_resource_limits(&self, limits: ResourceLimits) -> Result<()> {
+        let properties: Vec<(&str, glib::Variant)> = vec![
+            ("CPUWeight", limits.cpu_weight.unwrap_or(UNSET).to_variant()),
+            (
+                "CPUQuotaPerSecUSec",
+                limits
+                    .cpu_quota
+                    .map_or(UNSET, |percent| percent * 10_000)
+                    .to_variant(),
+            ),
+            ("MemoryMax", limits.memory_max.unwrap_or(UNSET).to_variant()),
+        ];
+
+        self.manager()
+            .await?
+            .call_future(
+                "SetUnitProperties",
+                Some(&(self.name.as_str(), true, properties).to_variant()),
+                gio::DBusCallFlags::ALLOW_INTERACTIVE_AUTHORIZATION,
+                i32::MAX,
+            )
+            .await
+            .with_context(|| format!("unable to set resource limits of {}", self.name))?;
+
+        Ok(())
+    }
+}
+
+fn bus_type(user: bool) -> gio::BusType {
+    if user {
+        gio::BusType::Session
+    } else {
+        gio::BusType::System
+    }
+}
+
+async fn manager(bus_type: gio::BusType) -> Result<gio::DBusProxy> {
+    gio::DBusProxy::for_bus_future(
+        bus_type,
+        gio::DBusProxyFlags::DO_NOT_LOAD_PROPERTIES | gio::DBusProxyFlags::DO_NOT_CONNECT_SIGNALS,
+        None,
+        SYSTEMD_BUS_NAME,
+        SYSTEMD_OBJECT_PATH,
+        SYSTEMD_MANAGER_INTERFACE,
+    )
+    .await
+    .context("unable to connect to systemd")
+}